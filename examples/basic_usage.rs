@@ -1,4 +1,4 @@
-use distributed_ledger::{DistributedLedger, Transaction};
+use distributed_ledger::{DistributedLedger, LoadGenerator, LoadProfile};
 use tokio::time::{sleep, Duration};
 
 #[tokio::main]
@@ -9,54 +9,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🚀 Starting Distributed Ledger Demo");
     println!("Target: 10,000+ transactions per second");
     
-    // Create ledger
-    let ledger = DistributedLedger::new();
+    // Create ledger with the funded accounts of a load profile and their signing keys; a few hot accounts send most
+    let profile = LoadProfile { accounts: 20, seed: 42, ..LoadProfile::zipfian(1.1) };
+    let genesis = profile.genesis().with_chain_id("basic-usage");
+    let ledger = DistributedLedger::from_genesis(genesis.clone())?;
     let ledger_clone = ledger.clone();
+    let mut load = LoadGenerator::new(profile.clone(), &genesis)?;
     
     // Start background processor
     ledger_clone.start_background_processor().await;
     
-    // Create test accounts with initial balances
-    let accounts = vec![
-        "alice".to_string(),
-        "bob".to_string(),
-        "charlie".to_string(),
-        "diana".to_string(),
-    ];
-    
-    // Give initial balances to accounts
-    for account in &accounts {
-        let tx = Transaction::new(
-            "".to_string(), // Empty sender for genesis transaction
-            account.clone(),
-            1_000_000, // 1M initial balance
-        );
-        ledger.add_transaction(tx).await?;
-    }
-    
-    println!("✅ Initial balances set for {} accounts", accounts.len());
+    println!("✅ Initial balances set for {} accounts", profile.accounts);
     
     // Generate high-volume transactions
     let start_time = std::time::Instant::now();
     let transaction_count = 50_000; // Target for testing
+    let mut rejected = 0;
     
     println!("📊 Generating {} transactions...", transaction_count);
     
-    for i in 0..transaction_count {
-        let from = &accounts[i % accounts.len()];
-        let to = &accounts[(i + 1) % accounts.len()];
-        let amount = (i % 1000) + 1; // Varying amounts
-        
-        let tx = Transaction::new(from.clone(), to.clone(), amount);
-        ledger.add_transaction(tx).await?;
+    for (i, tx) in load.by_ref().take(transaction_count).enumerate() {
+        // Hot accounts can hit the mempool's per-account limit
+        if ledger.add_transaction(tx).await.is_err() {
+            rejected += 1;
+        }
         
         // Print progress every 10k transactions
         if (i + 1) % 10_000 == 0 {
             let stats = ledger.get_performance_stats();
-            println!("Processed {} transactions, Current TPS: {:.2}", 
+            println!("Processed {} transactions, Current TPS: {:.2}",
                 i + 1, stats.transactions_per_second);
         }
     }
+    println!("Rejected {} transactions", rejected);
     
     // Wait for all transactions to be processed
     println!("⏳ Waiting for all transactions to be processed...");
@@ -72,10 +57,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Peak TPS: {:.2}", stats.peak_tps);
     println!("Average batch time: {:?}", stats.average_batch_time);
     
-    // Check final balances
+    // Check final balances of the hottest accounts
     println!("\n💰 Final Account Balances:");
-    for account in &accounts {
-        let balance = ledger.get_balance(account).await;
+    for index in 0..5 {
+        let account = LoadProfile::account(index);
+        let balance = ledger.get_balance(&account).await;
         println!("{}: {}", account, balance);
     }
     
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use distributed_ledger::{AdminApi, Block, DistributedLedger, GenesisConfig, ProductionPolicy, Transaction};
+use futures::StreamExt;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::sleep;
+
+const PAGE: &str = include_str!("index.html");
+const ACCOUNTS: [&str; 5] = ["alice", "bob", "charlie", "diana", "eve"];
+const ADMIN_TOKEN: &str = "explorer";
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+// Runs a dev node with some traffic on it and serves an explorer for it; pass the listen address to override
+// 127.0.0.1:8080
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt::init();
+    let addr: SocketAddr = std::env::args().nth(1).unwrap_or_else(|| "127.0.0.1:8080".to_string()).parse()?;
+    
+    let genesis = GenesisConfig::dev("explorer").with_chain_id("explorer");
+    let ledger = DistributedLedger::from_genesis(genesis.clone())?;
+    for account in ACCOUNTS {
+        ledger.label_address(account, &format!("{}-dev-wallet", account))?;
+    }
+    
+    // A few transactions per block, so the mempool view has something in it between blocks
+    let admin = AdminApi::new(ledger.clone(), ADMIN_TOKEN);
+    admin.set_production_policy(ADMIN_TOKEN, ProductionPolicy {
+        batch_threshold: Some(5),
+        max_interval: Some(Duration::from_secs(3)),
+        produce_empty: false,
+    })?;
+    ledger.start_background_processor().await;
+    tokio::spawn(generate_traffic(ledger.clone(), genesis));
+    
+    let listener = TcpListener::bind(addr).await?;
+    println!("🔎 Explorer running at http://{}", addr);
+    
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let ledger = ledger.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle(stream, &ledger).await {
+                eprintln!("Request from {} failed: {}", peer, e);
+            }
+        });
+    }
+}
+
+// Small transfers round the dev accounts, one every 400ms
+async fn generate_traffic(ledger: DistributedLedger, genesis: GenesisConfig) {
+    for round in 0usize.. {
+        let from = ACCOUNTS[round % ACCOUNTS.len()];
+        let to = ACCOUNTS[(round + 1 + round / ACCOUNTS.len() % 4) % ACCOUNTS.len()];
+        let mut tx = Transaction::new(from.to_string(), to.to_string(), (round % 50 + 1) as u64)
+            .for_chain(&genesis.chain_id)
+            .with_fee(1);
+        tx.sign(&genesis.dev_keypair(from).unwrap());
+        
+        if let Err(e) = ledger.add_transaction(tx).await {
+            eprintln!("Traffic transaction rejected: {}", e);
+        }
+        sleep(Duration::from_millis(400)).await;
+    }
+}
+
+// One GET per connection, answered and closed
+async fn handle(mut stream: TcpStream, ledger: &DistributedLedger) -> std::io::Result<()> {
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 || head.len() + read > MAX_REQUEST_HEAD {
+            return Ok(());
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+    
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let (method, target) = (request_line.next().unwrap_or_default(), request_line.next().unwrap_or("/"));
+    let (status, content_type, body) = match method {
+        "GET" => route(ledger, target).await,
+        _ => (405, "text/plain", "Only GET is supported".to_string()),
+    };
+    
+    let reason = match status {
+        200 => "OK",
+        404 => "Not Found",
+        _ => "Method Not Allowed",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, reason, content_type, body.len(), body,
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn route(ledger: &DistributedLedger, target: &str) -> (u16, &'static str, String) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let params = parse_query(query);
+    let json = |value: Value| (200, "application/json", value.to_string());
+    
+    match path {
+        "/" => (200, "text/html; charset=utf-8", PAGE.to_string()),
+        "/api/blocks" => {
+            let limit = params.get("limit").and_then(|limit| limit.parse().ok()).unwrap_or(10u64).clamp(1, 100);
+            json(latest_blocks(ledger, limit).await)
+        }
+        // A height, block hash, transaction id or address
+        "/api/search" => match ledger.search(params.get("q").map_or("", String::as_str)).await {
+            Some(result) => json(serde_json::to_value(result).unwrap_or_default()),
+            None => (404, "application/json", json!({ "error": "Nothing matches" }).to_string()),
+        },
+        "/api/mempool" => json(json!({
+            "size": ledger.mempool_size(),
+            "transactions": ledger.mempool_head(50),
+        })),
+        "/api/stats" => json(json!({
+            "chain": ledger.chain_stats(50).await,
+            "balances": ledger.balance_summary(),
+            "rich_list": ledger.rich_list(5),
+        })),
+        _ => (404, "text/plain", "Not found".to_string()),
+    }
+}
+
+// Newest first
+async fn latest_blocks(ledger: &DistributedLedger, limit: u64) -> Value {
+    let tip = ledger.get_latest_block().await.header.height;
+    let blocks: Vec<Block> = ledger.iter_blocks((tip + 1).saturating_sub(limit))
+        .take(limit as usize)
+        .collect()
+        .await;
+    
+    blocks.iter()
+        .rev()
+        .map(|block| json!({
+            "height": block.header.height,
+            "hash": block.header.hash,
+            "timestamp": block.header.timestamp,
+            "transactions": block.transactions.len(),
+            "fees": block.transactions.iter().map(|tx| tx.fee).sum::<u64>(),
+        }))
+        .collect()
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (decode(key), decode(value)))
+        .collect()
+}
+
+// Undoes form encoding: `+` for spaces and `%XX` escapes
+fn decode(component: &str) -> String {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 3 <= bytes.len() => match hex::decode(&bytes[i + 1..i + 3]) {
+                Ok(byte) => {
+                    decoded.extend(byte);
+                    i += 2;
+                }
+                Err(_) => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
\ No newline at end of file
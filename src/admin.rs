@@ -0,0 +1,184 @@
+use std::path::Path;
+use std::sync::RwLock;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::audit::{AuditFilter, RejectedTransaction};
+use crate::limits::SpendingLimit;
+use crate::mempool::MempoolLimits;
+use crate::peers::PeerRecord;
+use crate::production::{ProcessorConfig, ProductionPolicy};
+use crate::retention::IndexRetention;
+use crate::{DistributedLedger, LedgerError, Result};
+
+pub struct AdminApi {
+    ledger: DistributedLedger,
+    token_digest: RwLock<[u8; 32]>,
+}
+
+impl AdminApi {
+    pub fn new(ledger: DistributedLedger, token: &str) -> Self {
+        Self {
+            ledger,
+            token_digest: RwLock::new(Self::digest(token)),
+        }
+    }
+    
+    fn digest(token: &str) -> [u8; 32] {
+        Sha256::digest(token.as_bytes()).into()
+    }
+    
+    fn authorize(&self, token: &str) -> Result<()> {
+        let expected = *self.token_digest.read().unwrap();
+        let provided = Self::digest(token);
+        
+        // Compare digests in constant time so response timing doesn't leak the token
+        let diff = expected.iter()
+            .zip(provided.iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        
+        if diff != 0 {
+            warn!("Rejected admin request with invalid token");
+            return Err(LedgerError::Unauthorized("Invalid admin token".to_string()));
+        }
+        
+        Ok(())
+    }
+    
+    pub fn pause_block_production(&self, token: &str) -> Result<()> {
+        self.authorize(token)?;
+        self.ledger.set_paused(true);
+        info!("Block production paused by admin");
+        Ok(())
+    }
+    
+    pub fn resume_block_production(&self, token: &str) -> Result<()> {
+        self.authorize(token)?;
+        self.ledger.set_paused(false);
+        info!("Block production resumed by admin");
+        Ok(())
+    }
+    
+    pub fn set_batch_size(&self, token: &str, batch_size: usize) -> Result<()> {
+        self.authorize(token)?;
+        
+        if batch_size == 0 {
+            return Err(LedgerError::InvalidTransaction(
+                "Batch size must be greater than zero".to_string(),
+            ));
+        }
+        
+        self.ledger.set_batch_size(batch_size);
+        info!("Batch size set to {} by admin", batch_size);
+        Ok(())
+    }
+    
+    pub fn set_production_policy(&self, token: &str, policy: ProductionPolicy) -> Result<()> {
+        self.authorize(token)?;
+        self.ledger.set_production_policy(policy.clone())?;
+        info!("Block production policy set to {:?} by admin", policy);
+        Ok(())
+    }
+    
+    pub fn set_processor_config(&self, token: &str, config: ProcessorConfig) -> Result<()> {
+        self.authorize(token)?;
+        self.ledger.set_processor_config(config.clone())?;
+        info!("Processor config set to {:?} by admin", config);
+        Ok(())
+    }
+    
+    pub fn set_mempool_limits(&self, token: &str, limits: MempoolLimits) -> Result<()> {
+        self.authorize(token)?;
+        self.ledger.set_mempool_limits(limits)?;
+        info!("Mempool limits set to {:?} by admin", limits);
+        Ok(())
+    }
+    
+    // Indexes whose retention changes are rebuilt from the chain before this returns
+    pub async fn set_index_retention(&self, token: &str, retention: IndexRetention) -> Result<()> {
+        self.authorize(token)?;
+        self.ledger.set_index_retention(retention).await?;
+        info!("Index retention set to {:?} by admin", retention);
+        Ok(())
+    }
+    
+    // Account paid the coinbase of blocks this node produces, required under an emission schedule
+    pub fn set_coinbase_address(&self, token: &str, address: &str) -> Result<()> {
+        self.authorize(token)?;
+        
+        if address.is_empty() {
+            return Err(LedgerError::InvalidParameters(
+                "Coinbase address cannot be empty".to_string(),
+            ));
+        }
+        
+        self.ledger.set_coinbase_address(address.to_string());
+        info!("Coinbase address set to {} by admin", address);
+        Ok(())
+    }
+    
+    // Only this node enforces it, when admitting transactions; `None` lifts it
+    pub fn set_spending_limit(&self, token: &str, account: &str, limit: Option<SpendingLimit>) -> Result<()> {
+        self.authorize(token)?;
+        self.ledger.set_local_spending_limit(account, limit);
+        info!("Spending limit for {} set to {:?} by admin", account, limit);
+        Ok(())
+    }
+    
+    pub fn list_peers(&self, token: &str) -> Result<Vec<PeerRecord>> {
+        self.authorize(token)?;
+        Ok(self.ledger.peers().list())
+    }
+    
+    pub fn ban_peer(&self, token: &str, peer: &str, duration: std::time::Duration) -> Result<()> {
+        self.authorize(token)?;
+        self.ledger.peers().ban(peer, duration, "banned by admin");
+        info!("Peer {} banned for {:?} by admin", peer, duration);
+        Ok(())
+    }
+    
+    pub fn unban_peer(&self, token: &str, peer: &str) -> Result<bool> {
+        self.authorize(token)?;
+        let unbanned = self.ledger.peers().unban(peer);
+        info!("Peer {} unbanned by admin", peer);
+        Ok(unbanned)
+    }
+    
+    // Refused and dropped transactions, newest first
+    pub fn rejected_transactions(&self, token: &str, filter: &AuditFilter) -> Result<Vec<RejectedTransaction>> {
+        self.authorize(token)?;
+        Ok(self.ledger.rejected_transactions(filter))
+    }
+    
+    pub fn drain_mempool(&self, token: &str) -> Result<usize> {
+        self.authorize(token)?;
+        let drained = self.ledger.drain_mempool();
+        info!("Drained {} pending transactions by admin", drained);
+        Ok(drained)
+    }
+    
+    pub async fn trigger_snapshot(&self, token: &str, path: impl AsRef<Path>) -> Result<()> {
+        self.authorize(token)?;
+        
+        let snapshot = self.ledger.snapshot().await;
+        let json = serde_json::to_vec_pretty(&snapshot)
+            .map_err(anyhow::Error::from)?;
+        tokio::fs::write(path.as_ref(), json).await
+            .map_err(anyhow::Error::from)?;
+        
+        info!("Wrote ledger snapshot to {}", path.as_ref().display());
+        Ok(())
+    }
+    
+    pub fn rotate_token(&self, token: &str, new_token: &str) -> Result<()> {
+        self.authorize(token)?;
+        
+        if new_token.is_empty() {
+            return Err(LedgerError::Unauthorized("Admin token cannot be empty".to_string()));
+        }
+        
+        *self.token_digest.write().unwrap() = Self::digest(new_token);
+        info!("Admin token rotated");
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use uuid::Uuid;
+
+// What each owner lets each spender move out of its balance, kept in step with the chain
+#[derive(Debug, Default)]
+pub struct Allowances {
+    // By owner and spender
+    current: RwLock<HashMap<(String, String), u64>>,
+    // Allowance each committed approval replaced, so a reorg can put it back
+    replaced: RwLock<HashMap<Uuid, u64>>,
+}
+
+impl Allowances {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn get(&self, owner: &str, spender: &str) -> u64 {
+        self.current.read().unwrap().get(&(owner.to_string(), spender.to_string())).copied().unwrap_or(0)
+    }
+    
+    // Spenders `owner` approved, with what each may still move
+    pub fn granted_by(&self, owner: &str) -> Vec<(String, u64)> {
+        let mut granted: Vec<_> = self.current.read().unwrap()
+            .iter()
+            .filter(|((granter, _), _)| granter == owner)
+            .map(|((_, spender), allowance)| (spender.clone(), *allowance))
+            .collect();
+        granted.sort();
+        granted
+    }
+    
+    pub(crate) fn approve(&self, owner: &str, spender: &str, allowance: u64, approval: Uuid) {
+        let previous = self.set(owner, spender, allowance);
+        self.replaced.write().unwrap().insert(approval, previous);
+    }
+    
+    pub(crate) fn unapprove(&self, owner: &str, spender: &str, approval: Uuid) {
+        let previous = self.replaced.write().unwrap().remove(&approval).unwrap_or(0);
+        self.set(owner, spender, previous);
+    }
+    
+    // Only for amounts already checked against the allowance
+    pub(crate) fn spend(&self, owner: &str, spender: &str, amount: u64) {
+        let allowance = self.get(owner, spender);
+        self.set(owner, spender, allowance.saturating_sub(amount));
+    }
+    
+    pub(crate) fn refund(&self, owner: &str, spender: &str, amount: u64) {
+        let allowance = self.get(owner, spender);
+        self.set(owner, spender, allowance + amount);
+    }
+    
+    // Returns the allowance it replaces
+    fn set(&self, owner: &str, spender: &str, allowance: u64) -> u64 {
+        let mut current = self.current.write().unwrap();
+        let key = (owner.to_string(), spender.to_string());
+        match allowance {
+            0 => current.remove(&key),
+            allowance => current.insert(key, allowance),
+        }
+        .unwrap_or(0)
+    }
+}
\ No newline at end of file
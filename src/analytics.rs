@@ -0,0 +1,114 @@
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::state::BalanceChange;
+
+// Decimal orders of magnitude, enough for any u64 balance
+const BUCKETS: usize = 20;
+
+// Funded accounts holding between `min` and `max` inclusive
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DistributionBucket {
+    pub min: u64,
+    pub max: u64,
+    pub accounts: usize,
+    pub balance: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BalanceSummary {
+    // Accounts with a non-zero balance
+    pub active_accounts: usize,
+    // Held in balances; unlike the total supply, without stakes, locks or escrow
+    pub circulating: u64,
+    // By order of magnitude, skipping empty ones
+    pub distribution: Vec<DistributionBucket>,
+}
+
+#[derive(Debug, Default)]
+struct Ranking {
+    // Largest balance first, ties by address
+    ranked: BTreeSet<(Reverse<u64>, String)>,
+    circulating: u64,
+    buckets: [(usize, u64); BUCKETS],
+}
+
+impl Ranking {
+    fn bucket(balance: u64) -> usize {
+        balance.checked_ilog10().unwrap_or(0) as usize
+    }
+    
+    fn add(&mut self, address: &str, balance: u64) {
+        if balance == 0 {
+            return;
+        }
+        self.ranked.insert((Reverse(balance), address.to_string()));
+        self.circulating = self.circulating.saturating_add(balance);
+        let bucket = &mut self.buckets[Self::bucket(balance)];
+        bucket.0 += 1;
+        bucket.1 = bucket.1.saturating_add(balance);
+    }
+    
+    fn subtract(&mut self, address: &str, balance: u64) {
+        if balance == 0 || !self.ranked.remove(&(Reverse(balance), address.to_string())) {
+            return;
+        }
+        self.circulating = self.circulating.saturating_sub(balance);
+        let bucket = &mut self.buckets[Self::bucket(balance)];
+        bucket.0 -= 1;
+        bucket.1 = bucket.1.saturating_sub(balance);
+    }
+}
+
+// Rich list and balance aggregates over the main chain's committed balances, updated with each block's changes
+// rather than recomputed per query
+#[derive(Debug, Default)]
+pub struct BalanceAnalytics {
+    ranking: RwLock<Ranking>,
+}
+
+impl BalanceAnalytics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // The `n` largest balances, largest first
+    pub fn top(&self, n: usize) -> Vec<(String, u64)> {
+        self.ranking.read().unwrap()
+            .ranked
+            .iter()
+            .take(n)
+            .map(|(Reverse(balance), address)| (address.clone(), *balance))
+            .collect()
+    }
+    
+    pub fn summary(&self) -> BalanceSummary {
+        let ranking = self.ranking.read().unwrap();
+        let distribution = ranking.buckets.iter()
+            .enumerate()
+            .filter(|(_, (accounts, _))| *accounts > 0)
+            .map(|(magnitude, (accounts, balance))| DistributionBucket {
+                min: 10u64.pow(magnitude as u32).max(1),
+                max: 10u64.checked_pow(magnitude as u32 + 1).map_or(u64::MAX, |bound| bound - 1),
+                accounts: *accounts,
+                balance: *balance,
+            })
+            .collect();
+        
+        BalanceSummary {
+            active_accounts: ranking.ranked.len(),
+            circulating: ranking.circulating,
+            distribution,
+        }
+    }
+    
+    pub(crate) fn update(&self, changes: &[BalanceChange]) {
+        let mut ranking = self.ranking.write().unwrap();
+        for change in changes {
+            ranking.subtract(&change.address, change.before);
+            ranking.add(&change.address, change.after);
+        }
+    }
+}
\ No newline at end of file
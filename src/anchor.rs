@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::transaction::TransactionKind;
+use crate::{Block, LedgerError, Result, Transaction};
+
+pub const ANCHOR_MODULE: &str = "native:anchor";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Anchor {
+    pub namespace: String,
+    pub sequence: u64,
+    pub state_root: String,
+    pub batch_hash: String,
+}
+
+impl Anchor {
+    pub fn new(namespace: String, sequence: u64, state_root: String, batch_hash: String) -> Self {
+        Self {
+            namespace,
+            sequence,
+            state_root,
+            batch_hash,
+        }
+    }
+    
+    pub fn validate(&self) -> Result<()> {
+        if self.namespace.is_empty() {
+            return Err(LedgerError::InvalidTransaction(
+                "Anchor namespace cannot be empty".to_string(),
+            ));
+        }
+        
+        for (name, value) in [("state root", &self.state_root), ("batch hash", &self.batch_hash)] {
+            if value.len() != 64 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(LedgerError::InvalidTransaction(format!(
+                    "Anchor {} must be a 32-byte hex digest",
+                    name,
+                )));
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AnchorRecord {
+    pub transaction_id: Uuid,
+    pub block_height: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnchorProof {
+    pub anchor: Anchor,
+    pub transaction: Transaction,
+    pub block_height: u64,
+    pub block_hash: String,
+}
+
+impl AnchorProof {
+    // Checks the proof against a block obtained independently, e.g. from another node
+    pub fn verify(&self, block: &Block) -> bool {
+        if block.header.hash != self.block_hash || block.header.hash != block.calculate_hash() {
+            return false;
+        }
+        
+        if self.transaction.validate().is_err() {
+            return false;
+        }
+        
+        match &self.transaction.kind {
+            TransactionKind::Anchor(anchor) if anchor == &self.anchor => {}
+            _ => return false,
+        }
+        
+        block.transactions.iter().any(|tx| tx == &self.transaction)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{Result, Transaction};
+
+// Rejections kept, in memory and on disk, before the oldest are forgotten
+pub const AUDIT_CAPACITY: usize = 10_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RejectedTransaction {
+    pub at: DateTime<Utc>,
+    pub transaction: Transaction,
+    pub reason: String,
+    // Who handed it to this node, when they said so; see `DistributedLedger::add_transaction_from`
+    pub submitter: Option<String>,
+    // Dropped from the pool after being admitted, rather than refused on submission
+    pub pooled: bool,
+}
+
+// Which audit entries `RejectionAudit::query` returns; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AuditFilter {
+    // Sender or any recipient
+    pub address: Option<String>,
+    pub transaction: Option<Uuid>,
+    pub submitter: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    pub fn matches(&self, entry: &RejectedTransaction) -> bool {
+        let parties = crate::history::parties(&entry.transaction);
+        self.address.as_ref().is_none_or(|address| parties.contains(address.as_str()))
+            && self.transaction.is_none_or(|id| entry.transaction.id == id)
+            && self.submitter.as_ref().is_none_or(|submitter| entry.submitter.as_ref() == Some(submitter))
+            && self.since.is_none_or(|since| entry.at >= since)
+    }
+}
+
+#[derive(Debug)]
+struct Journal {
+    path: PathBuf,
+    file: File,
+    // Lines in the file; it is rewritten from memory once this reaches twice the capacity
+    lines: usize,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    entries: VecDeque<RejectedTransaction>,
+    journal: Option<Journal>,
+}
+
+// Every transaction this node turned away or dropped, for tracing what became of a transfer. Bounded, and once a
+// journal is opened also kept as JSON lines so the record outlives a restart
+#[derive(Debug)]
+pub struct RejectionAudit {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl Default for RejectionAudit {
+    fn default() -> Self {
+        Self::new(AUDIT_CAPACITY)
+    }
+}
+
+impl RejectionAudit {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    // Newest first
+    pub fn query(&self, filter: &AuditFilter) -> Vec<RejectedTransaction> {
+        self.entries.lock().unwrap().entries.iter()
+            .rev()
+            .filter(|entry| filter.matches(entry))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+    
+    // Loads what an earlier run journaled at `path`, ahead of anything recorded since, then journals there; returns
+    // the number of entries loaded. Unreadable lines, such as one cut short by a crash, are skipped
+    pub fn open(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let path = path.as_ref().to_path_buf();
+        let mut loaded = Vec::new();
+        if path.exists() {
+            let file = File::open(&path).map_err(anyhow::Error::from)?;
+            for line in BufReader::new(file).lines() {
+                let line = line.map_err(anyhow::Error::from)?;
+                match serde_json::from_str::<RejectedTransaction>(&line) {
+                    Ok(entry) => loaded.push(entry),
+                    Err(e) => warn!("Skipping unreadable audit entry in {}: {}", path.display(), e),
+                }
+            }
+        }
+        
+        let mut entries = self.entries.lock().unwrap();
+        let count = loaded.len();
+        for entry in loaded.into_iter().rev() {
+            entries.entries.push_front(entry);
+        }
+        while entries.entries.len() > self.capacity {
+            entries.entries.pop_front();
+        }
+        
+        let journal = Self::rewrite(&path, &entries.entries)?;
+        entries.journal = Some(journal);
+        Ok(count)
+    }
+    
+    pub(crate) fn record(&self, entry: RejectedTransaction) {
+        let mut guard = self.entries.lock().unwrap();
+        let Entries { entries, journal } = &mut *guard;
+        if let Some(journal) = journal {
+            let written = serde_json::to_string(&entry)
+                .map_err(anyhow::Error::from)
+                .and_then(|line| writeln!(journal.file, "{}", line).map_err(anyhow::Error::from));
+            match written {
+                Ok(()) => journal.lines += 1,
+                Err(e) => warn!("Failed to journal a rejected transaction to {}: {}", journal.path.display(), e),
+            }
+        }
+        
+        entries.push_back(entry);
+        if entries.len() > self.capacity {
+            entries.pop_front();
+        }
+        
+        if let Some(open) = journal.as_mut().filter(|journal| journal.lines >= 2 * self.capacity) {
+            match Self::rewrite(&open.path, entries) {
+                Ok(rewritten) => *open = rewritten,
+                Err(e) => warn!("Failed to compact the audit journal at {}: {}", open.path.display(), e),
+            }
+        }
+    }
+    
+    // Replaces the file at `path` with `entries` and opens it for appending
+    fn rewrite(path: &Path, entries: &VecDeque<RejectedTransaction>) -> Result<Journal> {
+        let mut contents = String::new();
+        for entry in entries {
+            contents.push_str(&serde_json::to_string(entry).map_err(anyhow::Error::from)?);
+            contents.push('\n');
+        }
+        
+        let staging = path.with_extension("tmp");
+        std::fs::write(&staging, contents).map_err(anyhow::Error::from)?;
+        std::fs::rename(&staging, path).map_err(anyhow::Error::from)?;
+        let file = OpenOptions::new().append(true).open(path).map_err(anyhow::Error::from)?;
+        Ok(Journal { path: path.to_path_buf(), file, lines: entries.len() })
+    }
+}
\ No newline at end of file
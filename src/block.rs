@@ -1,86 +1,398 @@
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU64, Ordering};
 use serde::{Deserialize, Serialize};
+use sha2::digest::Output;
 use sha2::{Digest, Sha256};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
-use crate::transaction::Transaction;
+use crate::bloom::AddressBloom;
+use crate::checkpoint::CheckpointVote;
+use crate::keys::{self, Keypair};
+use crate::merkle::MerkleProof;
+use crate::transaction::{Transaction, TransactionKind};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Block {
+// Everything needed to link and authenticate a block without its transactions, which it commits to
+// through `merkle_root`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlockHeader {
     pub id: Uuid,
+    #[serde(default)]
+    pub height: u64,
     pub previous_hash: String,
-    pub transactions: Vec<Transaction>,
+    pub merkle_root: String,
+    // Root of the balances as the parent block left them; empty for genesis
+    #[serde(default)]
+    pub state_root: String,
     pub timestamp: DateTime<Utc>,
     pub nonce: u64,
+    #[serde(default)]
+    pub difficulty: usize,
+    #[serde(default)]
+    pub producer: Option<String>,
+    // Scheduled proposers skipped after timing out before this block's producer took the slot
+    #[serde(default)]
+    pub round: u64,
+    // Set by engines that elect proposers with a VRF
+    #[serde(default)]
+    pub vrf_output: Option<String>,
+    #[serde(default)]
+    pub vrf_proof: Option<String>,
     pub hash: String,
+    #[serde(default)]
+    pub producer_signature: Option<String>,
+    // Total weight of the transactions, so a header alone tells how full its block is
+    #[serde(default)]
+    pub weight: u64,
+    // Accounts the transactions involve, so a header alone tells whether the block may concern an account
+    #[serde(default)]
+    pub address_bloom: AddressBloom,
+    // Slots since the parent's that passed without a block; see `BlockTimeRules::slot_ms`
+    #[serde(default)]
+    pub skipped_slots: u64,
 }
 
-impl Block {
-    pub fn new(previous_hash: String, transactions: Vec<Transaction>) -> Self {
-        let id = Uuid::new_v4();
-        let timestamp = Utc::now();
-        let nonce = 0;
-        
-        let mut block = Self {
-            id,
-            previous_hash,
-            transactions,
-            timestamp,
-            nonce,
-            hash: String::new(),
-        };
-        
-        block.hash = block.calculate_hash();
-        block
+// Below this many leading zero digits a solution turns up within a few thousand hashes, sooner than threads start
+const PARALLEL_MINING_DIFFICULTY: usize = 4;
+
+// Whether a hash written out in hex would start with `difficulty` zeros. sha2 picks the CPU's SHA extensions at
+// runtime where there are any, so each hash tried is hardware accelerated without a feature to turn on
+fn meets_difficulty(digest: &[u8], difficulty: usize) -> bool {
+    (0..difficulty).all(|digit| {
+        digest.get(digit / 2).is_some_and(|byte| if digit % 2 == 0 { byte >> 4 == 0 } else { byte & 0x0f == 0 })
+    })
+}
+
+impl BlockHeader {
+    pub fn calculate_hash(&self) -> String {
+        self.hash_with_root(&self.merkle_root)
     }
     
-    pub fn calculate_hash(&self) -> String {
+    fn hash_with_root(&self, merkle_root: &str) -> String {
+        format!("{:x}", self.finish_hash(self.hash_to_nonce(merkle_root), self.nonce))
+    }
+    
+    // The fields hashed ahead of the nonce, so mining hashes them once and not once per nonce tried
+    fn hash_to_nonce(&self, merkle_root: &str) -> Sha256 {
         let mut hasher = Sha256::new();
         hasher.update(self.id.as_bytes());
+        hasher.update(self.height.to_le_bytes());
         hasher.update(self.previous_hash.as_bytes());
-        hasher.update(self.timestamp.timestamp().to_le_bytes());
-        hasher.update(self.nonce.to_le_bytes());
+        hasher.update(merkle_root.as_bytes());
+        hasher.update(self.state_root.as_bytes());
+        // Millisecond precision, since proposer rounds are timed against it
+        hasher.update(self.timestamp.timestamp_millis().to_le_bytes());
+        hasher
+    }
+    
+    fn finish_hash(&self, mut hasher: Sha256, nonce: u64) -> Output<Sha256> {
+        hasher.update(nonce.to_le_bytes());
+        hasher.update((self.difficulty as u64).to_le_bytes());
+        hasher.update(self.round.to_le_bytes());
+        hasher.update(self.weight.to_le_bytes());
+        hasher.update(self.address_bloom.as_bytes());
+        hasher.update(self.skipped_slots.to_le_bytes());
         
-        // Include transaction hashes
-        for tx in &self.transactions {
-            hasher.update(tx.hash().as_bytes());
+        if let Some(producer) = &self.producer {
+            hasher.update(producer.as_bytes());
+        }
+        
+        if let (Some(output), Some(proof)) = (&self.vrf_output, &self.vrf_proof) {
+            hasher.update(output.as_bytes());
+            hasher.update(proof.as_bytes());
         }
         
-        format!("{:x}", hasher.finalize())
+        hasher.finalize()
     }
     
-    pub fn mine(&mut self, difficulty: usize) {
-        let target = "0".repeat(difficulty);
-        
-        while !self.hash.starts_with(&target) {
-            self.nonce += 1;
-            self.hash = self.calculate_hash();
+    pub fn verify_producer_signature(&self) -> bool {
+        match (&self.producer, &self.producer_signature) {
+            (Some(producer), Some(signature)) => {
+                keys::verify_signature(producer, self.hash.as_bytes(), signature)
+            }
+            _ => false,
         }
     }
     
-    pub fn validate(&self, previous_block: Option<&Block>) -> crate::Result<()> {
-        // Validate hash
+    // Checks the header on its own and its link to the parent's, e.g. while syncing headers ahead of bodies
+    pub fn validate(&self, previous: Option<&BlockHeader>) -> crate::Result<()> {
         if self.hash != self.calculate_hash() {
             return Err(crate::LedgerError::BlockValidationFailed(
                 "Invalid block hash".to_string(),
             ));
         }
         
-        // Validate previous hash
-        if let Some(prev) = previous_block {
+        if let Some(prev) = previous {
             if self.previous_hash != prev.hash {
                 return Err(crate::LedgerError::BlockValidationFailed(
                     "Invalid previous hash".to_string(),
                 ));
             }
+            
+            if self.height != prev.height + 1 {
+                return Err(crate::LedgerError::BlockValidationFailed(
+                    "Invalid block height".to_string(),
+                ));
+            }
         } else if !self.previous_hash.is_empty() {
             return Err(crate::LedgerError::BlockValidationFailed(
                 "Genesis block should have empty previous hash".to_string(),
             ));
         }
         
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Block {
+    pub header: BlockHeader,
+    pub transactions: Vec<Transaction>,
+    // Each transaction's digest as of the last `seal`; see `transaction_digests`
+    #[serde(skip)]
+    digests: Vec<[u8; 32]>,
+    // Each transaction's weight, from the same pass over its encoding as its digest
+    #[serde(skip)]
+    weights: Vec<usize>,
+}
+
+// Blocks compare by what they hold, whichever of them kept its digests
+impl PartialEq for Block {
+    fn eq(&self, other: &Self) -> bool {
+        self.header == other.header && self.transactions == other.transactions
+    }
+}
+
+impl Block {
+    pub fn new(previous_hash: String, transactions: Vec<Transaction>) -> Self {
+        let header = BlockHeader {
+            id: crate::clock::new_id(),
+            height: 0,
+            previous_hash,
+            // Filled in by `seal`
+            merkle_root: String::new(),
+            state_root: String::new(),
+            timestamp: crate::clock::now(),
+            nonce: 0,
+            difficulty: 0,
+            producer: None,
+            round: 0,
+            vrf_output: None,
+            vrf_proof: None,
+            hash: String::new(),
+            producer_signature: None,
+            weight: 0,
+            address_bloom: AddressBloom::default(),
+            skipped_slots: 0,
+        };
+        
+        let mut block = Self { header, transactions, digests: Vec::new(), weights: Vec::new() };
+        block.seal();
+        block
+    }
+    
+    pub fn child_of(parent: &Block, transactions: Vec<Transaction>) -> Self {
+        let mut block = Self::new(parent.header.hash.clone(), transactions);
+        block.header.height = parent.header.height + 1;
+        // Keeps timestamps increasing even if blocks follow each other within a millisecond
+        let earliest = parent.header.timestamp + chrono::Duration::milliseconds(1);
+        block.header.timestamp = block.header.timestamp.max(earliest);
+        block.header.difficulty = parent.header.difficulty;
+        // The body is as `new` sealed it, so only the header hash changes
+        block.header.hash = block.header.calculate_hash();
+        block
+    }
+    
+    // Reassembles a block from a header and a body fetched separately
+    pub fn from_parts(header: BlockHeader, transactions: Vec<Transaction>) -> crate::Result<Self> {
+        let (digests, weights) = transactions.iter().map(Transaction::digest_and_weight).unzip();
+        let block = Self { header, transactions, digests, weights };
+        let matches = block.header.merkle_root == crate::merkle::root(&block.digests)
+            && block.header.weight == block.weights.iter().sum::<usize>() as u64
+            && block.header.address_bloom == block.address_bloom();
+        if !matches {
+            return Err(crate::LedgerError::BlockValidationFailed(
+                "Transactions do not match the header".to_string(),
+            ));
+        }
+        
+        Ok(block)
+    }
+    
+    pub fn into_parts(self) -> (BlockHeader, Vec<Transaction>) {
+        (self.header, self.transactions)
+    }
+    
+    pub fn merkle_root(transactions: &[Transaction]) -> String {
+        crate::merkle::root(&transactions.iter().map(Transaction::digest).collect::<Vec<_>>())
+    }
+    
+    // The digests the header was last sealed with, worked out afresh for a block that never was, e.g. one received
+    // from a peer. Transactions edited in place since aren't seen until the block is sealed again
+    pub fn transaction_digests(&self) -> Cow<'_, [[u8; 32]]> {
+        match self.digests.len() == self.transactions.len() {
+            true => Cow::Borrowed(&self.digests),
+            false => Cow::Owned(self.transactions.iter().map(Transaction::digest).collect()),
+        }
+    }
+    
+    // As `transaction_digests`, for each transaction's weight
+    pub fn transaction_weights(&self) -> Cow<'_, [usize]> {
+        match self.weights.len() == self.transactions.len() {
+            true => Cow::Borrowed(&self.weights),
+            false => Cow::Owned(self.transactions.iter().map(Transaction::weight).collect()),
+        }
+    }
+    
+    // Proves the transaction is in this block to anyone holding just the header; see `merkle::verify_inclusion_proof`
+    pub fn merkle_proof(&self, tx_id: Uuid) -> Option<MerkleProof> {
+        let index = self.transactions.iter().position(|tx| tx.id == tx_id)?;
+        crate::merkle::proof(&self.transaction_digests(), index)
+    }
+    
+    // The header hash as it should be for these transactions, so a changed body is caught too
+    pub fn calculate_hash(&self) -> String {
+        self.header.hash_with_root(&Self::merkle_root(&self.transactions))
+    }
+    
+    // Commits the header to the current transactions, keeping their digests, and rehashes it
+    pub fn seal(&mut self) {
+        (self.digests, self.weights) = self.transactions.iter().map(Transaction::digest_and_weight).unzip();
+        self.header.merkle_root = crate::merkle::root(&self.digests);
+        self.header.weight = self.weights.iter().sum::<usize>() as u64;
+        self.header.address_bloom = self.address_bloom();
+        self.header.hash = self.header.calculate_hash();
+    }
+    
+    // Rehashes the header after a change to the header alone, from the digests kept by the last `seal`. A body edited
+    // in place since goes unseen, so the block fails validation rather than committing to the edit
+    fn reseal(&mut self) {
+        match self.digests.len() == self.transactions.len() {
+            true => self.header.hash = self.header.calculate_hash(),
+            false => self.seal(),
+        }
+    }
+    
+    // Mines on every core once the difficulty makes that worth it; returns how many hashes were tried
+    pub fn mine(&mut self, difficulty: usize) -> u64 {
+        let threads = if difficulty < PARALLEL_MINING_DIFFICULTY {
+            1
+        } else {
+            std::thread::available_parallelism().map_or(1, |threads| threads.get())
+        };
+        self.mine_with_threads(difficulty, threads)
+    }
+    
+    // Searches nonces upward from the current one, thread `i` of `n` trying every `n`th. Threads stop once a lower
+    // nonce is known to solve the block, and the lowest solution wins, so the block comes out as mining it on one
+    // thread would however the threads are scheduled. Returns how many hashes were tried
+    pub fn mine_with_threads(&mut self, difficulty: usize, threads: usize) -> u64 {
+        self.header.difficulty = difficulty;
+        self.reseal();
+        
+        let threads = threads.max(1) as u64;
+        let start = self.header.nonce;
+        let prefix = self.header.hash_to_nonce(&self.header.merkle_root);
+        let solution = AtomicU64::new(u64::MAX);
+        let header = &self.header;
+        let search = |offset: u64| {
+            let mut tried = 0;
+            let mut nonce = start.checked_add(offset);
+            while let Some(candidate) = nonce.filter(|&nonce| nonce < solution.load(Ordering::Relaxed)) {
+                tried += 1;
+                if meets_difficulty(&header.finish_hash(prefix.clone(), candidate), difficulty) {
+                    solution.fetch_min(candidate, Ordering::Relaxed);
+                    break;
+                }
+                nonce = candidate.checked_add(threads);
+            }
+            tried
+        };
+        
+        let tried = if threads == 1 {
+            search(0)
+        } else {
+            std::thread::scope(|scope| {
+                let searches: Vec<_> = (0..threads).map(|offset| scope.spawn(move || search(offset))).collect();
+                searches.into_iter().map(|search| search.join().unwrap()).sum()
+            })
+        };
+        
+        self.header.nonce = solution.into_inner();
+        self.header.hash = self.header.calculate_hash();
+        tried
+    }
+    
+    // Commits to the producer's key in the hash, then signs that hash
+    pub fn sign(&mut self, keypair: &Keypair) {
+        self.header.producer = Some(keypair.public_key());
+        self.reseal();
+        self.header.producer_signature = Some(keypair.sign(self.header.hash.as_bytes()));
+    }
+    
+    pub fn verify_producer_signature(&self) -> bool {
+        self.header.verify_producer_signature()
+    }
+    
+    pub fn body_size(&self) -> usize {
+        self.transactions.iter().map(Transaction::weight).sum()
+    }
+    
+    pub fn weight(&self) -> u64 {
+        self.body_size() as u64
+    }
+    
+    pub fn address_bloom(&self) -> AddressBloom {
+        AddressBloom::for_transactions(&self.transactions)
+    }
+    
+    // Finality votes carried by this block, with the validator that cast each
+    pub fn checkpoint_votes(&self) -> impl Iterator<Item = (&str, &CheckpointVote)> {
+        self.transactions.iter().filter_map(|tx| match &tx.kind {
+            TransactionKind::CheckpointVote(vote) => Some((tx.from.as_str(), vote)),
+            _ => None,
+        })
+    }
+    
+    pub fn validate(&self, previous_block: Option<&Block>) -> crate::Result<()> {
+        self.header.validate(previous_block.map(|prev| &prev.header))?;
+        
+        if self.header.merkle_root != Self::merkle_root(&self.transactions) {
+            return Err(crate::LedgerError::BlockValidationFailed(
+                "Transactions do not match the header".to_string(),
+            ));
+        }
+        
+        crate::bundle::check_block(&self.transactions).map_err(crate::LedgerError::BlockValidationFailed)?;
+        
+        if self.header.weight != self.weight() {
+            return Err(crate::LedgerError::BlockValidationFailed(format!(
+                "Header claims a weight of {}, but the transactions weigh {}",
+                self.header.weight, self.weight(),
+            )));
+        }
+        if self.header.address_bloom != self.address_bloom() {
+            return Err(crate::LedgerError::BlockValidationFailed(
+                "Header address bloom does not match the transactions".to_string(),
+            ));
+        }
+        
         // Validate transactions
         for tx in &self.transactions {
             tx.validate()?;
+            
+            if tx.is_expired_at(self.header.height, self.header.timestamp) {
+                return Err(crate::LedgerError::BlockValidationFailed(format!(
+                    "Transaction {} expired before block {}",
+                    tx.id, self.header.height,
+                )));
+            }
+            
+            if tx.is_locked_at(self.header.height, self.header.timestamp) {
+                return Err(crate::LedgerError::BlockValidationFailed(format!(
+                    "Transaction {} is locked until after block {}",
+                    tx.id, self.header.height,
+                )));
+            }
         }
         
         Ok(())
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+
+use crate::Transaction;
+
+pub const BLOOM_BYTES: usize = 256;
+// Bits set per address; with 2048 bits, a block touching 100 accounts answers a false "maybe" about 0.3% of the time
+const PROBES: usize = 3;
+
+// The accounts a block's transactions send from or pay into, as a bloom filter committed to by the header, so a
+// scan for one account can pass over blocks that cannot involve it without fetching their bodies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressBloom([u8; BLOOM_BYTES]);
+
+impl Default for AddressBloom {
+    fn default() -> Self {
+        Self([0; BLOOM_BYTES])
+    }
+}
+
+impl AddressBloom {
+    pub fn for_transactions(transactions: &[Transaction]) -> Self {
+        let mut bloom = Self::default();
+        for party in transactions.iter().flat_map(crate::history::parties) {
+            bloom.insert(party);
+        }
+        bloom
+    }
+    
+    pub fn insert(&mut self, address: &str) {
+        for bit in Self::bits(address) {
+            self.0[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+    
+    // False only if no transaction in the block involves the account
+    pub fn may_contain(&self, address: &str) -> bool {
+        Self::bits(address).all(|bit| self.0[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|byte| *byte == 0)
+    }
+    
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+    
+    fn bits(address: &str) -> impl Iterator<Item = usize> {
+        let digest = Sha256::digest(address.as_bytes());
+        (0..PROBES).map(move |i| u16::from_le_bytes([digest[2 * i], digest[2 * i + 1]]) as usize % (BLOOM_BYTES * 8))
+    }
+}
+
+// As hex, like the header's hashes
+impl Serialize for AddressBloom {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for AddressBloom {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let mut bloom = [0; BLOOM_BYTES];
+        hex::decode_to_slice(&encoded, &mut bloom).map_err(serde::de::Error::custom)?;
+        Ok(Self(bloom))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::Transaction;
+
+pub const MAX_BUNDLE_SIZE: u32 = 16;
+
+// A transaction's place in a bundle: the `index`th of `size` transactions that commit in one block, together and in
+// order, or not at all
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct BundlePosition {
+    pub bundle: Uuid,
+    pub index: u32,
+    pub size: u32,
+}
+
+// Places the transactions in a new bundle, in the order given; like `Transaction::for_chain`, before they are signed
+pub fn bundle(transactions: Vec<Transaction>) -> Vec<Transaction> {
+    let bundle = crate::clock::new_id();
+    let size = transactions.len() as u32;
+    transactions.into_iter()
+        .enumerate()
+        .map(|(index, tx)| tx.in_bundle(BundlePosition { bundle, index: index as u32, size }))
+        .collect()
+}
+
+// Checks that `transactions` are one bundle, whole and in order, and returns its id
+pub fn check_bundle(transactions: &[Transaction]) -> Result<Uuid, String> {
+    let Some(first) = transactions.first().and_then(|tx| tx.bundle) else {
+        return Err("A bundle needs at least one transaction, each placed in it".to_string());
+    };
+    if transactions.len() != first.size as usize {
+        return Err(format!("Bundle {} has {} of its {} transactions", first.bundle, transactions.len(), first.size));
+    }
+    
+    for (index, tx) in transactions.iter().enumerate() {
+        let expected = BundlePosition { bundle: first.bundle, index: index as u32, size: first.size };
+        if tx.bundle != Some(expected) {
+            return Err(format!("Transaction {} is out of place in bundle {}", tx.id, first.bundle));
+        }
+    }
+    Ok(first.bundle)
+}
+
+// Every bundle in a block appears whole, its members next to each other
+pub fn check_block(transactions: &[Transaction]) -> Result<(), String> {
+    let mut i = 0;
+    while i < transactions.len() {
+        let Some(position) = transactions[i].bundle else {
+            i += 1;
+            continue;
+        };
+        
+        let members = transactions.get(i..i + position.size as usize)
+            .ok_or_else(|| format!("Bundle {} is incomplete", position.bundle))?;
+        check_bundle(members)?;
+        i += members.len();
+    }
+    Ok(())
+}
\ No newline at end of file
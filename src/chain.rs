@@ -0,0 +1,79 @@
+use std::ops::Index;
+use std::sync::Arc;
+
+use crate::Block;
+
+// Blocks per segment. Appending copies at most the handles in the last segment, so a commit costs the same however
+// long the chain has grown
+const SEGMENT_LEN: usize = 256;
+
+// The main chain, genesis first, held in fixed-size segments behind shared handles. A clone shares every segment with
+// the original, and a segment is only copied once one of them changes it, so the ledger publishes a clone after each
+// commit for readers to hold on to while the next block is applied
+#[derive(Debug, Clone, Default)]
+pub struct Chain {
+    segments: Vec<Arc<Vec<Arc<Block>>>>,
+    len: usize,
+}
+
+impl Chain {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    pub fn get(&self, height: usize) -> Option<&Arc<Block>> {
+        self.segments.get(height / SEGMENT_LEN)?.get(height % SEGMENT_LEN)
+    }
+    
+    pub fn last(&self) -> Option<&Arc<Block>> {
+        self.segments.last()?.last()
+    }
+    
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Arc<Block>> {
+        self.segments.iter().flat_map(|segment| segment.iter())
+    }
+    
+    // From `height` up, without walking the blocks below it
+    pub fn iter_from(&self, height: usize) -> impl Iterator<Item = &Arc<Block>> {
+        let segment = height / SEGMENT_LEN;
+        let first = self.segments.get(segment)
+            .and_then(|blocks| blocks.get(height % SEGMENT_LEN..))
+            .unwrap_or_default();
+        first.iter().chain(self.segments.iter().skip(segment + 1).flat_map(|segment| segment.iter()))
+    }
+    
+    pub(crate) fn push(&mut self, block: Arc<Block>) {
+        match self.segments.last_mut().filter(|segment| segment.len() < SEGMENT_LEN) {
+            Some(segment) => Arc::make_mut(segment).push(block),
+            None => {
+                let mut segment = Vec::with_capacity(SEGMENT_LEN);
+                segment.push(block);
+                self.segments.push(Arc::new(segment));
+            }
+        }
+        self.len += 1;
+    }
+    
+    // Takes off the blocks from `height` up, as `Vec::split_off` would
+    pub(crate) fn split_off(&mut self, height: usize) -> Vec<Arc<Block>> {
+        let removed = self.iter_from(height).cloned().collect();
+        self.len = height.min(self.len);
+        self.segments.truncate(self.len.div_ceil(SEGMENT_LEN));
+        if let Some(segment) = self.segments.last_mut() {
+            Arc::make_mut(segment).truncate(self.len - (self.len - 1) / SEGMENT_LEN * SEGMENT_LEN);
+        }
+        removed
+    }
+}
+
+impl Index<usize> for Chain {
+    type Output = Arc<Block>;
+    
+    fn index(&self, height: usize) -> &Arc<Block> {
+        self.get(height).expect("height is on the chain")
+    }
+}
\ No newline at end of file
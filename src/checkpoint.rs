@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+
+pub const CHECKPOINT_MODULE: &str = "native:checkpoint";
+
+// A validator's signed claim that the main-chain block at `height` is `hash`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CheckpointVote {
+    pub height: u64,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Checkpoint {
+    pub height: u64,
+    pub hash: String,
+    // Stake each voting validator held when its vote was committed
+    pub votes: BTreeMap<String, u64>,
+    // Height of the block whose vote took the checkpoint past two thirds of the stake
+    pub justified_at: Option<u64>,
+}
+
+impl Checkpoint {
+    pub fn voted_stake(&self) -> u64 {
+        self.votes.values().sum()
+    }
+    
+    pub fn is_final(&self) -> bool {
+        self.justified_at.is_some()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CheckpointTracker {
+    checkpoints: RwLock<BTreeMap<u64, Checkpoint>>,
+}
+
+impl CheckpointTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn checkpoint(&self, height: u64) -> Option<Checkpoint> {
+        self.checkpoints.read().unwrap().get(&height).cloned()
+    }
+    
+    pub fn has_voted(&self, height: u64, validator: &str) -> bool {
+        self.checkpoints.read().unwrap()
+            .get(&height)
+            .is_some_and(|checkpoint| checkpoint.votes.contains_key(validator))
+    }
+    
+    // The highest checkpoint two thirds of the stake signed
+    pub fn latest_final(&self) -> Option<Checkpoint> {
+        self.checkpoints.read().unwrap()
+            .values()
+            .rev()
+            .find(|checkpoint| checkpoint.is_final())
+            .cloned()
+    }
+    
+    pub fn finalized_height(&self) -> u64 {
+        self.latest_final().map(|checkpoint| checkpoint.height).unwrap_or(0)
+    }
+    
+    // Returns true when this vote is the one that finalizes the checkpoint
+    pub(crate) fn record(&self, validator: &str, vote: &CheckpointVote, stake: u64, total_stake: u64, block_height: u64) -> bool {
+        let mut checkpoints = self.checkpoints.write().unwrap();
+        let checkpoint = checkpoints.entry(vote.height).or_insert_with(|| Checkpoint {
+            height: vote.height,
+            hash: vote.hash.clone(),
+            votes: BTreeMap::new(),
+            justified_at: None,
+        });
+        checkpoint.votes.insert(validator.to_string(), stake);
+        
+        let reached = checkpoint.voted_stake() as u128 * 3 >= total_stake as u128 * 2;
+        if reached && !checkpoint.is_final() {
+            checkpoint.justified_at = Some(block_height);
+            return true;
+        }
+        
+        false
+    }
+    
+    // Undoes `record` for a block leaving the main chain
+    pub(crate) fn revert(&self, validator: &str, vote: &CheckpointVote, block_height: u64) {
+        let mut checkpoints = self.checkpoints.write().unwrap();
+        let Some(checkpoint) = checkpoints.get_mut(&vote.height) else {
+            return;
+        };
+        
+        checkpoint.votes.remove(validator);
+        if checkpoint.justified_at == Some(block_height) {
+            checkpoint.justified_at = None;
+        }
+        
+        if checkpoint.votes.is_empty() {
+            checkpoints.remove(&vote.height);
+        }
+    }
+}
\ No newline at end of file
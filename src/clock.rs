@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+// Time and ids stamped on new blocks and transactions; a simulation swaps in its
+// virtual clock and seeded generator for the task driving it
+#[cfg(feature = "simulation")]
+tokio::task_local! {
+    pub(crate) static SIMULATED: std::sync::Arc<crate::simulation::SimEnv>;
+}
+
+pub(crate) fn now() -> DateTime<Utc> {
+    #[cfg(feature = "simulation")]
+    if let Ok(now) = SIMULATED.try_with(|env| env.now()) {
+        return now;
+    }
+    
+    Utc::now()
+}
+
+pub(crate) fn new_id() -> Uuid {
+    #[cfg(feature = "simulation")]
+    if let Ok(id) = SIMULATED.try_with(|env| env.next_id()) {
+        return id;
+    }
+    
+    Uuid::new_v4()
+}
\ No newline at end of file
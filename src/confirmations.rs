@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::status::TransactionStatus;
+use crate::{Block, DistributedLedger, LedgerError, Result};
+
+// The tip of the main chain; see `DistributedLedger::watch_head`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChainHead {
+    pub height: u64,
+    pub hash: String,
+}
+
+impl ChainHead {
+    pub fn of(block: &Block) -> Self {
+        Self { height: block.header.height, hash: block.header.hash.clone() }
+    }
+}
+
+// Where a transaction sat once it was deep enough
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Confirmation {
+    pub height: u64,
+    pub block_hash: String,
+    // Blocks from the transaction's to the tip, both included
+    pub confirmations: u64,
+}
+
+// Waits for transactions to get buried under enough blocks. Depth is counted afresh on every new head, so a
+// transaction a reorg takes back out of the chain is waited for again until it is included once more
+pub struct ConfirmationTracker {
+    ledger: DistributedLedger,
+}
+
+impl ConfirmationTracker {
+    pub fn new(ledger: DistributedLedger) -> Self {
+        Self { ledger }
+    }
+    
+    // Resolves once the transaction is on the main chain with at least `confirmations` blocks from its own to the
+    // tip; a transaction not yet seen is waited for too. Fails if the transaction is rejected
+    pub async fn wait_for(&self, id: Uuid, confirmations: u64) -> Result<Confirmation> {
+        // Subscribed before the first look, so no change between the look and the wait is missed
+        let mut head = self.ledger.watch_head();
+        let mut events = self.ledger.subscribe();
+        
+        loop {
+            head.borrow_and_update();
+            match self.ledger.confirmation(id).await {
+                Some(confirmation) if confirmation.confirmations >= confirmations => return Ok(confirmation),
+                Some(_) => {}
+                None => {
+                    if let TransactionStatus::Rejected { reason } = self.ledger.get_transaction(id).await.1 {
+                        return Err(LedgerError::InvalidTransaction(format!(
+                            "Transaction {} was rejected: {}",
+                            id, reason,
+                        )));
+                    }
+                }
+            }
+            
+            // A new head can confirm it; an event about it can reject it between blocks. Neither channel closes
+            // while the tracker holds the ledger
+            tokio::select! {
+                _ = head.changed() => {}
+                _ = events.recv() => {}
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use crate::{Transaction, TransactionKind};
+
+// Smallest wave worth handing to the thread pool; a balance change is cheap enough that smaller waves are applied
+// faster on the calling thread
+pub const PARALLEL_WAVE: usize = 256;
+
+// A stretch of a block applied one way
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleStep<'a> {
+    // Consecutive plain transfers, with their balance changes split into waves. Transfers in a wave touch disjoint
+    // accounts, so a wave can be applied in any order; waves go one after another, and a transfer is never in an
+    // earlier wave than one before it in the block that shares an account
+    Parallel {
+        transactions: &'a [Transaction],
+        waves: Vec<Vec<&'a Transaction>>,
+    },
+    // Anything else, applied alone in block order
+    Serial(&'a Transaction),
+}
+
+// The order a block's transactions are applied in. Only plain transfers are spread out; every other kind can read
+// or move balances anywhere, so it stays a barrier between the runs around it. The schedule depends only on the
+// block, so every node applies it the same way
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConflictSchedule<'a> {
+    steps: Vec<ScheduleStep<'a>>,
+}
+
+impl<'a> ConflictSchedule<'a> {
+    pub fn new(transactions: &'a [Transaction]) -> Self {
+        let mut steps = Vec::new();
+        let mut start = 0;
+        for (i, tx) in transactions.iter().enumerate() {
+            if !Self::is_plain_transfer(tx) {
+                if start < i {
+                    steps.push(Self::run(&transactions[start..i]));
+                }
+                steps.push(ScheduleStep::Serial(tx));
+                start = i + 1;
+            }
+        }
+        if start < transactions.len() {
+            steps.push(Self::run(&transactions[start..]));
+        }
+        Self { steps }
+    }
+    
+    pub fn steps(&self) -> &[ScheduleStep<'a>] {
+        &self.steps
+    }
+    
+    // The most waves any run needs; 1 means no two transfers in a run share an account
+    pub fn depth(&self) -> usize {
+        self.steps.iter()
+            .map(|step| match step {
+                ScheduleStep::Parallel { waves, .. } => waves.len(),
+                ScheduleStep::Serial(_) => 1,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+    
+    fn is_plain_transfer(tx: &Transaction) -> bool {
+        matches!(tx.kind, TransactionKind::Transfer)
+    }
+    
+    // Each transfer goes in the wave after the last one that touched either of its accounts
+    fn run(transactions: &'a [Transaction]) -> ScheduleStep<'a> {
+        let mut next_wave: HashMap<&str, usize> = HashMap::new();
+        let mut waves: Vec<Vec<&Transaction>> = Vec::new();
+        for tx in transactions {
+            let wave = next_wave.get(tx.from.as_str()).copied().unwrap_or(0)
+                .max(next_wave.get(tx.to.as_str()).copied().unwrap_or(0));
+            if wave == waves.len() {
+                waves.push(Vec::new());
+            }
+            waves[wave].push(tx);
+            next_wave.insert(&tx.from, wave + 1);
+            next_wave.insert(&tx.to, wave + 1);
+        }
+        ScheduleStep::Parallel { transactions, waves }
+    }
+}
\ No newline at end of file
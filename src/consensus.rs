@@ -0,0 +1,112 @@
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+
+use crate::params::ChainParams;
+use crate::staking::StakeRegistry;
+use crate::{Block, LedgerError, Result};
+
+pub trait Consensus: Send + Sync {
+    fn name(&self) -> &'static str;
+    
+    // Whether this node may produce the block on top of `parent`; nodes skip slots that aren't theirs
+    fn can_propose(&self, _parent: &Block) -> bool {
+        true
+    }
+    
+    // Seals `block`, a child of `parent` the ledger assembled with `Block::child_of`, so this engine considers it valid
+    fn propose(&self, parent: &Block, block: Block, params: &ChainParams) -> Result<Block>;
+    
+    // As `propose`, also returning how many hashes sealing took; engines that don't mine try none
+    fn propose_counting_hashes(&self, parent: &Block, block: Block, params: &ChainParams) -> Result<(Block, u64)> {
+        self.propose(parent, block, params).map(|block| (block, 0))
+    }
+    
+    fn validate(&self, block: &Block, parent: &Block, params: &ChainParams) -> Result<()>;
+    
+    // Handed the ledger's bonded validator set at construction, for engines that elect by stake
+    fn attach_stakes(&self, _stakes: Arc<StakeRegistry>) {}
+    
+    // Balances credited when the block is applied, e.g. staking rewards
+    fn block_rewards(&self, _block: &Block) -> Vec<(String, u64)> {
+        Vec::new()
+    }
+    
+    // Weight a block adds to its chain when competing forks are compared
+    fn work(&self, _block: &Block, _params: &ChainParams) -> u128 {
+        1
+    }
+    
+    // Blocks this far below the tip can no longer be reorganised; BFT-style engines return 0
+    fn finality_depth(&self, params: &ChainParams) -> u64 {
+        params.finality_depth
+    }
+    
+    // Called by the chain writer right before the block is appended; an error aborts the append
+    fn finalize(&self, _block: &Block) -> Result<()> {
+        Ok(())
+    }
+}
+
+// Proposer rounds that have timed out since `parent` as of `now`; each passes the slot to the next proposer
+pub fn round_at(parent: &Block, now: DateTime<Utc>, timeout_ms: Option<u64>) -> u64 {
+    let Some(timeout) = timeout_ms.filter(|timeout| *timeout > 0) else {
+        return 0;
+    };
+    
+    let elapsed = (now - parent.header.timestamp).num_milliseconds().max(0) as u64;
+    elapsed / timeout
+}
+
+// A block may only claim a round once every proposer ahead of it has had its full timeout
+pub(crate) fn check_round(block: &Block, parent: &Block, timeout_ms: Option<u64>) -> Result<()> {
+    if block.header.round > round_at(parent, block.header.timestamp, timeout_ms) {
+        return Err(LedgerError::BlockValidationFailed(format!(
+            "Block claims round {} before the proposers ahead of it timed out",
+            block.header.round,
+        )));
+    }
+    
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+pub struct ProofOfWork;
+
+impl Consensus for ProofOfWork {
+    fn name(&self) -> &'static str {
+        "proof-of-work"
+    }
+    
+    fn propose(&self, parent: &Block, block: Block, params: &ChainParams) -> Result<Block> {
+        self.propose_counting_hashes(parent, block, params).map(|(block, _)| block)
+    }
+    
+    fn propose_counting_hashes(&self, _parent: &Block, mut block: Block, params: &ChainParams) -> Result<(Block, u64)> {
+        let hashes = block.mine(params.difficulty);
+        Ok((block, hashes))
+    }
+    
+    // Each leading zero hex digit makes a hash 16 times harder to find
+    fn work(&self, block: &Block, _params: &ChainParams) -> u128 {
+        16u128.saturating_pow(block.header.difficulty as u32)
+    }
+    
+    fn validate(&self, block: &Block, parent: &Block, params: &ChainParams) -> Result<()> {
+        block.validate(Some(parent))?;
+        
+        if block.header.difficulty != params.difficulty {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Block difficulty {} does not match the expected {}",
+                block.header.difficulty, params.difficulty,
+            )));
+        }
+        
+        if !block.header.hash.starts_with(&"0".repeat(block.header.difficulty)) {
+            return Err(LedgerError::BlockValidationFailed(
+                "Block hash does not meet the difficulty target".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+}
\ No newline at end of file
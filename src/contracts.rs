@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+use wasmi::{Caller, Config, Engine, Extern, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::{LedgerError, Result, Transaction};
+
+pub const CONTRACT_MODULE: &str = "contract";
+const ADDRESS_PREFIX: &str = "contract:";
+const MAX_MEMORY_BYTES: usize = 1 << 20;
+// Largest key, value, topic or address a host function copies in or out
+const MAX_HOST_BYTES: usize = 64 * 1024;
+// Fuel a host function burns on top of the instructions around it, plus one per byte it copies
+const HOST_CALL_GAS: u64 = 100;
+
+// Values by contract address and key
+type Storage = DashMap<(String, Vec<u8>), Vec<u8>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContractEvent {
+    pub topic: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Receipt {
+    pub contract: String,
+    pub success: bool,
+    pub gas_used: u64,
+    // The trap, e.g. running out of gas, when the call failed; its writes and transfers are discarded
+    pub error: Option<String>,
+    pub events: Vec<ContractEvent>,
+}
+
+// What a committed call changed, so a reorg can put it back
+struct Journal {
+    // Each key's value before the call
+    storage: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+    transfers: Vec<(String, u64)>,
+}
+
+// Everything a running call sees and buffers; nothing reaches the ledger unless the call returns
+struct HostState {
+    contract: String,
+    caller: String,
+    value: u64,
+    input: Vec<u8>,
+    storage: Arc<Storage>,
+    writes: HashMap<Vec<u8>, Vec<u8>>,
+    // Contract balance not yet promised to a transfer
+    available: u64,
+    transfers: Vec<(String, u64)>,
+    events: Vec<ContractEvent>,
+    limits: StoreLimits,
+}
+
+// Deployed WASM modules and their key-value state
+pub struct ContractStore {
+    engine: Engine,
+    modules: DashMap<String, Module>,
+    storage: Arc<Storage>,
+    receipts: DashMap<Uuid, Receipt>,
+    journals: DashMap<Uuid, Journal>,
+}
+
+impl Default for ContractStore {
+    fn default() -> Self {
+        // Floats are off so every node computes the same results
+        let mut config = Config::default();
+        config.consume_fuel(true).floats(false);
+        
+        Self {
+            engine: Engine::new(&config),
+            modules: DashMap::new(),
+            storage: Arc::new(DashMap::new()),
+            receipts: DashMap::new(),
+            journals: DashMap::new(),
+        }
+    }
+}
+
+impl ContractStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // Where a deploy transaction puts its contract
+    pub fn address_for(tx: &Transaction) -> String {
+        let digest = Sha256::digest(tx.id.as_bytes());
+        format!("{}{}", ADDRESS_PREFIX, &hex::encode(digest)[..40])
+    }
+    
+    pub fn is_contract(address: &str) -> bool {
+        address.starts_with(ADDRESS_PREFIX)
+    }
+    
+    pub fn exists(&self, address: &str) -> bool {
+        self.modules.contains_key(address)
+    }
+    
+    pub fn storage(&self, address: &str, key: &[u8]) -> Option<Vec<u8>> {
+        self.storage.get(&(address.to_string(), key.to_vec())).map(|value| value.clone())
+    }
+    
+    pub fn receipt(&self, tx_id: Uuid) -> Option<Receipt> {
+        self.receipts.get(&tx_id).map(|receipt| receipt.clone())
+    }
+    
+    pub fn validate_code(&self, code: &[u8]) -> std::result::Result<(), String> {
+        Module::new(&self.engine, code).map(|_| ()).map_err(|e| format!("Invalid contract code: {}", e))
+    }
+    
+    pub(crate) fn deploy(&self, address: &str, code: &[u8]) -> Result<()> {
+        let module = Module::new(&self.engine, code)
+            .map_err(|e| LedgerError::InvalidTransaction(format!("Invalid contract code: {}", e)))?;
+        self.modules.insert(address.to_string(), module);
+        Ok(())
+    }
+    
+    // Calls are reverted first, so the contract's storage is already empty
+    pub(crate) fn undeploy(&self, address: &str) {
+        self.modules.remove(address);
+    }
+    
+    // Runs `method` with `tx.amount` already credited to the contract. On success the writes are
+    // committed and the returned transfers are owed out of the contract's balance
+    pub(crate) fn call(&self, tx: &Transaction, method: &str, input: &[u8], gas_limit: u64, balance: u64) -> (Receipt, Vec<(String, u64)>) {
+        let state = HostState {
+            contract: tx.to.clone(),
+            caller: tx.from.clone(),
+            value: tx.amount,
+            input: input.to_vec(),
+            storage: Arc::clone(&self.storage),
+            writes: HashMap::new(),
+            available: balance,
+            transfers: Vec::new(),
+            events: Vec::new(),
+            limits: StoreLimitsBuilder::new().memory_size(MAX_MEMORY_BYTES).instances(1).build(),
+        };
+        
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(gas_limit).expect("fuel metering is enabled");
+        
+        let result = match self.modules.get(&tx.to) {
+            Some(module) => Self::run(&mut store, &module, method),
+            None => Err(wasmi::Error::new(format!("No contract at {}", tx.to))),
+        };
+        let gas_used = gas_limit - store.get_fuel().unwrap_or(0);
+        let state = store.into_data();
+        
+        let mut receipt = Receipt {
+            contract: state.contract,
+            success: result.is_ok(),
+            gas_used,
+            error: result.err().map(|e| e.to_string()),
+            events: Vec::new(),
+        };
+        if !receipt.success {
+            self.receipts.insert(tx.id, receipt.clone());
+            return (receipt, Vec::new());
+        }
+        
+        let mut journal = Journal {
+            storage: Vec::new(),
+            transfers: state.transfers.clone(),
+        };
+        for (key, value) in state.writes {
+            let previous = self.storage.insert((receipt.contract.clone(), key.clone()), value);
+            journal.storage.push((key, previous));
+        }
+        
+        receipt.events = state.events;
+        self.receipts.insert(tx.id, receipt.clone());
+        self.journals.insert(tx.id, journal);
+        (receipt, state.transfers)
+    }
+    
+    // Undoes a call's storage writes; returns its receipt and the transfers to take back
+    pub(crate) fn revert(&self, tx_id: Uuid) -> Option<(Receipt, Vec<(String, u64)>)> {
+        let (_, receipt) = self.receipts.remove(&tx_id)?;
+        let Some((_, journal)) = self.journals.remove(&tx_id) else {
+            return Some((receipt, Vec::new()));
+        };
+        
+        for (key, previous) in journal.storage.into_iter().rev() {
+            let key = (receipt.contract.clone(), key);
+            match previous {
+                Some(value) => self.storage.insert(key, value),
+                None => self.storage.remove(&key).map(|(_, value)| value),
+            };
+        }
+        Some((receipt, journal.transfers))
+    }
+    
+    fn run(store: &mut Store<HostState>, module: &Module, method: &str) -> std::result::Result<(), wasmi::Error> {
+        let linker = Self::linker(store.engine())?;
+        let instance = linker.instantiate(&mut *store, module)?.start(&mut *store)?;
+        let func = instance.get_typed_func::<(), ()>(&*store, method)?;
+        func.call(&mut *store, ())
+    }
+    
+    fn linker(engine: &Engine) -> std::result::Result<Linker<HostState>, wasmi::Error> {
+        let mut linker = Linker::new(engine);
+        
+        // Copies the value stored under a key into memory; returns its full length, or -1 if unset
+        linker.func_wrap("env", "storage_read", |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32, out_ptr: i32, out_cap: i32| {
+            let key = read_bytes(&mut caller, key_ptr, key_len)?;
+            let state = caller.data();
+            let value = match state.writes.get(&key) {
+                Some(value) => Some(value.clone()),
+                None => state.storage.get(&(state.contract.clone(), key)).map(|value| value.clone()),
+            };
+            match value {
+                Some(value) => write_bytes(&mut caller, out_ptr, out_cap, &value),
+                None => Ok(-1),
+            }
+        })?;
+        
+        linker.func_wrap("env", "storage_write", |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32, value_ptr: i32, value_len: i32| {
+            let key = read_bytes(&mut caller, key_ptr, key_len)?;
+            let value = read_bytes(&mut caller, value_ptr, value_len)?;
+            caller.data_mut().writes.insert(key, value);
+            Ok(())
+        })?;
+        
+        // Pays out of the contract's balance; returns 0, or 1 if the balance can't cover it
+        linker.func_wrap("env", "transfer", |mut caller: Caller<'_, HostState>, to_ptr: i32, to_len: i32, amount: i64| {
+            let to = String::from_utf8(read_bytes(&mut caller, to_ptr, to_len)?)
+                .map_err(|_| wasmi::Error::new("Transfer recipient is not UTF-8"))?;
+            let state = caller.data_mut();
+            let amount = amount as u64;
+            if to.is_empty() || to == state.contract || amount > state.available {
+                return Ok(1);
+            }
+            
+            state.available -= amount;
+            state.transfers.push((to, amount));
+            Ok(0)
+        })?;
+        
+        linker.func_wrap("env", "emit", |mut caller: Caller<'_, HostState>, topic_ptr: i32, topic_len: i32, data_ptr: i32, data_len: i32| {
+            let topic = String::from_utf8(read_bytes(&mut caller, topic_ptr, topic_len)?)
+                .map_err(|_| wasmi::Error::new("Event topic is not UTF-8"))?;
+            let data = read_bytes(&mut caller, data_ptr, data_len)?;
+            caller.data_mut().events.push(ContractEvent { topic, data });
+            Ok(())
+        })?;
+        
+        linker.func_wrap("env", "input", |mut caller: Caller<'_, HostState>, out_ptr: i32, out_cap: i32| {
+            let input = caller.data().input.clone();
+            write_bytes(&mut caller, out_ptr, out_cap, &input)
+        })?;
+        
+        linker.func_wrap("env", "caller", |mut caller: Caller<'_, HostState>, out_ptr: i32, out_cap: i32| {
+            let address = caller.data().caller.clone().into_bytes();
+            write_bytes(&mut caller, out_ptr, out_cap, &address)
+        })?;
+        
+        linker.func_wrap("env", "value", |caller: Caller<'_, HostState>| caller.data().value as i64)?;
+        
+        Ok(linker)
+    }
+}
+
+fn charge(caller: &mut Caller<'_, HostState>, bytes: usize) -> std::result::Result<(), wasmi::Error> {
+    let cost = HOST_CALL_GAS + bytes as u64;
+    let fuel = caller.get_fuel().map_err(|e| wasmi::Error::new(e.to_string()))?;
+    if fuel < cost {
+        return Err(wasmi::Error::new("Out of gas"));
+    }
+    caller.set_fuel(fuel - cost).map_err(|e| wasmi::Error::new(e.to_string()))
+}
+
+fn span(ptr: i32, len: i32) -> std::result::Result<(usize, usize), wasmi::Error> {
+    if ptr < 0 || len < 0 || len as usize > MAX_HOST_BYTES {
+        return Err(wasmi::Error::new("Host buffer out of range"));
+    }
+    Ok((ptr as usize, len as usize))
+}
+
+fn read_bytes(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> std::result::Result<Vec<u8>, wasmi::Error> {
+    let (ptr, len) = span(ptr, len)?;
+    charge(caller, len)?;
+    
+    let memory = caller.get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| wasmi::Error::new("Contract exports no memory"))?;
+    let mut buffer = vec![0; len];
+    memory.read(&*caller, ptr, &mut buffer).map_err(|e| wasmi::Error::new(e.to_string()))?;
+    Ok(buffer)
+}
+
+// Writes as much of `bytes` as fits in `cap`; returns the full length so the contract can retry larger
+fn write_bytes(caller: &mut Caller<'_, HostState>, ptr: i32, cap: i32, bytes: &[u8]) -> std::result::Result<i32, wasmi::Error> {
+    let (ptr, cap) = span(ptr, cap)?;
+    let len = bytes.len().min(cap);
+    charge(caller, len)?;
+    
+    let memory = caller.get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| wasmi::Error::new("Contract exports no memory"))?;
+    memory.write(&mut *caller, ptr, &bytes[..len]).map_err(|e| wasmi::Error::new(e.to_string()))?;
+    Ok(bytes.len() as i32)
+}
\ No newline at end of file
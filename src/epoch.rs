@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::params::ChainParams;
+use crate::schedules::Installment;
+use crate::staking::StakeRegistry;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum EpochLength {
+    Blocks(u64),
+    // Counted from the genesis timestamp (the Unix epoch) using block timestamps, so every node agrees
+    Millis(u64),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EpochSchedule {
+    pub length: EpochLength,
+    pub epochs_per_era: u64,
+}
+
+impl EpochSchedule {
+    pub fn epoch_at(&self, height: u64, timestamp: DateTime<Utc>) -> u64 {
+        match self.length {
+            EpochLength::Blocks(blocks) => height / blocks,
+            EpochLength::Millis(millis) => timestamp.timestamp_millis().max(0) as u64 / millis,
+        }
+    }
+    
+    pub fn era_of(&self, epoch: u64) -> u64 {
+        epoch / self.epochs_per_era
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Epoch {
+    pub number: u64,
+    pub era: u64,
+    pub start_height: u64,
+    pub start_time: DateTime<Utc>,
+}
+
+impl Epoch {
+    // A new era starts with this epoch
+    pub fn opens_era(&self, previous: &Epoch) -> bool {
+        self.era != previous.era
+    }
+}
+
+// State changes a hook asks for at an epoch boundary; the ledger applies and records them
+#[derive(Debug, Clone, Default)]
+pub struct EpochEffects {
+    pub credits: Vec<(String, u64)>,
+    pub params: Option<ChainParams>,
+}
+
+// Runs once per epoch, while the epoch's first block is applied; every node must register the same hooks
+pub trait EpochHook: Send + Sync {
+    fn name(&self) -> &'static str;
+    
+    fn on_epoch(&self, epoch: &Epoch, previous: &Epoch, stakes: &StakeRegistry, params: &ChainParams) -> EpochEffects;
+}
+
+// What an epoch boundary changed, kept so a reorg can undo it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EpochRecord {
+    pub epoch: Epoch,
+    // Active validators and their stake as the epoch opened
+    pub validators: Vec<(String, u64)>,
+    pub credits: Vec<(String, u64)>,
+    // Parameters replaced by a hook or proposal, restored if the boundary block is reverted
+    pub previous_params: Option<ChainParams>,
+    // Governance proposals this boundary enacted or rejected
+    #[serde(default)]
+    pub decided: Vec<Uuid>,
+    // Standing orders that fell due as the epoch opened
+    #[serde(default)]
+    pub installments: Vec<Installment>,
+}
+
+// Pays a fixed amount per epoch, split by stake across the active validators and their delegators
+#[derive(Debug, Clone)]
+pub struct EpochRewards {
+    pub per_epoch: u64,
+}
+
+impl EpochHook for EpochRewards {
+    fn name(&self) -> &'static str {
+        "epoch-rewards"
+    }
+    
+    fn on_epoch(&self, _epoch: &Epoch, _previous: &Epoch, stakes: &StakeRegistry, _params: &ChainParams) -> EpochEffects {
+        let total = stakes.total_stake();
+        if total == 0 {
+            return EpochEffects::default();
+        }
+        
+        // Rounding dust stays unminted
+        let credits = stakes.active_validators()
+            .into_iter()
+            .flat_map(|validator| {
+                let share = self.per_epoch as u128 * validator.total_stake() as u128 / total as u128;
+                stakes.reward_shares(&validator.address, share as u64)
+            })
+            .collect();
+        
+        EpochEffects { credits, params: None }
+    }
+}
\ No newline at end of file
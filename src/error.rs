@@ -8,6 +8,9 @@ pub enum LedgerError {
     #[error("Block validation failed: {0}")]
     BlockValidationFailed(String),
     
+    #[error("Consensus error: {0}")]
+    Consensus(String),
+    
     #[error("Insufficient balance for transaction")]
     InsufficientBalance,
     
@@ -17,6 +20,21 @@ pub enum LedgerError {
     #[error("Block already exists")]
     DuplicateBlock,
     
+    #[error("Invalid proof: {0}")]
+    InvalidProof(String),
+    
+    #[error("Invalid chain parameters: {0}")]
+    InvalidParameters(String),
+    
+    #[error("Query aborted: {0}")]
+    QueryAborted(String),
+    
+    #[error("Invalid listen address: {0}")]
+    InvalidListenAddress(String),
+    
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    
     #[error("Performance limit exceeded: {0}")]
     PerformanceLimitExceeded(String),
     
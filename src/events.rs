@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::fork::ReorgEvent;
+use crate::status::TransactionStatus;
+use crate::Transaction;
+
+// Events a subscriber can fall behind by before it starts missing them
+pub const EVENT_CAPACITY: usize = 1024;
+
+// What happens to the ledger, in the order it happens; see `DistributedLedger::subscribe`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum LedgerEvent {
+    // Pooled, alone or in a bundle
+    TransactionAdmitted { transaction: Transaction },
+    // Dropped from the pool without committing
+    TransactionRejected { transaction: Transaction, reason: String },
+    // Put on the main chain, whether by this node's block, an imported one or a reorg
+    TransactionIncluded { transaction: Transaction, height: u64, block_hash: String },
+    // Its block fell below the finalized height
+    TransactionFinalized { transaction: Transaction, height: u64, block_hash: String },
+    // This node extended the chain
+    BlockProduced { height: u64, hash: String, transactions: usize },
+    ReorgOccurred(ReorgEvent),
+}
+
+impl LedgerEvent {
+    // The transaction the event is about and where it now stands
+    pub fn transaction_status(&self) -> Option<(&Transaction, TransactionStatus)> {
+        match self {
+            LedgerEvent::TransactionAdmitted { transaction } => Some((transaction, TransactionStatus::Pending)),
+            LedgerEvent::TransactionRejected { transaction, reason } => {
+                Some((transaction, TransactionStatus::Rejected { reason: reason.clone() }))
+            }
+            LedgerEvent::TransactionIncluded { transaction, height, block_hash } => {
+                Some((transaction, TransactionStatus::Included { height: *height, block_hash: block_hash.clone() }))
+            }
+            LedgerEvent::TransactionFinalized { transaction, height, block_hash } => {
+                Some((transaction, TransactionStatus::Finalized { height: *height, block_hash: block_hash.clone() }))
+            }
+            _ => None,
+        }
+    }
+}
+
+// A transaction moving on to `status`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatusUpdate {
+    pub transaction: Transaction,
+    pub status: TransactionStatus,
+}
+
+// Status changes of the transactions sending from or paying into one account; see
+// `DistributedLedger::subscribe_address`
+pub struct AddressSubscription {
+    address: String,
+    events: broadcast::Receiver<LedgerEvent>,
+}
+
+impl AddressSubscription {
+    pub(crate) fn new(address: String, events: broadcast::Receiver<LedgerEvent>) -> Self {
+        Self { address, events }
+    }
+    
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+    
+    // Waits for the account's next update; None once the ledger is gone. Updates missed by falling more than
+    // `EVENT_CAPACITY` events behind are skipped
+    pub async fn next(&mut self) -> Option<StatusUpdate> {
+        loop {
+            let event = match self.events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            };
+            
+            if let Some((transaction, status)) = event.transaction_status() {
+                if crate::history::parties(transaction).contains(self.address.as_str()) {
+                    return Some(StatusUpdate { transaction: transaction.clone(), status });
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{Block, Transaction};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReorgEvent {
+    // Height of the last block both chains share
+    pub fork_height: u64,
+    pub old_tip: String,
+    pub new_tip: String,
+    // Hashes of the blocks taken off and put on the main chain, in height order
+    pub reverted: Vec<String>,
+    pub applied: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum BlockImport {
+    Extended,
+    SideChain,
+    Reorganized(ReorgEvent),
+    // Held until the named ancestor arrives; a sync layer should fetch it
+    Orphaned {
+        missing_ancestor: String,
+    },
+}
+
+// The end of a chain this node knows of: the main chain's tip, or a side block nothing builds on yet
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChainTip {
+    pub hash: String,
+    pub height: u64,
+    // Height of the last main-chain block the tip descends from; its own height for the main tip
+    pub fork_height: u64,
+    pub main: bool,
+}
+
+// Side blocks from just above the main chain up to `tip`, oldest first; empty for a main-chain block
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Branch {
+    pub tip: String,
+    pub fork_height: u64,
+    pub blocks: Vec<Block>,
+}
+
+// Transactions two chains hold above the last block they share
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BranchComparison {
+    pub common_ancestor: String,
+    pub common_height: u64,
+    pub only_in_first: Vec<Transaction>,
+    pub only_in_second: Vec<Transaction>,
+    // Committed on both sides, possibly at different heights
+    pub in_both: Vec<Uuid>,
+}
\ No newline at end of file
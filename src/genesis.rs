@@ -0,0 +1,238 @@
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::block::BlockHeader;
+use crate::bloom::AddressBloom;
+use crate::hybrid::ConsensusSchedule;
+use crate::keys::Keypair;
+use crate::params::ChainParams;
+use crate::utxo::LedgerModel;
+use crate::vesting::VestingSchedule;
+use crate::{Block, LedgerError, Result};
+
+pub const DEV_ACCOUNTS: [&str; 5] = ["alice", "bob", "charlie", "diana", "eve"];
+pub const DEV_ACCOUNT_BALANCE: u64 = 1_000_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GenesisAllocation {
+    pub address: String,
+    pub balance: u64,
+    // Locks the whole balance until it vests
+    #[serde(default)]
+    pub vesting: Option<VestingSchedule>,
+}
+
+// Stake bonded from genesis so a proof-of-stake network has proposers at height 1
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GenesisValidator {
+    pub address: String,
+    pub public_key: String,
+    pub stake: u64,
+    #[serde(default)]
+    pub vrf_key: Option<String>,
+}
+
+// Named accounts whose keys are derived from `seed`, for local networks and tests
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DevAccounts {
+    pub seed: String,
+    pub accounts: Vec<GenesisAllocation>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GenesisConfig {
+    // Transactions signed for another chain id are rejected; also changes the genesis hash
+    #[serde(default)]
+    pub chain_id: String,
+    pub params: ChainParams,
+    #[serde(default)]
+    pub allocations: Vec<GenesisAllocation>,
+    #[serde(default)]
+    pub dev: Option<DevAccounts>,
+    #[serde(default)]
+    pub validators: Vec<GenesisValidator>,
+    // Engines to switch between at fixed heights; plain proof-of-work when unset
+    #[serde(default)]
+    pub consensus: Option<ConsensusSchedule>,
+    // Fixed for the chain's lifetime
+    #[serde(default)]
+    pub model: LedgerModel,
+}
+
+impl GenesisConfig {
+    pub fn new(params: ChainParams) -> Self {
+        Self {
+            params,
+            ..Default::default()
+        }
+    }
+    
+    pub fn dev(seed: &str) -> Self {
+        let accounts = DEV_ACCOUNTS.iter()
+            .map(|name| GenesisAllocation {
+                address: name.to_string(),
+                balance: DEV_ACCOUNT_BALANCE,
+                vesting: None,
+            })
+            .collect();
+        
+        Self {
+            dev: Some(DevAccounts {
+                seed: seed.to_string(),
+                accounts,
+            }),
+            ..Default::default()
+        }
+    }
+    
+    pub fn with_chain_id(mut self, chain_id: &str) -> Self {
+        self.chain_id = chain_id.to_string();
+        self
+    }
+    
+    pub fn with_consensus_schedule(mut self, schedule: ConsensusSchedule) -> Self {
+        self.consensus = Some(schedule);
+        self
+    }
+    
+    pub fn with_model(mut self, model: LedgerModel) -> Self {
+        self.model = model;
+        self
+    }
+    
+    pub fn with_allocation(mut self, address: &str, balance: u64) -> Self {
+        self.allocations.push(GenesisAllocation {
+            address: address.to_string(),
+            balance,
+            vesting: None,
+        });
+        self
+    }
+    
+    pub fn with_vesting_allocation(mut self, address: &str, balance: u64, vesting: VestingSchedule) -> Self {
+        self.allocations.push(GenesisAllocation {
+            address: address.to_string(),
+            balance,
+            vesting: Some(vesting),
+        });
+        self
+    }
+    
+    pub fn with_validator(mut self, address: &str, public_key: &str, stake: u64) -> Self {
+        self.validators.push(GenesisValidator {
+            address: address.to_string(),
+            public_key: public_key.to_string(),
+            stake,
+            vrf_key: None,
+        });
+        self
+    }
+    
+    // Registers both the signing and the VRF key of `keypair`
+    pub fn with_validator_keypair(mut self, address: &str, keypair: &Keypair, stake: u64) -> Self {
+        self.validators.push(GenesisValidator {
+            address: address.to_string(),
+            public_key: keypair.public_key(),
+            stake,
+            vrf_key: Some(keypair.vrf_public_key()),
+        });
+        self
+    }
+    
+    pub fn dev_keypair(&self, name: &str) -> Option<Keypair> {
+        let dev = self.dev.as_ref()?;
+        dev.accounts.iter()
+            .any(|account| account.address == name)
+            .then(|| Keypair::from_seed(&dev.seed, name))
+    }
+    
+    // Every node built from the same config starts from the same block, so their chains can be compared
+    pub fn block(&self) -> Block {
+        let config = serde_json::to_vec(self).expect("genesis config serializes");
+        let digest = Sha256::digest(&config);
+        let mut id = [0u8; 16];
+        id.copy_from_slice(&digest[..16]);
+        
+        let header = BlockHeader {
+            id: Uuid::from_bytes(id),
+            height: 0,
+            previous_hash: String::new(),
+            merkle_root: String::new(),
+            state_root: String::new(),
+            timestamp: DateTime::UNIX_EPOCH,
+            nonce: 0,
+            difficulty: self.params.difficulty,
+            producer: None,
+            round: 0,
+            vrf_output: None,
+            vrf_proof: None,
+            hash: String::new(),
+            producer_signature: None,
+            weight: 0,
+            address_bloom: AddressBloom::default(),
+            skipped_slots: 0,
+        };
+        let mut block = Block::new(String::new(), Vec::new());
+        block.header = header;
+        block.seal();
+        block
+    }
+    
+    pub fn validate(&self) -> Result<()> {
+        self.params.validate()?;
+        
+        if let Some(schedule) = &self.consensus {
+            schedule.validate()?;
+        }
+        
+        let dev_accounts = self.dev.iter().flat_map(|dev| dev.accounts.iter());
+        let mut seen = std::collections::HashSet::new();
+        
+        for allocation in self.allocations.iter().chain(dev_accounts) {
+            if allocation.address.is_empty() {
+                return Err(LedgerError::InvalidParameters(
+                    "Genesis allocation address cannot be empty".to_string(),
+                ));
+            }
+            
+            if !seen.insert(allocation.address.as_str()) {
+                return Err(LedgerError::InvalidParameters(format!(
+                    "Duplicate genesis allocation for {}",
+                    allocation.address,
+                )));
+            }
+        }
+        
+        if self.model == LedgerModel::Utxo && self.allocations.iter().any(|allocation| allocation.vesting.is_some()) {
+            return Err(LedgerError::InvalidParameters(
+                "Vesting allocations need the account model, where balances are locked".to_string(),
+            ));
+        }
+        
+        if self.model == LedgerModel::Utxo && !self.validators.is_empty() {
+            return Err(LedgerError::InvalidParameters(
+                "The UTXO model has no staking, so genesis cannot bond validators".to_string(),
+            ));
+        }
+        
+        let mut seen = std::collections::HashSet::new();
+        for validator in &self.validators {
+            if validator.address.is_empty() || validator.public_key.is_empty() || validator.stake == 0 {
+                return Err(LedgerError::InvalidParameters(
+                    "Genesis validators need an address, a public key and stake".to_string(),
+                ));
+            }
+            
+            if !seen.insert(validator.address.as_str()) {
+                return Err(LedgerError::InvalidParameters(format!(
+                    "Duplicate genesis validator {}",
+                    validator.address,
+                )));
+            }
+        }
+        
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::params::ChainParams;
+use crate::{LedgerError, Result};
+
+pub const GOVERNANCE_MODULE: &str = "native:governance";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ProposalAction {
+    // Registers a validator that can then bond stake and be delegated to
+    AddValidator {
+        address: String,
+        public_key: String,
+        #[serde(default)]
+        vrf_key: Option<String>,
+    },
+    // Jails the validator indefinitely; its bonds stay so stakers can unstake
+    RemoveValidator {
+        address: String,
+    },
+    ChangeParams(Box<ChainParams>),
+}
+
+impl ProposalAction {
+    // Checks what can be verified without chain state
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ProposalAction::AddValidator { address, public_key, .. } => {
+                if address.is_empty() || public_key.is_empty() {
+                    return Err(LedgerError::InvalidTransaction(
+                        "New validators need an address and a public key".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            ProposalAction::RemoveValidator { address } if address.is_empty() => Err(LedgerError::InvalidTransaction(
+                "Validator to remove cannot be empty".to_string(),
+            )),
+            ProposalAction::RemoveValidator { .. } => Ok(()),
+            ProposalAction::ChangeParams(params) => params.validate(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ProposalStatus {
+    Open,
+    Enacted { epoch: u64 },
+    // More than a third of the stake voted against, so it could never pass
+    Rejected { epoch: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Proposal {
+    // The proposing transaction's id
+    pub id: Uuid,
+    pub proposer: String,
+    pub action: ProposalAction,
+    pub proposed_at: u64,
+    // Approval by validator address
+    pub votes: BTreeMap<String, bool>,
+    pub status: ProposalStatus,
+    // Jail a removed validator served before, restored if the enactment is reverted
+    #[serde(default)]
+    pub(crate) previous_jail: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Governance {
+    proposals: RwLock<BTreeMap<Uuid, Proposal>>,
+}
+
+impl Governance {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn proposal(&self, id: Uuid) -> Option<Proposal> {
+        self.proposals.read().unwrap().get(&id).cloned()
+    }
+    
+    // Open proposals, oldest first by id so every node tallies them in the same order
+    pub fn open_proposals(&self) -> Vec<Proposal> {
+        self.proposals.read().unwrap()
+            .values()
+            .filter(|proposal| proposal.status == ProposalStatus::Open)
+            .cloned()
+            .collect()
+    }
+    
+    pub(crate) fn propose(&self, id: Uuid, proposer: &str, action: ProposalAction, height: u64) {
+        self.proposals.write().unwrap().insert(id, Proposal {
+            id,
+            proposer: proposer.to_string(),
+            action,
+            proposed_at: height,
+            votes: BTreeMap::new(),
+            status: ProposalStatus::Open,
+            previous_jail: None,
+        });
+    }
+    
+    pub(crate) fn withdraw(&self, id: Uuid) {
+        self.proposals.write().unwrap().remove(&id);
+    }
+    
+    pub(crate) fn vote(&self, id: Uuid, voter: &str, approve: bool) {
+        if let Some(proposal) = self.proposals.write().unwrap().get_mut(&id) {
+            proposal.votes.insert(voter.to_string(), approve);
+        }
+    }
+    
+    pub(crate) fn unvote(&self, id: Uuid, voter: &str) {
+        if let Some(proposal) = self.proposals.write().unwrap().get_mut(&id) {
+            proposal.votes.remove(voter);
+        }
+    }
+    
+    pub(crate) fn set_status(&self, id: Uuid, status: ProposalStatus, previous_jail: Option<u64>) {
+        if let Some(proposal) = self.proposals.write().unwrap().get_mut(&id) {
+            proposal.status = status;
+            proposal.previous_jail = previous_jail;
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,92 @@
+use std::time::Duration;
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{error, info};
+
+use crate::listen::{Connection, ListenAddr, Listener};
+use crate::{DistributedLedger, Result};
+
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    pub max_heartbeat_age: Duration,
+    pub max_queue_utilization: f64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            max_heartbeat_age: Duration::from_secs(1),
+            max_queue_utilization: 0.9,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub live: bool,
+    pub ready: bool,
+    pub processor_running: bool,
+    pub paused: bool,
+    pub heartbeat_age_ms: Option<u64>,
+    pub pending_transactions: usize,
+    pub queue_utilization: f64,
+}
+
+pub async fn serve_probes(ledger: DistributedLedger, addrs: &[ListenAddr], config: HealthConfig) -> Result<()> {
+    // Bind everything up front so a bad address fails the call instead of a background task
+    let mut listeners = Vec::with_capacity(addrs.len());
+    for addr in addrs {
+        let listener = Listener::bind(addr).await.map_err(anyhow::Error::from)?;
+        listeners.push((addr.clone(), listener));
+    }
+    
+    for (addr, listener) in listeners {
+        info!("Serving health probes on {}", addr);
+        let ledger = ledger.clone();
+        let config = config.clone();
+        
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok(stream) => {
+                        let report = ledger.health(&config);
+                        tokio::spawn(async move {
+                            if let Err(e) = respond(stream, report).await {
+                                error!("Health probe connection failed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => error!("Failed to accept health probe connection on {}: {}", addr, e),
+                }
+            }
+        });
+    }
+    
+    Ok(())
+}
+
+async fn respond(mut stream: Box<dyn Connection>, report: HealthReport) -> std::io::Result<()> {
+    let mut buf = [0u8; 1024];
+    let read = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..read]);
+    let path = request.split_whitespace().nth(1).unwrap_or("");
+    
+    let (status, body) = match path {
+        "/healthz" => (report.live, serde_json::to_string(&report)?),
+        "/readyz" => (report.ready, serde_json::to_string(&report)?),
+        _ => {
+            let response = "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+            return stream.write_all(response.as_bytes()).await;
+        }
+    };
+    
+    let status_line = if status { "200 OK" } else { "503 Service Unavailable" };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status_line,
+        body.len(),
+        body,
+    );
+    
+    stream.write_all(response.as_bytes()).await
+}
\ No newline at end of file
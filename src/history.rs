@@ -0,0 +1,175 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::state::BalanceChange;
+use crate::{Block, Transaction, TransactionKind};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Direction {
+    Incoming,
+    Outgoing,
+}
+
+// One line of an account statement
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AccountEntry {
+    pub height: u64,
+    pub direction: Direction,
+    pub transaction: Transaction,
+    // The account's balance once the block holding the transaction was applied
+    pub balance: u64,
+}
+
+// Which entries of a history to return, counted from the oldest
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Page {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Self { offset: 0, limit: 100 }
+    }
+}
+
+// Every account a transaction sends from or pays into
+pub fn parties(tx: &Transaction) -> BTreeSet<&str> {
+    let mut parties = BTreeSet::new();
+    parties.insert(tx.from.as_str());
+    parties.insert(tx.to.as_str());
+    match &tx.kind {
+        TransactionKind::Payout(payments) | TransactionKind::Spend { outputs: payments, .. } => {
+            parties.extend(payments.iter().map(|payment| payment.to.as_str()));
+        }
+        _ => {}
+    }
+    parties.remove("");
+    parties
+}
+
+// Height, position in the block and id of a transaction
+type Position = (u64, u32, Uuid);
+
+// Main-chain transactions by the accounts they touch, kept in step with the chain like the memo index
+#[derive(Debug, Default)]
+pub struct AddressIndex {
+    by_address: RwLock<HashMap<String, BTreeSet<Position>>>,
+}
+
+impl AddressIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // Heights and ids of the account's transactions within `from..=to`, in chain order
+    pub fn range(&self, address: &str, from: u64, to: u64, page: Page) -> Vec<(u64, Uuid)> {
+        if from > to {
+            return Vec::new();
+        }
+        
+        self.by_address.read().unwrap()
+            .get(address)
+            .map(|hits| {
+                hits.range((from, 0, Uuid::nil())..=(to, u32::MAX, Uuid::max()))
+                    .skip(page.offset)
+                    .take(page.limit)
+                    .map(|(height, _, id)| (*height, *id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+    
+    pub(crate) fn insert(&self, block: &Block) {
+        let mut index = self.by_address.write().unwrap();
+        for (position, tx) in block.transactions.iter().enumerate() {
+            for party in parties(tx) {
+                index.entry(party.to_string()).or_default().insert((block.header.height, position as u32, tx.id));
+            }
+        }
+    }
+    
+    pub(crate) fn clear(&self) {
+        self.by_address.write().unwrap().clear();
+    }
+    
+    pub(crate) fn remove(&self, block: &Block) {
+        let mut index = self.by_address.write().unwrap();
+        for (position, tx) in block.transactions.iter().enumerate() {
+            for party in parties(tx) {
+                if let Some(hits) = index.get_mut(party) {
+                    hits.remove(&(block.header.height, position as u32, tx.id));
+                    if hits.is_empty() {
+                        index.remove(party);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Touches {
+    by_height: Vec<BTreeSet<String>>,
+    by_account: HashMap<String, BTreeSet<u64>>,
+}
+
+// Accounts each main-chain block touched, as a party to one of its transactions or through any balance change such
+// as a fee, reward or contract payout, and the heights that touched each account
+#[derive(Debug, Default)]
+pub struct TouchedAccounts {
+    touches: RwLock<Touches>,
+}
+
+impl TouchedAccounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn at(&self, height: u64) -> Option<BTreeSet<String>> {
+        self.touches.read().unwrap().by_height.get(height as usize).cloned()
+    }
+    
+    // Heights within `from..=to` whose block touched the account, oldest first
+    pub fn heights(&self, address: &str, from: u64, to: u64) -> Vec<u64> {
+        if from > to {
+            return Vec::new();
+        }
+        
+        self.touches.read().unwrap()
+            .by_account
+            .get(address)
+            .map(|heights| heights.range(from..=to).copied().collect())
+            .unwrap_or_default()
+    }
+    
+    // Called for each block in height order, genesis first, with what committing it changed
+    pub(crate) fn push(&self, block: &Block, changes: &[BalanceChange]) {
+        let mut touches = self.touches.write().unwrap();
+        let accounts: BTreeSet<String> = block.transactions.iter()
+            .flat_map(parties)
+            .map(str::to_string)
+            .chain(changes.iter().map(|change| change.address.clone()))
+            .collect();
+        
+        for account in &accounts {
+            touches.by_account.entry(account.clone()).or_default().insert(block.header.height);
+        }
+        touches.by_height.push(accounts);
+    }
+    
+    pub(crate) fn pop(&self) {
+        let mut touches = self.touches.write().unwrap();
+        let height = touches.by_height.len().saturating_sub(1) as u64;
+        for account in touches.by_height.pop().unwrap_or_default() {
+            if let Some(heights) = touches.by_account.get_mut(&account) {
+                heights.remove(&height);
+                if heights.is_empty() {
+                    touches.by_account.remove(&account);
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub const HTLC_MODULE: &str = "native:htlc";
+
+// The hex SHA-256 digest a lock commits to; whoever reveals `preimage` can release the funds
+pub fn hashlock(preimage: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(preimage))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum HashLockStatus {
+    Open,
+    // The claim reveals the preimage, so the counterparty of a cross-chain swap can read it here
+    Claimed { tx: Uuid, preimage: Vec<u8> },
+    Refunded { tx: Uuid },
+}
+
+// Funds the sender locked for `recipient`; claimable with the preimage of `hash` below height `timeout`, refundable
+// to the sender from there on. `id` is the locking transaction
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HashLock {
+    pub id: Uuid,
+    pub sender: String,
+    pub recipient: String,
+    pub amount: u64,
+    pub hash: String,
+    pub timeout: u64,
+    pub status: HashLockStatus,
+}
+
+impl HashLock {
+    pub fn is_open(&self) -> bool {
+        self.status == HashLockStatus::Open
+    }
+    
+    // Whether a claim revealing `preimage` may settle the lock in a block at `height`
+    pub fn check_claim(&self, preimage: &[u8], height: u64) -> Result<(), String> {
+        if !self.is_open() {
+            return Err(format!("Hash lock {} is already settled", self.id));
+        }
+        if height >= self.timeout {
+            return Err(format!("Hash lock {} timed out at height {}", self.id, self.timeout));
+        }
+        if hashlock(preimage) != self.hash {
+            return Err(format!("Preimage does not open hash lock {}", self.id));
+        }
+        Ok(())
+    }
+    
+    pub fn check_refund(&self, sender: &str, height: u64) -> Result<(), String> {
+        if !self.is_open() {
+            return Err(format!("Hash lock {} is already settled", self.id));
+        }
+        if sender != self.sender {
+            return Err(format!("Only {} can take back hash lock {}", self.sender, self.id));
+        }
+        if height < self.timeout {
+            return Err(format!("Hash lock {} can't be refunded before height {}", self.id, self.timeout));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct HashLocks {
+    locks: RwLock<BTreeMap<Uuid, HashLock>>,
+}
+
+impl HashLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn get(&self, id: Uuid) -> Option<HashLock> {
+        self.locks.read().unwrap().get(&id).cloned()
+    }
+    
+    // Open locks sent by or to `address`
+    pub fn open_for(&self, address: &str) -> Vec<HashLock> {
+        self.locks.read().unwrap()
+            .values()
+            .filter(|lock| lock.is_open() && (lock.sender == address || lock.recipient == address))
+            .cloned()
+            .collect()
+    }
+    
+    // Held by open locks, so part of the supply though in no balance
+    pub fn locked(&self) -> u64 {
+        self.locks.read().unwrap()
+            .values()
+            .filter(|lock| lock.is_open())
+            .map(|lock| lock.amount)
+            .sum()
+    }
+    
+    pub(crate) fn open(&self, lock: HashLock) {
+        self.locks.write().unwrap().insert(lock.id, lock);
+    }
+    
+    pub(crate) fn remove(&self, id: Uuid) {
+        self.locks.write().unwrap().remove(&id);
+    }
+    
+    pub(crate) fn settle(&self, id: Uuid, status: HashLockStatus) {
+        if let Some(lock) = self.locks.write().unwrap().get_mut(&id) {
+            lock.status = status;
+        }
+    }
+}
\ No newline at end of file
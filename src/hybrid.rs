@@ -0,0 +1,178 @@
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+
+use crate::consensus::{Consensus, ProofOfWork};
+use crate::keys::Keypair;
+use crate::params::ChainParams;
+use crate::poa::{PoaConfig, ProofOfAuthority};
+use crate::pos::{PosConfig, ProofOfStake};
+use crate::staking::StakeRegistry;
+use crate::{Block, LedgerError, Result};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum EngineConfig {
+    ProofOfWork,
+    ProofOfAuthority(PoaConfig),
+    ProofOfStake(PosConfig),
+}
+
+impl EngineConfig {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            EngineConfig::ProofOfAuthority(config) => config.validate(),
+            EngineConfig::ProofOfWork | EngineConfig::ProofOfStake(_) => Ok(()),
+        }
+    }
+    
+    pub fn build(&self, local_keys: Vec<Keypair>) -> Result<Arc<dyn Consensus>> {
+        Ok(match self {
+            EngineConfig::ProofOfWork => Arc::new(ProofOfWork),
+            EngineConfig::ProofOfAuthority(config) => Arc::new(ProofOfAuthority::new(config.clone(), local_keys)?),
+            EngineConfig::ProofOfStake(config) => Arc::new(ProofOfStake::new(config.clone(), local_keys)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConsensusTransition {
+    // First block produced and validated by `engine`
+    pub height: u64,
+    pub engine: EngineConfig,
+}
+
+// Engines a chain runs over its lifetime, e.g. proof-of-work handing over to authorities at a height
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConsensusSchedule {
+    pub initial: EngineConfig,
+    #[serde(default)]
+    pub transitions: Vec<ConsensusTransition>,
+}
+
+impl ConsensusSchedule {
+    pub fn new(initial: EngineConfig) -> Self {
+        Self {
+            initial,
+            transitions: Vec::new(),
+        }
+    }
+    
+    pub fn switch_at(mut self, height: u64, engine: EngineConfig) -> Self {
+        self.transitions.push(ConsensusTransition { height, engine });
+        self
+    }
+    
+    pub fn validate(&self) -> Result<()> {
+        self.initial.validate()?;
+        
+        let mut previous = 0;
+        for transition in &self.transitions {
+            if transition.height <= previous {
+                return Err(LedgerError::InvalidParameters(
+                    "Consensus transitions must be at increasing heights above genesis".to_string(),
+                ));
+            }
+            
+            transition.engine.validate()?;
+            previous = transition.height;
+        }
+        
+        Ok(())
+    }
+    
+    // Every engine gets the same keys; each only signs with those it recognises
+    pub fn build(&self, local_keys: Vec<Keypair>) -> Result<HybridConsensus> {
+        self.validate()?;
+        
+        let mut hybrid = HybridConsensus::new(self.initial.build(local_keys.clone())?);
+        for transition in &self.transitions {
+            hybrid = hybrid.switch_at(transition.height, transition.engine.build(local_keys.clone())?)?;
+        }
+        Ok(hybrid)
+    }
+}
+
+// Delegates each block to the engine scheduled for its height, so every node switches at the same block
+pub struct HybridConsensus {
+    // Activation heights in increasing order, starting at 0
+    phases: Vec<(u64, Arc<dyn Consensus>)>,
+}
+
+impl HybridConsensus {
+    pub fn new(initial: Arc<dyn Consensus>) -> Self {
+        Self {
+            phases: vec![(0, initial)],
+        }
+    }
+    
+    pub fn switch_at(mut self, height: u64, engine: Arc<dyn Consensus>) -> Result<Self> {
+        if height <= self.phases.last().map_or(0, |(start, _)| *start) {
+            return Err(LedgerError::InvalidParameters(format!(
+                "Consensus transition at height {} must follow the previous one",
+                height,
+            )));
+        }
+        
+        self.phases.push((height, engine));
+        Ok(self)
+    }
+    
+    pub fn engine_at(&self, height: u64) -> &dyn Consensus {
+        self.phases.iter()
+            .rev()
+            .find(|(start, _)| *start <= height)
+            .map(|(_, engine)| engine.as_ref())
+            .expect("the initial phase starts at genesis")
+    }
+    
+    pub fn transition_heights(&self) -> Vec<u64> {
+        self.phases.iter().skip(1).map(|(start, _)| *start).collect()
+    }
+}
+
+impl Consensus for HybridConsensus {
+    fn name(&self) -> &'static str {
+        "hybrid"
+    }
+    
+    fn can_propose(&self, parent: &Block) -> bool {
+        self.engine_at(parent.header.height + 1).can_propose(parent)
+    }
+    
+    fn propose(&self, parent: &Block, block: Block, params: &ChainParams) -> Result<Block> {
+        self.engine_at(parent.header.height + 1).propose(parent, block, params)
+    }
+    
+    fn propose_counting_hashes(&self, parent: &Block, block: Block, params: &ChainParams) -> Result<(Block, u64)> {
+        self.engine_at(parent.header.height + 1).propose_counting_hashes(parent, block, params)
+    }
+    
+    fn validate(&self, block: &Block, parent: &Block, params: &ChainParams) -> Result<()> {
+        self.engine_at(block.header.height).validate(block, parent, params)
+    }
+    
+    fn attach_stakes(&self, stakes: Arc<StakeRegistry>) {
+        for (_, engine) in &self.phases {
+            engine.attach_stakes(Arc::clone(&stakes));
+        }
+    }
+    
+    fn block_rewards(&self, block: &Block) -> Vec<(String, u64)> {
+        self.engine_at(block.header.height).block_rewards(block)
+    }
+    
+    fn work(&self, block: &Block, params: &ChainParams) -> u128 {
+        self.engine_at(block.header.height).work(block, params)
+    }
+    
+    // Not height-aware, so keep the deepest any phase needs
+    fn finality_depth(&self, params: &ChainParams) -> u64 {
+        self.phases.iter()
+            .map(|(_, engine)| engine.finality_depth(params))
+            .max()
+            .unwrap_or(params.finality_depth)
+    }
+    
+    fn finalize(&self, block: &Block) -> Result<()> {
+        self.engine_at(block.header.height).finalize(block)
+    }
+}
\ No newline at end of file
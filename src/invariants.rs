@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// A way the ledger's state disagrees with itself; see `DistributedLedger::check_invariants`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum InvariantViolation {
+    // Balances, bonded stake and open hash locks don't add up to the units issued so far
+    SupplyMismatch { issued: u64, held: u128 },
+    // A balance above everything ever issued, which only a subtraction wrapping below zero leaves
+    Underflow { address: String, balance: u64 },
+    // The block at `height` doesn't hash to its header or doesn't follow its parent
+    BrokenLink { height: u64, reason: String },
+    // Still pooled though the main chain holds it
+    PooledButIncluded { id: Uuid, height: u64 },
+}
\ No newline at end of file
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{Block, Transaction, TransactionKind};
+
+pub const NATIVE_ASSET: &str = "native";
+pub const INVOICE_SCHEME: &str = "ledger";
+
+// A request for `amount` paid to `recipient` before `expires_at`; a transfer pays it when its payload is `reference`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Invoice {
+    pub reference: String,
+    pub recipient: String,
+    pub amount: u64,
+    pub asset: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Invoice {
+    pub fn new(reference: &str, recipient: &str, amount: u64, expires_at: DateTime<Utc>) -> Self {
+        Self {
+            reference: reference.to_string(),
+            recipient: recipient.to_string(),
+            amount,
+            asset: NATIVE_ASSET.to_string(),
+            expires_at,
+        }
+    }
+    
+    pub fn validate(&self) -> Result<(), String> {
+        if self.reference.is_empty() || self.recipient.is_empty() || self.amount == 0 {
+            return Err("Invoices need a reference, a recipient and an amount".to_string());
+        }
+        if self.asset != NATIVE_ASSET {
+            return Err(format!("Unknown asset {}; only {} exists", self.asset, NATIVE_ASSET));
+        }
+        Ok(())
+    }
+    
+    // The transfer that settles this invoice; the payer adds a fee covering the reference as payload, then signs
+    pub fn payment(&self, payer: &str) -> Transaction {
+        Transaction::new(payer.to_string(), self.recipient.clone(), self.amount)
+            .with_data(self.reference.as_bytes().to_vec())
+    }
+    
+    // Paying more than asked still settles the invoice
+    pub fn is_paid_by(&self, tx: &Transaction, timestamp: DateTime<Utc>) -> bool {
+        tx.kind == TransactionKind::Transfer
+            && tx.to == self.recipient
+            && tx.amount >= self.amount
+            && tx.data == self.reference.as_bytes()
+            && timestamp <= self.expires_at
+    }
+    
+    // `ledger:<recipient>?amount=..&asset=..&expires=<unix seconds>&ref=..`, short enough for a QR code
+    pub fn to_uri(&self) -> String {
+        format!(
+            "{}:{}?amount={}&asset={}&expires={}&ref={}",
+            INVOICE_SCHEME,
+            encode(&self.recipient),
+            self.amount,
+            encode(&self.asset),
+            self.expires_at.timestamp(),
+            encode(&self.reference),
+        )
+    }
+    
+    pub fn from_uri(uri: &str) -> Result<Self, String> {
+        let rest = uri.strip_prefix(INVOICE_SCHEME).and_then(|rest| rest.strip_prefix(':'))
+            .ok_or_else(|| format!("Invoice URIs start with {}:", INVOICE_SCHEME))?;
+        let (recipient, query) = rest.split_once('?').unwrap_or((rest, ""));
+        
+        let mut fields = HashMap::new();
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').ok_or_else(|| format!("Malformed field {}", pair))?;
+            fields.insert(key, decode(value)?);
+        }
+        let field = |key: &str| fields.get(key).cloned().ok_or_else(|| format!("Invoice URI has no {}", key));
+        
+        let amount = field("amount")?.parse().map_err(|_| "Invalid amount".to_string())?;
+        let expires = field("expires")?.parse().map_err(|_| "Invalid expiry".to_string())?;
+        let invoice = Self {
+            reference: field("ref")?,
+            recipient: decode(recipient)?,
+            amount,
+            asset: fields.get("asset").cloned().unwrap_or_else(|| NATIVE_ASSET.to_string()),
+            expires_at: DateTime::from_timestamp(expires, 0).ok_or_else(|| "Invalid expiry".to_string())?,
+        };
+        invoice.validate()?;
+        Ok(invoice)
+    }
+}
+
+// Percent-encodes everything but unreserved characters
+fn encode(value: &str) -> String {
+    value.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn decode(value: &str) -> Result<String, String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let byte = value.get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| format!("Invalid escape in {}", value))?;
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| format!("{} is not UTF-8", value))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum InvoiceStatus {
+    Pending,
+    Paid { tx: Uuid, height: u64 },
+    Expired,
+}
+
+// Sent when a committed transfer settles an invoice
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InvoicePaid {
+    pub reference: String,
+    pub tx: Uuid,
+    pub payer: String,
+    pub amount: u64,
+    pub height: u64,
+}
+
+// Invoices this node issued; local to it, like the payments it watches for
+#[derive(Debug, Default)]
+pub struct InvoiceBook {
+    invoices: RwLock<HashMap<String, Invoice>>,
+    // Settling transaction and height by reference, undone if a reorg drops the payment
+    payments: RwLock<HashMap<String, (Uuid, u64)>>,
+}
+
+impl InvoiceBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn get(&self, reference: &str) -> Option<Invoice> {
+        self.invoices.read().unwrap().get(reference).cloned()
+    }
+    
+    pub fn status(&self, reference: &str, now: DateTime<Utc>) -> Option<InvoiceStatus> {
+        let invoice = self.get(reference)?;
+        Some(match self.payments.read().unwrap().get(reference) {
+            Some((tx, height)) => InvoiceStatus::Paid { tx: *tx, height: *height },
+            None if now > invoice.expires_at => InvoiceStatus::Expired,
+            None => InvoiceStatus::Pending,
+        })
+    }
+    
+    pub(crate) fn register(&self, invoice: Invoice) -> Result<(), String> {
+        invoice.validate()?;
+        let mut invoices = self.invoices.write().unwrap();
+        if invoices.contains_key(&invoice.reference) {
+            return Err(format!("Invoice {} already exists", invoice.reference));
+        }
+        invoices.insert(invoice.reference.clone(), invoice);
+        Ok(())
+    }
+    
+    // Marks the invoices `block` pays; the first payment of each wins
+    pub(crate) fn settle(&self, height: u64, block: &Block) -> Vec<InvoicePaid> {
+        let invoices = self.invoices.read().unwrap();
+        let mut payments = self.payments.write().unwrap();
+        let mut paid = Vec::new();
+        
+        for tx in &block.transactions {
+            let Some(invoice) = std::str::from_utf8(&tx.data).ok().and_then(|reference| invoices.get(reference)) else {
+                continue;
+            };
+            if payments.contains_key(&invoice.reference) || !invoice.is_paid_by(tx, block.header.timestamp) {
+                continue;
+            }
+            
+            payments.insert(invoice.reference.clone(), (tx.id, height));
+            paid.push(InvoicePaid {
+                reference: invoice.reference.clone(),
+                tx: tx.id,
+                payer: tx.from.clone(),
+                amount: tx.amount,
+                height,
+            });
+        }
+        paid
+    }
+    
+    pub(crate) fn unsettle(&self, tx: &Transaction) {
+        let Ok(reference) = std::str::from_utf8(&tx.data) else {
+            return;
+        };
+        
+        let mut payments = self.payments.write().unwrap();
+        if payments.get(reference).is_some_and(|(paid_by, _)| *paid_by == tx.id) {
+            payments.remove(reference);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,175 @@
+use std::path::{Path, PathBuf};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::UnixStream;
+use tracing::{debug, error, info};
+
+use crate::listen::{ListenAddr, Listener};
+use crate::search::SearchResult;
+use crate::stale::StaleBlock;
+use crate::{Block, DistributedLedger, LedgerError, Result, Transaction};
+
+// Frames are a big-endian u32 length followed by a bincode payload
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IpcRequest {
+    SubmitTransaction(Box<Transaction>),
+    GetBalance(String),
+    GetLatestBlock,
+    GetTransactionCount,
+    Search(String),
+    GetStaleBlocks,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IpcResponse {
+    Submitted,
+    Balance(u64),
+    Block(Block),
+    TransactionCount(usize),
+    SearchResult(Option<SearchResult>),
+    StaleBlocks(Vec<StaleBlock>),
+    Error(String),
+}
+
+pub async fn serve_ipc(ledger: DistributedLedger, path: impl Into<PathBuf>) -> Result<()> {
+    let addr = ListenAddr::Unix(path.into());
+    let listener = Listener::bind(&addr).await.map_err(anyhow::Error::from)?;
+    info!("Serving IPC on {}", addr);
+    
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok(mut stream) => {
+                    let ledger = ledger.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(&ledger, &mut stream).await {
+                            debug!("IPC connection closed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => error!("Failed to accept IPC connection: {}", e),
+            }
+        }
+    });
+    
+    Ok(())
+}
+
+async fn handle_connection<S>(ledger: &DistributedLedger, stream: &mut S) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + ?Sized,
+{
+    loop {
+        let request: IpcRequest = match read_frame(stream).await? {
+            Some(request) => request,
+            None => return Ok(()),
+        };
+        
+        let response = match request {
+            IpcRequest::SubmitTransaction(tx) => match ledger.add_transaction_from("ipc", *tx).await {
+                Ok(()) => IpcResponse::Submitted,
+                Err(e) => IpcResponse::Error(e.to_string()),
+            },
+            IpcRequest::GetBalance(address) => IpcResponse::Balance(ledger.get_balance(&address).await),
+            IpcRequest::GetLatestBlock => IpcResponse::Block(ledger.get_latest_block().await),
+            IpcRequest::GetTransactionCount => {
+                IpcResponse::TransactionCount(ledger.get_transaction_count().await)
+            }
+            IpcRequest::Search(query) => IpcResponse::SearchResult(ledger.search(&query).await),
+            IpcRequest::GetStaleBlocks => IpcResponse::StaleBlocks(ledger.stale_blocks()),
+        };
+        
+        write_frame(stream, &response).await?;
+    }
+}
+
+// Returns `None` on a clean EOF between frames
+async fn read_frame<S, T>(stream: &mut S) -> Result<Option<T>>
+where
+    S: AsyncRead + Unpin + ?Sized,
+    T: DeserializeOwned,
+{
+    let mut len = [0u8; 4];
+    match stream.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(anyhow::Error::from(e).into()),
+    }
+    
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(LedgerError::InvalidTransaction(format!(
+            "IPC frame of {} bytes exceeds the {} byte limit",
+            len, MAX_FRAME_SIZE,
+        )));
+    }
+    
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload).await.map_err(anyhow::Error::from)?;
+    let message = bincode::deserialize(&payload).map_err(anyhow::Error::from)?;
+    Ok(Some(message))
+}
+
+async fn write_frame<S, T>(stream: &mut S, message: &T) -> Result<()>
+where
+    S: AsyncWrite + Unpin + ?Sized,
+    T: Serialize,
+{
+    let payload = bincode::serialize(message).map_err(anyhow::Error::from)?;
+    stream.write_all(&(payload.len() as u32).to_be_bytes()).await.map_err(anyhow::Error::from)?;
+    stream.write_all(&payload).await.map_err(anyhow::Error::from)?;
+    Ok(())
+}
+
+pub struct IpcClient {
+    stream: UnixStream,
+}
+
+impl IpcClient {
+    pub async fn connect(path: impl AsRef<Path>) -> Result<Self> {
+        let stream = UnixStream::connect(path).await.map_err(anyhow::Error::from)?;
+        Ok(Self { stream })
+    }
+    
+    pub async fn request(&mut self, request: &IpcRequest) -> Result<IpcResponse> {
+        write_frame(&mut self.stream, request).await?;
+        read_frame(&mut self.stream).await?.ok_or_else(|| {
+            LedgerError::Internal(anyhow::anyhow!("IPC server closed the connection"))
+        })
+    }
+    
+    pub async fn submit_transaction(&mut self, tx: Transaction) -> Result<()> {
+        match self.request(&IpcRequest::SubmitTransaction(Box::new(tx))).await? {
+            IpcResponse::Submitted => Ok(()),
+            IpcResponse::Error(e) => Err(LedgerError::InvalidTransaction(e)),
+            other => Err(unexpected(other)),
+        }
+    }
+    
+    pub async fn get_balance(&mut self, address: &str) -> Result<u64> {
+        match self.request(&IpcRequest::GetBalance(address.to_string())).await? {
+            IpcResponse::Balance(balance) => Ok(balance),
+            other => Err(unexpected(other)),
+        }
+    }
+    
+    pub async fn get_latest_block(&mut self) -> Result<Block> {
+        match self.request(&IpcRequest::GetLatestBlock).await? {
+            IpcResponse::Block(block) => Ok(block),
+            other => Err(unexpected(other)),
+        }
+    }
+    
+    pub async fn get_stale_blocks(&mut self) -> Result<Vec<StaleBlock>> {
+        match self.request(&IpcRequest::GetStaleBlocks).await? {
+            IpcResponse::StaleBlocks(blocks) => Ok(blocks),
+            other => Err(unexpected(other)),
+        }
+    }
+}
+
+fn unexpected(response: IpcResponse) -> LedgerError {
+    LedgerError::Internal(anyhow::anyhow!("Unexpected IPC response: {:?}", response))
+}
\ No newline at end of file
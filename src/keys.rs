@@ -0,0 +1,97 @@
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use schnorrkel::vrf::{VRFPreOut, VRFProof};
+use schnorrkel::{signing_context, ExpansionMode, MiniSecretKey, PublicKey};
+use sha2::{Digest, Sha256};
+
+const VRF_CONTEXT: &[u8] = b"distributed-ledger-vrf";
+
+#[derive(Clone)]
+pub struct Keypair {
+    signing_key: SigningKey,
+}
+
+impl Keypair {
+    // Deterministic derivation for dev/test networks only: anyone who knows the seed knows the key
+    pub fn from_seed(seed: &str, name: &str) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(seed.as_bytes());
+        hasher.update(b":");
+        hasher.update(name.as_bytes());
+        let secret: [u8; 32] = hasher.finalize().into();
+        
+        Self {
+            signing_key: SigningKey::from_bytes(&secret),
+        }
+    }
+    
+    pub fn public_key(&self) -> String {
+        hex::encode(self.signing_key.verifying_key().as_bytes())
+    }
+    
+    pub fn sign(&self, message: &[u8]) -> String {
+        hex::encode(self.signing_key.sign(message).to_bytes())
+    }
+    
+    // The VRF key is derived from the same secret, so a validator still manages a single seed
+    fn vrf_keypair(&self) -> schnorrkel::Keypair {
+        MiniSecretKey::from_bytes(&self.signing_key.to_bytes())
+            .expect("ed25519 secrets are 32 bytes")
+            .expand_to_keypair(ExpansionMode::Ed25519)
+    }
+    
+    pub fn vrf_public_key(&self) -> String {
+        hex::encode(self.vrf_keypair().public.to_bytes())
+    }
+    
+    // Returns the hex VRF output for `input` and the proof that this key produced it
+    pub fn vrf_prove(&self, input: &[u8]) -> (String, String) {
+        let (inout, proof, _) = self.vrf_keypair().vrf_sign(signing_context(VRF_CONTEXT).bytes(input));
+        (hex::encode(inout.to_preout().to_bytes()), hex::encode(proof.to_bytes()))
+    }
+}
+
+impl std::fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Keypair")
+            .field("public_key", &self.public_key())
+            .finish_non_exhaustive()
+    }
+}
+
+pub fn verify_signature(public_key: &str, message: &[u8], signature: &str) -> bool {
+    let Ok(public_key) = hex::decode(public_key) else {
+        return false;
+    };
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    
+    let Ok(public_key) = <[u8; 32]>::try_from(public_key.as_slice()) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_slice(&signature) else {
+        return false;
+    };
+    
+    VerifyingKey::from_bytes(&public_key)
+        .map(|key| key.verify(message, &signature).is_ok())
+        .unwrap_or(false)
+}
+
+pub fn verify_vrf(public_key: &str, input: &[u8], output: &str, proof: &str) -> bool {
+    let decoded = (hex::decode(public_key), hex::decode(output), hex::decode(proof));
+    let (Ok(public_key), Ok(output), Ok(proof)) = decoded else {
+        return false;
+    };
+    
+    let parsed = (
+        PublicKey::from_bytes(&public_key),
+        VRFPreOut::from_bytes(&output),
+        VRFProof::from_bytes(&proof),
+    );
+    let (Ok(public_key), Ok(output), Ok(proof)) = parsed else {
+        return false;
+    };
+    
+    public_key.vrf_verify(signing_context(VRF_CONTEXT).bytes(input), &output, &proof).is_ok()
+}
\ No newline at end of file
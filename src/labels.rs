@@ -0,0 +1,86 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::Transaction;
+
+// An operator's name and tags for an address, kept on this node only
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AddressLabel {
+    // Empty for an address that was only tagged
+    pub label: String,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+}
+
+// Labels by address, as exported and imported
+pub type LabelSet = BTreeMap<String, AddressLabel>;
+
+#[derive(Debug, Default)]
+pub struct AddressLabels {
+    labels: RwLock<LabelSet>,
+}
+
+impl AddressLabels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn get(&self, address: &str) -> Option<AddressLabel> {
+        self.labels.read().unwrap().get(address).cloned()
+    }
+    
+    // Keeps the address's tags
+    pub fn set(&self, address: &str, label: &str) {
+        self.labels.write().unwrap().entry(address.to_string()).or_default().label = label.to_string();
+    }
+    
+    // False if the address already had the tag
+    pub fn tag(&self, address: &str, tag: &str) -> bool {
+        self.labels.write().unwrap().entry(address.to_string()).or_default().tags.insert(tag.to_string())
+    }
+    
+    pub fn untag(&self, address: &str, tag: &str) -> bool {
+        let mut labels = self.labels.write().unwrap();
+        let Some(entry) = labels.get_mut(address) else {
+            return false;
+        };
+        
+        let removed = entry.tags.remove(tag);
+        if entry.label.is_empty() && entry.tags.is_empty() {
+            labels.remove(address);
+        }
+        removed
+    }
+    
+    pub fn remove(&self, address: &str) -> Option<AddressLabel> {
+        self.labels.write().unwrap().remove(address)
+    }
+    
+    pub fn tagged(&self, tag: &str) -> Vec<String> {
+        self.labels.read().unwrap()
+            .iter()
+            .filter(|(_, entry)| entry.tags.contains(tag))
+            .map(|(address, _)| address.clone())
+            .collect()
+    }
+    
+    // Labels of whichever accounts the transaction sends from or pays into
+    pub fn for_transaction(&self, tx: &Transaction) -> LabelSet {
+        let labels = self.labels.read().unwrap();
+        crate::history::parties(tx).into_iter()
+            .filter_map(|party| labels.get(party).map(|entry| (party.to_string(), entry.clone())))
+            .collect()
+    }
+    
+    pub fn export(&self) -> LabelSet {
+        self.labels.read().unwrap().clone()
+    }
+    
+    // Imported labels replace those already held for the same address; returns how many were imported
+    pub fn import(&self, set: LabelSet) -> usize {
+        let count = set.len();
+        self.labels.write().unwrap().extend(set);
+        count
+    }
+}
\ No newline at end of file
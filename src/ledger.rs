@@ -1,186 +1,4374 @@
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::ops::{Bound, RangeBounds};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, RwLock as StdRwLock};
+use tokio::sync::{broadcast, watch, Mutex, MutexGuard};
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
-use crossbeam_channel::{bounded, Receiver, Sender};
+use arc_swap::ArcSwap;
+use futures::stream::{self, Stream, StreamExt};
 use rayon::prelude::*;
-use tracing::{info, warn, error};
+use serde::{Deserialize, Serialize};
+use tracing::{info, error, warn};
 
-use crate::{Transaction, Block, LedgerError, Result};
-use crate::performance::PerformanceMonitor;
+use crate::{Transaction, Block, BlockHeader, LedgerError, Result};
+use crate::chain::Chain;
+use crate::anchor::{AnchorProof, AnchorRecord, ANCHOR_MODULE};
+use crate::epoch::{Epoch, EpochHook, EpochRecord};
+use crate::governance::{Governance, Proposal, ProposalAction, ProposalStatus, GOVERNANCE_MODULE};
+use crate::checkpoint::{Checkpoint, CheckpointTracker, CheckpointVote, CHECKPOINT_MODULE};
+use crate::fork::{BlockImport, Branch, BranchComparison, ChainTip, ReorgEvent};
+use crate::transaction::{Authorization, TransactionKind};
+use crate::health::{HealthConfig, HealthReport};
+use crate::genesis::GenesisConfig;
+use crate::params::{BlockTimeRules, ChainParams, FeeDestination, TimestampOrdering};
+use crate::consensus::{Consensus, ProofOfWork};
+use crate::search::SearchResult;
+use crate::logs::{Log, LogFilter, LogStore, SUPPLY_MODULE, TRANSFER_MODULE};
+use crate::mempool::{Mempool, MempoolDump, MempoolEntry, MempoolLimits, MempoolOccupancy, PendingTransaction, ReplacementEvent, ValidationState};
+use crate::performance::{MiningGuard, PerformanceMonitor};
+use crate::peers::{PeerRegistry, Violation};
+use crate::stale::{StaleBlock, StaleStats, StaleTracker};
+use crate::contracts::{ContractStore, Receipt, CONTRACT_MODULE};
+use crate::memo::MemoIndex;
+use crate::merkle::MerkleProof;
+use crate::state::{BalanceChange, BalanceProof, Balances, StateTree};
+use crate::allowance::Allowances;
+use crate::htlc::{HashLock, HashLockStatus, HashLocks, HTLC_MODULE};
+use crate::status::{Rejections, TransactionStatus};
+use crate::history::{AccountEntry, AddressIndex, Direction, Page, TouchedAccounts};
+use crate::analytics::{BalanceAnalytics, BalanceSummary};
+use crate::events::{AddressSubscription, LedgerEvent, EVENT_CAPACITY};
+use crate::stream::{TransactionFilter, STREAM_CHUNK};
+use crate::invariants::InvariantViolation;
+use crate::conflicts::{ConflictSchedule, ScheduleStep, PARALLEL_WAVE};
+use crate::stats::{ChainStats, MempoolDepth};
+use crate::volume::{TimeBucket, VolumeBucket, VolumeIndex};
+use crate::view::ReadView;
+use crate::audit::{AuditFilter, RejectedTransaction, RejectionAudit};
+use crate::confirmations::{ChainHead, Confirmation};
+use crate::labels::{AddressLabels, LabelSet};
+use crate::reserve::ReserveReport;
+use crate::retention::{IndexRetention, Retention};
+use crate::keys::Keypair;
+use crate::bundle::check_bundle;
+use crate::utxo::{Coin, LedgerModel, OutPoint, UtxoSet};
+use crate::vesting::{Vesting, VestingBalance, VestingGrant};
+use crate::invoice::{Invoice, InvoiceBook, InvoicePaid, InvoiceStatus};
+use crate::limits::{SpendingLimit, SpendingLimits};
+use crate::schedules::{Installment, StandingOrder, StandingOrders, SCHEDULE_MODULE};
+use crate::production::{ProcessorConfig, ProductionPolicy};
+use crate::staking::{StakeRegistry, Validator, STAKE_MODULE};
+use crate::slashing::{Evidence, SlashRecord, SLASH_MODULE};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerSnapshot {
+    pub blocks: Vec<Block>,
+    pub balances: HashMap<String, u64>,
+    // Blocks above this height, and balances they touched, could still be reorganised
+    #[serde(default)]
+    pub finalized_height: u64,
+    #[serde(default)]
+    pub labels: LabelSet,
+}
+
+const REORG_EVENT_CAPACITY: usize = 64;
+const PRODUCTION_POLL_MS: u64 = 10;
+// Batches the background processor takes ahead of the block being mined
+const MINING_QUEUE: usize = 1;
+const MAX_ORPHANS: usize = 256;
+const FEE_ESTIMATE_BLOCKS: usize = 20;
 
 pub struct DistributedLedger {
-    blocks: Arc<RwLock<Vec<Block>>>,
-    balances: Arc<DashMap<String, u64>>,
-    transaction_pool: Arc<DashMap<uuid::Uuid, Transaction>>,
+    // Held by whatever extends, reorganizes or must see no block applied while it runs: producing, importing,
+    // invariant checks and index rebuilds. Only its holder publishes to `chain`
+    writer: Arc<Mutex<()>>,
+    balances: Arc<Balances>,
+    // Balances after each main-chain block, authenticated for light clients
+    state: Arc<StateTree>,
+    // Units created net of those destroyed, whichever account or bond holds them; what `total_supply` must come to
+    issued: Arc<AtomicU64>,
+    analytics: Arc<BalanceAnalytics>,
+    transaction_pool: Arc<DashMap<uuid::Uuid, PendingTransaction>>,
+    // Pooled transactions dropped before they committed
+    rejections: Arc<Rejections>,
+    // Every refused or dropped transaction, with who submitted it when known
+    audit: Arc<RejectionAudit>,
+    // Submitters of pooled transactions, for the audit entry should they be dropped
+    submitters: Arc<DashMap<uuid::Uuid, String>>,
+    // Pending bundles by id, members in order; they sit in the pool but not the mempool, which orders singles
+    bundles: Arc<DashMap<uuid::Uuid, Vec<Transaction>>>,
+    // Outflows of pooled transactions per account, held back from further spends until they commit or leave
+    reserved: Arc<DashMap<String, u64>>,
     performance_monitor: Arc<PerformanceMonitor>,
-    tx_sender: Sender<Transaction>,
-    tx_receiver: Receiver<Transaction>,
+    mempool_depth: Arc<MempoolDepth>,
+    // Operator annotations, off-chain and local to this node
+    labels: Arc<AddressLabels>,
+    logs: Arc<LogStore>,
+    anchors: Arc<DashMap<(String, u64), AnchorRecord>>,
+    block_index: Arc<DashMap<String, u64>>,
+    tx_index: Arc<DashMap<uuid::Uuid, u64>>,
+    // Main-chain heights each producer key signed, for downtime evidence
+    producer_index: Arc<DashMap<String, BTreeSet<u64>>>,
+    memos: Arc<MemoIndex>,
+    addresses: Arc<AddressIndex>,
+    retention: Arc<StdRwLock<IndexRetention>>,
+    touched: Arc<TouchedAccounts>,
+    volumes: Arc<VolumeIndex>,
+    // Unspent coins under the UTXO model; `balances` then holds each owner's total
+    utxos: Arc<UtxoSet>,
+    vesting: Arc<Vesting>,
+    allowances: Arc<Allowances>,
+    hashlocks: Arc<HashLocks>,
+    // Valid blocks off the main chain, keyed by hash
+    side_blocks: Arc<DashMap<String, Block>>,
+    stale: Arc<StaleTracker>,
+    contracts: Arc<ContractStore>,
+    reorg_events: broadcast::Sender<ReorgEvent>,
+    head: Arc<watch::Sender<ChainHead>>,
+    // The main chain as of the last commit; loading it never waits on the writer
+    chain: Arc<ArcSwap<Chain>>,
+    // Committed replacements by the transaction they replaced, which can then never commit
+    replacements: Arc<DashMap<uuid::Uuid, uuid::Uuid>>,
+    replacement_events: broadcast::Sender<ReplacementEvent>,
+    invoices: Arc<InvoiceBook>,
+    invoice_events: broadcast::Sender<InvoicePaid>,
+    // Blocks whose parent hasn't arrived yet, keyed by hash
+    orphans: Arc<DashMap<String, Block>>,
+    peers: Arc<PeerRegistry>,
+    missing_blocks: broadcast::Sender<String>,
+    events: broadcast::Sender<LedgerEvent>,
+    // Height up to which finality events went out
+    announced_finality: Arc<AtomicU64>,
+    mempool: Arc<Mempool>,
+    paused: Arc<AtomicBool>,
+    batch_size: Arc<AtomicUsize>,
+    // Set by an explicit batch size; the processor keeps it until a new `ProcessorConfig` resumes adapting
+    batch_size_fixed: Arc<AtomicBool>,
+    params: Arc<StdRwLock<ChainParams>>,
+    consensus: Arc<dyn Consensus>,
+    genesis: Arc<GenesisConfig>,
+    account_keys: Arc<DashMap<String, String>>,
+    stakes: Arc<StakeRegistry>,
+    checkpoints: Arc<CheckpointTracker>,
+    governance: Arc<Governance>,
+    schedules: Arc<StandingOrders>,
+    limits: Arc<SpendingLimits>,
+    epoch_hooks: Arc<StdRwLock<Vec<Arc<dyn EpochHook>>>>,
+    // One record per epoch the main chain has entered, oldest first
+    epochs: Arc<StdRwLock<Vec<EpochRecord>>>,
+    processor_heartbeat: Arc<AtomicI64>,
+    production_policy: Arc<StdRwLock<ProductionPolicy>>,
+    processor_config: Arc<StdRwLock<ProcessorConfig>>,
+    // Unix millis of the last block this node produced, for the interval trigger
+    last_produced: Arc<AtomicI64>,
+    // Paid the coinbase of blocks this node produces under an emission schedule
+    coinbase_address: Arc<StdRwLock<Option<String>>>,
+    // Blocks between invariant checks in debug builds, 0 for none
+    invariant_interval: Arc<AtomicU64>,
+    // Smallest wave of transfers applied on the thread pool
+    parallel_wave: Arc<AtomicUsize>,
 }
 
 impl DistributedLedger {
     pub fn new() -> Self {
-        let (tx_sender, tx_receiver) = bounded(100_000); // Large buffer for high throughput
-        
+        Self::build(GenesisConfig::default(), Arc::new(ProofOfWork))
+    }
+    
+    pub fn with_params(params: ChainParams) -> Result<Self> {
+        Self::from_genesis(GenesisConfig::new(params))
+    }
+    
+    // A scheduled switch-over is followed without signing keys; producing nodes build it with theirs
+    pub fn from_genesis(genesis: GenesisConfig) -> Result<Self> {
+        let consensus: Arc<dyn Consensus> = match &genesis.consensus {
+            Some(schedule) => Arc::new(schedule.build(Vec::new())?),
+            None => Arc::new(ProofOfWork),
+        };
+        Self::with_consensus(genesis, consensus)
+    }
+    
+    pub fn with_consensus(genesis: GenesisConfig, consensus: Arc<dyn Consensus>) -> Result<Self> {
+        genesis.validate()?;
+        Ok(Self::build(genesis, consensus))
+    }
+    
+    fn build(genesis: GenesisConfig, consensus: Arc<dyn Consensus>) -> Self {
+        let genesis_block = genesis.block();
+        let head = ChainHead::of(&genesis_block);
         let ledger = Self {
-            blocks: Arc::new(RwLock::new(Vec::new())),
-            balances: Arc::new(DashMap::new()),
+            writer: Arc::new(Mutex::new(())),
+            balances: Arc::new(Balances::default()),
+            state: Arc::new(StateTree::new()),
+            issued: Arc::new(AtomicU64::new(0)),
+            analytics: Arc::new(BalanceAnalytics::new()),
             transaction_pool: Arc::new(DashMap::new()),
+            rejections: Arc::new(Rejections::new()),
+            audit: Arc::new(RejectionAudit::default()),
+            submitters: Arc::new(DashMap::new()),
+            bundles: Arc::new(DashMap::new()),
+            reserved: Arc::new(DashMap::new()),
             performance_monitor: Arc::new(PerformanceMonitor::new()),
-            tx_sender,
-            tx_receiver,
+            mempool_depth: Arc::new(MempoolDepth::new()),
+            labels: Arc::new(AddressLabels::new()),
+            logs: Arc::new(LogStore::new()),
+            anchors: Arc::new(DashMap::new()),
+            block_index: Arc::new(DashMap::new()),
+            tx_index: Arc::new(DashMap::new()),
+            producer_index: Arc::new(DashMap::new()),
+            memos: Arc::new(MemoIndex::new()),
+            addresses: Arc::new(AddressIndex::new()),
+            retention: Arc::new(StdRwLock::new(IndexRetention::default())),
+            touched: Arc::new(TouchedAccounts::new()),
+            volumes: Arc::new(VolumeIndex::new()),
+            utxos: Arc::new(UtxoSet::new()),
+            vesting: Arc::new(Vesting::new()),
+            allowances: Arc::new(Allowances::new()),
+            hashlocks: Arc::new(HashLocks::new()),
+            side_blocks: Arc::new(DashMap::new()),
+            stale: Arc::new(StaleTracker::new()),
+            contracts: Arc::new(ContractStore::new()),
+            reorg_events: broadcast::channel(REORG_EVENT_CAPACITY).0,
+            head: Arc::new(watch::Sender::new(head)),
+            chain: Arc::new(ArcSwap::from_pointee(Chain::default())),
+            replacements: Arc::new(DashMap::new()),
+            replacement_events: broadcast::channel(REORG_EVENT_CAPACITY).0,
+            invoices: Arc::new(InvoiceBook::new()),
+            invoice_events: broadcast::channel(REORG_EVENT_CAPACITY).0,
+            orphans: Arc::new(DashMap::new()),
+            peers: Arc::new(PeerRegistry::default()),
+            missing_blocks: broadcast::channel(REORG_EVENT_CAPACITY).0,
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            announced_finality: Arc::new(AtomicU64::new(0)),
+            mempool: Arc::new(Mempool::new(MempoolLimits::default())),
+            paused: Arc::new(AtomicBool::new(false)),
+            batch_size: Arc::new(AtomicUsize::new(1000)),
+            batch_size_fixed: Arc::new(AtomicBool::new(false)),
+            params: Arc::new(StdRwLock::new(genesis.params.clone())),
+            consensus,
+            genesis: Arc::new(genesis),
+            account_keys: Arc::new(DashMap::new()),
+            stakes: Arc::new(StakeRegistry::new()),
+            checkpoints: Arc::new(CheckpointTracker::new()),
+            governance: Arc::new(Governance::new()),
+            schedules: Arc::new(StandingOrders::new()),
+            limits: Arc::new(SpendingLimits::new()),
+            epoch_hooks: Arc::new(StdRwLock::new(Vec::new())),
+            epochs: Arc::new(StdRwLock::new(Vec::new())),
+            processor_heartbeat: Arc::new(AtomicI64::new(0)),
+            production_policy: Arc::new(StdRwLock::new(ProductionPolicy::default())),
+            processor_config: Arc::new(StdRwLock::new(ProcessorConfig::default())),
+            last_produced: Arc::new(AtomicI64::new(chrono::Utc::now().timestamp_millis())),
+            coinbase_address: Arc::new(StdRwLock::new(None)),
+            invariant_interval: Arc::new(AtomicU64::new(0)),
+            parallel_wave: Arc::new(AtomicUsize::new(PARALLEL_WAVE)),
         };
+        ledger.consensus.attach_stakes(Arc::clone(&ledger.stakes));
         
         // Initialize with genesis block
         ledger.initialize_genesis_block();
+        ledger.apply_genesis_allocations();
         ledger
     }
     
-    fn initialize_genesis_block(&self) {
-        let genesis_block = Block::new(String::new(), Vec::new());
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                let mut blocks = self.blocks.write().await;
-                blocks.push(genesis_block);
+    fn apply_genesis_allocations(&self) {
+        for allocation in &self.genesis.allocations {
+            self.balances.insert(allocation.address.clone(), allocation.balance);
+            
+            if let Some(schedule) = allocation.vesting {
+                self.vesting.grant(VestingGrant {
+                    id: self.genesis.block().header.id,
+                    account: allocation.address.clone(),
+                    amount: allocation.balance,
+                    schedule,
+                });
+            }
+        }
+        
+        if let Some(dev) = &self.genesis.dev {
+            for account in &dev.accounts {
+                self.balances.insert(account.address.clone(), account.balance);
+                
+                if let Some(keypair) = self.genesis.dev_keypair(&account.address) {
+                    self.account_keys.insert(account.address.clone(), keypair.public_key());
+                }
+            }
+        }
+        
+        if self.genesis.model == LedgerModel::Utxo {
+            let genesis_id = self.genesis.block().header.id;
+            let dev_accounts = self.genesis.dev.iter().flat_map(|dev| dev.accounts.iter());
+            for (index, allocation) in self.genesis.allocations.iter().chain(dev_accounts).enumerate() {
+                self.utxos.create(
+                    OutPoint { tx: genesis_id, index: index as u32 },
+                    Coin { owner: allocation.address.clone(), amount: allocation.balance },
+                );
+            }
+        }
+        
+        for validator in &self.genesis.validators {
+            self.stakes.register(&validator.address, &validator.public_key, validator.vrf_key.as_deref(), 0);
+            self.stakes.bond(&validator.address, &validator.address, validator.stake);
+        }
+        
+        if self.genesis.params.epochs.is_some() {
+            let genesis_block = self.genesis.block();
+            self.epochs.write().unwrap().push(EpochRecord {
+                epoch: Epoch { number: 0, era: 0, start_height: 0, start_time: genesis_block.header.timestamp },
+                validators: self.epoch_validators(),
+                credits: Vec::new(),
+                previous_params: None,
+                decided: Vec::new(),
+                installments: Vec::new(),
             });
-        });
+        }
+        self.issued.store(self.total_supply(), Ordering::Relaxed);
+        self.commit_state(&self.genesis.block());
     }
     
-    pub async fn add_transaction(&self, transaction: Transaction) -> Result<()> {
-        // Validate transaction
-        transaction.validate()?;
+    fn initialize_genesis_block(&self) {
+        let genesis_block = self.genesis.block();
+        let mut blocks = Chain::default();
+        self.index_block(0, &genesis_block);
+        blocks.push(Arc::new(genesis_block));
+        self.chain.store(Arc::new(blocks));
+    }
+    
+    // Applies a committed block's effects; called by the chain writer
+    fn apply_block(&self, block: &Block) {
+        let mut block_logs = Vec::with_capacity(block.transactions.len());
+        self.advance_epoch(block);
         
-        // Check for duplicates
-        if self.transaction_pool.contains_key(&transaction.id) {
-            return Err(LedgerError::DuplicateTransaction);
+        // Rewards are worked out from the stake bonded before this block's own staking changes
+        for (address, reward) in self.consensus.block_rewards(block) {
+            *self.balances.entry(address).or_insert(0) += reward;
+            self.issue(reward);
         }
         
-        // Check balance (for non-genesis transactions)
-        if !transaction.from.is_empty() {
-            let current_balance = self.balances.get(&transaction.from)
-                .map(|entry| *entry.value())
-                .unwrap_or(0);
-            
-            if current_balance < transaction.amount {
-                return Err(LedgerError::InsufficientBalance);
+        let epoch = self.spending_epoch(block.header.height, block.header.timestamp);
+        let parallel_wave = self.parallel_wave.load(Ordering::Relaxed);
+        for step in ConflictSchedule::new(&block.transactions).steps() {
+            match step {
+                ScheduleStep::Parallel { transactions, waves } => {
+                    for wave in waves {
+                        match wave.len() >= parallel_wave {
+                            true => wave.par_iter().for_each(|tx| self.apply_transfer(tx)),
+                            false => wave.iter().for_each(|tx| self.apply_transfer(tx)),
+                        }
+                    }
+                    for tx in transactions.iter() {
+                        self.apply_effects(block, tx, epoch, &mut block_logs);
+                    }
+                }
+                ScheduleStep::Serial(tx) => {
+                    self.charge_fee(tx);
+                    self.apply_effects(block, tx, epoch, &mut block_logs);
+                }
             }
         }
         
-        // Add to transaction pool
-        self.transaction_pool.insert(transaction.id, transaction.clone());
+        self.pay_standing_orders(block, &mut block_logs);
         
-        // Send to processing queue
-        if let Err(_) = self.tx_sender.try_send(transaction) {
-            return Err(LedgerError::PerformanceLimitExceeded(
-                "Transaction queue is full".to_string(),
-            ));
+        let fees = block.transactions.iter().map(|tx| tx.fee).sum::<u64>();
+        match self.fee_recipient(block).filter(|_| fees > 0) {
+            Some(recipient) => *self.balances.entry(recipient).or_insert(0) += fees,
+            None => self.retire(fees),
         }
         
-        Ok(())
+        self.logs.record(block.header.height, block_logs);
+        self.commit_state(block);
     }
     
-    pub async fn process_transactions(&self, batch_size: usize) -> Result<()> {
-        let mut transactions = Vec::new();
-        
-        // Collect transactions from the queue
-        for _ in 0..batch_size {
-            if let Ok(tx) = self.tx_receiver.try_recv() {
-                transactions.push(tx);
-            } else {
-                break;
+    // Blocks are checked against a `StateOverlay` before they're applied, so every debit a committed block makes is
+    // covered. One that isn't is a validation gap: debug builds panic on it, and release builds hold the balance at
+    // zero rather than let it wrap, on whichever thread of a parallel wave it lands
+    fn debit(balance: &mut u64, amount: u64) {
+        debug_assert!(*balance >= amount, "Debit of {} from a balance of {}", amount, balance);
+        *balance = balance.saturating_sub(amount);
+    }
+    
+    fn charge_fee(&self, tx: &Transaction) {
+        if tx.fee > 0 {
+            if let Some(mut balance) = self.balances.get_mut(&tx.from) {
+                Self::debit(&mut balance, tx.fee);
             }
         }
+    }
+    
+    // A plain transfer's balance changes, fee included; safe alongside others touching neither account
+    fn apply_transfer(&self, tx: &Transaction) {
+        self.charge_fee(tx);
+        self.balances.entry(tx.from.clone()).and_modify(|balance| {
+            Self::debit(balance, tx.amount);
+        }).or_insert(0);
         
-        if transactions.is_empty() {
-            return Ok(());
+        self.balances.entry(tx.to.clone()).and_modify(|balance| {
+            *balance += tx.amount;
+        }).or_insert(tx.amount);
+    }
+    
+    // Everything a committed transaction does but a plain transfer's balance changes and its fee
+    fn apply_effects(&self, block: &Block, tx: &Transaction, epoch: u64, logs: &mut Vec<Log>) {
+        for (account, amount) in Self::spends(tx) {
+            self.limits.record(account, epoch, amount);
+        }
+        if let Some(original) = tx.replaces {
+            self.replacements.insert(original, tx.id);
         }
         
-        // Process transactions in parallel
-        let start_time = std::time::Instant::now();
+        match &tx.kind {
+            TransactionKind::Anchor(anchor) => {
+                // The first committed anchor for a sequence wins if duplicates were pending together
+                self.anchors.entry((anchor.namespace.clone(), anchor.sequence)).or_insert(AnchorRecord {
+                    transaction_id: tx.id,
+                    block_height: block.header.height,
+                });
+                
+                logs.push(Log::new(
+                    ANCHOR_MODULE.to_string(),
+                    vec!["anchor".to_string(), anchor.namespace.clone(), tx.from.clone()],
+                    anchor.sequence.to_le_bytes().to_vec(),
+                    tx.id,
+                ));
+            }
+            TransactionKind::Transfer => logs.push(Log::transfer(tx)),
+            TransactionKind::Vest(schedule) => {
+                self.move_balance(&tx.from, &tx.to, tx.amount);
+                self.vesting.grant(VestingGrant {
+                    id: tx.id,
+                    account: tx.to.clone(),
+                    amount: tx.amount,
+                    schedule: *schedule,
+                });
+                logs.push(Log::transfer(tx));
+            }
+            TransactionKind::Approve { allowance } => {
+                self.allowances.approve(&tx.from, &tx.to, *allowance, tx.id);
+            }
+            TransactionKind::HashLock { hash, timeout } => {
+                if let Some(mut balance) = self.balances.get_mut(&tx.from) {
+                    Self::debit(&mut balance, tx.amount);
+                }
+                self.hashlocks.open(HashLock {
+                    id: tx.id,
+                    sender: tx.from.clone(),
+                    recipient: tx.to.clone(),
+                    amount: tx.amount,
+                    hash: hash.clone(),
+                    timeout: *timeout,
+                    status: HashLockStatus::Open,
+                });
+                logs.push(Log::new(
+                    HTLC_MODULE.to_string(),
+                    vec!["lock".to_string(), tx.id.to_string(), tx.from.clone(), tx.to.clone()],
+                    tx.amount.to_le_bytes().to_vec(),
+                    tx.id,
+                ));
+            }
+            TransactionKind::Claim { lock, preimage } => {
+                if let Some(lock) = self.hashlocks.get(*lock) {
+                    self.hashlocks.settle(lock.id, HashLockStatus::Claimed { tx: tx.id, preimage: preimage.clone() });
+                    *self.balances.entry(lock.recipient.clone()).or_insert(0) += lock.amount;
+                    logs.push(Log::new(
+                        HTLC_MODULE.to_string(),
+                        vec!["claim".to_string(), lock.id.to_string(), lock.recipient],
+                        preimage.clone(),
+                        tx.id,
+                    ));
+                }
+            }
+            TransactionKind::Refund { lock } => {
+                if let Some(lock) = self.hashlocks.get(*lock) {
+                    self.hashlocks.settle(lock.id, HashLockStatus::Refunded { tx: tx.id });
+                    *self.balances.entry(lock.sender.clone()).or_insert(0) += lock.amount;
+                    logs.push(Log::new(
+                        HTLC_MODULE.to_string(),
+                        vec!["refund".to_string(), lock.id.to_string(), lock.sender],
+                        lock.amount.to_le_bytes().to_vec(),
+                        tx.id,
+                    ));
+                }
+            }
+            TransactionKind::Coinbase { .. } => {
+                *self.balances.entry(tx.to.clone()).or_insert(0) += tx.amount;
+                self.issue(tx.amount);
+                if self.genesis.model == LedgerModel::Utxo {
+                    self.utxos.create(OutPoint { tx: tx.id, index: 0 }, Coin { owner: tx.to.clone(), amount: tx.amount });
+                }
+                logs.push(Log::new(
+                    SUPPLY_MODULE.to_string(),
+                    vec!["coinbase".to_string(), tx.to.clone()],
+                    tx.amount.to_le_bytes().to_vec(),
+                    tx.id,
+                ));
+            }
+            TransactionKind::TransferFrom { owner } => {
+                self.allowances.spend(owner, &tx.from, tx.amount);
+                self.move_balance(owner, &tx.to, tx.amount);
+                logs.push(Log::payment(tx, owner, &tx.to, tx.amount));
+            }
+            TransactionKind::Spend { inputs, outputs } => {
+                // The fee was taken above, so together this removes the inputs' full value from the sender
+                if let Some(mut balance) = self.balances.get_mut(&tx.from) {
+                    Self::debit(&mut balance, tx.amount);
+                }
+                self.utxos.spend(tx.id, inputs);
+                
+                for (index, output) in outputs.iter().enumerate() {
+                    let outpoint = OutPoint { tx: tx.id, index: index as u32 };
+                    self.utxos.create(outpoint, Coin { owner: output.to.clone(), amount: output.amount });
+                    *self.balances.entry(output.to.clone()).or_insert(0) += output.amount;
+                    logs.push(Log::payment(tx, &tx.from, &output.to, output.amount));
+                }
+            }
+            TransactionKind::Mint => {
+                *self.balances.entry(tx.to.clone()).or_insert(0) += tx.amount;
+                self.issue(tx.amount);
+                logs.push(Log::new(
+                    SUPPLY_MODULE.to_string(),
+                    vec!["mint".to_string(), tx.to.clone(), tx.from.clone()],
+                    tx.amount.to_le_bytes().to_vec(),
+                    tx.id,
+                ));
+            }
+            TransactionKind::Burn => {
+                if let Some(mut balance) = self.balances.get_mut(&tx.from) {
+                    Self::debit(&mut balance, tx.amount);
+                }
+                self.retire(tx.amount);
+                logs.push(Log::new(
+                    SUPPLY_MODULE.to_string(),
+                    vec!["burn".to_string(), tx.from.clone()],
+                    tx.amount.to_le_bytes().to_vec(),
+                    tx.id,
+                ));
+            }
+            TransactionKind::Payout(payments) => {
+                if let Some(mut balance) = self.balances.get_mut(&tx.from) {
+                    Self::debit(&mut balance, tx.amount);
+                }
+                
+                for payment in payments {
+                    *self.balances.entry(payment.to.clone()).or_insert(0) += payment.amount;
+                    logs.push(Log::payment(tx, &tx.from, &payment.to, payment.amount));
+                }
+            }
+            TransactionKind::Swap { counter_amount } => {
+                if let Some(mut balance) = self.balances.get_mut(&tx.from) {
+                    Self::debit(&mut balance, tx.amount);
+                }
+                if let Some(mut balance) = self.balances.get_mut(&tx.to) {
+                    Self::debit(&mut balance, *counter_amount);
+                }
+                
+                *self.balances.entry(tx.to.clone()).or_insert(0) += tx.amount;
+                *self.balances.entry(tx.from.clone()).or_insert(0) += counter_amount;
+                logs.push(Log::transfer(tx));
+                logs.push(Log::payment(tx, &tx.to, &tx.from, *counter_amount));
+            }
+            TransactionKind::Stake { .. } | TransactionKind::Delegate => {
+                if let Some(mut balance) = self.balances.get_mut(&tx.from) {
+                    Self::debit(&mut balance, tx.amount);
+                }
+                
+                if let (TransactionKind::Stake { vrf_key }, Some(auth)) = (&tx.kind, &tx.authorization) {
+                    self.stakes.register(&tx.to, &auth.public_key, vrf_key.as_deref(), block.header.height);
+                }
+                self.stakes.bond(&tx.to, &tx.from, tx.amount);
+                logs.push(Self::stake_log(tx));
+            }
+            TransactionKind::Unstake => {
+                self.stakes.unbond(&tx.to, &tx.from, tx.amount);
+                *self.balances.entry(tx.from.clone()).or_insert(0) += tx.amount;
+                logs.push(Self::stake_log(tx));
+            }
+            TransactionKind::Deploy { code } => {
+                let address = ContractStore::address_for(tx);
+                if let Err(e) = self.contracts.deploy(&address, code) {
+                    error!("Committed deploy {} failed: {}", tx.id, e);
+                }
+                self.move_balance(&tx.from, &address, tx.amount);
+                
+                logs.push(Log::new(
+                    CONTRACT_MODULE.to_string(),
+                    vec!["deploy".to_string(), address, tx.from.clone()],
+                    Vec::new(),
+                    tx.id,
+                ));
+            }
+            TransactionKind::Call { method, input, gas_limit } => {
+                self.move_balance(&tx.from, &tx.to, tx.amount);
+                let balance = self.balances.get(&tx.to).map(|entry| *entry.value()).unwrap_or(0);
+                let (receipt, transfers) = self.contracts.call(tx, method, input, *gas_limit, balance);
+                
+                // A failed call sends the value back; its fee is still spent
+                if !receipt.success {
+                    self.move_balance(&tx.to, &tx.from, tx.amount);
+                }
+                for (to, amount) in &transfers {
+                    self.move_balance(&tx.to, to, *amount);
+                    logs.push(Log::payment(tx, &tx.to, to, *amount));
+                }
+                for event in receipt.events {
+                    logs.push(Log::new(
+                        CONTRACT_MODULE.to_string(),
+                        vec!["event".to_string(), tx.to.clone(), event.topic],
+                        event.data,
+                        tx.id,
+                    ));
+                }
+            }
+            TransactionKind::Evidence(evidence) => {
+                let bonded = self.stakes.total_stake();
+                self.stakes.slash(&tx.to, evidence, tx.id, block.header.height, &self.params().slashing);
+                self.retire(bonded - self.stakes.total_stake());
+                
+                logs.push(Log::new(
+                    SLASH_MODULE.to_string(),
+                    vec![format!("{:?}", evidence.reason()), tx.to.clone(), tx.from.clone()],
+                    evidence.offense_height().to_le_bytes().to_vec(),
+                    tx.id,
+                ));
+            }
+            TransactionKind::Propose(action) => {
+                self.governance.propose(tx.id, &tx.from, action.clone(), block.header.height);
+                logs.push(Log::new(
+                    GOVERNANCE_MODULE.to_string(),
+                    vec!["propose".to_string(), tx.id.to_string(), tx.from.clone()],
+                    Vec::new(),
+                    tx.id,
+                ));
+            }
+            TransactionKind::Vote { proposal, approve } => {
+                self.governance.vote(*proposal, &tx.from, *approve);
+                logs.push(Log::new(
+                    GOVERNANCE_MODULE.to_string(),
+                    vec!["vote".to_string(), proposal.to_string(), tx.from.clone()],
+                    vec![*approve as u8],
+                    tx.id,
+                ));
+            }
+            TransactionKind::Schedule { amount, interval } => {
+                let epoch = self.epochs.read().unwrap().last().map_or(0, |record| record.epoch.number);
+                self.schedules.register(StandingOrder {
+                    id: tx.id,
+                    payer: tx.from.clone(),
+                    recipient: tx.to.clone(),
+                    amount: *amount,
+                    interval: *interval,
+                    created_at: block.header.height,
+                    next_due: epoch + interval,
+                    payments: 0,
+                    missed: 0,
+                    cancelled_at: None,
+                });
+                logs.push(Log::new(
+                    SCHEDULE_MODULE.to_string(),
+                    vec!["schedule".to_string(), tx.id.to_string(), tx.from.clone(), tx.to.clone()],
+                    amount.to_le_bytes().to_vec(),
+                    tx.id,
+                ));
+            }
+            TransactionKind::SetSpendingLimit(limit) => self.limits.set(&tx.from, tx.id, *limit),
+            TransactionKind::CancelSchedule { schedule } => {
+                self.schedules.cancel(*schedule, block.header.height);
+                logs.push(Log::new(
+                    SCHEDULE_MODULE.to_string(),
+                    vec!["cancel".to_string(), schedule.to_string(), tx.from.clone()],
+                    Vec::new(),
+                    tx.id,
+                ));
+            }
+            TransactionKind::CheckpointVote(vote) => {
+                let stake = self.stakes.validator(&tx.from).map(|v| v.total_stake()).unwrap_or(0);
+                let finalized = self.checkpoints.record(&tx.from, vote, stake, self.stakes.total_stake(), block.header.height);
+                
+                let action = if finalized { "finalized" } else { "vote" };
+                logs.push(Log::new(
+                    CHECKPOINT_MODULE.to_string(),
+                    vec![action.to_string(), vote.hash.clone(), tx.from.clone()],
+                    vote.height.to_le_bytes().to_vec(),
+                    tx.id,
+                ));
+            }
+        }
+    }
+    
+    // Takes in whatever balances the block just applied changed
+    fn commit_state(&self, block: &Block) {
+        let changes = self.state.commit(self.balances.take_written());
+        self.analytics.update(&changes);
+        self.touched.push(block, &changes);
+    }
+    
+    // Pays the standing orders due in the epoch `block` opened; runs after the block's transactions so they validate without it
+    fn pay_standing_orders(&self, block: &Block, block_logs: &mut Vec<Log>) {
+        let mut epochs = self.epochs.write().unwrap();
+        let Some(record) = epochs.last_mut().filter(|record| Self::opened_by(record, block)) else {
+            return;
+        };
         
-        // Update balances
-        for tx in &transactions {
-            if !tx.from.is_empty() {
-                self.balances.entry(tx.from.clone()).and_modify(|balance| {
-                    *balance -= tx.amount;
-                }).or_insert(0);
+        let epoch = record.epoch.number;
+        for order in self.schedules.due(epoch) {
+            let paid = self.liquid_balance(&order.payer, block.header.height) >= order.amount;
+            if paid {
+                self.move_balance(&order.payer, &order.recipient, order.amount);
+                block_logs.push(Log::new(
+                    TRANSFER_MODULE.to_string(),
+                    vec!["transfer".to_string(), order.payer.clone(), order.recipient.clone()],
+                    order.amount.to_le_bytes().to_vec(),
+                    order.id,
+                ));
             }
             
-            self.balances.entry(tx.to.clone()).and_modify(|balance| {
-                *balance += tx.amount;
-            }).or_insert(tx.amount);
+            self.schedules.settle(order.id, epoch, paid);
+            record.installments.push(Installment { order: order.id, paid, previous_due: order.next_due });
         }
+    }
+    
+    fn revert_standing_orders(&self, block: &Block) {
+        let epochs = self.epochs.read().unwrap();
+        let Some(record) = epochs.last().filter(|record| Self::opened_by(record, block)) else {
+            return;
+        };
         
-        // Create new block
-        let previous_hash = {
-            let blocks = self.blocks.read().await;
-            blocks.last().map(|b| b.hash.clone()).unwrap_or_default()
+        for installment in record.installments.iter().rev() {
+            if let (true, Some(order)) = (installment.paid, self.schedules.get(installment.order)) {
+                self.move_balance(&order.recipient, &order.payer, order.amount);
+            }
+            self.schedules.unsettle(installment);
+        }
+    }
+    
+    fn opened_by(record: &EpochRecord, block: &Block) -> bool {
+        record.epoch.number > 0 && record.epoch.start_height == block.header.height
+    }
+    
+    // Only for amounts already validated against `from`'s balance
+    fn move_balance(&self, from: &str, to: &str, amount: u64) {
+        if amount == 0 {
+            return;
+        }
+        
+        if let Some(mut balance) = self.balances.get_mut(from) {
+            *balance = balance.saturating_sub(amount);
+        }
+        *self.balances.entry(to.to_string()).or_insert(0) += amount;
+    }
+    
+    // Units coming into existence, as opposed to moving between accounts
+    fn issue(&self, amount: u64) {
+        self.issued.fetch_add(amount, Ordering::Relaxed);
+    }
+    
+    fn retire(&self, amount: u64) {
+        self.issued.fetch_sub(amount, Ordering::Relaxed);
+    }
+    
+    // Account credited with a block's fees, or None to burn them
+    fn fee_recipient(&self, block: &Block) -> Option<String> {
+        let params = self.params();
+        if params.fee_destination == FeeDestination::Burn || params.emission.is_some() || self.genesis.model == LedgerModel::Utxo {
+            return None;
+        }
+        
+        let producer = block.header.producer.as_ref()?;
+        if let Some(validator) = self.stakes.validator_by_key(producer) {
+            return Some(validator.address);
+        }
+        self.account_keys.iter()
+            .find(|entry| entry.value() == producer)
+            .map(|entry| entry.key().clone())
+    }
+    
+    // Opens a new epoch when `block` crosses a boundary, running the hooks and recording what they changed
+    fn advance_epoch(&self, block: &Block) {
+        let Some(schedule) = self.params().epochs else {
+            return;
         };
         
-        let mut new_block = Block::new(previous_hash, transactions);
-        new_block.mine(2); // Simple proof-of-work
+        let mut epochs = self.epochs.write().unwrap();
+        let Some(previous) = epochs.last().map(|record| record.epoch.clone()) else {
+            return;
+        };
         
-        // Validate and add block
-        new_block.validate(Some(&self.get_latest_block().await))?;
+        let number = schedule.epoch_at(block.header.height, block.header.timestamp);
+        if number <= previous.number {
+            return;
+        }
         
-        {
-            let mut blocks = self.blocks.write().await;
-            blocks.push(new_block);
+        let mut record = EpochRecord {
+            epoch: Epoch {
+                number,
+                era: schedule.era_of(number),
+                start_height: block.header.height,
+                start_time: block.header.timestamp,
+            },
+            validators: self.epoch_validators(),
+            credits: Vec::new(),
+            previous_params: None,
+            decided: Vec::new(),
+            installments: Vec::new(),
+        };
+        
+        for hook in self.epoch_hooks.read().unwrap().iter() {
+            let effects = hook.on_epoch(&record.epoch, &previous, &self.stakes, &self.params());
+            
+            for (address, amount) in effects.credits {
+                *self.balances.entry(address.clone()).or_insert(0) += amount;
+                self.issue(amount);
+                record.credits.push((address, amount));
+            }
+            
+            if let Some(params) = effects.params {
+                if let Err(e) = params.validate() {
+                    warn!("Epoch hook {} proposed invalid parameters: {}", hook.name(), e);
+                    continue;
+                }
+                
+                let replaced = std::mem::replace(&mut *self.params.write().unwrap(), params);
+                record.previous_params.get_or_insert(replaced);
+                info!("Epoch hook {} changed chain parameters at epoch {}", hook.name(), number);
+            }
         }
         
-        let processing_time = start_time.elapsed();
-        self.performance_monitor.record_batch(transactions.len(), processing_time);
+        self.decide_proposals(&mut record, block.header.height);
+        epochs.push(record);
+    }
+    
+    // Enacts proposals approved by two thirds of the voting stake and closes those that can no longer pass
+    fn decide_proposals(&self, record: &mut EpochRecord, height: u64) {
+        let voting: HashMap<String, u128> = self.stakes.active_validators()
+            .into_iter()
+            .filter(|validator| !validator.is_jailed(height))
+            .map(|validator| (validator.address.clone(), validator.total_stake() as u128))
+            .collect();
+        let total: u128 = voting.values().sum();
+        
+        for proposal in self.governance.open_proposals() {
+            let (mut approved, mut rejected) = (0u128, 0u128);
+            for (voter, approve) in &proposal.votes {
+                let stake = voting.get(voter).copied().unwrap_or(0);
+                match approve {
+                    true => approved += stake,
+                    false => rejected += stake,
+                }
+            }
+            
+            let epoch = record.epoch.number;
+            if total > 0 && approved * 3 >= total * 2 {
+                let previous_jail = self.enact(&proposal.action, height, record);
+                self.governance.set_status(proposal.id, ProposalStatus::Enacted { epoch }, previous_jail);
+                info!("Enacted proposal {} at epoch {}", proposal.id, epoch);
+            } else if rejected * 3 > total {
+                self.governance.set_status(proposal.id, ProposalStatus::Rejected { epoch }, None);
+            } else {
+                continue;
+            }
+            record.decided.push(proposal.id);
+        }
+    }
+    
+    // Returns the jail a removed validator had, so the removal can be undone
+    fn enact(&self, action: &ProposalAction, height: u64, record: &mut EpochRecord) -> Option<u64> {
+        match action {
+            ProposalAction::AddValidator { address, public_key, vrf_key } => {
+                self.stakes.register(address, public_key, vrf_key.as_deref(), height);
+                None
+            }
+            ProposalAction::RemoveValidator { address } => self.stakes.set_jail(address, Some(u64::MAX)),
+            ProposalAction::ChangeParams(params) => {
+                let replaced = std::mem::replace(&mut *self.params.write().unwrap(), (**params).clone());
+                record.previous_params.get_or_insert(replaced);
+                None
+            }
+        }
+    }
+    
+    fn undo_decision(&self, proposal: &Proposal) {
+        if let ProposalStatus::Enacted { .. } = proposal.status {
+            match &proposal.action {
+                ProposalAction::AddValidator { address, .. } => self.stakes.deregister(address),
+                ProposalAction::RemoveValidator { address } => {
+                    self.stakes.set_jail(address, proposal.previous_jail);
+                }
+                // Restored from the epoch record
+                ProposalAction::ChangeParams(_) => {}
+            }
+        }
         
-        info!("Processed {} transactions in {:?}", transactions.len(), processing_time);
+        self.governance.set_status(proposal.id, ProposalStatus::Open, None);
+    }
+    
+    // Undoes `advance_epoch` when the block that opened the current epoch leaves the main chain
+    fn revert_epoch(&self, block: &Block) {
+        let mut epochs = self.epochs.write().unwrap();
+        if !epochs.last().is_some_and(|record| Self::opened_by(record, block)) {
+            return;
+        }
         
-        Ok(())
+        let record = epochs.pop().unwrap();
+        for proposal in record.decided.iter().rev().filter_map(|id| self.governance.proposal(*id)) {
+            self.undo_decision(&proposal);
+        }
+        
+        for (address, amount) in &record.credits {
+            if let Some(mut balance) = self.balances.get_mut(address) {
+                *balance = balance.saturating_sub(*amount);
+            }
+            self.retire(*amount);
+        }
+        
+        if let Some(params) = record.previous_params {
+            *self.params.write().unwrap() = params;
+        }
     }
     
-    pub async fn get_latest_block(&self) -> Block {
-        let blocks = self.blocks.read().await;
-        blocks.last().unwrap().clone()
+    fn epoch_validators(&self) -> Vec<(String, u64)> {
+        self.stakes.active_validators()
+            .into_iter()
+            .map(|validator| (validator.address.clone(), validator.total_stake()))
+            .collect()
     }
     
-    pub async fn get_balance(&self, address: &str) -> u64 {
-        self.balances.get(address)
-            .map(|entry| *entry.value())
-            .unwrap_or(0)
+    // Hooks only affect epochs opened after they are added
+    pub fn add_epoch_hook(&self, hook: Arc<dyn EpochHook>) {
+        self.epoch_hooks.write().unwrap().push(hook);
     }
     
-    pub async fn get_transaction_count(&self) -> usize {
-        let blocks = self.blocks.read().await;
-        blocks.iter().map(|b| b.transactions.len()).sum()
+    pub fn current_epoch(&self) -> Option<EpochRecord> {
+        self.epochs.read().unwrap().last().cloned()
     }
     
-    pub fn get_performance_stats(&self) -> crate::performance::PerformanceStats {
-        self.performance_monitor.get_stats()
+    pub fn proposal(&self, id: uuid::Uuid) -> Option<Proposal> {
+        self.governance.proposal(id)
     }
     
-    pub async fn start_background_processor(&self) {
-        let ledger = self.clone();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(std::time::Duration::from_millis(10));
+    pub fn epoch(&self, number: u64) -> Option<EpochRecord> {
+        self.epochs.read().unwrap().iter().find(|record| record.epoch.number == number).cloned()
+    }
+    
+    fn stake_log(tx: &Transaction) -> Log {
+        let action = match tx.kind {
+            TransactionKind::Stake { .. } => "stake",
+            TransactionKind::Delegate => "delegate",
+            _ => "unstake",
+        };
+        
+        Log::new(
+            STAKE_MODULE.to_string(),
+            vec![action.to_string(), tx.to.clone(), tx.from.clone()],
+            tx.amount.to_le_bytes().to_vec(),
+            tx.id,
+        )
+    }
+    
+    // Undoes `apply_block` and `index_block` for a block leaving the main chain
+    fn revert_block(&self, block: &Block) {
+        let fees = block.transactions.iter().map(|tx| tx.fee).sum::<u64>();
+        match self.fee_recipient(block).filter(|_| fees > 0) {
+            Some(recipient) => {
+                if let Some(mut balance) = self.balances.get_mut(&recipient) {
+                    *balance = balance.saturating_sub(fees);
+                }
+            }
+            None => self.issue(fees),
+        }
+        
+        self.revert_standing_orders(block);
+        
+        let epoch = self.spending_epoch(block.header.height, block.header.timestamp);
+        for tx in block.transactions.iter().rev() {
+            match &tx.kind {
+                TransactionKind::Anchor(anchor) => {
+                    self.anchors.remove_if(&(anchor.namespace.clone(), anchor.sequence), |_, record| {
+                        record.transaction_id == tx.id
+                    });
+                }
+                TransactionKind::Transfer => {
+                    if let Some(mut balance) = self.balances.get_mut(&tx.to) {
+                        *balance = balance.saturating_sub(tx.amount);
+                    }
+                    *self.balances.entry(tx.from.clone()).or_insert(0) += tx.amount;
+                }
+                TransactionKind::Vest(_) => {
+                    self.vesting.revoke(&tx.to, tx.id);
+                    self.move_balance(&tx.to, &tx.from, tx.amount);
+                }
+                TransactionKind::Approve { .. } => {
+                    self.allowances.unapprove(&tx.from, &tx.to, tx.id);
+                }
+                TransactionKind::HashLock { .. } => {
+                    self.hashlocks.remove(tx.id);
+                    *self.balances.entry(tx.from.clone()).or_insert(0) += tx.amount;
+                }
+                TransactionKind::Claim { lock, .. } | TransactionKind::Refund { lock } => {
+                    if let Some(lock) = self.hashlocks.get(*lock) {
+                        let paid = if matches!(tx.kind, TransactionKind::Claim { .. }) { &lock.recipient } else { &lock.sender };
+                        if let Some(mut balance) = self.balances.get_mut(paid) {
+                            *balance = balance.saturating_sub(lock.amount);
+                        }
+                        self.hashlocks.settle(lock.id, HashLockStatus::Open);
+                    }
+                }
+                TransactionKind::Coinbase { .. } => {
+                    self.utxos.destroy(&OutPoint { tx: tx.id, index: 0 });
+                    if let Some(mut balance) = self.balances.get_mut(&tx.to) {
+                        *balance = balance.saturating_sub(tx.amount);
+                    }
+                    self.retire(tx.amount);
+                }
+                TransactionKind::TransferFrom { owner } => {
+                    self.move_balance(&tx.to, owner, tx.amount);
+                    self.allowances.refund(owner, &tx.from, tx.amount);
+                }
+                TransactionKind::Spend { outputs, .. } => {
+                    for (index, output) in outputs.iter().enumerate().rev() {
+                        self.utxos.destroy(&OutPoint { tx: tx.id, index: index as u32 });
+                        if let Some(mut balance) = self.balances.get_mut(&output.to) {
+                            *balance = balance.saturating_sub(output.amount);
+                        }
+                    }
+                    
+                    self.utxos.unspend(tx.id);
+                    *self.balances.entry(tx.from.clone()).or_insert(0) += tx.amount;
+                }
+                TransactionKind::Mint => {
+                    if let Some(mut balance) = self.balances.get_mut(&tx.to) {
+                        *balance = balance.saturating_sub(tx.amount);
+                    }
+                    self.retire(tx.amount);
+                }
+                TransactionKind::Burn => {
+                    *self.balances.entry(tx.from.clone()).or_insert(0) += tx.amount;
+                    self.issue(tx.amount);
+                }
+                TransactionKind::Payout(payments) => {
+                    for payment in payments.iter().rev() {
+                        if let Some(mut balance) = self.balances.get_mut(&payment.to) {
+                            *balance = balance.saturating_sub(payment.amount);
+                        }
+                    }
+                    *self.balances.entry(tx.from.clone()).or_insert(0) += tx.amount;
+                }
+                TransactionKind::Swap { counter_amount } => {
+                    if let Some(mut balance) = self.balances.get_mut(&tx.from) {
+                        *balance = balance.saturating_sub(*counter_amount);
+                    }
+                    if let Some(mut balance) = self.balances.get_mut(&tx.to) {
+                        *balance = balance.saturating_sub(tx.amount);
+                    }
+                    
+                    *self.balances.entry(tx.to.clone()).or_insert(0) += counter_amount;
+                    *self.balances.entry(tx.from.clone()).or_insert(0) += tx.amount;
+                }
+                TransactionKind::Stake { .. } | TransactionKind::Delegate => {
+                    // A validator registered by this stake stays listed with no bonds
+                    self.stakes.unbond(&tx.to, &tx.from, tx.amount);
+                    *self.balances.entry(tx.from.clone()).or_insert(0) += tx.amount;
+                }
+                TransactionKind::Unstake => {
+                    if let Some(mut balance) = self.balances.get_mut(&tx.from) {
+                        *balance = balance.saturating_sub(tx.amount);
+                    }
+                    self.stakes.bond(&tx.to, &tx.from, tx.amount);
+                }
+                TransactionKind::Deploy { .. } => {
+                    let address = ContractStore::address_for(tx);
+                    self.move_balance(&address, &tx.from, tx.amount);
+                    self.contracts.undeploy(&address);
+                }
+                TransactionKind::Call { .. } => {
+                    if let Some((receipt, transfers)) = self.contracts.revert(tx.id) {
+                        for (to, amount) in transfers.iter().rev() {
+                            self.move_balance(to, &tx.to, *amount);
+                        }
+                        if receipt.success {
+                            self.move_balance(&tx.to, &tx.from, tx.amount);
+                        }
+                    }
+                }
+                TransactionKind::Evidence(_) => {
+                    let bonded = self.stakes.total_stake();
+                    self.stakes.revert_slash(tx.id);
+                    self.issue(self.stakes.total_stake() - bonded);
+                }
+                TransactionKind::CheckpointVote(vote) => self.checkpoints.revert(&tx.from, vote, block.header.height),
+                TransactionKind::Propose(_) => self.governance.withdraw(tx.id),
+                TransactionKind::Vote { proposal, .. } => self.governance.unvote(*proposal, &tx.from),
+                TransactionKind::Schedule { .. } => self.schedules.unregister(tx.id),
+                TransactionKind::CancelSchedule { schedule } => self.schedules.reinstate(*schedule),
+                TransactionKind::SetSpendingLimit(_) => self.limits.unset(&tx.from, tx.id),
+            }
+            for (account, amount) in Self::spends(tx) {
+                self.limits.unrecord(account, epoch, amount);
+            }
             
-            loop {
-                interval.tick().await;
-                
-                if let Err(e) = ledger.process_transactions(1000).await {
-                    error!("Error processing transactions: {}", e);
+            if tx.fee > 0 {
+                *self.balances.entry(tx.from.clone()).or_insert(0) += tx.fee;
+            }
+            if let Some(original) = tx.replaces {
+                self.replacements.remove_if(&original, |_, replacement| *replacement == tx.id);
+            }
+            
+            self.tx_index.remove_if(&tx.id, |_, height| *height == block.header.height);
+            self.memos.remove(block.header.height, tx);
+            self.invoices.unsettle(tx);
+        }
+        self.addresses.remove(block);
+        self.volumes.remove(block);
+        
+        for (address, reward) in self.consensus.block_rewards(block) {
+            if let Some(mut balance) = self.balances.get_mut(&address) {
+                *balance = balance.saturating_sub(reward);
+            }
+            self.retire(reward);
+        }
+        
+        self.revert_epoch(block);
+        self.logs.remove(block.header.height);
+        self.analytics.update(&self.state.revert());
+        self.touched.pop();
+        self.block_index.remove(&block.header.hash);
+        if let Some(producer) = &block.header.producer {
+            if let Some(mut heights) = self.producer_index.get_mut(producer) {
+                heights.remove(&block.header.height);
+            }
+        }
+    }
+    
+    // Checks a block's transactions against the committed state, applying them in order
+    fn validate_block(&self, blocks: &Chain, block: &Block, parent: &Block, params: &ChainParams) -> Result<()> {
+        params.check_block_limits(block)?;
+        params.check_coinbase(block)?;
+        self.check_block_time(blocks, block, parent, &params.block_time)?;
+        self.consensus.validate(block, parent, params)
+    }
+    
+    // Compared at millisecond precision, which is what the block hash commits to
+    fn check_block_time(
+        &self,
+        blocks: &Chain,
+        block: &Block,
+        parent: &Block,
+        rules: &BlockTimeRules,
+    ) -> Result<()> {
+        let timestamp = block.header.timestamp.timestamp_millis();
+        
+        let floor = match rules.ordering {
+            TimestampOrdering::AfterParent => parent.header.timestamp,
+            TimestampOrdering::MedianTimePast { window } => self.median_time_past(blocks, parent, window),
+        };
+        if timestamp <= floor.timestamp_millis() {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Block timestamp {} is not after {}",
+                block.header.timestamp, floor,
+            )));
+        }
+        
+        let skipped = rules.skipped_slots(parent.header.timestamp, block.header.timestamp);
+        if block.header.skipped_slots != skipped {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Block claims {} skipped slots, but its timestamp is {} slots on from its parent's",
+                block.header.skipped_slots, skipped,
+            )));
+        }
+        
+        if let Some(drift) = rules.max_future_drift_ms {
+            let limit = crate::clock::now().timestamp_millis().saturating_add(drift as i64);
+            if timestamp > limit {
+                return Err(LedgerError::BlockValidationFailed(format!(
+                    "Block timestamp {} is more than {}ms ahead of the local clock",
+                    block.header.timestamp, drift,
+                )));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    // Median timestamp of `parent` and the blocks before it on its own branch
+    fn median_time_past(&self, blocks: &Chain, parent: &Block, window: usize) -> chrono::DateTime<chrono::Utc> {
+        let mut timestamps = vec![parent.header.timestamp];
+        let mut current = parent.clone();
+        while timestamps.len() < window {
+            match self.find_block(blocks, &current.header.previous_hash) {
+                Some(block) => {
+                    timestamps.push(block.header.timestamp);
+                    current = block;
                 }
+                None => break,
             }
-        });
+        }
+        
+        timestamps.sort();
+        timestamps[timestamps.len() / 2]
+    }
+    
+    fn check_block_state(&self, block: &Block) -> Result<()> {
+        let state_root = self.state.root();
+        if block.header.state_root != state_root {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Block commits to state root {}, but its parent left {}",
+                block.header.state_root, state_root,
+            )));
+        }
+        
+        let epoch = self.spending_epoch(block.header.height, block.header.timestamp);
+        let mut state = StateOverlay::new(self, block.header.height, epoch);
+        block.transactions.iter().try_for_each(|tx| state.apply(tx))
+    }
+    
+    // Appends a validated block to the main chain; the caller is the chain writer. A reorg passes `publish`
+    // false and publishes once the whole branch is in, so readers never see it half applied
+    fn append_block(&self, blocks: &mut Chain, block: Block, publish: bool) -> Result<()> {
+        self.consensus.finalize(&block)?;
+        self.apply_block(&block);
+        self.index_block(block.header.height, &block);
+        // Published before its transactions leave the pool, so a lock-free lookup finds each one in one or the other
+        let block = Arc::new(block);
+        blocks.push(Arc::clone(&block));
+        if publish {
+            self.publish_tip(blocks);
+        }
+        for tx in &block.transactions {
+            self.remove_pending(tx);
+            self.submitters.remove(&tx.id);
+            if let Some(position) = tx.bundle {
+                self.bundles.remove(&position.bundle);
+            }
+            
+            let replaced = tx.replaces.and_then(|original| self.transaction_pool.get(&original).map(|entry| entry.transaction.clone()));
+            if let Some(replaced) = replaced {
+                self.reject(&replaced, format!("Replaced by {}", tx.id));
+            }
+            
+            self.emit(|| LedgerEvent::TransactionIncluded {
+                transaction: tx.clone(),
+                height: block.header.height,
+                block_hash: block.header.hash.clone(),
+            });
+        }
+        self.mempool_depth.record(block.header.height, self.transaction_pool.len());
+        self.expire_indexes(blocks);
+        self.announce_finality(blocks);
+        #[cfg(debug_assertions)]
+        self.audit(blocks);
+        Ok(())
+    }
+    
+    // Waits out any other writer. Only the writer publishes, so the chain it starts from stays the tip until it's dropped
+    async fn write_chain(&self) -> ChainWriter<'_> {
+        let writer = self.writer.lock().await;
+        ChainWriter { _writer: writer, chain: Chain::clone(&self.chain.load()) }
+    }
+    
+    // Publishes `blocks` to readers of the chain, and its last block as the tip to head watchers
+    fn publish_tip(&self, blocks: &Chain) {
+        self.chain.store(Arc::new(blocks.clone()));
+        self.head.send_replace(ChainHead::of(blocks.last().unwrap()));
+    }
+    
+    // Panics on a broken invariant at every `invariant_interval`th height
+    #[cfg(debug_assertions)]
+    fn audit(&self, blocks: &Chain) {
+        let interval = self.invariant_interval.load(Ordering::Relaxed);
+        let height = blocks.len() as u64 - 1;
+        if interval == 0 || !height.is_multiple_of(interval) {
+            return;
+        }
+        
+        let violations = self.invariant_violations(blocks);
+        assert!(violations.is_empty(), "Ledger invariants broken at height {}: {:?}", height, violations);
+    }
+    
+    fn invariant_violations(&self, blocks: &Chain) -> Vec<InvariantViolation> {
+        let mut violations = Vec::new();
+        let issued = self.issued.load(Ordering::Relaxed);
+        let balances = self.balances.iter().map(|entry| *entry.value() as u128).sum::<u128>();
+        let held = balances + self.stakes.total_stake() as u128 + self.hashlocks.locked() as u128;
+        if held != issued as u128 {
+            violations.push(InvariantViolation::SupplyMismatch { issued, held });
+        }
+        
+        for entry in self.balances.iter().filter(|entry| *entry.value() > issued) {
+            violations.push(InvariantViolation::Underflow { address: entry.key().clone(), balance: *entry.value() });
+        }
+        
+        for (height, block) in blocks.iter().enumerate() {
+            let parent = height.checked_sub(1).map(|parent| &blocks[parent].header);
+            if let Err(e) = block.header.validate(parent) {
+                violations.push(InvariantViolation::BrokenLink { height: height as u64, reason: e.to_string() });
+            }
+        }
+        
+        for entry in self.transaction_pool.iter() {
+            if let Some(height) = self.tx_index.get(entry.key()) {
+                violations.push(InvariantViolation::PooledButIncluded { id: *entry.key(), height: *height });
+            }
+        }
+        violations
+    }
+    
+    // Drops the block that just fell out of each windowed index. A reorg reverting blocks doesn't re-index the ones
+    // below, so a window can run short until the chain grows back
+    fn expire_indexes(&self, blocks: &Chain) {
+        let retention = self.index_retention();
+        let tip = blocks.len() as u64 - 1;
+        if let Some(height) = retention.memos.expired(tip) {
+            for tx in &blocks[height as usize].transactions {
+                self.memos.remove(height, tx);
+            }
+        }
+        if let Some(height) = retention.addresses.expired(tip) {
+            self.addresses.remove(&blocks[height as usize]);
+        }
+        self.prune_state(retention.state, tip);
+    }
+    
+    // Past states are only dropped once no reorg can reach back to them, and can't be rebuilt afterwards
+    fn prune_state(&self, retention: Retention, tip: u64) {
+        self.state.prune_below(retention.floor(tip).unwrap_or(tip).min(self.finalized_below(tip)));
+    }
+    
+    // Emits finality for the main-chain blocks that became final since the last announcement
+    fn announce_finality(&self, blocks: &Chain) {
+        let finalized = self.finalized_below(blocks.len() as u64 - 1);
+        let announced = self.announced_finality.fetch_max(finalized, Ordering::Relaxed);
+        for block in blocks.iter().take(finalized as usize + 1).skip(announced as usize + 1) {
+            for tx in &block.transactions {
+                self.emit(|| LedgerEvent::TransactionFinalized {
+                    transaction: tx.clone(),
+                    height: block.header.height,
+                    block_hash: block.header.hash.clone(),
+                });
+            }
+        }
+    }
+    
+    // Must be called by the chain writer so indexes and chain stay in step
+    fn index_block(&self, height: u64, block: &Block) {
+        self.block_index.insert(block.header.hash.clone(), height);
+        if let Some(producer) = &block.header.producer {
+            self.producer_index.entry(producer.clone()).or_default().insert(height);
+        }
+        
+        let retention = self.index_retention();
+        for tx in &block.transactions {
+            self.tx_index.insert(tx.id, height);
+            if retention.memos.is_enabled() {
+                self.memos.insert(height, tx);
+            }
+        }
+        if retention.addresses.is_enabled() {
+            self.addresses.insert(block);
+        }
+        self.volumes.insert(block);
+        
+        for paid in self.invoices.settle(height, block) {
+            let _ = self.invoice_events.send(paid);
+        }
+    }
+    
+    pub async fn add_transaction(&self, transaction: Transaction) -> Result<()> {
+        self.submit(transaction, None, true).await
+    }
+    
+    // As `add_transaction`, naming the peer or client it came from in the rejection audit
+    pub async fn add_transaction_from(&self, submitter: &str, transaction: Transaction) -> Result<()> {
+        self.submit(transaction, Some(submitter), true).await
+    }
+    
+    // As `add_transaction`, except that a full mempool refuses the transaction outright instead of letting it
+    // outbid and evict what is queued. Fullness is decided under the lock the transaction is queued under, so a pool
+    // that fills up while it is being checked refuses it too
+    pub async fn try_add_transaction(&self, transaction: Transaction) -> Result<()> {
+        self.submit(transaction, None, false).await
+    }
+    
+    // As `add_transaction`, except that while the mempool is full this waits for room instead of failing, so a
+    // caller submitting faster than blocks are produced is slowed to their pace. Other refusals return at once, and
+    // nothing queued is ever evicted to make room
+    pub async fn add_transaction_with_backpressure(&self, transaction: Transaction) -> Result<()> {
+        loop {
+            self.mempool.wait_for_room().await;
+            match self.check_and_admit(transaction.clone(), false).await {
+                // Someone else took the room first
+                Err(LedgerError::PerformanceLimitExceeded(_)) if self.mempool.is_full() => continue,
+                Err(e) => {
+                    self.audit_refusal(transaction, &e, None);
+                    return Err(e);
+                }
+                Ok(()) => return Ok(()),
+            }
+        }
+    }
+    
+    async fn submit(&self, transaction: Transaction, submitter: Option<&str>, evict: bool) -> Result<()> {
+        let id = transaction.id;
+        match self.check_and_admit(transaction.clone(), evict).await {
+            Ok(()) => {
+                if let Some(submitter) = submitter {
+                    self.submitters.insert(id, submitter.to_string());
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.audit_refusal(transaction, &e, submitter);
+                Err(e)
+            }
+        }
+    }
+    
+    // See `Mempool::insert` for `evict`
+    async fn check_and_admit(&self, transaction: Transaction, evict: bool) -> Result<()> {
+        // A replay of a committed transaction is a duplicate, whatever the state says about it now
+        if self.tx_index.contains_key(&transaction.id) {
+            return Err(LedgerError::DuplicateTransaction);
+        }
+        if self.replacements.contains_key(&transaction.id) {
+            return Err(LedgerError::InvalidTransaction(
+                "Transaction was replaced by one already on the chain".to_string(),
+            ));
+        }
+        
+        if transaction.bundle.is_some() {
+            return Err(LedgerError::InvalidTransaction(
+                "Bundled transactions are submitted together through add_bundle".to_string(),
+            ));
+        }
+        
+        self.check_transaction(&transaction)?;
+        self.check_dust(&transaction).map_err(LedgerError::InvalidTransaction)?;
+        
+        let next_height = self.next_height();
+        if transaction.is_expired_at(next_height, crate::clock::now()) {
+            return Err(LedgerError::InvalidTransaction(
+                "Transaction has expired".to_string(),
+            ));
+        }
+        
+        let Some(original) = transaction.replaces else {
+            return self.admit(transaction, evict);
+        };
+        
+        // The original's reservation is released first so the replacement can spend the same funds
+        let replaced = self.check_replacement(&transaction, original)?;
+        let replaced_id = replaced.id;
+        self.remove_pending(&replaced);
+        if let Err(e) = self.admit(transaction.clone(), evict) {
+            if self.admit(replaced, evict).is_err() {
+                warn!("Transaction {} was dropped after its replacement failed", replaced_id);
+            }
+            return Err(e);
+        }
+        
+        info!("Transaction {} replaced {}", transaction.id, replaced_id);
+        self.record_rejection(replaced, format!("Replaced by {}", transaction.id));
+        let _ = self.replacement_events.send(ReplacementEvent {
+            replaced: replaced_id,
+            replacement: transaction.id,
+            sender: transaction.from.clone(),
+            cancelled: transaction.is_cancellation(),
+        });
+        Ok(())
+    }
+    
+    // Queues a bundle, see `bundle::bundle`; its members may spend what earlier ones pay in, so funds are checked
+    // against the bundle as a whole
+    pub async fn add_bundle(&self, transactions: Vec<Transaction>) -> Result<()> {
+        let result = self.check_and_queue_bundle(transactions.clone()).await;
+        if let Err(e) = &result {
+            for tx in transactions {
+                self.audit_refusal(tx, e, None);
+            }
+        }
+        result
+    }
+    
+    async fn check_and_queue_bundle(&self, transactions: Vec<Transaction>) -> Result<()> {
+        let id = check_bundle(&transactions).map_err(LedgerError::InvalidTransaction)?;
+        if self.bundles.contains_key(&id) {
+            return Err(LedgerError::DuplicateTransaction);
+        }
+        
+        let next_height = self.next_height();
+        for tx in &transactions {
+            if self.tx_index.contains_key(&tx.id) || self.transaction_pool.contains_key(&tx.id) {
+                return Err(LedgerError::DuplicateTransaction);
+            }
+            
+            match self.check_transaction(tx) {
+                Ok(()) | Err(LedgerError::InsufficientBalance) => {}
+                Err(e) => return Err(e),
+            }
+            self.check_dust(tx).map_err(LedgerError::InvalidTransaction)?;
+            
+            if tx.is_expired_at(next_height, crate::clock::now()) {
+                return Err(LedgerError::InvalidTransaction(format!(
+                    "Transaction {} has expired",
+                    tx.id,
+                )));
+            }
+        }
+        
+        self.check_bundle_state(&transactions, next_height)?;
+        self.queue_bundle(id, transactions)
+    }
+    
+    // Runs the bundle against the committed state, then checks it leaves what pending transactions already reserved
+    fn check_bundle_state(&self, transactions: &[Transaction], height: u64) -> Result<()> {
+        let epoch = self.spending_epoch(height, crate::clock::now());
+        let mut state = StateOverlay::new(self, height, epoch);
+        for tx in transactions {
+            state.apply(tx).map_err(|e| match e {
+                LedgerError::BlockValidationFailed(reason) => LedgerError::InvalidTransaction(reason),
+                e => e,
+            })?;
+        }
+        
+        for (account, _) in transactions.iter().flat_map(Self::outflows) {
+            if *state.balance(account) < self.reserved_balance(account) {
+                return Err(LedgerError::InsufficientBalance);
+            }
+        }
+        Ok(())
+    }
+    
+    // Pools a checked bundle; reservations aren't held to the spendable balance, which members may only reach
+    // through each other
+    fn queue_bundle(&self, id: uuid::Uuid, transactions: Vec<Transaction>) -> Result<()> {
+        if transactions.iter().any(|tx| self.transaction_pool.contains_key(&tx.id)) {
+            return Err(LedgerError::DuplicateTransaction);
+        }
+        
+        for tx in &transactions {
+            for (account, outflow) in Self::outflows(tx) {
+                *self.reserved.entry(account.to_string()).or_insert(0) += outflow;
+            }
+            self.transaction_pool.insert(tx.id, PendingTransaction {
+                transaction: tx.clone(),
+                admitted_at: chrono::Utc::now(),
+            });
+        }
+        for tx in &transactions {
+            self.emit(|| LedgerEvent::TransactionAdmitted { transaction: tx.clone() });
+        }
+        self.bundles.insert(id, transactions);
+        Ok(())
+    }
+    
+    fn drop_bundle(&self, id: uuid::Uuid, reason: &str) {
+        if let Some((_, transactions)) = self.bundles.remove(&id) {
+            transactions.iter().for_each(|tx| self.reject(tx, reason.to_string()));
+        }
+    }
+    
+    pub fn pending_bundles(&self) -> Vec<Vec<Transaction>> {
+        self.bundles.iter().map(|entry| entry.value().clone()).collect()
+    }
+    
+    // The pending transaction `transaction` takes the place of: `original` itself, or an earlier replacement of it
+    fn check_replacement(&self, transaction: &Transaction, original: uuid::Uuid) -> Result<Transaction> {
+        let pending = self.transaction_pool.get(&original).map(|entry| entry.transaction.clone()).or_else(|| {
+            self.transaction_pool.iter()
+                .find(|entry| entry.transaction.replaces == Some(original))
+                .map(|entry| entry.transaction.clone())
+        });
+        let Some(replaced) = pending else {
+            return Err(LedgerError::InvalidTransaction(format!(
+                "Transaction {} is not pending, so it cannot be replaced",
+                original,
+            )));
+        };
+        
+        if replaced.from != transaction.from {
+            return Err(LedgerError::InvalidTransaction(
+                "Only the sender can replace a pending transaction".to_string(),
+            ));
+        }
+        if transaction.fee <= replaced.fee {
+            return Err(LedgerError::InvalidTransaction(format!(
+                "A replacement must pay a higher fee than {}",
+                replaced.fee,
+            )));
+        }
+        Ok(replaced)
+    }
+    
+    // Adds a checked transaction to the pool and the mempool
+    fn admit(&self, transaction: Transaction, evict: bool) -> Result<()> {
+        // Add to transaction pool, reserving its outflow under the pool entry's lock
+        match self.transaction_pool.entry(transaction.id) {
+            Entry::Occupied(_) => return Err(LedgerError::DuplicateTransaction),
+            Entry::Vacant(entry) => {
+                self.reserve(&transaction)?;
+                entry.insert(PendingTransaction {
+                    transaction: transaction.clone(),
+                    admitted_at: chrono::Utc::now(),
+                });
+            }
+        }
+        
+        match self.mempool.insert(transaction.clone(), evict) {
+            Ok(evicted) => {
+                self.emit(|| LedgerEvent::TransactionAdmitted { transaction });
+                for tx in evicted {
+                    info!("Evicted transaction {} from the full mempool", tx.id);
+                    self.reject(&tx, "Evicted from the full mempool".to_string());
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.remove_pending(&transaction);
+                Err(e)
+            }
+        }
+    }
+    
+    // What a transaction takes out of each paying account's spendable balance, fee included
+    fn outflows(transaction: &Transaction) -> Vec<(&str, u64)> {
+        let spent = match transaction.kind {
+            TransactionKind::Transfer | TransactionKind::Burn | TransactionKind::Vest(_) => transaction.amount,
+            TransactionKind::HashLock { .. } => transaction.amount,
+            TransactionKind::Stake { .. } | TransactionKind::Delegate => transaction.amount,
+            TransactionKind::Payout(_) | TransactionKind::Swap { .. } | TransactionKind::Spend { .. } => transaction.amount,
+            TransactionKind::Deploy { .. } | TransactionKind::Call { .. } => transaction.amount,
+            _ => 0,
+        };
+        
+        let mut outflows = vec![(transaction.from.as_str(), spent.saturating_add(transaction.fee))];
+        match &transaction.kind {
+            TransactionKind::Swap { counter_amount } => outflows.push((transaction.to.as_str(), *counter_amount)),
+            TransactionKind::TransferFrom { owner } => outflows.push((owner.as_str(), transaction.amount)),
+            _ => {}
+        }
+        outflows.retain(|(_, outflow)| *outflow > 0);
+        outflows
+    }
+    
+    // What each account sends, not counting the sender's fee; what spending limits apply to
+    fn spends(transaction: &Transaction) -> Vec<(&str, u64)> {
+        let mut spends = Self::outflows(transaction);
+        for (account, spent) in &mut spends {
+            if *account == transaction.from {
+                *spent -= transaction.fee;
+            }
+        }
+        spends.retain(|(_, spent)| *spent > 0);
+        spends
+    }
+    
+    // Epoch a block at `height` stamped `timestamp` belongs to, for spending limits; always 0 without epochs
+    fn spending_epoch(&self, height: u64, timestamp: chrono::DateTime<chrono::Utc>) -> u64 {
+        self.params().epochs.map_or(0, |schedule| schedule.epoch_at(height, timestamp))
+    }
+    
+    // Reserves every outflow or, if one account can't cover its share, none of them
+    fn reserve(&self, transaction: &Transaction) -> Result<()> {
+        let outflows = Self::outflows(transaction);
+        let height = self.next_height();
+        for (i, (account, outflow)) in outflows.iter().enumerate() {
+            let balance = self.liquid_balance(account, height);
+            let mut reserved = self.reserved.entry(account.to_string()).or_insert(0);
+            if balance.saturating_sub(*reserved) < *outflow {
+                drop(reserved);
+                self.reserved.remove_if(*account, |_, reserved| *reserved == 0);
+                outflows[..i].iter().for_each(|(account, outflow)| self.release(account, *outflow));
+                return Err(LedgerError::InsufficientBalance);
+            }
+            
+            *reserved += outflow;
+        }
+        
+        Ok(())
+    }
+    
+    fn release(&self, account: &str, outflow: u64) {
+        if let Some(mut reserved) = self.reserved.get_mut(account) {
+            *reserved = reserved.saturating_sub(outflow);
+        }
+        self.reserved.remove_if(account, |_, reserved| *reserved == 0);
+    }
+    
+    // Drops a pooled transaction for good, remembering why for `get_transaction`
+    fn reject(&self, transaction: &Transaction, reason: String) {
+        self.remove_pending(transaction);
+        self.record_rejection(transaction.clone(), reason);
+    }
+    
+    fn record_rejection(&self, transaction: Transaction, reason: String) {
+        self.emit(|| LedgerEvent::TransactionRejected { transaction: transaction.clone(), reason: reason.clone() });
+        self.audit.record(RejectedTransaction {
+            at: chrono::Utc::now(),
+            transaction: transaction.clone(),
+            reason: reason.clone(),
+            submitter: self.submitters.remove(&transaction.id).map(|(_, submitter)| submitter),
+            pooled: true,
+        });
+        self.rejections.record(transaction, reason);
+    }
+    
+    fn audit_refusal(&self, transaction: Transaction, error: &LedgerError, submitter: Option<&str>) {
+        self.audit.record(RejectedTransaction {
+            at: chrono::Utc::now(),
+            transaction,
+            reason: error.to_string(),
+            submitter: submitter.map(str::to_string),
+            pooled: false,
+        });
+    }
+    
+    // Builds the event only if someone listens
+    fn emit(&self, event: impl FnOnce() -> LedgerEvent) {
+        if self.events.receiver_count() > 0 {
+            let _ = self.events.send(event());
+        }
+    }
+    
+    // Takes a transaction out of the pool, if it's there, and releases its reservation
+    fn remove_pending(&self, transaction: &Transaction) {
+        self.mempool.remove(&transaction.id);
+        if self.transaction_pool.remove(&transaction.id).is_none() {
+            return;
+        }
+        
+        for (account, outflow) in Self::outflows(transaction) {
+            self.release(account, outflow);
+        }
+    }
+    
+    // Puts a pooled transaction back in the mempool, dropping it from the pool if there's no room
+    fn requeue(&self, transaction: &Transaction) {
+        // Bundle members stay pooled with their bundle
+        if transaction.bundle.is_some() {
+            return;
+        }
+        
+        match self.mempool.insert(transaction.clone(), true) {
+            Ok(evicted) => evicted.iter().for_each(|tx| self.reject(tx, "Evicted from the full mempool".to_string())),
+            Err(e) => self.reject(transaction, e.to_string()),
+        }
+    }
+    
+    pub fn reserved_balance(&self, address: &str) -> u64 {
+        self.reserved.get(address).map(|entry| *entry.value()).unwrap_or(0)
+    }
+    
+    // Committed balance less what vesting still locks and what pending transactions already spend
+    pub async fn available_balance(&self, address: &str) -> u64 {
+        self.liquid_balance(address, self.next_height()).saturating_sub(self.reserved_balance(address))
+    }
+    
+    // What `address` could spend in a block at `height`
+    fn liquid_balance(&self, address: &str, height: u64) -> u64 {
+        let balance = self.balances.get(address).map(|entry| *entry.value()).unwrap_or(0);
+        balance.saturating_sub(self.vesting.locked(address, height))
+    }
+    
+    // The mempool's dust policy; like its other limits, it only decides what this node queues
+    fn check_dust(&self, transaction: &Transaction) -> std::result::Result<(), String> {
+        let limits = self.mempool.limits();
+        if limits.min_transfer == 0 && limits.min_balance == 0 {
+            return Ok(());
+        }
+        
+        let credits: Vec<(&str, u64)> = match &transaction.kind {
+            TransactionKind::Transfer if transaction.is_cancellation() => Vec::new(),
+            TransactionKind::Transfer | TransactionKind::Vest(_) | TransactionKind::TransferFrom { .. } => {
+                vec![(transaction.to.as_str(), transaction.amount)]
+            }
+            TransactionKind::Payout(payments) | TransactionKind::Spend { outputs: payments, .. } => {
+                payments.iter().map(|payment| (payment.to.as_str(), payment.amount)).collect()
+            }
+            _ => Vec::new(),
+        };
+        
+        let balance = |account: &str| self.balances.get(account).map(|entry| *entry.value()).unwrap_or(0);
+        let mut after: HashMap<&str, u64> = HashMap::new();
+        for (account, outflow) in Self::outflows(transaction) {
+            let left = balance(account).saturating_sub(self.reserved_balance(account));
+            after.insert(account, left.saturating_sub(outflow));
+        }
+        for (recipient, amount) in credits {
+            if amount < limits.min_transfer {
+                return Err(format!("Paying {} is below the minimum transfer of {}", amount, limits.min_transfer));
+            }
+            *after.entry(recipient).or_insert_with(|| balance(recipient)) += amount;
+        }
+        
+        match after.into_iter().find(|(_, left)| *left > 0 && *left < limits.min_balance) {
+            Some((account, left)) => Err(format!(
+                "{} would hold {}, below the minimum balance of {}",
+                account, left, limits.min_balance,
+            )),
+            None => Ok(()),
+        }
+    }
+    
+    // Admission checks against the committed state, excluding the pool duplicate check
+    fn check_transaction(&self, transaction: &Transaction) -> Result<()> {
+        // Validate transaction
+        transaction.validate()?;
+        if let TransactionKind::Coinbase { .. } = transaction.kind {
+            return Err(LedgerError::InvalidTransaction(
+                "Coinbases are only created by block producers".to_string(),
+            ));
+        }
+        self.check_authorization(transaction)?;
+        
+        let params = self.params();
+        if transaction.weight() > params.max_block_bytes || transaction.gas_limit() > params.max_block_gas {
+            return Err(LedgerError::InvalidTransaction(
+                "Transaction is larger than a block may be".to_string(),
+            ));
+        }
+        params.check_data(transaction).map_err(LedgerError::InvalidTransaction)?;
+        self.check_model(transaction).map_err(LedgerError::InvalidTransaction)?;
+        if let TransactionKind::Spend { inputs, .. } = &transaction.kind {
+            let coins = inputs.iter().map(|outpoint| self.utxos.get(outpoint)).collect::<Vec<_>>();
+            self.check_inputs(transaction, &coins).map_err(LedgerError::InvalidTransaction)?;
+        }
+        
+        let epoch = self.current_epoch().map_or(0, |record| record.epoch.number);
+        for (account, amount) in Self::spends(transaction) {
+            let local = self.limits.override_for(account);
+            let limit = match (self.limits.limit(account), local) {
+                (Some(limit), Some(local)) => Some(limit.tighten(&local)),
+                (limit, local) => limit.or(local),
+            };
+            if let Some(limit) = limit {
+                limit.check(amount, self.limits.spent(account, epoch)).map_err(LedgerError::InvalidTransaction)?;
+            }
+        }
+        
+        if let TransactionKind::Anchor(anchor) = &transaction.kind {
+            if self.anchors.contains_key(&(anchor.namespace.clone(), anchor.sequence)) {
+                return Err(LedgerError::InvalidTransaction(
+                    "Anchor sequence already committed".to_string(),
+                ));
+            }
+        }
+        
+        match &transaction.kind {
+            TransactionKind::Delegate if !self.stakes.contains(&transaction.to) => {
+                return Err(LedgerError::InvalidTransaction(format!(
+                    "Unknown validator {}",
+                    transaction.to,
+                )));
+            }
+            TransactionKind::Evidence(evidence) => {
+                return self.check_evidence(&transaction.to, evidence)
+                    .map_err(LedgerError::InvalidTransaction);
+            }
+            TransactionKind::CheckpointVote(vote) => {
+                return self.check_checkpoint_vote(&transaction.from, vote, transaction.authorization.as_ref())
+                    .map_err(LedgerError::InvalidTransaction);
+            }
+            TransactionKind::Propose(_) | TransactionKind::Vote { .. } => {
+                return self.check_governance(transaction).map_err(LedgerError::InvalidTransaction);
+            }
+            TransactionKind::Deploy { code } => {
+                self.contracts.validate_code(code).map_err(LedgerError::InvalidTransaction)?;
+            }
+            TransactionKind::Call { .. } if !self.contracts.exists(&transaction.to) => {
+                return Err(LedgerError::InvalidTransaction(format!(
+                    "No contract at {}",
+                    transaction.to,
+                )));
+            }
+            TransactionKind::Schedule { .. } if self.params().epochs.is_none() => {
+                return Err(LedgerError::InvalidTransaction(
+                    "Standing orders need an epoch schedule".to_string(),
+                ));
+            }
+            TransactionKind::Mint | TransactionKind::Burn => {
+                self.check_issuance(transaction, 0, 0).map_err(LedgerError::InvalidTransaction)?;
+            }
+            TransactionKind::CancelSchedule { schedule } => {
+                self.check_cancellation(&transaction.from, *schedule).map_err(LedgerError::InvalidTransaction)?;
+            }
+            TransactionKind::TransferFrom { owner } => {
+                let allowance = self.allowances.get(owner, &transaction.from);
+                if allowance < transaction.amount {
+                    return Err(LedgerError::InvalidTransaction(format!(
+                        "{} may only move {} of {}'s balance",
+                        transaction.from, allowance, owner,
+                    )));
+                }
+            }
+            TransactionKind::Claim { lock, preimage } => {
+                let height = self.next_height();
+                self.find_lock(*lock)
+                    .and_then(|lock| lock.check_claim(preimage, height))
+                    .map_err(LedgerError::InvalidTransaction)?;
+            }
+            TransactionKind::Refund { lock } => {
+                let height = self.next_height();
+                self.find_lock(*lock)
+                    .and_then(|lock| lock.check_refund(&transaction.from, height))
+                    .map_err(LedgerError::InvalidTransaction)?;
+            }
+            TransactionKind::Unstake => {
+                if self.stakes.bonded(&transaction.to, &transaction.from) < transaction.amount {
+                    return Err(LedgerError::InvalidTransaction(
+                        "Cannot unstake more than is bonded".to_string(),
+                    ));
+                }
+                return Ok(());
+            }
+            _ => {}
+        }
+        
+        // Check balances (for non-genesis transactions), as of the next block
+        let height = self.next_height();
+        for (account, outflow) in Self::outflows(transaction) {
+            let current_balance = self.liquid_balance(account, height);
+            
+            if current_balance < outflow {
+                return Err(LedgerError::InsufficientBalance);
+            }
+        }
+        
+        Ok(())
+    }
+    
+    fn find_lock(&self, id: uuid::Uuid) -> std::result::Result<HashLock, String> {
+        self.hashlocks.get(id).ok_or_else(|| format!("Unknown hash lock {}", id))
+    }
+    
+    // Checks evidence against the chain and the validator's record
+    fn check_evidence(&self, accused: &str, evidence: &Evidence) -> std::result::Result<(), String> {
+        let validator = self.stakes.validator(accused)
+            .ok_or_else(|| format!("Unknown validator {}", accused))?;
+        if validator.total_stake() == 0 {
+            return Err(format!("Validator {} has no stake to slash", accused));
+        }
+        
+        if self.stakes.is_slashed_for(accused, evidence.reason(), evidence.offense_height()) {
+            return Err(format!("Validator {} was already slashed for this offence", accused));
+        }
+        
+        match evidence {
+            Evidence::DoubleSign { first, .. } => {
+                if first.producer.as_ref() != Some(&validator.public_key) {
+                    return Err(format!("Evidence blocks were not signed by {}", accused));
+                }
+            }
+            Evidence::Downtime { from_height, to_height } => {
+                let window = self.params().slashing.downtime_window;
+                if to_height - from_height + 1 < window {
+                    return Err(format!("Downtime must span at least {} blocks", window));
+                }
+                
+                let tip_height = self.chain_height();
+                if *to_height > tip_height {
+                    return Err("Downtime range extends past the chain tip".to_string());
+                }
+                
+                if *from_height <= validator.registered_at {
+                    return Err(format!("Validator {} was not registered for the whole range", accused));
+                }
+                
+                if validator.is_jailed(tip_height + 1) {
+                    return Err(format!("Validator {} is already jailed", accused));
+                }
+                
+                let produced = self.producer_index.get(&validator.public_key)
+                    .is_some_and(|heights| heights.range(from_height..=to_height).next().is_some());
+                if produced {
+                    return Err(format!("Validator {} produced blocks in the range", accused));
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    // Checks a finality vote against the main chain and the voter's registration
+    fn check_checkpoint_vote(
+        &self,
+        voter: &str,
+        vote: &CheckpointVote,
+        authorization: Option<&Authorization>,
+    ) -> std::result::Result<(), String> {
+        let interval = self.params().checkpoint_interval
+            .ok_or_else(|| "Checkpoint finality is not enabled".to_string())?;
+        if vote.height == 0 || !vote.height.is_multiple_of(interval) {
+            return Err(format!("Height {} is not a checkpoint", vote.height));
+        }
+        
+        if self.block_index.get(&vote.hash).map(|entry| *entry.value()) != Some(vote.height) {
+            return Err(format!("Block {} is not on the main chain at height {}", vote.hash, vote.height));
+        }
+        
+        if vote.height <= self.checkpoints.finalized_height() {
+            return Err(format!("Checkpoint {} is already final", vote.height));
+        }
+        
+        self.check_validator_signer(voter, authorization)?;
+        if self.checkpoints.has_voted(vote.height, voter) {
+            return Err(format!("Validator {} already voted on checkpoint {}", voter, vote.height));
+        }
+        
+        Ok(())
+    }
+    
+    // Bonded validators sign their own votes and proposals
+    fn check_validator_signer(&self, address: &str, authorization: Option<&Authorization>) -> std::result::Result<Validator, String> {
+        let validator = self.stakes.validator(address)
+            .ok_or_else(|| format!("Unknown validator {}", address))?;
+        if validator.total_stake() == 0 {
+            return Err(format!("Validator {} has no stake to vote with", address));
+        }
+        
+        if validator.is_jailed(self.next_height()) {
+            return Err(format!("Validator {} is jailed", address));
+        }
+        
+        if authorization.map(|auth| &auth.public_key) != Some(&validator.public_key) {
+            return Err(format!("Transaction must be signed by {}'s key", address));
+        }
+        
+        Ok(validator)
+    }
+    
+    // Proposals take effect at epoch boundaries, so governance needs an epoch schedule
+    fn check_governance(&self, transaction: &Transaction) -> std::result::Result<(), String> {
+        if self.params().epochs.is_none() {
+            return Err("Governance needs an epoch schedule".to_string());
+        }
+        self.check_validator_signer(&transaction.from, transaction.authorization.as_ref())?;
+        
+        match &transaction.kind {
+            TransactionKind::Propose(ProposalAction::AddValidator { address, .. }) if self.stakes.contains(address) => {
+                Err(format!("Validator {} is already registered", address))
+            }
+            TransactionKind::Propose(ProposalAction::RemoveValidator { address }) if !self.stakes.contains(address) => {
+                Err(format!("Unknown validator {}", address))
+            }
+            TransactionKind::Vote { proposal, .. } => {
+                let proposal = self.governance.proposal(*proposal)
+                    .ok_or_else(|| format!("Unknown proposal {}", proposal))?;
+                if proposal.status != ProposalStatus::Open {
+                    return Err(format!("Proposal {} is closed", proposal.id));
+                }
+                
+                if proposal.votes.contains_key(&transaction.from) {
+                    return Err(format!("Validator {} already voted on proposal {}", transaction.from, proposal.id));
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    // Which kinds the chain's ledger model accepts
+    fn check_model(&self, transaction: &Transaction) -> std::result::Result<(), String> {
+        let spend = matches!(transaction.kind, TransactionKind::Spend { .. });
+        match self.genesis.model {
+            LedgerModel::Account if spend => Err("Spends need the UTXO ledger model".to_string()),
+            LedgerModel::Account => Ok(()),
+            LedgerModel::Utxo if spend || matches!(transaction.kind, TransactionKind::Coinbase { .. }) => Ok(()),
+            LedgerModel::Utxo if matches!(transaction.kind, TransactionKind::Anchor(_)) && transaction.fee == 0 => Ok(()),
+            LedgerModel::Utxo => Err("The UTXO ledger model only accepts spends and fee-less anchors".to_string()),
+        }
+    }
+    
+    // `coins` are the spend's inputs as they stand, `None` where one doesn't exist or was already spent
+    fn check_inputs(&self, transaction: &Transaction, coins: &[Option<Coin>]) -> std::result::Result<(), String> {
+        let mut total = 0u64;
+        for coin in coins {
+            match coin {
+                Some(coin) if coin.owner == transaction.from => total = total.saturating_add(coin.amount),
+                Some(_) => return Err("Spends can only consume the sender's own coins".to_string()),
+                None => return Err("Input is unknown or already spent".to_string()),
+            }
+        }
+        
+        if total != transaction.amount.saturating_add(transaction.fee) {
+            return Err(format!("Inputs hold {}, but the outputs and fee add up to {}", total, transaction.amount + transaction.fee));
+        }
+        Ok(())
+    }
+    
+    // `minted` and `burned` are what earlier transactions in the same block already changed
+    fn check_issuance(&self, transaction: &Transaction, minted: u64, burned: u64) -> std::result::Result<(), String> {
+        let Some(policy) = self.params().issuance else {
+            return Err("This chain has no issuer".to_string());
+        };
+        
+        let signed = transaction.authorization.as_ref().is_some_and(|auth| auth.public_key == policy.public_key);
+        if transaction.from != policy.issuer || !signed {
+            return Err("Only the issuer can change the supply".to_string());
+        }
+        if transaction.kind == TransactionKind::Burn {
+            return Ok(());
+        }
+        
+        let issued = minted.saturating_add(transaction.amount);
+        if policy.max_mint_per_block.is_some_and(|cap| issued > cap) {
+            return Err("Mint exceeds the per-block issuance cap".to_string());
+        }
+        let supply = self.total_supply().saturating_add(issued).saturating_sub(burned);
+        if policy.max_supply.is_some_and(|cap| supply > cap) {
+            return Err("Mint would take the supply past its cap".to_string());
+        }
+        Ok(())
+    }
+    
+    fn check_cancellation(&self, payer: &str, schedule: uuid::Uuid) -> std::result::Result<(), String> {
+        let Some(order) = self.schedules.get(schedule) else {
+            return Err(format!("Unknown standing order {}", schedule));
+        };
+        
+        if order.payer != payer {
+            return Err("Only the payer can cancel a standing order".to_string());
+        }
+        if !order.is_active() {
+            return Err(format!("Standing order {} is already cancelled", schedule));
+        }
+        Ok(())
+    }
+    
+    pub fn checkpoint(&self, height: u64) -> Option<Checkpoint> {
+        self.checkpoints.checkpoint(height)
+    }
+    
+    pub fn latest_final_checkpoint(&self) -> Option<Checkpoint> {
+        self.checkpoints.latest_final()
+    }
+    
+    pub fn slash_history(&self, validator: &str) -> Vec<SlashRecord> {
+        self.stakes.slash_history(validator)
+    }
+    
+    // Accounts with a registered key only accept transactions signed by it, and only for this chain
+    fn check_authorization(&self, transaction: &Transaction) -> Result<()> {
+        if transaction.chain_id != self.genesis.chain_id {
+            return Err(LedgerError::InvalidTransaction(format!(
+                "Transaction is for chain '{}', not '{}'",
+                transaction.chain_id, self.genesis.chain_id,
+            )));
+        }
+        
+        if let Some(key) = self.account_keys.get(&transaction.from) {
+            let authorized = transaction.authorization.as_ref()
+                .is_some_and(|auth| &auth.public_key == key.value());
+            
+            if !authorized {
+                return Err(LedgerError::InvalidTransaction(
+                    "Transaction must be signed by the sender's key".to_string(),
+                ));
+            }
+        }
+        
+        if let TransactionKind::Swap { .. } = transaction.kind {
+            if let Some(key) = self.account_keys.get(&transaction.to) {
+                let authorized = transaction.counter_authorization.as_ref()
+                    .is_some_and(|auth| &auth.public_key == key.value());
+                
+                if !authorized {
+                    return Err(LedgerError::InvalidTransaction(
+                        "Swap must be countersigned by the counterparty's key".to_string(),
+                    ));
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    pub async fn process_transactions(&self, batch_size: usize) -> Result<()> {
+        self.produce(batch_size, false).await.map(|_| ())
+    }
+    
+    // Runs the consensus engine's sealing, proof-of-work mining above all, on the blocking pool, so the runtime keeps
+    // taking transactions and answering reads while the block is mined
+    async fn seal(&self, parent: &Block, template: Block, params: &ChainParams) -> Result<Block> {
+        let consensus = Arc::clone(&self.consensus);
+        let (parent, params) = (parent.clone(), params.clone());
+        // The guard keeps the block counted as being mined until the search ends, even if the producer is cancelled
+        let mining = self.performance_monitor.mining_started();
+        let started = std::time::Instant::now();
+        let sealed = tokio::task::spawn_blocking(move || {
+            let _mining = mining;
+            consensus.propose_counting_hashes(&parent, template, &params)
+        }).await;
+        let mining_time = started.elapsed();
+        let (block, hashes) = sealed.map_err(anyhow::Error::from)??;
+        
+        if hashes > 0 {
+            self.performance_monitor.record_mining(hashes, mining_time);
+        }
+        Ok(block)
+    }
+    
+    // Seals `template` and appends it, unless the tip moved off `parent` in the meantime
+    async fn seal_and_append(&self, parent: &Block, template: Block, params: &ChainParams) -> Result<()> {
+        let new_block = self.seal(parent, template, params).await?;
+        self.validate_block(&self.chain.load(), &new_block, parent, params)?;
+        
+        let mut blocks = self.write_chain().await;
+        if blocks.last().map(|tip| &tip.header.hash) != Some(&new_block.header.previous_hash) {
+            return Err(LedgerError::BlockValidationFailed(
+                "Chain tip moved while the block was being produced".to_string(),
+            ));
+        }
+        
+        let (height, hash) = (new_block.header.height, new_block.header.hash.clone());
+        let transactions = new_block.transactions.len();
+        self.append_block(&mut blocks, new_block, true)?;
+        self.emit(|| LedgerEvent::BlockProduced { height, hash, transactions });
+        Ok(())
+    }
+    
+    // As `process_transactions`, producing a block even when nothing is pending if `allow_empty` is set; false if no
+    // block was due from this node or there was nothing to put in one
+    async fn produce(&self, batch_size: usize, allow_empty: bool) -> Result<bool> {
+        let batch = self.take_batch(batch_size);
+        self.produce_from(batch, batch_size, allow_empty).await
+    }
+    
+    // Takes up to `batch_size` transactions off the queue for the block after the tip, or none if this node can't
+    // propose it. Whatever of the batch `produce_from` doesn't include goes back
+    fn take_batch(&self, batch_size: usize) -> Vec<Transaction> {
+        let blocks = self.chain.load_full();
+        let parent = blocks.last().unwrap();
+        if !self.consensus.can_propose(parent) {
+            return Vec::new();
+        }
+        let params = self.params_for_child(&blocks, parent);
+        
+        // Checked against now; the block is stamped a moment later at most
+        let now = crate::clock::now();
+        for tx in self.mempool.remove_expired(parent.header.height + 1, now) {
+            info!("Dropping expired transaction {}", tx.id);
+            self.reject(&tx, "Transaction has expired".to_string());
+        }
+        self.mempool.release_due(parent.header.height + 1, now);
+        
+        // Take the highest-priority transactions from the mempool
+        let mut batch = Vec::new();
+        let (mut body_size, mut block_data, mut block_gas) = (0, 0, 0);
+        while batch.len() < batch_size {
+            let Some((tx, size)) = self.mempool.peek() else {
+                break;
+            };
+            
+            if size > params.max_block_bytes {
+                warn!("Dropping transaction {}: larger than any block may be", tx.id);
+                self.reject(&tx, "Larger than any block may be".to_string());
+                continue;
+            }
+            // Payload rules may have tightened since it was admitted
+            if let Err(e) = params.check_data(&tx) {
+                warn!("Dropping transaction {}: {}", tx.id, e);
+                self.reject(&tx, e);
+                continue;
+            }
+            
+            // Full; the transaction stays queued for a later block
+            let full = body_size + size > params.max_block_bytes
+                || block_data + tx.data.len() > params.max_block_data
+                || block_gas + tx.gas_limit() > params.max_block_gas;
+            if full {
+                break;
+            }
+            
+            self.mempool.remove(&tx.id);
+            body_size += size;
+            block_data += tx.data.len();
+            block_gas += tx.gas_limit();
+            batch.push(tx);
+        }
+        batch
+    }
+    
+    // Builds a block of at most `batch_size` on the tip from bundles that fit, then `batch`, and mines and appends it.
+    // The tip may have moved on since the batch was taken, so it is checked again against the block it goes into
+    async fn produce_from(&self, batch: Vec<Transaction>, batch_size: usize, allow_empty: bool) -> Result<bool> {
+        // Whatever doesn't make it into a block, failed, on a tip that moved or with production cancelled midway, waits
+        // for a later one
+        let mut requeue = Requeue { ledger: self, batch: batch.iter().map(|tx| tx.id).collect() };
+        let (parent, params) = {
+            let blocks = self.chain.load();
+            let parent = blocks.last().unwrap().clone();
+            let params = self.params_for_child(&blocks, &parent);
+            (parent, params)
+        };
+        
+        if !self.consensus.can_propose(&parent) {
+            return Ok(false);
+        }
+        let now = crate::clock::now();
+        
+        let height = parent.header.height + 1;
+        let coinbase_to = match params.emission {
+            Some(_) => Some(self.coinbase_address().ok_or_else(|| {
+                LedgerError::InvalidParameters("Producing under an emission schedule needs a coinbase address".to_string())
+            })?),
+            None => None,
+        };
+        // Room for the coinbase, sized for the largest amount it could pay
+        let capacity = batch_size.min(params.max_block_transactions - coinbase_to.is_some() as usize);
+        let coinbase_size = coinbase_to.as_ref().map_or(0, |to| {
+            Transaction::coinbase(height, to.clone(), u64::MAX).for_chain(&self.genesis.chain_id).weight()
+        });
+        
+        let epoch = self.spending_epoch(height, now);
+        let mut state = StateOverlay::new(self, height, epoch);
+        let mut bundled = Vec::new();
+        let mut body_size = coinbase_size;
+        let mut block_data = 0;
+        let mut block_gas = 0;
+        
+        // Bundles go first, each whole or not at all, best-paying first
+        let mut bundles = self.pending_bundles();
+        bundles.sort_by_key(|members| {
+            let fees = members.iter().map(|tx| tx.fee as u128).sum::<u128>();
+            std::cmp::Reverse(fees * 1_000_000 / members.iter().map(|tx| tx.weight() as u128).sum::<u128>().max(1))
+        });
+        for members in bundles {
+            let id = members[0].bundle.unwrap().bundle;
+            if members.iter().any(|tx| tx.is_expired_at(height, now)) {
+                info!("Dropping expired bundle {}", id);
+                self.drop_bundle(id, "Bundle has expired");
+                continue;
+            }
+            if members.iter().any(|tx| tx.is_locked_at(height, now)) {
+                continue;
+            }
+            
+            let size = members.iter().map(Transaction::weight).sum::<usize>();
+            let data = members.iter().map(|tx| tx.data.len()).sum::<usize>();
+            let gas = members.iter().fold(0u64, |gas, tx| gas.saturating_add(tx.gas_limit()));
+            let full = bundled.len() + members.len() > capacity
+                || body_size + size > params.max_block_bytes
+                || block_data + data > params.max_block_data
+                || block_gas + gas > params.max_block_gas;
+            if full {
+                continue;
+            }
+            
+            let mut trial = state.clone();
+            let result = members.iter()
+                .try_for_each(|tx| params.check_data(tx).map_err(LedgerError::InvalidTransaction).and_then(|_| trial.apply(tx)));
+            if let Err(e) = result {
+                warn!("Dropping bundle {}: {}", id, e);
+                self.drop_bundle(id, &e.to_string());
+                continue;
+            }
+            
+            state = trial;
+            body_size += size;
+            block_data += data;
+            block_gas += gas;
+            bundled.extend(members);
+        }
+        
+        let mut transactions = Vec::new();
+        for tx in batch {
+            if bundled.len() + transactions.len() >= capacity {
+                break;
+            }
+            if tx.is_expired_at(height, now) {
+                info!("Dropping expired transaction {}", tx.id);
+                self.reject(&tx, "Transaction has expired".to_string());
+                continue;
+            }
+            
+            let size = tx.weight();
+            let full = body_size + size > params.max_block_bytes
+                || block_data + tx.data.len() > params.max_block_data
+                || block_gas + tx.gas_limit() > params.max_block_gas;
+            if full {
+                break;
+            }
+            
+            body_size += size;
+            block_data += tx.data.len();
+            block_gas += tx.gas_limit();
+            transactions.push(tx);
+        }
+        
+        let mut transactions = crate::mempool::order_by_fee_rate(transactions);
+        
+        // Drop what the committed state can't cover together, e.g. two pending spends of the same funds
+        transactions.retain(|tx| match state.apply(tx) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Dropping transaction {}: {}", tx.id, e);
+                self.reject(tx, e.to_string());
+                false
+            }
+        });
+        bundled.append(&mut transactions);
+        let transactions = bundled;
+        
+        if transactions.is_empty() && !allow_empty {
+            return Ok(false);
+        }
+        
+        let start_time = std::time::Instant::now();
+        let tx_count = transactions.len();
+        let admitted: Vec<_> = transactions.iter()
+            .filter_map(|tx| self.transaction_pool.get(&tx.id).map(|entry| entry.admitted_at))
+            .collect();
+        
+        let transactions = match coinbase_to {
+            Some(to) => {
+                let amount = params.coinbase_amount(height, &transactions);
+                let coinbase = Transaction::coinbase(height, to, amount).for_chain(&self.genesis.chain_id);
+                std::iter::once(coinbase).chain(transactions).collect()
+            }
+            None => transactions,
+        };
+        
+        // Create new block
+        let mut template = Block::child_of(&parent, transactions);
+        template.header.state_root = self.state.root();
+        template.header.skipped_slots =
+            params.block_time.skipped_slots(parent.header.timestamp, template.header.timestamp);
+        requeue.keep(&template);
+        self.seal_and_append(&parent, template, &params).await?;
+        requeue.committed();
+        self.last_produced.store(chrono::Utc::now().timestamp_millis(), Ordering::Relaxed);
+        
+        let processing_time = start_time.elapsed();
+        self.performance_monitor.record_batch(tx_count, processing_time);
+        let included = chrono::Utc::now();
+        self.performance_monitor
+            .record_inclusions(admitted.iter().map(|at| (included - *at).to_std().unwrap_or_default()));
+        
+        info!("Processed {} transactions in {:?}", tx_count, processing_time);
+        
+        Ok(true)
+    }
+    
+    // Accepts a block produced elsewhere: it extends the tip, waits as a side block, wins a reorg,
+    // or waits as an orphan; orphans it unblocks are connected before returning
+    pub async fn import_block(&self, block: Block) -> Result<BlockImport> {
+        let mut blocks = self.write_chain().await;
+        let hash = block.header.hash.clone();
+        
+        let outcome = match self.import_into(&mut blocks, block) {
+            Err(LedgerError::DuplicateBlock) => return Err(LedgerError::DuplicateBlock),
+            Err(e) => {
+                self.drop_orphans_of(hash);
+                return Err(e);
+            }
+            Ok(outcome) => outcome,
+        };
+        
+        if !matches!(outcome, BlockImport::Orphaned { .. }) {
+            self.connect_orphans(&mut blocks, hash);
+        }
+        Ok(outcome)
+    }
+    
+    // As `import_block`, scoring the sending peer for invalid and oversized blocks and refusing banned ones
+    pub async fn import_block_from(&self, peer: &str, block: Block) -> Result<BlockImport> {
+        if self.peers.is_banned(peer) {
+            return Err(LedgerError::Unauthorized(format!("Peer {} is banned", peer)));
+        }
+        
+        // No block may be this big, so it isn't worth validating
+        if block.body_size() > self.params().max_block_bytes {
+            let reason = format!("Block of {} bytes is larger than any block may be", block.body_size());
+            self.peers.report(peer, Violation::OversizedMessage, &reason);
+            return Err(LedgerError::BlockValidationFailed(reason));
+        }
+        
+        let forged = block.header.producer.is_some() && !block.verify_producer_signature();
+        let result = self.import_block(block).await;
+        
+        if let Err(e) = &result {
+            let invalid = matches!(
+                e,
+                LedgerError::BlockValidationFailed(_) | LedgerError::InvalidTransaction(_) | LedgerError::Consensus(_)
+            );
+            if invalid {
+                let violation = if forged { Violation::BadSignature } else { Violation::InvalidBlock };
+                self.peers.report(peer, violation, &e.to_string());
+            }
+        }
+        result
+    }
+    
+    pub fn peers(&self) -> &PeerRegistry {
+        &self.peers
+    }
+    
+    fn import_into(&self, blocks: &mut Chain, block: Block) -> Result<BlockImport> {
+        if self.block_index.contains_key(&block.header.hash)
+            || self.side_blocks.contains_key(&block.header.hash)
+            || self.orphans.contains_key(&block.header.hash)
+        {
+            return Err(LedgerError::DuplicateBlock);
+        }
+        
+        let tip = blocks.last().unwrap();
+        if tip.header.hash == block.header.previous_hash {
+            let params = self.params_for_child(blocks, tip);
+            self.validate_block(blocks, &block, tip, &params)?;
+            self.check_block_state(&block)?;
+            self.append_block(blocks, block, true)?;
+            return Ok(BlockImport::Extended);
+        }
+        
+        let Some(parent) = self.find_block(blocks, &block.header.previous_hash) else {
+            return self.add_orphan(block);
+        };
+        self.validate_block(blocks, &block, &parent, &self.params_for_child(blocks, &parent))?;
+        self.side_blocks.insert(block.header.hash.clone(), block.clone());
+        
+        let branch = self.side_branch(block);
+        let fork_height = branch[0].header.height - 1;
+        
+        if fork_height < self.finalized_below(blocks.len() as u64 - 1) {
+            for block in &branch {
+                self.side_blocks.remove(&block.header.hash);
+            }
+            return Err(LedgerError::BlockValidationFailed(
+                "Block forks below the finalized height".to_string(),
+            ));
+        }
+        let params = self.params();
+        let work = |chain: &mut dyn Iterator<Item = &Block>| chain
+            .fold(0u128, |total, block| total.saturating_add(self.consensus.work(block, &params)));
+        
+        // Ties keep the chain we already have
+        if work(&mut branch.iter()) <= work(&mut blocks.iter_from(fork_height as usize + 1).map(Arc::as_ref)) {
+            self.stale.record(branch.last().unwrap());
+            return Ok(BlockImport::SideChain);
+        }
+        
+        let event = self.reorganize(blocks, fork_height, branch)?;
+        let _ = self.reorg_events.send(event.clone());
+        self.emit(|| LedgerEvent::ReorgOccurred(event.clone()));
+        Ok(BlockImport::Reorganized(event))
+    }
+    
+    // Only the hash is checked here; the block is validated in full once its parent is known
+    fn add_orphan(&self, block: Block) -> Result<BlockImport> {
+        if block.header.hash != block.calculate_hash() {
+            return Err(LedgerError::BlockValidationFailed("Invalid block hash".to_string()));
+        }
+        
+        if self.orphans.len() >= MAX_ORPHANS {
+            return Err(LedgerError::PerformanceLimitExceeded("Orphan pool is full".to_string()));
+        }
+        
+        // Ask for the oldest missing block, which may be an ancestor of another orphan
+        let mut missing_ancestor = block.header.previous_hash.clone();
+        while let Some(orphan) = self.orphans.get(&missing_ancestor) {
+            missing_ancestor = orphan.header.previous_hash.clone();
+        }
+        
+        self.orphans.insert(block.header.hash.clone(), block);
+        let _ = self.missing_blocks.send(missing_ancestor.clone());
+        Ok(BlockImport::Orphaned { missing_ancestor })
+    }
+    
+    // Imports orphans descending from `parent`, depth first
+    fn connect_orphans(&self, blocks: &mut Chain, parent: String) {
+        let mut connected = vec![parent];
+        
+        while let Some(parent) = connected.pop() {
+            let children: Vec<String> = self.orphans.iter()
+                .filter(|entry| entry.header.previous_hash == parent)
+                .map(|entry| entry.key().clone())
+                .collect();
+            
+            for hash in children {
+                let Some((_, orphan)) = self.orphans.remove(&hash) else {
+                    continue;
+                };
+                
+                match self.import_into(blocks, orphan) {
+                    Ok(_) => connected.push(hash),
+                    Err(e) => {
+                        warn!("Dropping orphan block {}: {}", hash, e);
+                        self.drop_orphans_of(hash);
+                    }
+                }
+            }
+        }
+    }
+    
+    // Orphans descending from a rejected block can never connect
+    fn drop_orphans_of(&self, rejected: String) {
+        let mut rejected = vec![rejected];
+        
+        while let Some(parent) = rejected.pop() {
+            let children: Vec<String> = self.orphans.iter()
+                .filter(|entry| entry.header.previous_hash == parent)
+                .map(|entry| entry.key().clone())
+                .collect();
+            
+            for hash in children {
+                self.orphans.remove(&hash);
+                rejected.push(hash);
+            }
+        }
+    }
+    
+    // Parents of the oldest orphans, i.e. the blocks a sync layer still has to fetch
+    pub fn missing_ancestors(&self) -> Vec<String> {
+        let parents: Vec<String> = self.orphans.iter().map(|entry| entry.header.previous_hash.clone()).collect();
+        let mut missing: Vec<String> = parents.into_iter()
+            .filter(|parent| !self.orphans.contains_key(parent))
+            .collect();
+        missing.sort();
+        missing.dedup();
+        missing
+    }
+    
+    pub fn subscribe_missing_blocks(&self) -> broadcast::Receiver<String> {
+        self.missing_blocks.subscribe()
+    }
+    
+    // Chain parameters in force for the block after `parent`, with its retargeted difficulty
+    fn params_for_child(&self, blocks: &Chain, parent: &Block) -> ChainParams {
+        let mut params = self.params();
+        let Some(adjustment) = &params.difficulty_adjustment else {
+            return params;
+        };
+        
+        params.difficulty = if !(parent.header.height + 1).is_multiple_of(adjustment.window) {
+            parent.header.difficulty
+        } else {
+            // Walk back along the parent's own branch; genesis has a fixed timestamp so it never counts
+            let mut first = parent.clone();
+            for _ in 1..adjustment.window {
+                match self.find_block(blocks, &first.header.previous_hash) {
+                    Some(ancestor) if ancestor.header.height > 0 => first = ancestor,
+                    _ => break,
+                }
+            }
+            
+            adjustment.retarget(parent.header.difficulty, &first, parent)
+        };
+        
+        params
+    }
+    
+    fn find_block(&self, blocks: &Chain, hash: &str) -> Option<Block> {
+        match self.block_index.get(hash) {
+            Some(height) => blocks.get(*height as usize).map(|block| Block::clone(block)),
+            None => self.side_blocks.get(hash).map(|entry| entry.value().clone()),
+        }
+    }
+    
+    // Side blocks from just above the fork point up to `tip`
+    fn side_branch(&self, tip: Block) -> Vec<Block> {
+        let mut branch = vec![tip];
+        
+        while let Some(parent) = self.side_blocks.get(&branch.last().unwrap().header.previous_hash) {
+            branch.push(parent.value().clone());
+        }
+        
+        branch.reverse();
+        branch
+    }
+    
+    fn reorganize(&self, blocks: &mut Chain, fork_height: u64, branch: Vec<Block>) -> Result<ReorgEvent> {
+        let old_tip = blocks.last().unwrap().header.hash.clone();
+        let reverted = blocks.split_off(fork_height as usize + 1);
+        for block in reverted.iter().rev() {
+            self.revert_block(block);
+        }
+        
+        for (i, block) in branch.iter().enumerate() {
+            let result = self.check_block_state(block)
+                .and_then(|_| self.append_block(blocks, block.clone(), false));
+            
+            if let Err(e) = result {
+                // Restore the original chain, which is still the one readers see, and forget the invalid part of the
+                // branch
+                for applied in blocks.split_off(fork_height as usize + 1).into_iter().rev() {
+                    self.revert_block(&applied);
+                }
+                for block in reverted {
+                    self.apply_block(&block);
+                    self.index_block(block.header.height, &block);
+                    blocks.push(block);
+                }
+                for invalid in &branch[i..] {
+                    self.side_blocks.remove(&invalid.header.hash);
+                    self.stale.remove(&invalid.header.hash);
+                }
+                return Err(e);
+            }
+        }
+        
+        self.publish_tip(blocks);
+        for block in &branch {
+            self.side_blocks.remove(&block.header.hash);
+            self.stale.remove(&block.header.hash);
+        }
+        
+        // Transactions only the old branch carried go back to the queue if they're still valid
+        let included: HashSet<uuid::Uuid> = branch.iter()
+            .flat_map(|block| block.transactions.iter().map(|tx| tx.id))
+            .collect();
+        let mut bundles: Vec<(uuid::Uuid, Vec<Transaction>)> = Vec::new();
+        for tx in reverted.iter().flat_map(|block| &block.transactions) {
+            if let Some(position) = tx.bundle.filter(|_| !included.contains(&tx.id)) {
+                match bundles.last_mut() {
+                    Some((id, members)) if *id == position.bundle => members.push(tx.clone()),
+                    _ => bundles.push((position.bundle, vec![tx.clone()])),
+                }
+                continue;
+            }
+            
+            if !included.contains(&tx.id) && self.check_transaction(tx).is_ok() {
+                if let Entry::Vacant(entry) = self.transaction_pool.entry(tx.id) {
+                    // Reserved without the spendable check; the producer drops it if the new chain can't cover it
+                    for (account, outflow) in Self::outflows(tx) {
+                        *self.reserved.entry(account.to_string()).or_insert(0) += outflow;
+                    }
+                    entry.insert(PendingTransaction {
+                        transaction: tx.clone(),
+                        admitted_at: chrono::Utc::now(),
+                    });
+                }
+                self.requeue(tx);
+            }
+        }
+        for (id, members) in bundles {
+            if self.check_bundle_state(&members, blocks.len() as u64).is_ok() {
+                let _ = self.queue_bundle(id, members);
+            }
+        }
+        
+        let event = ReorgEvent {
+            fork_height,
+            old_tip,
+            new_tip: branch.last().unwrap().header.hash.clone(),
+            reverted: reverted.iter().map(|block| block.header.hash.clone()).collect(),
+            applied: branch.iter().map(|block| block.header.hash.clone()).collect(),
+        };
+        
+        for block in reverted {
+            self.stale.record(&block);
+            self.side_blocks.insert(block.header.hash.clone(), Arc::unwrap_or_clone(block));
+        }
+        
+        info!("Reorganized {} blocks above height {}", event.reverted.len(), fork_height);
+        Ok(event)
+    }
+    
+    // Blocks that lost a fork race, oldest first
+    pub fn stale_blocks(&self) -> Vec<StaleBlock> {
+        self.stale.list()
+    }
+    
+    // The main chain's tip first, then the tip of each side chain, highest first
+    pub async fn chain_tips(&self) -> Vec<ChainTip> {
+        let blocks = self.chain.load_full();
+        let tip = &blocks.last().unwrap().header;
+        let mut tips = vec![ChainTip { hash: tip.hash.clone(), height: tip.height, fork_height: tip.height, main: true }];
+        
+        let parents: HashSet<String> = self.side_blocks.iter().map(|entry| entry.header.previous_hash.clone()).collect();
+        let ends: Vec<Block> = self.side_blocks.iter()
+            .filter(|entry| !parents.contains(entry.key()))
+            .map(|entry| entry.value().clone())
+            .collect();
+        let mut side: Vec<ChainTip> = ends.into_iter()
+            .filter_map(|end| {
+                let fork_height = *self.block_index.get(&self.side_branch(end.clone())[0].header.previous_hash)?;
+                Some(ChainTip { hash: end.header.hash, height: end.header.height, fork_height, main: false })
+            })
+            .collect();
+        side.sort_by(|a, b| b.height.cmp(&a.height).then_with(|| a.hash.cmp(&b.hash)));
+        tips.extend(side);
+        tips
+    }
+    
+    // The side chain ending at `tip`, which need not be a chain tip; None for a block this node doesn't hold
+    pub async fn get_branch(&self, tip: &str) -> Option<Branch> {
+        if let Some(height) = self.block_index.get(tip) {
+            return Some(Branch { tip: tip.to_string(), fork_height: *height, blocks: Vec::new() });
+        }
+        
+        let branch = self.side_branch(self.side_blocks.get(tip)?.value().clone());
+        let fork_height = *self.block_index.get(&branch[0].header.previous_hash)?;
+        Some(Branch { tip: tip.to_string(), fork_height, blocks: branch })
+    }
+    
+    // What the chains ending at `first` and `second` hold above the last block they share, each in height order;
+    // either may be on the main chain
+    pub async fn compare_branches(&self, first: &str, second: &str) -> Option<BranchComparison> {
+        let blocks = self.chain.load_full();
+        let (mut a, mut b) = (self.find_block(&blocks, first)?, self.find_block(&blocks, second)?);
+        let (mut first_blocks, mut second_blocks) = (Vec::new(), Vec::new());
+        while a.header.hash != b.header.hash {
+            if a.header.height >= b.header.height {
+                let parent = self.find_block(&blocks, &a.header.previous_hash)?;
+                first_blocks.push(std::mem::replace(&mut a, parent));
+            } else {
+                let parent = self.find_block(&blocks, &b.header.previous_hash)?;
+                second_blocks.push(std::mem::replace(&mut b, parent));
+            }
+        }
+        
+        let transactions = |chain: Vec<Block>| -> Vec<Transaction> {
+            chain.into_iter().rev().flat_map(|block| block.transactions).collect()
+        };
+        let (mut only_in_first, mut only_in_second) = (transactions(first_blocks), transactions(second_blocks));
+        let second_ids: HashSet<uuid::Uuid> = only_in_second.iter().map(|tx| tx.id).collect();
+        let in_both: Vec<uuid::Uuid> = only_in_first.iter().map(|tx| tx.id).filter(|id| second_ids.contains(id)).collect();
+        only_in_first.retain(|tx| !in_both.contains(&tx.id));
+        only_in_second.retain(|tx| !in_both.contains(&tx.id));
+        
+        Some(BranchComparison {
+            common_ancestor: a.header.hash,
+            common_height: a.header.height,
+            only_in_first,
+            only_in_second,
+            in_both,
+        })
+    }
+    
+    // How a committed contract call went; None until its block is on the main chain
+    // Every spendable and bonded unit; changes with mints, burns, rewards, burned fees and slashing
+    pub fn total_supply(&self) -> u64 {
+        let balances = self.balances.iter().map(|entry| *entry.value()).sum::<u64>();
+        balances + self.stakes.total_stake() + self.hashlocks.locked()
+    }
+    
+    // The `n` largest committed balances, largest first
+    pub fn rich_list(&self, n: usize) -> Vec<(String, u64)> {
+        self.analytics.top(n)
+    }
+    
+    pub fn balance_summary(&self) -> BalanceSummary {
+        self.analytics.summary()
+    }
+    
+    // Registers an invoice for this node to watch the chain for; see `Invoice::payment`
+    pub fn create_invoice(&self, invoice: Invoice) -> Result<()> {
+        self.invoices.register(invoice).map_err(LedgerError::InvalidParameters)
+    }
+    
+    pub fn invoice(&self, reference: &str) -> Option<Invoice> {
+        self.invoices.get(reference)
+    }
+    
+    pub fn invoice_status(&self, reference: &str) -> Option<InvoiceStatus> {
+        self.invoices.status(reference, crate::clock::now())
+    }
+    
+    // What vesting grants lock of `address`'s balance at the tip, and what is spendable
+    pub fn vesting_balance(&self, address: &str) -> VestingBalance {
+        let height = self.chain_height();
+        let balance = self.balances.get(address).map(|entry| *entry.value()).unwrap_or(0);
+        let locked = self.vesting.locked(address, height).min(balance);
+        VestingBalance { locked, liquid: balance - locked }
+    }
+    
+    // What `spender` may still move out of `owner`'s balance
+    pub fn allowance(&self, owner: &str, spender: &str) -> u64 {
+        self.allowances.get(owner, spender)
+    }
+    
+    pub fn allowances_granted_by(&self, owner: &str) -> Vec<(String, u64)> {
+        self.allowances.granted_by(owner)
+    }
+    
+    pub fn hash_lock(&self, id: uuid::Uuid) -> Option<HashLock> {
+        self.hashlocks.get(id)
+    }
+    
+    // Open hash locks sent by or to `address`
+    pub fn open_hash_locks(&self, address: &str) -> Vec<HashLock> {
+        self.hashlocks.open_for(address)
+    }
+    
+    pub fn vesting_grants(&self, address: &str) -> Vec<VestingGrant> {
+        self.vesting.grants(address)
+    }
+    
+    pub fn ledger_model(&self) -> LedgerModel {
+        self.genesis.model
+    }
+    
+    // Unspent coins of `owner` under the UTXO model
+    pub fn utxos(&self, owner: &str) -> Vec<(OutPoint, Coin)> {
+        self.utxos.owned_by(owner)
+    }
+    
+    pub fn utxo(&self, outpoint: &OutPoint) -> Option<Coin> {
+        self.utxos.get(outpoint)
+    }
+    
+    // The limit `account` set on chain
+    pub fn spending_limit(&self, account: &str) -> Option<SpendingLimit> {
+        self.limits.limit(account)
+    }
+    
+    // What `account` has sent in the current epoch
+    pub fn spent_this_epoch(&self, account: &str) -> u64 {
+        let epoch = self.current_epoch().map_or(0, |record| record.epoch.number);
+        self.limits.spent(account, epoch)
+    }
+    
+    pub(crate) fn set_local_spending_limit(&self, account: &str, limit: Option<SpendingLimit>) {
+        self.limits.set_override(account, limit);
+    }
+    
+    pub fn standing_order(&self, id: uuid::Uuid) -> Option<StandingOrder> {
+        self.schedules.get(id)
+    }
+    
+    // Active standing orders paid by or to `address`
+    pub fn standing_orders(&self, address: &str) -> Vec<StandingOrder> {
+        self.schedules.active_for(address)
+    }
+    
+    pub fn contract_receipt(&self, tx_id: uuid::Uuid) -> Option<Receipt> {
+        self.contracts.receipt(tx_id)
+    }
+    
+    pub fn contract_storage(&self, contract: &str, key: &[u8]) -> Option<Vec<u8>> {
+        self.contracts.storage(contract, key)
+    }
+    
+    pub fn is_deployed(&self, contract: &str) -> bool {
+        self.contracts.exists(contract)
+    }
+    
+    pub async fn stale_stats(&self) -> StaleStats {
+        let since = crate::clock::now() - chrono::Duration::hours(1);
+        let blocks = self.chain.load_full();
+        let main_chain = blocks.iter().rev()
+            .take_while(|block| block.header.height > 0 && block.header.timestamp >= since)
+            .count();
+        self.stale.stats(main_chain)
+    }
+    
+    // Fee for a transaction of `size` encoded bytes to get into a block soon. Free while recent blocks
+    // are mostly empty; past that, the fuller they are the higher up recent fee rates the estimate sits
+    pub async fn estimate_fee(&self, size: usize) -> u64 {
+        let params = self.params();
+        let blocks = self.chain.load_full();
+        let recent: Vec<&Block> = blocks.iter().rev()
+            .take_while(|block| block.header.height > 0)
+            .take(FEE_ESTIMATE_BLOCKS)
+            .map(Arc::as_ref)
+            .collect();
+        if recent.is_empty() {
+            return 0;
+        }
+        
+        let fullness = recent.iter()
+            .map(|block| {
+                let by_count = block.transactions.len() as f64 / params.max_block_transactions.max(1) as f64;
+                let by_bytes = block.header.weight as f64 / params.max_block_bytes as f64;
+                by_count.max(by_bytes).min(1.0)
+            })
+            .sum::<f64>() / recent.len() as f64;
+        if fullness < 0.5 {
+            return 0;
+        }
+        
+        let mut rates: Vec<u128> = Vec::new();
+        for block in &recent {
+            let weights = block.transaction_weights();
+            rates.extend(block.transactions.iter().zip(weights.iter())
+                .filter(|(tx, _)| !tx.from.is_empty())
+                .map(|(tx, weight)| tx.fee_rate_at(*weight)));
+        }
+        if rates.is_empty() {
+            return 0;
+        }
+        
+        rates.sort_unstable();
+        let rate = rates[((rates.len() - 1) as f64 * fullness).round() as usize];
+        (rate * size as u128).div_ceil(1_000_000).min(u64::MAX as u128) as u64
+    }
+    
+    pub fn subscribe_replacements(&self) -> broadcast::Receiver<ReplacementEvent> {
+        self.replacement_events.subscribe()
+    }
+    
+    // Invoices settled by committed transfers; a reorg that drops the payment puts the invoice back to pending
+    pub fn subscribe_invoice_payments(&self) -> broadcast::Receiver<InvoicePaid> {
+        self.invoice_events.subscribe()
+    }
+    
+    pub fn subscribe_reorgs(&self) -> broadcast::Receiver<ReorgEvent> {
+        self.reorg_events.subscribe()
+    }
+    
+    // Status changes of transactions sending from or paying into `address`, from now on
+    pub fn subscribe_address(&self, address: &str) -> AddressSubscription {
+        AddressSubscription::new(address.to_string(), self.events.subscribe())
+    }
+    
+    // The main-chain tip, updated as each block is applied; a reorg shows as a head at or below the one before
+    pub fn watch_head(&self) -> watch::Receiver<ChainHead> {
+        self.head.subscribe()
+    }
+    
+    // Every ledger event from now on; a subscriber more than `EVENT_CAPACITY` events behind skips ahead
+    pub fn subscribe(&self) -> broadcast::Receiver<LedgerEvent> {
+        self.events.subscribe()
+    }
+    
+    pub async fn get_latest_block(&self) -> Block {
+        self.latest_block().as_ref().clone()
+    }
+    
+    // The tip without cloning it or waiting for a block being applied; it can be ahead of a read view that was
+    // already taken
+    pub fn latest_block(&self) -> Arc<Block> {
+        Arc::clone(self.chain.load().last().unwrap())
+    }
+    
+    pub fn chain_height(&self) -> u64 {
+        self.chain.load().len() as u64 - 1
+    }
+    
+    // Height of the block after the published tip, the first a transaction admitted now could go into
+    fn next_height(&self) -> u64 {
+        self.chain.load().last().unwrap().header.height + 1
+    }
+    
+    // Main-chain headers from `start`, for peers that sync and check headers before fetching bodies
+    pub async fn get_headers(&self, start: u64, limit: usize) -> Vec<BlockHeader> {
+        let blocks = self.chain.load_full();
+        blocks.iter_from(start as usize).take(limit).map(|block| block.header.clone()).collect()
+    }
+    
+    async fn read_blocks(&self, start: u64, limit: usize) -> Vec<Block> {
+        self.read_blocks_blocking(start as usize, limit)
+    }
+    
+    // Main-chain blocks from `from_height` to the tip, copied a chunk at a time so the chain is never locked for
+    // long; each chunk reads the chain as it stands then, so a reorg mid-stream shows up from the next one
+    pub fn iter_blocks(&self, from_height: u64) -> impl Stream<Item = Block> + Send + 'static {
+        let start = (self.clone(), from_height, VecDeque::new());
+        stream::unfold(start, |(ledger, height, mut chunk)| async move {
+            if chunk.is_empty() {
+                chunk = ledger.read_blocks(height, STREAM_CHUNK).await.into();
+            }
+            let block = chunk.pop_front()?;
+            Some((block, (ledger, height + 1, chunk)))
+        })
+    }
+    
+    // Committed transactions matching `filter`, oldest first, with the height of the block holding each
+    pub fn iter_transactions(&self, filter: TransactionFilter) -> impl Stream<Item = (u64, Transaction)> + Send + 'static {
+        let to_height = filter.to_height.unwrap_or(u64::MAX);
+        let address = filter.address.clone();
+        self.iter_blocks(filter.from_height)
+            .take_while(move |block| std::future::ready(block.header.height <= to_height))
+            // The header's bloom rules most blocks out without looking at their transactions
+            .filter(move |block| std::future::ready(match &address {
+                Some(address) => block.header.address_bloom.may_contain(address),
+                None => true,
+            }))
+            .flat_map(move |block| {
+                let height = block.header.height;
+                let matching: Vec<_> = block.transactions.into_iter()
+                    .filter(|tx| filter.matches(tx))
+                    .map(|tx| (height, tx))
+                    .collect();
+                stream::iter(matching)
+            })
+    }
+    
+    // Never waits on the writer, so safe on or off the async runtime
+    pub(crate) fn read_blocks_blocking(&self, start: usize, limit: usize) -> Vec<Block> {
+        let blocks = self.chain.load_full();
+        blocks.iter_from(start).take(limit).map(|block| Block::clone(block)).collect()
+    }
+    
+    pub async fn get_balance(&self, address: &str) -> u64 {
+        self.balances.get(address)
+            .map(|entry| *entry.value())
+            .unwrap_or(0)
+    }
+    
+    // A consistent handle for several queries in a row, over the chain as last published
+    pub async fn read_view(&self) -> ReadView<'_> {
+        loop {
+            let blocks = self.chain.load_full();
+            
+            // A producer takes transactions off the queue before their block commits, so pooled ones missing from the
+            // queue are pending behind it, oldest first
+            let mut pooled: HashMap<uuid::Uuid, PendingTransaction> = self.transaction_pool.iter()
+                .map(|entry| (*entry.key(), entry.value().clone()))
+                .collect();
+            let mut pending: Vec<Transaction> = self.mempool.ordered()
+                .into_iter()
+                .filter(|tx| pooled.remove(&tx.id).is_some())
+                .collect();
+            let mut taken: Vec<PendingTransaction> = pooled.into_values().collect();
+            taken.sort_by_key(|entry| entry.admitted_at);
+            pending.extend(taken.into_iter().map(|entry| entry.transaction));
+            
+            // A block's transactions leave the pool just after it's published. Those of one published before the
+            // chain was loaded are dropped here; one published since means loading both again
+            pending.retain(|tx| self.committed_transaction(&blocks, tx.id).is_none());
+            if Arc::ptr_eq(&blocks, &self.chain.load()) {
+                return ReadView::new(self, blocks, pending);
+            }
+        }
+    }
+    
+    // A transaction this node has seen, and whether it is queued, on the main chain, or was dropped
+    pub async fn get_transaction(&self, id: uuid::Uuid) -> (Option<Transaction>, TransactionStatus) {
+        if self.tx_index.contains_key(&id) {
+            if let Some((tx, status)) = self.committed_transaction(&self.chain.load_full(), id) {
+                return (Some(tx), status);
+            }
+        }
+        
+        if let Some(pending) = self.transaction_pool.get(&id) {
+            return (Some(pending.transaction.clone()), TransactionStatus::Pending);
+        }
+        self.rejected_transaction(id)
+    }
+    
+    pub(crate) fn committed_transaction(
+        &self,
+        blocks: &Chain,
+        id: uuid::Uuid,
+    ) -> Option<(Transaction, TransactionStatus)> {
+        let height = self.tx_index.get(&id).map(|entry| *entry.value())?;
+        let block = blocks.get(height as usize)?;
+        let tx = block.transactions.iter().find(|tx| tx.id == id)?;
+        let block_hash = block.header.hash.clone();
+        let status = match height <= self.finalized_below(blocks.len() as u64 - 1) {
+            true => TransactionStatus::Finalized { height, block_hash },
+            false => TransactionStatus::Included { height, block_hash },
+        };
+        Some((tx.clone(), status))
+    }
+    
+    // Where the transaction sits on the main chain and how deep, read against a single tip
+    pub(crate) async fn confirmation(&self, id: uuid::Uuid) -> Option<Confirmation> {
+        let blocks = self.chain.load_full();
+        let height = self.tx_index.get(&id).map(|entry| *entry.value())?;
+        let block_hash = blocks.get(height as usize)?.header.hash.clone();
+        Some(Confirmation { height, block_hash, confirmations: blocks.len() as u64 - height })
+    }
+    
+    pub(crate) fn rejected_transaction(&self, id: uuid::Uuid) -> (Option<Transaction>, TransactionStatus) {
+        match self.rejections.get(id) {
+            Some((tx, reason)) => (Some(tx), TransactionStatus::Rejected { reason }),
+            None => (None, TransactionStatus::Unknown),
+        }
+    }
+    
+    // Every account whose balance differs between the states main-chain blocks `from` and `to` left, with its
+    // balance at each; `from` may be above `to`
+    pub async fn state_diff(&self, from: u64, to: u64) -> Option<Vec<BalanceChange>> {
+        self.state.diff(from, to)
+    }
+    
+    // The balance as the main-chain block at `height` left it, read from the state tree's root for that block
+    pub async fn get_balance_at(&self, address: &str, height: u64) -> Option<u64> {
+        self.state.balance_at(address, height)
+    }
+    
+    pub async fn get_transaction_count(&self) -> usize {
+        let blocks = self.chain.load_full();
+        blocks.iter().map(|b| b.transactions.len()).sum()
+    }
+    
+    pub async fn get_logs(&self, filter: &LogFilter) -> Vec<Log> {
+        let tip_height = self.chain_height();
+        
+        let max_height = match filter.include_unfinalized {
+            true => tip_height,
+            false => self.finalized_below(tip_height),
+        };
+        self.logs.query(filter, max_height)
+    }
+    
+    pub async fn finalized_height(&self) -> u64 {
+        self.finalized_below(self.chain_height())
+    }
+    
+    pub async fn is_finalized(&self, block_hash: &str) -> bool {
+        let Some(height) = self.block_index.get(block_hash).map(|entry| *entry.value()) else {
+            return false;
+        };
+        
+        height <= self.finalized_height().await
+    }
+    
+    // Depth-based finality, raised to the latest checkpoint validators finalized
+    fn finalized_below(&self, tip_height: u64) -> u64 {
+        let by_depth = tip_height.saturating_sub(self.consensus.finality_depth(&self.params()));
+        by_depth.max(self.checkpoints.finalized_height())
+    }
+    
+    pub async fn get_anchor_proof(
+        &self,
+        namespace: &str,
+        sequence: u64,
+        include_unfinalized: bool,
+    ) -> Option<AnchorProof> {
+        let record = *self.anchors.get(&(namespace.to_string(), sequence))?;
+        if !include_unfinalized && record.block_height > self.finalized_height().await {
+            return None;
+        }
+        
+        let blocks = self.chain.load_full();
+        let block = blocks.get(record.block_height as usize)?;
+        let transaction = block.transactions.iter()
+            .find(|tx| tx.id == record.transaction_id)?
+            .clone();
+        
+        match &transaction.kind {
+            TransactionKind::Anchor(anchor) => Some(AnchorProof {
+                anchor: anchor.clone(),
+                transaction: transaction.clone(),
+                block_height: record.block_height,
+                block_hash: block.header.hash.clone(),
+            }),
+            _ => None,
+        }
+    }
+    
+    // The header of the main-chain block holding the transaction and the proof it is in it, for light clients
+    pub async fn inclusion_proof(&self, tx_id: uuid::Uuid) -> Option<(BlockHeader, MerkleProof)> {
+        let height = *self.tx_index.get(&tx_id)?;
+        let blocks = self.chain.load_full();
+        let block = blocks.get(height as usize)?;
+        Some((block.header.clone(), block.merkle_proof(tx_id)?))
+    }
+    
+    // Root of the balances the next block must commit to
+    pub fn state_root(&self) -> String {
+        self.state.root()
+    }
+    
+    pub(crate) fn state(&self) -> &StateTree {
+        &self.state
+    }
+    
+    // The main-chain header at `height` and a proof of the balance its state root commits to, i.e. the balance after
+    // block `height - 1`; see `state::verify_balance_proof`
+    pub async fn get_balance_with_proof(&self, address: &str, height: u64) -> Option<(BlockHeader, BalanceProof)> {
+        let blocks = self.chain.load_full();
+        let header = blocks.get(height as usize)?.header.clone();
+        Some((header, self.state.prove(address, height)?))
+    }
+    
+    // Proves `accounts`' balances against the finalized header at `height` and signs the lot with the custodian's
+    // key; see `reserve::verify_reserve_report`
+    pub async fn reserve_report(&self, accounts: &[String], height: u64, keypair: &Keypair) -> Result<ReserveReport> {
+        if accounts.is_empty() {
+            return Err(LedgerError::InvalidParameters("A reserve report needs at least one account".to_string()));
+        }
+        
+        let blocks = self.chain.load_full();
+        if height == 0 || height > self.finalized_below(blocks.len() as u64 - 1) {
+            return Err(LedgerError::InvalidParameters(format!("Height {} is not finalized", height)));
+        }
+        
+        let proofs = accounts.iter()
+            .map(|account| self.state.prove(account, height))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| LedgerError::InvalidParameters(format!("No state committed at height {}", height)))?;
+        Ok(ReserveReport::new(&self.genesis.chain_id, blocks[height as usize].header.clone(), proofs, keypair))
+    }
+    
+    // Resolves explorer input to a block height or hash, a transaction id, or an address
+    pub async fn search(&self, query: &str) -> Option<SearchResult> {
+        let query = query.trim();
+        
+        if let Ok(height) = query.parse::<u64>() {
+            let blocks = self.chain.load_full();
+            if let Some(block) = blocks.get(height as usize) {
+                return Some(SearchResult::Block { height, block: Block::clone(block) });
+            }
+        }
+        
+        if let Some(height) = self.block_index.get(&query.to_ascii_lowercase()).map(|entry| *entry.value()) {
+            let blocks = self.chain.load_full();
+            if let Some(block) = blocks.get(height as usize) {
+                return Some(SearchResult::Block { height, block: Block::clone(block) });
+            }
+        }
+        
+        if let Ok(id) = query.parse::<uuid::Uuid>() {
+            if let Some(height) = self.tx_index.get(&id).map(|entry| *entry.value()) {
+                let blocks = self.chain.load_full();
+                let transaction = blocks.get(height as usize)
+                    .and_then(|block| block.transactions.iter().find(|tx| tx.id == id));
+                
+                if let Some(transaction) = transaction {
+                    return Some(SearchResult::Transaction {
+                        labels: self.labels.for_transaction(transaction),
+                        transaction: transaction.clone(),
+                        block_height: Some(height),
+                    });
+                }
+            }
+            
+            if let Some(pending) = self.transaction_pool.get(&id) {
+                let transaction = pending.value().transaction.clone();
+                return Some(SearchResult::Transaction {
+                    labels: self.labels.for_transaction(&transaction),
+                    transaction,
+                    block_height: None,
+                });
+            }
+        }
+        
+        self.balances.get(query).map(|entry| SearchResult::Address {
+            address: query.to_string(),
+            balance: *entry.value(),
+            label: self.labels.get(query),
+        })
+    }
+    
+    // Committed transactions whose memo starts with `prefix`, in memo order
+    pub async fn find_transactions_by_memo_prefix(&self, prefix: &str, limit: usize) -> Vec<Transaction> {
+        let hits = self.memos.by_prefix(prefix, limit);
+        self.committed_transactions(&hits).await
+    }
+    
+    // Committed transactions tagged `#tag` in their memo, oldest first
+    pub async fn find_transactions_by_tag(&self, tag: &str, limit: usize) -> Vec<Transaction> {
+        let hits = self.memos.by_tag(tag, limit);
+        self.committed_transactions(&hits).await
+    }
+    
+    // The account's main-chain transactions between the heights in `range`, oldest first, each with the balance the
+    // account was left with by its block
+    pub async fn get_account_history(&self, address: &str, range: impl RangeBounds<u64>, page: Page) -> Vec<AccountEntry> {
+        self.account_history(&self.chain.load_full(), address, range, page)
+    }
+    
+    pub(crate) fn account_history(
+        &self,
+        blocks: &Chain,
+        address: &str,
+        range: impl RangeBounds<u64>,
+        page: Page,
+    ) -> Vec<AccountEntry> {
+        let Some((from, to)) = Self::height_bounds(range) else {
+            return Vec::new();
+        };
+        
+        self.addresses.range(address, from, to, page)
+            .into_iter()
+            .filter_map(|(height, id)| {
+                let transaction = blocks.get(height as usize)?.transactions.iter().find(|tx| tx.id == id)?.clone();
+                let direction = match transaction.from == address {
+                    true => Direction::Outgoing,
+                    false => Direction::Incoming,
+                };
+                let balance = self.state.balance_at(address, height)?;
+                Some(AccountEntry { height, direction, transaction, balance })
+            })
+            .collect()
+    }
+    
+    // Accounts the main-chain block at `height` touched, by a transaction or any other balance change
+    pub fn accounts_touched_at(&self, height: u64) -> Option<BTreeSet<String>> {
+        self.touched.at(height)
+    }
+    
+    // Transfer volume and count per hour or day of block time, for one account or the whole chain; read from running
+    // totals, so as cheap at any chain length
+    pub fn aggregate_volume(&self, address: Option<&str>, bucket: TimeBucket) -> Vec<VolumeBucket> {
+        self.volumes.aggregate(address, bucket)
+    }
+    
+    // How each block in `range` that touched the account changed its balance, without reading the blocks; a block
+    // that only named the account shows no change
+    pub fn balance_changes(&self, address: &str, range: impl RangeBounds<u64>) -> Vec<(u64, BalanceChange)> {
+        let Some((from, to)) = Self::height_bounds(range) else {
+            return Vec::new();
+        };
+        
+        self.touched.heights(address, from, to)
+            .into_iter()
+            .filter_map(|height| {
+                let before = match height {
+                    0 => 0,
+                    height => self.state.balance_at(address, height - 1)?,
+                };
+                let after = self.state.balance_at(address, height)?;
+                Some((height, BalanceChange { address: address.to_string(), before, after }))
+            })
+            .collect()
+    }
+    
+    // Inclusive heights a range covers, or None if it covers none
+    fn height_bounds(range: impl RangeBounds<u64>) -> Option<(u64, u64)> {
+        let from = match range.start_bound() {
+            Bound::Included(height) => *height,
+            Bound::Excluded(height) => height.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let to = match range.end_bound() {
+            Bound::Included(height) => *height,
+            Bound::Excluded(height) => height.checked_sub(1)?,
+            Bound::Unbounded => u64::MAX,
+        };
+        Some((from, to))
+    }
+    
+    async fn committed_transactions(&self, hits: &[(u64, uuid::Uuid)]) -> Vec<Transaction> {
+        let blocks = self.chain.load_full();
+        hits.iter()
+            .filter_map(|(height, id)| blocks.get(*height as usize)?.transactions.iter().find(|tx| tx.id == *id))
+            .cloned()
+            .collect()
+    }
+    
+    pub fn latest_anchor_sequence(&self, namespace: &str) -> Option<u64> {
+        self.anchors.iter()
+            .filter(|entry| entry.key().0 == namespace)
+            .map(|entry| entry.key().1)
+            .max()
+    }
+    
+    pub fn get_performance_stats(&self) -> crate::performance::PerformanceStats {
+        self.performance_monitor.get_stats()
+    }
+    
+    // Intervals, fill, mempool depth and difficulty over the latest `window` blocks, genesis aside
+    pub async fn chain_stats(&self, window: usize) -> ChainStats {
+        let blocks = self.chain.load_full();
+        let start = blocks.len().saturating_sub(window.saturating_add(1));
+        let window: Vec<Arc<Block>> = blocks.iter_from(start).cloned().collect();
+        ChainStats::over(&window, self.mempool_depth.since(blocks[start].header.height + 1))
+    }
+    
+    // Takes batches off the queue on every poll that finds production due, and hands them to a task that mines and
+    // commits them in turn, so the next batch is taken while a block is mined and the heartbeat never waits on one
+    pub async fn start_background_processor(&self) {
+        let (queue, mut batches) = tokio::sync::mpsc::channel::<QueuedBatch>(MINING_QUEUE);
+        
+        let miner = self.clone();
+        tokio::spawn(async move {
+            while let Some(QueuedBatch { transactions, batch_size, allow_empty, queued }) = batches.recv().await {
+                drop(queued);
+                if miner.is_paused() {
+                    // Taken just before a pause; back in the mempool, it waits for production to resume
+                    drop(Requeue { ledger: &miner, batch: transactions.iter().map(|tx| tx.id).collect() });
+                    continue;
+                }
+                
+                let started = std::time::Instant::now();
+                let sealed = match miner.produce_from(transactions, batch_size, allow_empty).await {
+                    Ok(sealed) => sealed,
+                    Err(e) => {
+                        error!("Error processing transactions: {}", e);
+                        false
+                    }
+                };
+                // Only a sealed block says anything about how long a batch takes to produce
+                if sealed && !miner.batch_size_fixed.load(Ordering::Relaxed) {
+                    let config = miner.processor_config();
+                    let next = config.next_batch_size(batch_size, started.elapsed(), miner.mempool.len());
+                    // A size set while the block was produced wins over the one adapted from it
+                    let _ = miner.batch_size.compare_exchange(batch_size, next, Ordering::Relaxed, Ordering::Relaxed);
+                }
+            }
+        });
+        
+        let ledger = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_millis(PRODUCTION_POLL_MS));
+            
+            loop {
+                interval.tick().await;
+                let now = chrono::Utc::now().timestamp_millis();
+                ledger.processor_heartbeat.store(now, Ordering::Relaxed);
+                
+                if ledger.is_paused() {
+                    continue;
+                }
+                
+                let since_last_block = (now - ledger.last_produced.load(Ordering::Relaxed)).max(0) as u64;
+                let policy = ledger.production_policy();
+                let slot = ledger.params().block_time.slot_ms.map(std::time::Duration::from_millis);
+                let due = policy.should_produce(
+                    ledger.mempool.len(),
+                    std::time::Duration::from_millis(since_last_block),
+                    slot,
+                );
+                if !due {
+                    continue;
+                }
+                
+                // Mining is behind; the backlog waits in the mempool until a batch is taken from it
+                let Ok(permit) = queue.try_reserve() else {
+                    continue;
+                };
+                let batch_size = ledger.batch_size.load(Ordering::Relaxed);
+                let transactions = ledger.take_batch(batch_size);
+                // An empty block in the works already fills the slot
+                if transactions.is_empty() && ledger.performance_monitor.mining_queue_depth() > 0 {
+                    continue;
+                }
+                permit.send(QueuedBatch {
+                    transactions,
+                    batch_size,
+                    allow_empty: policy.produce_empty,
+                    queued: ledger.performance_monitor.mining_started(),
+                });
+            }
+        });
+    }
+    
+    // Produces a block from whatever is pending, regardless of the production policy
+    pub async fn produce_block_now(&self) -> Result<()> {
+        self.process_transactions(self.batch_size.load(Ordering::Relaxed)).await
+    }
+    
+    pub fn production_policy(&self) -> ProductionPolicy {
+        self.production_policy.read().unwrap().clone()
+    }
+    
+    pub(crate) fn set_production_policy(&self, policy: ProductionPolicy) -> Result<()> {
+        policy.validate()?;
+        *self.production_policy.write().unwrap() = policy;
+        Ok(())
+    }
+    
+    pub fn health(&self, config: &HealthConfig) -> HealthReport {
+        let heartbeat = self.processor_heartbeat.load(Ordering::Relaxed);
+        let heartbeat_age_ms = (heartbeat > 0)
+            .then(|| (chrono::Utc::now().timestamp_millis() - heartbeat).max(0) as u64);
+        
+        let processor_running = heartbeat_age_ms
+            .is_some_and(|age| age <= config.max_heartbeat_age.as_millis() as u64);
+        let pending_transactions = self.mempool.len();
+        let queue_utilization = pending_transactions as f64 / self.mempool.limits().capacity as f64;
+        
+        HealthReport {
+            // A processor that was started but stopped ticking is stuck; before it starts we're still alive
+            live: heartbeat_age_ms.is_none() || processor_running,
+            ready: processor_running && queue_utilization < config.max_queue_utilization,
+            processor_running,
+            paused: self.is_paused(),
+            heartbeat_age_ms,
+            pending_transactions,
+            queue_utilization,
+        }
+    }
+    
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+    
+    pub(crate) fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+    
+    pub fn coinbase_address(&self) -> Option<String> {
+        self.coinbase_address.read().unwrap().clone()
+    }
+    
+    pub(crate) fn set_coinbase_address(&self, address: String) {
+        *self.coinbase_address.write().unwrap() = Some(address);
+    }
+    
+    // Transactions the next block takes at most; see `ProcessorConfig`
+    pub fn batch_size(&self) -> usize {
+        self.batch_size.load(Ordering::Relaxed)
+    }
+    
+    // Fixes the batch size; the processor stops adapting it until the next `set_processor_config`
+    pub(crate) fn set_batch_size(&self, batch_size: usize) {
+        self.batch_size_fixed.store(true, Ordering::Relaxed);
+        self.batch_size.store(batch_size, Ordering::Relaxed);
+    }
+    
+    pub fn processor_config(&self) -> ProcessorConfig {
+        self.processor_config.read().unwrap().clone()
+    }
+    
+    // Adaptation resumes from the current batch size
+    pub(crate) fn set_processor_config(&self, config: ProcessorConfig) -> Result<()> {
+        config.validate()?;
+        *self.processor_config.write().unwrap() = config;
+        self.batch_size_fixed.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+    
+    // Whatever in the ledger's state has stopped adding up, empty when all is well. Walks the whole chain and
+    // every balance, so it's meant for tests and audits rather than the hot path
+    pub async fn check_invariants(&self) -> Vec<InvariantViolation> {
+        let blocks = self.write_chain().await;
+        self.invariant_violations(&blocks)
+    }
+    
+    // Debug builds run `check_invariants` after every `blocks`th block and panic on a violation; 0 turns it off,
+    // and release builds never check
+    pub fn set_invariant_interval(&self, blocks: u64) {
+        self.invariant_interval.store(blocks, Ordering::Relaxed);
+    }
+    
+    // Waves of at least `transfers` go to the thread pool and smaller ones are applied on the calling thread;
+    // `usize::MAX` applies every block serially. The state a block leaves is the same either way
+    pub fn set_parallel_wave(&self, transfers: usize) {
+        self.parallel_wave.store(transfers.max(1), Ordering::Relaxed);
+    }
+    
+    pub fn stakes(&self) -> &StakeRegistry {
+        &self.stakes
+    }
+    
+    // Names an address in search results and snapshots; tags and removal go through `labels`
+    pub fn label_address(&self, address: &str, label: &str) -> Result<()> {
+        if label.trim().is_empty() {
+            return Err(LedgerError::InvalidParameters("Address labels cannot be empty".to_string()));
+        }
+        self.labels.set(address, label.trim());
+        Ok(())
+    }
+    
+    pub fn labels(&self) -> &AddressLabels {
+        &self.labels
+    }
+    
+    pub async fn export_labels(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let labels = self.labels.export();
+        let json = serde_json::to_vec_pretty(&labels)
+            .map_err(anyhow::Error::from)?;
+        tokio::fs::write(path.as_ref(), json).await
+            .map_err(anyhow::Error::from)?;
+        
+        info!("Exported {} address labels to {}", labels.len(), path.as_ref().display());
+        Ok(labels.len())
+    }
+    
+    // Merges an exported label set in, its entries winning over labels already held for the same address
+    pub async fn import_labels(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let json = tokio::fs::read(path.as_ref()).await
+            .map_err(anyhow::Error::from)?;
+        let labels: LabelSet = serde_json::from_slice(&json)
+            .map_err(anyhow::Error::from)?;
+        
+        let imported = self.labels.import(labels);
+        info!("Imported {} address labels from {}", imported, path.as_ref().display());
+        Ok(imported)
+    }
+    
+    pub fn chain_id(&self) -> &str {
+        &self.genesis.chain_id
+    }
+    
+    pub fn genesis(&self) -> &GenesisConfig {
+        &self.genesis
+    }
+    
+    pub fn consensus(&self) -> &dyn Consensus {
+        self.consensus.as_ref()
+    }
+    
+    // There is no setter: parameters change only as the chain writer applies a block that opens an epoch, through an
+    // enacted `ChangeParams` proposal or an epoch hook. They hold for the rest of that block and the blocks built on
+    // it, and reverting the block restores the old ones
+    pub fn params(&self) -> ChainParams {
+        self.params.read().unwrap().clone()
+    }
+    
+    pub(crate) async fn snapshot(&self) -> LedgerSnapshot {
+        // Live balances can be partway into the next block, so they're read from the state tree at this chain's tip;
+        // that tip can only have been pruned if a later one was published meanwhile
+        let (chain, balances) = loop {
+            let chain = self.chain.load_full();
+            if let Some(balances) = self.state.balances_at(chain.len() as u64 - 1) {
+                break (chain, balances);
+            }
+        };
+        let blocks = chain.iter().map(|block| Block::clone(block)).collect::<Vec<_>>();
+        let finalized_height = self.finalized_below(blocks.len() as u64 - 1);
+        
+        LedgerSnapshot { blocks, balances, finalized_height, labels: self.labels.export() }
+    }
+    
+    // Queued transactions in processing order
+    fn pending_transactions(&self) -> Vec<PendingTransaction> {
+        self.mempool.ordered().iter()
+            .filter_map(|tx| self.transaction_pool.get(&tx.id).map(|entry| entry.value().clone()))
+            .collect()
+    }
+    
+    pub fn mempool_size(&self) -> usize {
+        self.mempool.len()
+    }
+    
+    pub fn mempool_occupancy(&self) -> MempoolOccupancy {
+        self.mempool.occupancy()
+    }
+    
+    // The next `limit` queued transactions, in processing order
+    pub fn mempool_head(&self, limit: usize) -> Vec<Transaction> {
+        let mut pending = self.mempool.ordered();
+        pending.truncate(limit);
+        pending
+    }
+    
+    // An account's queued transactions, in processing order
+    pub fn pending_for(&self, address: &str) -> Vec<Transaction> {
+        self.mempool.pending_for(address)
+    }
+    
+    pub fn mempool_limits(&self) -> MempoolLimits {
+        self.mempool.limits()
+    }
+    
+    pub(crate) fn set_mempool_limits(&self, limits: MempoolLimits) -> Result<()> {
+        self.mempool.set_limits(limits)
+    }
+    
+    pub fn index_retention(&self) -> IndexRetention {
+        *self.retention.read().unwrap()
+    }
+    
+    // Rebuilds from the chain each index whose retention changed, so one disabled earlier comes back complete
+    pub(crate) async fn set_index_retention(&self, retention: IndexRetention) -> Result<()> {
+        retention.validate()?;
+        let blocks = self.write_chain().await;
+        let previous = std::mem::replace(&mut *self.retention.write().unwrap(), retention);
+        let tip = blocks.len() as u64 - 1;
+        
+        if retention.memos != previous.memos {
+            self.memos.clear();
+            let floor = retention.memos.floor(tip).unwrap_or(u64::MAX) as usize;
+            for block in blocks.iter().skip(floor) {
+                for tx in &block.transactions {
+                    self.memos.insert(block.header.height, tx);
+                }
+            }
+        }
+        if retention.addresses != previous.addresses {
+            self.addresses.clear();
+            let floor = retention.addresses.floor(tip).unwrap_or(u64::MAX) as usize;
+            for block in blocks.iter().skip(floor) {
+                self.addresses.insert(block);
+            }
+        }
+        if retention.state != previous.state {
+            self.prune_state(retention.state, tip);
+        }
+        Ok(())
+    }
+    
+    pub async fn dump_mempool(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let tip_height = self.chain_height();
+        let entries: Vec<MempoolEntry> = self.pending_transactions()
+            .into_iter()
+            .enumerate()
+            .map(|(priority, pending)| {
+                let validation = match self.check_transaction(&pending.transaction) {
+                    Ok(()) => ValidationState::Valid,
+                    Err(e) => ValidationState::Invalid(e.to_string()),
+                };
+                
+                MempoolEntry {
+                    transaction: pending.transaction,
+                    admitted_at: pending.admitted_at,
+                    priority,
+                    validation,
+                }
+            })
+            .collect();
+        
+        let count = entries.len();
+        let dump = MempoolDump {
+            dumped_at: chrono::Utc::now(),
+            tip_height,
+            entries,
+        };
+        
+        let json = serde_json::to_vec_pretty(&dump)
+            .map_err(anyhow::Error::from)?;
+        tokio::fs::write(path.as_ref(), json).await
+            .map_err(anyhow::Error::from)?;
+        
+        info!("Dumped {} pending transactions to {}", count, path.as_ref().display());
+        Ok(count)
+    }
+    
+    // Re-submits a dumped mempool in its original order; entries this ledger rejects are skipped
+    pub async fn import_mempool(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let json = tokio::fs::read(path.as_ref()).await
+            .map_err(anyhow::Error::from)?;
+        let mut dump: MempoolDump = serde_json::from_slice(&json)
+            .map_err(anyhow::Error::from)?;
+        dump.entries.sort_by_key(|entry| entry.priority);
+        
+        let mut imported = 0;
+        for entry in dump.entries {
+            let id = entry.transaction.id;
+            match self.add_transaction(entry.transaction).await {
+                Ok(()) => imported += 1,
+                Err(e) => warn!("Skipped mempool entry {}: {}", id, e),
+            }
+        }
+        
+        info!("Imported {} pending transactions from {}", imported, path.as_ref().display());
+        Ok(imported)
+    }
+    
+    // Loads the rejection audit an earlier run kept at `path` and keeps it there from now on; returns how many
+    // entries were loaded
+    pub fn open_audit_log(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let loaded = self.audit.open(path.as_ref())?;
+        info!("Loaded {} audit entries from {}", loaded, path.as_ref().display());
+        Ok(loaded)
+    }
+    
+    pub(crate) fn rejected_transactions(&self, filter: &AuditFilter) -> Vec<RejectedTransaction> {
+        self.audit.query(filter)
+    }
+    
+    pub(crate) fn drain_mempool(&self) -> usize {
+        let drained = self.mempool.drain();
+        for tx in &drained {
+            self.reject(tx, "Drained from the mempool".to_string());
+        }
+        
+        let bundles: Vec<_> = self.bundles.iter().map(|entry| (*entry.key(), entry.value().len())).collect();
+        for (id, _) in &bundles {
+            self.drop_bundle(*id, "Drained from the mempool");
+        }
+        
+        drained.len() + bundles.iter().map(|(_, size)| size).sum::<usize>()
+    }
+}
+
+// The published chain as its one writer builds on it; changes reach readers only through `publish_tip`
+struct ChainWriter<'a> {
+    _writer: MutexGuard<'a, ()>,
+    chain: Chain,
+}
+
+impl Deref for ChainWriter<'_> {
+    type Target = Chain;
+    
+    fn deref(&self) -> &Chain {
+        &self.chain
+    }
+}
+
+impl DerefMut for ChainWriter<'_> {
+    fn deref_mut(&mut self) -> &mut Chain {
+        &mut self.chain
+    }
+}
+
+// A batch the background processor took for the mining task, counted in `mining_queue_depth` while it waits
+struct QueuedBatch {
+    transactions: Vec<Transaction>,
+    batch_size: usize,
+    allow_empty: bool,
+    queued: MiningGuard,
+}
+
+// A batch taken out of the mempool for a block; dropped before the block is committed, it goes back in
+struct Requeue<'a> {
+    ledger: &'a DistributedLedger,
+    batch: Vec<uuid::Uuid>,
+}
+
+impl Requeue<'_> {
+    // Puts back at once what of the batch didn't make it into `block`; the rest waits on the block being committed
+    fn keep(&mut self, block: &Block) {
+        let kept: HashSet<uuid::Uuid> = block.transactions.iter().map(|tx| tx.id).collect();
+        let (kept, left) = std::mem::take(&mut self.batch).into_iter().partition(|id| kept.contains(id));
+        self.batch = kept;
+        drop(Requeue { ledger: self.ledger, batch: left });
+    }
+    
+    fn committed(mut self) {
+        self.batch.clear();
+    }
+}
+
+impl Drop for Requeue<'_> {
+    fn drop(&mut self) {
+        for id in &self.batch {
+            if let Some(tx) = self.ledger.transaction_pool.get(id).map(|entry| entry.transaction.clone()) {
+                self.ledger.requeue(&tx);
+            }
+        }
+    }
+}
+
+// Balances and bonds as a block's transactions leave them, so each one is checked against its predecessors
+#[derive(Clone)]
+struct StateOverlay<'a> {
+    ledger: &'a DistributedLedger,
+    // Height of the block; balances start out as what is liquid at it
+    height: u64,
+    balances: HashMap<String, u64>,
+    bonds: HashMap<(String, String), u64>,
+    validators: HashSet<String>,
+    // Validators already punished earlier in the block, so the same evidence can't land twice
+    slashed: HashSet<(String, u64)>,
+    // Checkpoint votes earlier in the block, by validator and height
+    votes: HashSet<(String, u64)>,
+    // Governance votes earlier in the block, by validator and proposal
+    ballots: HashSet<(String, uuid::Uuid)>,
+    // Contracts deployed earlier in the block
+    contracts: HashSet<String>,
+    // Standing orders registered earlier in the block, with their payers
+    schedules: HashMap<uuid::Uuid, String>,
+    // Standing orders cancelled earlier in the block
+    cancelled: HashSet<uuid::Uuid>,
+    // Transactions applied and replaced earlier in the block, which a later one may not replace or be
+    applied: HashSet<uuid::Uuid>,
+    replaced: HashSet<uuid::Uuid>,
+    // Supply issued and destroyed earlier in the block
+    minted: u64,
+    burned: u64,
+    // Epoch the block falls in, with what accounts sent and the limits they set earlier in it
+    epoch: u64,
+    spent: HashMap<String, u64>,
+    limits: HashMap<String, SpendingLimit>,
+    // Coins created and consumed earlier in the block
+    created: HashMap<OutPoint, Coin>,
+    consumed: HashSet<OutPoint>,
+    // Allowances by owner and spender as earlier transactions in the block left them
+    allowances: HashMap<(String, String), u64>,
+    // Hash locks claimed or refunded earlier in the block
+    settled: HashSet<uuid::Uuid>,
+}
+
+impl<'a> StateOverlay<'a> {
+    fn new(ledger: &'a DistributedLedger, height: u64, epoch: u64) -> Self {
+        Self {
+            ledger,
+            height,
+            balances: HashMap::new(),
+            bonds: HashMap::new(),
+            validators: HashSet::new(),
+            slashed: HashSet::new(),
+            votes: HashSet::new(),
+            ballots: HashSet::new(),
+            contracts: HashSet::new(),
+            schedules: HashMap::new(),
+            cancelled: HashSet::new(),
+            applied: HashSet::new(),
+            replaced: HashSet::new(),
+            minted: 0,
+            burned: 0,
+            epoch,
+            spent: HashMap::new(),
+            limits: HashMap::new(),
+            created: HashMap::new(),
+            consumed: HashSet::new(),
+            allowances: HashMap::new(),
+            settled: HashSet::new(),
+        }
+    }
+    
+    fn balance(&mut self, address: &str) -> &mut u64 {
+        let (ledger, height) = (self.ledger, self.height);
+        self.balances.entry(address.to_string())
+            .or_insert_with(|| ledger.liquid_balance(address, height))
+    }
+    
+    fn allowance(&mut self, owner: &str, spender: &str) -> &mut u64 {
+        let ledger = self.ledger;
+        self.allowances.entry((owner.to_string(), spender.to_string()))
+            .or_insert_with(|| ledger.allowances.get(owner, spender))
+    }
+    
+    // Settles a committed lock once per block, if `check` allows it at this height
+    fn settle<F>(&mut self, tx: &Transaction, id: uuid::Uuid, check: F) -> Result<HashLock>
+    where
+        F: FnOnce(&HashLock, u64) -> std::result::Result<(), String>,
+    {
+        let lock = self.ledger.find_lock(id)
+            .and_then(|lock| match self.settled.contains(&id) {
+                true => Err(format!("Hash lock {} is already settled", id)),
+                false => check(&lock, self.height).map(|_| lock),
+            })
+            .map_err(|e| LedgerError::BlockValidationFailed(format!("Transaction {}: {}", tx.id, e)))?;
+        self.settled.insert(id);
+        Ok(lock)
+    }
+    
+    fn bond(&mut self, validator: &str, staker: &str) -> &mut u64 {
+        let ledger = self.ledger;
+        self.bonds.entry((validator.to_string(), staker.to_string()))
+            .or_insert_with(|| ledger.stakes.bonded(validator, staker))
+    }
+    
+    fn debit(&mut self, tx: &Transaction) -> Result<()> {
+        self.charge(tx, &tx.from, tx.amount)
+    }
+    
+    fn charge(&mut self, tx: &Transaction, account: &str, amount: u64) -> Result<()> {
+        let balance = self.balance(account);
+        if *balance < amount {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Transaction {} overspends {}",
+                tx.id, account,
+            )));
+        }
+        
+        *balance -= amount;
+        Ok(())
+    }
+    
+    fn check_spending(&self, tx: &Transaction) -> Result<()> {
+        for (account, amount) in DistributedLedger::spends(tx) {
+            let limit = match self.limits.get(account) {
+                Some(limit) => Some(*limit).filter(|limit| !limit.is_unlimited()),
+                None => self.ledger.limits.limit(account),
+            };
+            let Some(limit) = limit else {
+                continue;
+            };
+            
+            let spent = self.ledger.limits.spent(account, self.epoch) + self.spent.get(account).copied().unwrap_or(0);
+            limit.check(amount, spent).map_err(|e| {
+                LedgerError::BlockValidationFailed(format!("Transaction {}: {}", tx.id, e))
+            })?;
+        }
+        Ok(())
+    }
+    
+    // Only one of a transaction and its replacement can ever commit
+    fn check_replacement(&self, tx: &Transaction) -> Result<()> {
+        if self.replaced.contains(&tx.id) || self.ledger.replacements.contains_key(&tx.id) {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Transaction {} was replaced",
+                tx.id,
+            )));
+        }
+        
+        if let Some(original) = tx.replaces {
+            let settled = self.applied.contains(&original)
+                || self.ledger.tx_index.contains_key(&original)
+                || self.replaced.contains(&original)
+                || self.ledger.replacements.contains_key(&original);
+            if settled {
+                return Err(LedgerError::BlockValidationFailed(format!(
+                    "Transaction {} replaces {}, which is already settled",
+                    tx.id, original,
+                )));
+            }
+        }
+        Ok(())
+    }
+    
+    fn apply(&mut self, tx: &Transaction) -> Result<()> {
+        if self.ledger.tx_index.contains_key(&tx.id) {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Transaction {} is already on the chain",
+                tx.id,
+            )));
+        }
+        
+        self.ledger.check_authorization(tx)?;
+        self.check_replacement(tx)?;
+        self.check_spending(tx)?;
+        self.ledger.check_model(tx).map_err(|e| {
+            LedgerError::BlockValidationFailed(format!("Transaction {}: {}", tx.id, e))
+        })?;
+        
+        // Producers aren't credited here, so their fees only become spendable in the next block
+        if tx.fee > 0 {
+            self.charge(tx, &tx.from, tx.fee)?;
+        }
+        
+        match &tx.kind {
+            TransactionKind::Transfer => {
+                self.debit(tx)?;
+                *self.balance(&tx.to) += tx.amount;
+            }
+            TransactionKind::Vest(schedule) => {
+                self.debit(tx)?;
+                *self.balance(&tx.to) += schedule.unlocked(tx.amount, self.height);
+            }
+            TransactionKind::Approve { allowance } => {
+                *self.allowance(&tx.from, &tx.to) = *allowance;
+            }
+            TransactionKind::HashLock { .. } => self.debit(tx)?,
+            TransactionKind::Claim { lock, preimage } => {
+                let lock = self.settle(tx, *lock, |lock, height| lock.check_claim(preimage, height))?;
+                *self.balance(&lock.recipient) += lock.amount;
+            }
+            TransactionKind::Refund { lock } => {
+                let lock = self.settle(tx, *lock, |lock, height| lock.check_refund(&tx.from, height))?;
+                *self.balance(&lock.sender) += lock.amount;
+            }
+            TransactionKind::Coinbase { .. } => {
+                *self.balance(&tx.to) += tx.amount;
+            }
+            TransactionKind::TransferFrom { owner } => {
+                let allowance = self.allowance(owner, &tx.from);
+                if *allowance < tx.amount {
+                    return Err(LedgerError::BlockValidationFailed(format!(
+                        "Transaction {} exceeds the allowance {} granted {}",
+                        tx.id, owner, tx.from,
+                    )));
+                }
+                *allowance -= tx.amount;
+                
+                self.charge(tx, owner, tx.amount)?;
+                *self.balance(&tx.to) += tx.amount;
+            }
+            TransactionKind::Spend { inputs, outputs } => {
+                let coins: Vec<_> = inputs.iter()
+                    .map(|outpoint| match self.consumed.contains(outpoint) {
+                        true => None,
+                        false => self.created.get(outpoint).cloned().or_else(|| self.ledger.utxos.get(outpoint)),
+                    })
+                    .collect();
+                self.ledger.check_inputs(tx, &coins).map_err(|e| {
+                    LedgerError::BlockValidationFailed(format!("Transaction {}: {}", tx.id, e))
+                })?;
+                
+                self.debit(tx)?;
+                self.consumed.extend(inputs.iter().copied());
+                for (index, output) in outputs.iter().enumerate() {
+                    let outpoint = OutPoint { tx: tx.id, index: index as u32 };
+                    self.created.insert(outpoint, Coin { owner: output.to.clone(), amount: output.amount });
+                    *self.balance(&output.to) += output.amount;
+                }
+            }
+            TransactionKind::Mint | TransactionKind::Burn => {
+                self.ledger.check_issuance(tx, self.minted, self.burned).map_err(|e| {
+                    LedgerError::BlockValidationFailed(format!("Transaction {}: {}", tx.id, e))
+                })?;
+                
+                if tx.kind == TransactionKind::Mint {
+                    *self.balance(&tx.to) += tx.amount;
+                    self.minted += tx.amount;
+                } else {
+                    self.debit(tx)?;
+                    self.burned += tx.amount;
+                }
+            }
+            TransactionKind::Payout(payments) => {
+                self.debit(tx)?;
+                for payment in payments {
+                    *self.balance(&payment.to) += payment.amount;
+                }
+            }
+            TransactionKind::Swap { counter_amount } => {
+                self.debit(tx)?;
+                self.charge(tx, &tx.to, *counter_amount)?;
+                *self.balance(&tx.to) += tx.amount;
+                *self.balance(&tx.from) += counter_amount;
+            }
+            TransactionKind::Anchor(_) | TransactionKind::SetSpendingLimit(_) => {}
+            TransactionKind::Evidence(evidence) => {
+                self.ledger.check_evidence(&tx.to, evidence).map_err(|e| {
+                    LedgerError::BlockValidationFailed(format!("Transaction {}: {}", tx.id, e))
+                })?;
+                
+                if !self.slashed.insert((tx.to.clone(), evidence.offense_height())) {
+                    return Err(LedgerError::BlockValidationFailed(format!(
+                        "Transaction {} repeats evidence against {}",
+                        tx.id, tx.to,
+                    )));
+                }
+            }
+            TransactionKind::CheckpointVote(vote) => {
+                self.ledger.check_checkpoint_vote(&tx.from, vote, tx.authorization.as_ref()).map_err(|e| {
+                    LedgerError::BlockValidationFailed(format!("Transaction {}: {}", tx.id, e))
+                })?;
+                
+                if !self.votes.insert((tx.from.clone(), vote.height)) {
+                    return Err(LedgerError::BlockValidationFailed(format!(
+                        "Transaction {} repeats {}'s vote on checkpoint {}",
+                        tx.id, tx.from, vote.height,
+                    )));
+                }
+            }
+            TransactionKind::Propose(_) | TransactionKind::Vote { .. } => {
+                self.ledger.check_governance(tx).map_err(|e| {
+                    LedgerError::BlockValidationFailed(format!("Transaction {}: {}", tx.id, e))
+                })?;
+                
+                if let TransactionKind::Vote { proposal, .. } = &tx.kind {
+                    if !self.ballots.insert((tx.from.clone(), *proposal)) {
+                        return Err(LedgerError::BlockValidationFailed(format!(
+                            "Transaction {} repeats {}'s vote on proposal {}",
+                            tx.id, tx.from, proposal,
+                        )));
+                    }
+                }
+            }
+            TransactionKind::Stake { .. } => {
+                self.debit(tx)?;
+                self.validators.insert(tx.to.clone());
+                *self.bond(&tx.to, &tx.from) += tx.amount;
+            }
+            TransactionKind::Delegate => {
+                if !self.validators.contains(&tx.to) && !self.ledger.stakes.contains(&tx.to) {
+                    return Err(LedgerError::BlockValidationFailed(format!(
+                        "Transaction {} delegates to unknown validator {}",
+                        tx.id, tx.to,
+                    )));
+                }
+                
+                self.debit(tx)?;
+                *self.bond(&tx.to, &tx.from) += tx.amount;
+            }
+            TransactionKind::Deploy { code } => {
+                self.ledger.contracts.validate_code(code).map_err(|e| {
+                    LedgerError::BlockValidationFailed(format!("Transaction {}: {}", tx.id, e))
+                })?;
+                
+                self.debit(tx)?;
+                let address = ContractStore::address_for(tx);
+                *self.balance(&address) += tx.amount;
+                self.contracts.insert(address);
+            }
+            // What the call pays out isn't known until it runs, so its recipients can't spend it in the same block
+            TransactionKind::Call { .. } => {
+                if !self.contracts.contains(&tx.to) && !self.ledger.contracts.exists(&tx.to) {
+                    return Err(LedgerError::BlockValidationFailed(format!(
+                        "Transaction {} calls unknown contract {}",
+                        tx.id, tx.to,
+                    )));
+                }
+                
+                self.debit(tx)?;
+                *self.balance(&tx.to) += tx.amount;
+            }
+            TransactionKind::Schedule { .. } => {
+                if self.ledger.params().epochs.is_none() {
+                    return Err(LedgerError::BlockValidationFailed(format!(
+                        "Transaction {} registers a standing order without an epoch schedule",
+                        tx.id,
+                    )));
+                }
+                self.schedules.insert(tx.id, tx.from.clone());
+            }
+            TransactionKind::CancelSchedule { schedule } => {
+                let checked = match self.schedules.get(schedule) {
+                    Some(payer) if payer != &tx.from => Err("Only the payer can cancel a standing order".to_string()),
+                    Some(_) => Ok(()),
+                    None => self.ledger.check_cancellation(&tx.from, *schedule),
+                };
+                checked.map_err(|e| LedgerError::BlockValidationFailed(format!("Transaction {}: {}", tx.id, e)))?;
+                
+                if !self.cancelled.insert(*schedule) {
+                    return Err(LedgerError::BlockValidationFailed(format!(
+                        "Transaction {} cancels standing order {} twice",
+                        tx.id, schedule,
+                    )));
+                }
+            }
+            TransactionKind::Unstake => {
+                let bond = self.bond(&tx.to, &tx.from);
+                if *bond < tx.amount {
+                    return Err(LedgerError::BlockValidationFailed(format!(
+                        "Transaction {} unstakes more than {} has bonded",
+                        tx.id, tx.from,
+                    )));
+                }
+                
+                *bond -= tx.amount;
+                *self.balance(&tx.from) += tx.amount;
+            }
+        }
+        
+        if let TransactionKind::SetSpendingLimit(limit) = &tx.kind {
+            self.limits.insert(tx.from.clone(), *limit);
+        }
+        for (account, amount) in DistributedLedger::spends(tx) {
+            *self.spent.entry(account.to_string()).or_insert(0) += amount;
+        }
+        
+        self.applied.insert(tx.id);
+        self.replaced.extend(tx.replaces);
+        Ok(())
+    }
+}
+
+impl Default for DistributedLedger {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Clone for DistributedLedger {
     fn clone(&self) -> Self {
         Self {
-            blocks: Arc::clone(&self.blocks),
+            writer: Arc::clone(&self.writer),
             balances: Arc::clone(&self.balances),
+            state: Arc::clone(&self.state),
+            issued: Arc::clone(&self.issued),
+            analytics: Arc::clone(&self.analytics),
             transaction_pool: Arc::clone(&self.transaction_pool),
+            rejections: Arc::clone(&self.rejections),
+            audit: Arc::clone(&self.audit),
+            submitters: Arc::clone(&self.submitters),
+            bundles: Arc::clone(&self.bundles),
+            reserved: Arc::clone(&self.reserved),
             performance_monitor: Arc::clone(&self.performance_monitor),
-            tx_sender: self.tx_sender.clone(),
-            tx_receiver: self.tx_receiver.clone(),
+            mempool_depth: Arc::clone(&self.mempool_depth),
+            labels: Arc::clone(&self.labels),
+            logs: Arc::clone(&self.logs),
+            anchors: Arc::clone(&self.anchors),
+            block_index: Arc::clone(&self.block_index),
+            tx_index: Arc::clone(&self.tx_index),
+            producer_index: Arc::clone(&self.producer_index),
+            memos: Arc::clone(&self.memos),
+            addresses: Arc::clone(&self.addresses),
+            retention: Arc::clone(&self.retention),
+            touched: Arc::clone(&self.touched),
+            volumes: Arc::clone(&self.volumes),
+            utxos: Arc::clone(&self.utxos),
+            vesting: Arc::clone(&self.vesting),
+            allowances: Arc::clone(&self.allowances),
+            hashlocks: Arc::clone(&self.hashlocks),
+            side_blocks: Arc::clone(&self.side_blocks),
+            stale: Arc::clone(&self.stale),
+            contracts: Arc::clone(&self.contracts),
+            reorg_events: self.reorg_events.clone(),
+            head: Arc::clone(&self.head),
+            chain: Arc::clone(&self.chain),
+            replacements: Arc::clone(&self.replacements),
+            replacement_events: self.replacement_events.clone(),
+            invoices: Arc::clone(&self.invoices),
+            invoice_events: self.invoice_events.clone(),
+            orphans: Arc::clone(&self.orphans),
+            peers: Arc::clone(&self.peers),
+            missing_blocks: self.missing_blocks.clone(),
+            events: self.events.clone(),
+            announced_finality: Arc::clone(&self.announced_finality),
+            mempool: Arc::clone(&self.mempool),
+            paused: Arc::clone(&self.paused),
+            batch_size: Arc::clone(&self.batch_size),
+            batch_size_fixed: Arc::clone(&self.batch_size_fixed),
+            params: Arc::clone(&self.params),
+            consensus: Arc::clone(&self.consensus),
+            genesis: Arc::clone(&self.genesis),
+            account_keys: Arc::clone(&self.account_keys),
+            stakes: Arc::clone(&self.stakes),
+            checkpoints: Arc::clone(&self.checkpoints),
+            governance: Arc::clone(&self.governance),
+            schedules: Arc::clone(&self.schedules),
+            limits: Arc::clone(&self.limits),
+            epoch_hooks: Arc::clone(&self.epoch_hooks),
+            epochs: Arc::clone(&self.epochs),
+            processor_heartbeat: Arc::clone(&self.processor_heartbeat),
+            production_policy: Arc::clone(&self.production_policy),
+            processor_config: Arc::clone(&self.processor_config),
+            last_produced: Arc::clone(&self.last_produced),
+            coinbase_address: Arc::clone(&self.coinbase_address),
+            invariant_interval: Arc::clone(&self.invariant_interval),
+            parallel_wave: Arc::clone(&self.parallel_wave),
         }
     }
 }
\ No newline at end of file
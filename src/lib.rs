@@ -1,10 +1,125 @@
 pub mod ledger;
 pub mod transaction;
 pub mod block;
+pub mod chain;
+pub mod merkle;
+pub mod state;
+pub mod status;
+pub mod history;
+pub mod analytics;
+pub mod events;
+pub mod stream;
+pub mod invariants;
+pub mod stats;
+pub mod labels;
+pub mod reserve;
+pub mod retention;
+pub mod volume;
+pub mod view;
+pub mod audit;
+pub mod bloom;
+pub mod confirmations;
+pub mod conflicts;
 pub mod error;
 pub mod performance;
+pub mod admin;
+pub mod logs;
+pub mod params;
+pub mod health;
+pub mod listen;
+pub mod anchor;
+pub mod query;
+pub mod search;
+pub mod consensus;
+pub mod keys;
+pub mod genesis;
+pub mod poa;
+pub mod ipc;
+pub mod mempool;
+pub mod fork;
+pub mod staking;
+pub mod pos;
+pub mod slashing;
+pub mod hybrid;
+pub mod checkpoint;
+pub mod epoch;
+pub mod production;
+pub mod governance;
+pub mod peers;
+pub mod stale;
+pub mod contracts;
+pub mod schedules;
+pub mod limits;
+pub mod memo;
+pub mod utxo;
+pub mod vesting;
+pub mod invoice;
+pub mod bundle;
+pub mod allowance;
+pub mod htlc;
+pub mod rng;
+pub mod loadgen;
+mod clock;
+#[cfg(feature = "simulation")]
+pub mod simulation;
+#[cfg(feature = "zk")]
+pub mod zk;
 
 pub use error::{LedgerError, Result};
-pub use ledger::DistributedLedger;
-pub use transaction::Transaction;
-pub use block::Block;
\ No newline at end of file
+pub use ledger::{DistributedLedger, LedgerSnapshot};
+pub use transaction::{Payment, Transaction, TransactionKind, BlockBound};
+pub use block::{Block, BlockHeader};
+pub use merkle::{verify_inclusion_proof, MerkleProof};
+pub use state::{verify_balance_proof, BalanceChange, BalanceProof, StateTree};
+pub use status::{Rejections, TransactionStatus};
+pub use history::{AccountEntry, AddressIndex, Direction, Page, TouchedAccounts};
+pub use analytics::{BalanceAnalytics, BalanceSummary, DistributionBucket};
+pub use events::{AddressSubscription, LedgerEvent, StatusUpdate};
+pub use stream::TransactionFilter;
+pub use invariants::InvariantViolation;
+pub use stats::{ChainStats, MempoolDepth};
+pub use labels::{AddressLabel, AddressLabels, LabelSet};
+pub use reserve::{verify_reserve_report, ReserveReport};
+pub use retention::{IndexRetention, Retention};
+pub use volume::{TimeBucket, VolumeBucket, VolumeIndex};
+pub use view::ReadView;
+pub use audit::{AuditFilter, RejectedTransaction, RejectionAudit};
+pub use bloom::AddressBloom;
+pub use confirmations::{ChainHead, Confirmation, ConfirmationTracker};
+pub use conflicts::{ConflictSchedule, ScheduleStep, PARALLEL_WAVE};
+pub use admin::AdminApi;
+pub use logs::{Log, LogFilter};
+pub use params::{BlockTimeRules, ChainParams, DifficultyAdjustment, EmissionSchedule, FeeDestination, IssuancePolicy, SlashingParams, TimestampOrdering};
+pub use health::{HealthConfig, HealthReport};
+pub use listen::ListenAddr;
+pub use anchor::{Anchor, AnchorProof};
+pub use query::{CancellationToken, QueryBudget, QueryExecutor};
+pub use search::SearchResult;
+pub use consensus::{Consensus, ProofOfWork};
+pub use keys::Keypair;
+pub use genesis::GenesisConfig;
+pub use poa::{PoaConfig, ProofOfAuthority};
+pub use ipc::{IpcClient, IpcRequest, IpcResponse};
+pub use mempool::{Mempool, MempoolDump, MempoolEntry, MempoolLimits, MempoolOccupancy, ReplacementEvent, ValidationState};
+pub use fork::{BlockImport, Branch, BranchComparison, ChainTip, ReorgEvent};
+pub use staking::{StakeRegistry, Validator};
+pub use pos::{Election, PosConfig, ProofOfStake};
+pub use hybrid::{ConsensusSchedule, ConsensusTransition, EngineConfig, HybridConsensus};
+pub use slashing::{Evidence, SlashReason, SlashRecord};
+pub use checkpoint::{Checkpoint, CheckpointTracker, CheckpointVote};
+pub use production::{ProcessorConfig, ProductionPolicy};
+pub use stale::{StaleBlock, StaleStats, StaleTracker};
+pub use contracts::{ContractEvent, ContractStore, Receipt};
+pub use peers::{PeerPolicy, PeerRecord, PeerRegistry, Violation, ViolationReport};
+pub use memo::MemoIndex;
+pub use utxo::{Coin, LedgerModel, OutPoint, UtxoSet};
+pub use allowance::Allowances;
+pub use htlc::{HashLock, HashLockStatus, HashLocks};
+pub use loadgen::{AccountDistribution, LoadGenerator, LoadProfile, TrafficPattern};
+pub use bundle::BundlePosition;
+pub use invoice::{Invoice, InvoiceBook, InvoicePaid, InvoiceStatus};
+pub use vesting::{Vesting, VestingBalance, VestingGrant, VestingSchedule};
+pub use limits::{SpendingLimit, SpendingLimits};
+pub use schedules::{Installment, StandingOrder, StandingOrders};
+pub use governance::{Governance, Proposal, ProposalAction, ProposalStatus};
+pub use epoch::{Epoch, EpochEffects, EpochHook, EpochLength, EpochRecord, EpochRewards, EpochSchedule};
\ No newline at end of file
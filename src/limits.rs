@@ -0,0 +1,117 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Caps on what an account sends, fees aside; `per_epoch` counts over the chain's lifetime when epochs are off
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct SpendingLimit {
+    #[serde(default)]
+    pub per_transaction: Option<u64>,
+    #[serde(default)]
+    pub per_epoch: Option<u64>,
+}
+
+impl SpendingLimit {
+    pub fn is_unlimited(&self) -> bool {
+        self.per_transaction.is_none() && self.per_epoch.is_none()
+    }
+    
+    // The stricter of each cap
+    pub fn tighten(&self, other: &SpendingLimit) -> SpendingLimit {
+        let min = |a: Option<u64>, b: Option<u64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        
+        SpendingLimit {
+            per_transaction: min(self.per_transaction, other.per_transaction),
+            per_epoch: min(self.per_epoch, other.per_epoch),
+        }
+    }
+    
+    // `spent` is what the account already sent this epoch
+    pub fn check(&self, amount: u64, spent: u64) -> Result<(), String> {
+        if self.per_transaction.is_some_and(|cap| amount > cap) {
+            return Err(format!("Spending {} exceeds the per-transaction limit", amount));
+        }
+        if self.per_epoch.is_some_and(|cap| spent.saturating_add(amount) > cap) {
+            return Err(format!("Spending {} exceeds the per-epoch limit, with {} already spent", amount, spent));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SpendingLimits {
+    // Limits each account set on chain, newest last, so reverting one restores the one before
+    history: RwLock<HashMap<String, Vec<(Uuid, SpendingLimit)>>>,
+    // Set by this node's operator and only enforced when admitting transactions
+    overrides: RwLock<HashMap<String, SpendingLimit>>,
+    // Amount sent by epoch and account
+    spent: RwLock<BTreeMap<(u64, String), u64>>,
+}
+
+impl SpendingLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn limit(&self, account: &str) -> Option<SpendingLimit> {
+        self.history.read().unwrap()
+            .get(account)
+            .and_then(|history| history.last())
+            .map(|(_, limit)| *limit)
+            .filter(|limit| !limit.is_unlimited())
+    }
+    
+    pub fn override_for(&self, account: &str) -> Option<SpendingLimit> {
+        self.overrides.read().unwrap().get(account).copied()
+    }
+    
+    pub fn spent(&self, account: &str, epoch: u64) -> u64 {
+        self.spent.read().unwrap().get(&(epoch, account.to_string())).copied().unwrap_or(0)
+    }
+    
+    pub(crate) fn set(&self, account: &str, id: Uuid, limit: SpendingLimit) {
+        self.history.write().unwrap().entry(account.to_string()).or_default().push((id, limit));
+    }
+    
+    pub(crate) fn unset(&self, account: &str, id: Uuid) {
+        let mut history = self.history.write().unwrap();
+        if let Some(limits) = history.get_mut(account) {
+            limits.retain(|(set_by, _)| *set_by != id);
+            if limits.is_empty() {
+                history.remove(account);
+            }
+        }
+    }
+    
+    pub(crate) fn set_override(&self, account: &str, limit: Option<SpendingLimit>) {
+        let mut overrides = self.overrides.write().unwrap();
+        match limit {
+            Some(limit) => overrides.insert(account.to_string(), limit),
+            None => overrides.remove(account),
+        };
+    }
+    
+    // Counters from before the previous epoch are dropped as a new one is recorded
+    pub(crate) fn record(&self, account: &str, epoch: u64, amount: u64) {
+        let mut spent = self.spent.write().unwrap();
+        if spent.keys().next().is_some_and(|(oldest, _)| oldest + 1 < epoch) {
+            *spent = spent.split_off(&(epoch - 1, String::new()));
+        }
+        *spent.entry((epoch, account.to_string())).or_insert(0) += amount;
+    }
+    
+    pub(crate) fn unrecord(&self, account: &str, epoch: u64, amount: u64) {
+        let mut spent = self.spent.write().unwrap();
+        let key = (epoch, account.to_string());
+        if let Some(total) = spent.get_mut(&key) {
+            *total = total.saturating_sub(amount);
+            if *total == 0 {
+                spent.remove(&key);
+            }
+        }
+    }
+}
\ No newline at end of file
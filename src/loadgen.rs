@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+
+use crate::genesis::{DevAccounts, GenesisAllocation, GenesisConfig};
+use crate::keys::Keypair;
+use crate::rng::SimRng;
+use crate::{LedgerError, Result, Transaction};
+
+// How often each account sends; receivers are always drawn uniformly from the others
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum AccountDistribution {
+    Uniform,
+    // The `k`th account sends in proportion to 1 / k^exponent, so a few hot accounts dominate
+    Zipfian { exponent: f64 },
+}
+
+// How many transactions each tick of `LoadGenerator::next_tick` yields
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum TrafficPattern {
+    Steady { per_tick: usize },
+    // `base` per tick, and `burst` instead on every `every`th one
+    Burst { base: usize, burst: usize, every: usize },
+}
+
+// A reproducible stream of signed transfers between `accounts` funded accounts; the same profile always yields the
+// same stream
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LoadProfile {
+    pub accounts: usize,
+    pub balance: u64,
+    pub distribution: AccountDistribution,
+    pub pattern: TrafficPattern,
+    // Chance, in basis points, that a transfer spends from the previous one's sender, contending for its funds
+    pub conflict_bps: u32,
+    pub min_amount: u64,
+    pub max_amount: u64,
+    pub fee: u64,
+    pub seed: u64,
+}
+
+impl Default for LoadProfile {
+    fn default() -> Self {
+        Self {
+            accounts: 100,
+            balance: 1_000_000_000,
+            distribution: AccountDistribution::Uniform,
+            pattern: TrafficPattern::Steady { per_tick: 1_000 },
+            conflict_bps: 0,
+            min_amount: 1,
+            max_amount: 1_000,
+            fee: 0,
+            seed: 0,
+        }
+    }
+}
+
+impl LoadProfile {
+    pub fn zipfian(exponent: f64) -> Self {
+        Self {
+            distribution: AccountDistribution::Zipfian { exponent },
+            ..Self::default()
+        }
+    }
+    
+    pub fn bursty(base: usize, burst: usize, every: usize) -> Self {
+        Self {
+            pattern: TrafficPattern::Burst { base, burst, every },
+            ..Self::default()
+        }
+    }
+    
+    pub fn validate(&self) -> Result<()> {
+        if self.accounts < 2 {
+            return Err(LedgerError::InvalidParameters(
+                "Load needs at least two accounts".to_string(),
+            ));
+        }
+        
+        if self.min_amount == 0 || self.min_amount > self.max_amount {
+            return Err(LedgerError::InvalidParameters(
+                "Amounts must satisfy 0 < min <= max".to_string(),
+            ));
+        }
+        
+        if self.conflict_bps > 10_000 {
+            return Err(LedgerError::InvalidParameters(
+                "Conflict rate cannot exceed 10000 basis points".to_string(),
+            ));
+        }
+        
+        if let AccountDistribution::Zipfian { exponent } = self.distribution {
+            if !exponent.is_finite() || exponent <= 0.0 {
+                return Err(LedgerError::InvalidParameters(
+                    "Zipfian exponent must be positive".to_string(),
+                ));
+            }
+        }
+        
+        let empty = match self.pattern {
+            TrafficPattern::Steady { per_tick } => per_tick == 0,
+            TrafficPattern::Burst { base, burst, every } => burst == 0 || every == 0 || burst < base,
+        };
+        if empty {
+            return Err(LedgerError::InvalidParameters(
+                "Traffic patterns need transactions per tick, and bursts above the base rate".to_string(),
+            ));
+        }
+        Ok(())
+    }
+    
+    pub fn account(index: usize) -> String {
+        format!("load_{}", index)
+    }
+    
+    // Funds every account as a dev account, so the ledger knows the keys the generator signs with
+    pub fn genesis(&self) -> GenesisConfig {
+        let accounts = (0..self.accounts)
+            .map(|index| GenesisAllocation {
+                address: Self::account(index),
+                balance: self.balance,
+                vesting: None,
+            })
+            .collect();
+        
+        GenesisConfig {
+            dev: Some(DevAccounts {
+                seed: format!("loadgen-{}", self.seed),
+                accounts,
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+pub struct LoadGenerator {
+    profile: LoadProfile,
+    chain_id: String,
+    keys: Vec<Keypair>,
+    // Running totals of the senders' weights; empty when uniform
+    weights: Vec<f64>,
+    rng: SimRng,
+    tick: usize,
+    last_sender: Option<usize>,
+}
+
+impl LoadGenerator {
+    // `genesis` must fund the profile's accounts as dev accounts, as `LoadProfile::genesis` does; its params and chain
+    // id can be changed freely
+    pub fn new(profile: LoadProfile, genesis: &GenesisConfig) -> Result<Self> {
+        profile.validate()?;
+        
+        let keys = (0..profile.accounts)
+            .map(|index| genesis.dev_keypair(&LoadProfile::account(index)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| LedgerError::InvalidParameters("Genesis does not fund the load accounts".to_string()))?;
+        
+        let weights = match profile.distribution {
+            AccountDistribution::Uniform => Vec::new(),
+            AccountDistribution::Zipfian { exponent } => (1..=profile.accounts)
+                .scan(0.0, |total, rank| {
+                    *total += 1.0 / (rank as f64).powf(exponent);
+                    Some(*total)
+                })
+                .collect(),
+        };
+        
+        Ok(Self {
+            rng: SimRng::new(profile.seed),
+            profile,
+            chain_id: genesis.chain_id.clone(),
+            keys,
+            weights,
+            tick: 0,
+            last_sender: None,
+        })
+    }
+    
+    pub fn profile(&self) -> &LoadProfile {
+        &self.profile
+    }
+    
+    pub fn next_transaction(&mut self) -> Transaction {
+        let conflicting = self.rng.range(0, 9_999) < self.profile.conflict_bps as u64;
+        let sender = match self.last_sender {
+            Some(previous) if conflicting => previous,
+            _ => self.pick_sender(),
+        };
+        self.last_sender = Some(sender);
+        
+        // Any account but the sender
+        let receiver = self.rng.range(0, self.profile.accounts as u64 - 2) as usize;
+        let receiver = if receiver >= sender { receiver + 1 } else { receiver };
+        let amount = self.rng.range(self.profile.min_amount, self.profile.max_amount);
+        
+        let mut tx = Transaction::new(LoadProfile::account(sender), LoadProfile::account(receiver), amount)
+            .for_chain(&self.chain_id)
+            .with_fee(self.profile.fee);
+        tx.sign(&self.keys[sender]);
+        tx
+    }
+    
+    // The transactions of the next tick, sized by the traffic pattern
+    pub fn next_tick(&mut self) -> Vec<Transaction> {
+        let count = match self.profile.pattern {
+            TrafficPattern::Steady { per_tick } => per_tick,
+            TrafficPattern::Burst { burst, every, .. } if self.tick % every == every - 1 => burst,
+            TrafficPattern::Burst { base, .. } => base,
+        };
+        self.tick += 1;
+        (0..count).map(|_| self.next_transaction()).collect()
+    }
+    
+    fn pick_sender(&mut self) -> usize {
+        let Some(total) = self.weights.last() else {
+            return self.rng.range(0, self.profile.accounts as u64 - 1) as usize;
+        };
+        let target = self.rng.next_f64() * total;
+        self.weights.partition_point(|weight| *weight <= target).min(self.profile.accounts - 1)
+    }
+}
+
+impl Iterator for LoadGenerator {
+    type Item = Transaction;
+    
+    fn next(&mut self) -> Option<Transaction> {
+        Some(self.next_transaction())
+    }
+}
\ No newline at end of file
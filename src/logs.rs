@@ -0,0 +1,152 @@
+use std::collections::BTreeSet;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::Transaction;
+
+pub const TRANSFER_MODULE: &str = "native:transfer";
+pub const SUPPLY_MODULE: &str = "native:supply";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Log {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: Vec<u8>,
+    pub block_height: u64,
+    pub transaction_id: Uuid,
+}
+
+impl Log {
+    pub fn new(address: String, topics: Vec<String>, data: Vec<u8>, transaction_id: Uuid) -> Self {
+        Self {
+            address,
+            topics,
+            data,
+            block_height: 0,
+            transaction_id,
+        }
+    }
+    
+    pub(crate) fn transfer(tx: &Transaction) -> Self {
+        Self::payment(tx, &tx.from, &tx.to, tx.amount)
+    }
+    
+    // One leg of a transfer; payouts log one per output and swaps one per direction
+    pub(crate) fn payment(tx: &Transaction, from: &str, to: &str, amount: u64) -> Self {
+        Self::new(
+            TRANSFER_MODULE.to_string(),
+            vec!["transfer".to_string(), from.to_string(), to.to_string()],
+            amount.to_le_bytes().to_vec(),
+            tx.id,
+        )
+    }
+}
+
+// Topics match positionally: `None` is a wildcard, `Some(t)` requires topic `t` at that index
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    pub address: Option<String>,
+    pub topics: Vec<Option<String>>,
+    pub from_height: Option<u64>,
+    pub to_height: Option<u64>,
+    // Logs from blocks that could still be reorganised are left out unless this is set
+    pub include_unfinalized: bool,
+}
+
+impl LogFilter {
+    pub fn matches(&self, log: &Log) -> bool {
+        if let Some(address) = &self.address {
+            if &log.address != address {
+                return false;
+            }
+        }
+        
+        if self.from_height.is_some_and(|from| log.block_height < from) {
+            return false;
+        }
+        
+        if self.to_height.is_some_and(|to| log.block_height > to) {
+            return false;
+        }
+        
+        self.topics.iter().enumerate().all(|(i, topic)| match topic {
+            Some(topic) => log.topics.get(i) == Some(topic),
+            None => true,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct LogStore {
+    by_height: DashMap<u64, Vec<Log>>,
+    topic_index: DashMap<String, BTreeSet<u64>>,
+}
+
+impl LogStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn record(&self, block_height: u64, mut logs: Vec<Log>) {
+        if logs.is_empty() {
+            return;
+        }
+        
+        for log in &mut logs {
+            log.block_height = block_height;
+            
+            for topic in &log.topics {
+                self.topic_index.entry(topic.clone()).or_default().insert(block_height);
+            }
+        }
+        
+        self.by_height.insert(block_height, logs);
+    }
+    
+    // Drops a height's logs when its block leaves the main chain
+    pub fn remove(&self, block_height: u64) {
+        let Some((_, logs)) = self.by_height.remove(&block_height) else {
+            return;
+        };
+        
+        for topic in logs.iter().flat_map(|log| &log.topics) {
+            if let Some(mut heights) = self.topic_index.get_mut(topic) {
+                heights.remove(&block_height);
+            }
+        }
+    }
+    
+    pub fn query(&self, filter: &LogFilter, tip_height: u64) -> Vec<Log> {
+        let from = filter.from_height.unwrap_or(0);
+        let to = filter.to_height.unwrap_or(tip_height).min(tip_height);
+        
+        if from > to {
+            return Vec::new();
+        }
+        
+        // Narrow the scan to heights carrying every required topic
+        let mut heights: Option<BTreeSet<u64>> = None;
+        for topic in filter.topics.iter().flatten() {
+            let indexed = self.topic_index.get(topic)
+                .map(|entry| entry.range(from..=to).copied().collect())
+                .unwrap_or_default();
+            
+            heights = Some(match heights {
+                Some(current) => current.intersection(&indexed).copied().collect(),
+                None => indexed,
+            });
+        }
+        
+        let heights: Vec<u64> = match heights {
+            Some(heights) => heights.into_iter().collect(),
+            None => (from..=to).collect(),
+        };
+        
+        heights.into_iter()
+            .filter_map(|height| self.by_height.get(&height))
+            .flat_map(|entry| entry.value().clone())
+            .filter(|log| filter.matches(log))
+            .collect()
+    }
+}
\ No newline at end of file
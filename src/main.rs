@@ -1,4 +1,4 @@
-use distributed_ledger::{DistributedLedger, Transaction};
+use distributed_ledger::{DistributedLedger, GenesisConfig, Transaction};
 use tokio::time::{sleep, Duration};
 
 #[tokio::main]
@@ -11,34 +11,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!();
     
     // Create ledger
-    let ledger = DistributedLedger::new();
+    println!("💰 Initializing accounts with 1M balance each...");
+    let genesis = GenesisConfig::dev("demo");
+    let ledger = DistributedLedger::from_genesis(genesis.clone())?;
     let ledger_clone = ledger.clone();
     
     // Start background processor
     ledger_clone.start_background_processor().await;
     
     // Create test accounts
-    let accounts = vec![
+    let accounts = [
         "alice".to_string(),
         "bob".to_string(),
         "charlie".to_string(),
         "diana".to_string(),
         "eve".to_string(),
     ];
-    
-    // Initialize accounts with balances
-    println!("💰 Initializing accounts with 1M balance each...");
-    for account in &accounts {
-        let tx = Transaction::new(
-            "".to_string(), // Genesis transaction
-            account.clone(),
-            1_000_000,
-        );
-        ledger.add_transaction(tx).await?;
-    }
-    
-    // Wait for initialization
-    sleep(Duration::from_millis(100)).await;
+    let keys: Vec<_> = accounts.iter()
+        .map(|account| genesis.dev_keypair(account).expect("dev account"))
+        .collect();
     
     println!("✅ {} accounts initialized", accounts.len());
     println!();
@@ -52,16 +43,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     for i in 0..transaction_count {
         let from = &accounts[i % accounts.len()];
         let to = &accounts[(i + 1) % accounts.len()];
-        let amount = (i % 1000) + 1;
+        let amount = ((i % 1000) + 1) as u64;
         
-        let tx = Transaction::new(from.clone(), to.clone(), amount);
+        let mut tx = Transaction::new(from.clone(), to.clone(), amount);
+        tx.sign(&keys[i % accounts.len()]);
         ledger.add_transaction(tx).await?;
         
         // Progress indicator
         if (i + 1) % 5_000 == 0 {
             let stats = ledger.get_performance_stats();
-            println!("  Processed {} transactions, Current TPS: {:.0}", 
-                i + 1, stats.transactions_per_second);
+            println!("  Processed {} transactions, Current TPS: {:.0}, mining queue: {}",
+                i + 1, stats.transactions_per_second, stats.mining_queue_depth);
         }
     }
     
@@ -78,6 +70,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  Average TPS: {:.0}", stats.transactions_per_second);
     println!("  Peak TPS: {:.0}", stats.peak_tps);
     println!("  Average batch time: {:?}", stats.average_batch_time);
+    println!("  Hash rate: {:.0} H/s", stats.hash_rate);
+    println!("  Inclusion latency: p50 {:?}, p95 {:?}, p99 {:?}, max {:?}",
+        stats.inclusion_latency.p50, stats.inclusion_latency.p95,
+        stats.inclusion_latency.p99, stats.inclusion_latency.max);
     
     // Performance validation
     println!();
@@ -0,0 +1,83 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::RwLock;
+use uuid::Uuid;
+
+use crate::Transaction;
+
+// A payload that is valid UTF-8 text reads as a memo; `#words` in it are its tags
+pub fn memo(tx: &Transaction) -> Option<&str> {
+    std::str::from_utf8(&tx.data).ok().filter(|memo| !memo.trim().is_empty())
+}
+
+pub fn tags(memo: &str) -> BTreeSet<String> {
+    memo.split_whitespace()
+        .filter_map(|word| word.strip_prefix('#'))
+        .map(|tag| tag.trim_end_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+// Main-chain transactions by memo and by tag, kept in step with the chain as blocks are applied and reverted
+#[derive(Debug, Default)]
+pub struct MemoIndex {
+    memos: RwLock<BTreeMap<(String, Uuid), u64>>,
+    tags: RwLock<HashMap<String, BTreeSet<(u64, Uuid)>>>,
+}
+
+impl MemoIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // Heights and ids of transactions whose memo starts with `prefix`, in memo order
+    pub fn by_prefix(&self, prefix: &str, limit: usize) -> Vec<(u64, Uuid)> {
+        self.memos.read().unwrap()
+            .range((prefix.to_string(), Uuid::nil())..)
+            .take_while(|((memo, _), _)| memo.starts_with(prefix))
+            .take(limit)
+            .map(|((_, id), height)| (*height, *id))
+            .collect()
+    }
+    
+    // Oldest first
+    pub fn by_tag(&self, tag: &str, limit: usize) -> Vec<(u64, Uuid)> {
+        self.tags.read().unwrap()
+            .get(&tag.trim_start_matches('#').to_lowercase())
+            .map(|hits| hits.iter().take(limit).copied().collect())
+            .unwrap_or_default()
+    }
+    
+    pub(crate) fn insert(&self, height: u64, tx: &Transaction) {
+        let Some(memo) = memo(tx) else {
+            return;
+        };
+        
+        self.memos.write().unwrap().insert((memo.to_string(), tx.id), height);
+        let mut index = self.tags.write().unwrap();
+        for tag in tags(memo) {
+            index.entry(tag).or_default().insert((height, tx.id));
+        }
+    }
+    
+    pub(crate) fn clear(&self) {
+        self.memos.write().unwrap().clear();
+        self.tags.write().unwrap().clear();
+    }
+    
+    pub(crate) fn remove(&self, height: u64, tx: &Transaction) {
+        let Some(memo) = memo(tx) else {
+            return;
+        };
+        
+        self.memos.write().unwrap().remove(&(memo.to_string(), tx.id));
+        let mut index = self.tags.write().unwrap();
+        for tag in tags(memo) {
+            if let Some(hits) = index.get_mut(&tag) {
+                hits.remove(&(height, tx.id));
+                if hits.is_empty() {
+                    index.remove(&tag);
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
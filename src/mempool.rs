@@ -0,0 +1,503 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, RwLock};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use crate::{LedgerError, Result, Transaction};
+
+// Queues the pool is split into by sender, so submitters from different accounts rarely wait on each other
+pub const MEMPOOL_SHARDS: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTransaction {
+    pub transaction: Transaction,
+    pub admitted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ValidationState {
+    Valid,
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolEntry {
+    pub transaction: Transaction,
+    pub admitted_at: DateTime<Utc>,
+    // Processing order, lowest first: highest fee rate, then oldest
+    pub priority: usize,
+    // Re-checked against the committed state when the dump was taken
+    pub validation: ValidationState,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolDump {
+    pub dumped_at: DateTime<Utc>,
+    pub tip_height: u64,
+    pub entries: Vec<MempoolEntry>,
+}
+
+// Sent when a pending transaction gives way to a higher-fee one from the same sender
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReplacementEvent {
+    pub replaced: Uuid,
+    pub replacement: Uuid,
+    pub sender: String,
+    // The replacement only pays its fee, cancelling the original
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct MempoolLimits {
+    // Past this many pending transactions, a newcomer has to outbid the lowest-priority one to get in
+    pub capacity: usize,
+    pub max_per_account: usize,
+    // Dust policy, off at zero: the least a transaction may pay each recipient, and the least it may leave any
+    // account it touches holding, other than nothing
+    #[serde(default)]
+    pub min_transfer: u64,
+    #[serde(default)]
+    pub min_balance: u64,
+}
+
+impl Default for MempoolLimits {
+    fn default() -> Self {
+        Self {
+            capacity: 100_000,
+            max_per_account: 1_000,
+            min_transfer: 0,
+            min_balance: 0,
+        }
+    }
+}
+
+impl MempoolLimits {
+    pub fn validate(&self) -> Result<()> {
+        if self.capacity == 0 || self.max_per_account == 0 {
+            return Err(LedgerError::InvalidParameters(
+                "Mempool limits must be greater than zero".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+}
+
+// How full the pool is, for callers deciding whether to back off
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MempoolOccupancy {
+    pub pending: usize,
+    pub capacity: usize,
+    // `pending` over `capacity`; can pass 1 for a while after the capacity is lowered
+    pub utilization: f64,
+    // Submitters held back until a transaction leaves the pool
+    pub waiting_for_room: usize,
+    // Pending transactions in each shard; a lopsided spread means a few senders submit most of the load
+    pub shards: Vec<usize>,
+}
+
+// Sorts highest fee rate first, then oldest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Priority {
+    fee_rate: Reverse<u128>,
+    sequence: u64,
+}
+
+#[derive(Default)]
+struct Queue {
+    ordered: BTreeMap<Priority, Transaction>,
+    // Time-locked transactions, held back until a block could include them; ranked as on admission, so they can be
+    // evicted like anything else
+    waiting: BTreeMap<Priority, Transaction>,
+    priorities: HashMap<Uuid, Priority>,
+    // Each transaction's weight, worked out once on admission for its fee rate and the producer's size checks
+    weights: HashMap<Uuid, usize>,
+    per_account: HashMap<String, usize>,
+    // The best of `ordered` as last published to the pool's heads
+    head: Option<Priority>,
+}
+
+impl Queue {
+    fn len(&self) -> usize {
+        self.ordered.len() + self.waiting.len()
+    }
+    
+    fn contains(&self, id: &Uuid) -> bool {
+        self.priorities.contains_key(id)
+    }
+    
+    fn all(&self) -> impl Iterator<Item = &Transaction> {
+        self.ordered.values().chain(self.waiting.values())
+    }
+    
+    fn enqueue(&mut self, priority: Priority, tx: Transaction) {
+        self.priorities.insert(tx.id, priority);
+        self.ordered.insert(priority, tx);
+    }
+    
+    // Refuses a transaction already queued, or one its sender has no more room for
+    fn check_admissible(&self, tx: &Transaction, limits: &MempoolLimits) -> Result<()> {
+        if self.contains(&tx.id) {
+            return Err(LedgerError::DuplicateTransaction);
+        }
+        
+        // Minting and other sender-less transactions aren't anyone's to limit
+        if !tx.from.is_empty() && self.per_account.get(&tx.from).copied().unwrap_or(0) >= limits.max_per_account {
+            return Err(LedgerError::PerformanceLimitExceeded(format!(
+                "{} already has {} pending transactions",
+                tx.from, limits.max_per_account,
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    fn admit(&mut self, priority: Priority, tx: Transaction, weight: usize) {
+        if !tx.from.is_empty() {
+            *self.per_account.entry(tx.from.clone()).or_insert(0) += 1;
+        }
+        self.weights.insert(tx.id, weight);
+        if tx.not_before.is_some() {
+            self.priorities.insert(tx.id, priority);
+            self.waiting.insert(priority, tx);
+        } else {
+            self.enqueue(priority, tx);
+        }
+    }
+    
+    fn remove(&mut self, id: &Uuid) -> Option<Transaction> {
+        let priority = self.priorities.remove(id)?;
+        let tx = self.ordered.remove(&priority).or_else(|| self.waiting.remove(&priority))?;
+        self.weights.remove(id);
+        if let Some(count) = self.per_account.get_mut(&tx.from) {
+            *count -= 1;
+            if *count == 0 {
+                self.per_account.remove(&tx.from);
+            }
+        }
+        Some(tx)
+    }
+}
+
+// Transactions waiting for a block, in the order the producer takes them. Each sender's transactions live in one
+// shard, so the per-account limit is checked within it; priorities come from one sequence across shards, so the
+// producer takes them in the same order a single queue would
+pub struct Mempool {
+    limits: RwLock<MempoolLimits>,
+    shards: Vec<Mutex<Queue>>,
+    // Transactions across all shards, only changed under the lock of the shard concerned. Claimed before a
+    // transaction is queued, so submitters to different shards can't overfill the pool between them
+    pending: AtomicUsize,
+    next_sequence: AtomicU64,
+    // Each non-empty shard's best queued priority, so finding the best transaction locks one shard rather than all.
+    // Only changed under the lock of the shard concerned
+    heads: Mutex<BTreeMap<Priority, usize>>,
+    // Woken whenever transactions leave the queue or the capacity changes
+    room: Notify,
+    waiting_for_room: AtomicUsize,
+}
+
+// Counts a submitter waiting for room for as long as it waits, however the wait ends
+struct WaitingForRoom<'a>(&'a AtomicUsize);
+
+impl Drop for WaitingForRoom<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Mempool {
+    pub fn new(limits: MempoolLimits) -> Self {
+        Self {
+            limits: RwLock::new(limits),
+            shards: (0..MEMPOOL_SHARDS).map(|_| Mutex::new(Queue::default())).collect(),
+            pending: AtomicUsize::new(0),
+            next_sequence: AtomicU64::new(0),
+            heads: Mutex::new(BTreeMap::new()),
+            room: Notify::new(),
+            waiting_for_room: AtomicUsize::new(0),
+        }
+    }
+    
+    pub fn limits(&self) -> MempoolLimits {
+        *self.limits.read().unwrap()
+    }
+    
+    // Tighter limits apply to new arrivals; nothing already queued is evicted for them
+    pub(crate) fn set_limits(&self, limits: MempoolLimits) -> Result<()> {
+        limits.validate()?;
+        *self.limits.write().unwrap() = limits;
+        self.room.notify_waiters();
+        Ok(())
+    }
+    
+    // Time-locked transactions included
+    pub fn len(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    // A newcomer only gets in by outbidding what is queued
+    pub fn is_full(&self) -> bool {
+        self.len() >= self.limits().capacity
+    }
+    
+    pub fn occupancy(&self) -> MempoolOccupancy {
+        let pending = self.len();
+        let capacity = self.limits().capacity;
+        MempoolOccupancy {
+            pending,
+            capacity,
+            utilization: pending as f64 / capacity as f64,
+            waiting_for_room: self.waiting_for_room.load(Ordering::Relaxed),
+            shards: self.shards.iter().map(|shard| shard.lock().unwrap().len()).collect(),
+        }
+    }
+    
+    // Returns once the pool has room, which another submitter may take first
+    pub(crate) async fn wait_for_room(&self) {
+        self.waiting_for_room.fetch_add(1, Ordering::Relaxed);
+        let _waiting = WaitingForRoom(&self.waiting_for_room);
+        loop {
+            // Registered before the check, so room made between the two still wakes it
+            let notified = self.room.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if !self.is_full() {
+                return;
+            }
+            notified.await;
+        }
+    }
+    
+    fn shard_of(&self, sender: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        sender.hash(&mut hasher);
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+    
+    // Every shard, locked in order; anything that needs more than one shard at a time takes them this way
+    fn lock_all(&self) -> Vec<MutexGuard<'_, Queue>> {
+        self.shards.iter().map(|shard| shard.lock().unwrap()).collect()
+    }
+    
+    // Brings `heads` up to date with a shard's queue after a change to it, under that shard's lock
+    fn publish_head(&self, shard: usize, queue: &mut Queue) {
+        let head = queue.ordered.keys().next().copied();
+        if head == queue.head {
+            return;
+        }
+        let mut heads = self.heads.lock().unwrap();
+        if let Some(old) = queue.head {
+            heads.remove(&old);
+        }
+        if let Some(new) = head {
+            heads.insert(new, shard);
+        }
+        queue.head = head;
+    }
+    
+    fn priority(&self, tx: &Transaction, weight: usize) -> Priority {
+        Priority {
+            fee_rate: Reverse(tx.fee_rate_at(weight)),
+            sequence: self.next_sequence.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+    
+    pub fn contains(&self, id: &Uuid) -> bool {
+        self.shards.iter().any(|shard| shard.lock().unwrap().contains(id))
+    }
+    
+    // Everything queued, in processing order, followed by what is still time-locked
+    pub fn ordered(&self) -> Vec<Transaction> {
+        let queues = self.lock_all();
+        let mut ordered: Vec<(&Priority, &Transaction)> = queues.iter().flat_map(|queue| &queue.ordered).collect();
+        ordered.sort_unstable_by_key(|(priority, _)| **priority);
+        ordered.into_iter()
+            .map(|(_, tx)| tx)
+            .chain(queues.iter().flat_map(|queue| queue.waiting.values()))
+            .cloned()
+            .collect()
+    }
+    
+    pub fn pending_for(&self, address: &str) -> Vec<Transaction> {
+        let queue = self.shards[self.shard_of(address)].lock().unwrap();
+        queue.ordered.values()
+            .chain(queue.waiting.values())
+            .filter(|tx| tx.from == address)
+            .cloned()
+            .collect()
+    }
+    
+    // Queues a transaction; if the pool is full, evicts lower-priority ones to make room when `evict` is set and
+    // refuses it otherwise. Returns the evicted
+    pub(crate) fn insert(&self, tx: Transaction, evict: bool) -> Result<Vec<Transaction>> {
+        let limits = self.limits();
+        let shard = self.shard_of(&tx.from);
+        let weight = tx.weight();
+        {
+            let mut queue = self.shards[shard].lock().unwrap();
+            queue.check_admissible(&tx, &limits)?;
+            if self.pending.fetch_add(1, Ordering::Relaxed) < limits.capacity {
+                let priority = self.priority(&tx, weight);
+                queue.admit(priority, tx, weight);
+                self.publish_head(shard, &mut queue);
+                return Ok(Vec::new());
+            }
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+            if !evict {
+                return Err(LedgerError::PerformanceLimitExceeded(
+                    "Mempool is full".to_string(),
+                ));
+            }
+        }
+        
+        // Making room means weighing the newcomer against every shard's lowest, so hold them all. The sender's shard
+        // was let go in between, so it is checked again
+        let mut queues = self.lock_all();
+        queues[shard].check_admissible(&tx, &limits)?;
+        
+        // Only lower-priority transactions make room, and only if enough of them do; time-locked ones included, or
+        // they could fill the pool for good
+        let priority = self.priority(&tx, weight);
+        let excess = (self.len() + 1).saturating_sub(limits.capacity);
+        let mut lowest: Vec<(Priority, usize)> = queues.iter().enumerate()
+            .flat_map(|(i, queue)| {
+                let ordered = queue.ordered.keys().rev().take(excess);
+                let waiting = queue.waiting.keys().rev().take(excess);
+                ordered.chain(waiting).map(move |priority| (*priority, i))
+            })
+            .collect();
+        lowest.sort_unstable_by(|a, b| b.cmp(a));
+        if excess > 0 && lowest.get(excess - 1).is_none_or(|(lowest, _)| *lowest <= priority) {
+            return Err(LedgerError::PerformanceLimitExceeded(
+                "Mempool is full".to_string(),
+            ));
+        }
+        
+        let evicted: Vec<Transaction> = lowest.into_iter().take(excess)
+            .filter_map(|(priority, i)| {
+                let id = queues[i].ordered.get(&priority).or_else(|| queues[i].waiting.get(&priority))?.id;
+                queues[i].remove(&id)
+            })
+            .collect();
+        self.pending.fetch_sub(evicted.len(), Ordering::Relaxed);
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        queues[shard].admit(priority, tx, weight);
+        for (i, queue) in queues.iter_mut().enumerate() {
+            self.publish_head(i, queue);
+        }
+        Ok(evicted)
+    }
+    
+    // Queues the time-locked transactions a block at `height` stamped `timestamp` may include
+    pub(crate) fn release_due(&self, height: u64, timestamp: DateTime<Utc>) {
+        for (shard, mut queue) in self.lock_all().into_iter().enumerate() {
+            let due: Vec<Priority> = queue.waiting.iter()
+                .filter(|(_, tx)| !tx.is_locked_at(height, timestamp))
+                .map(|(priority, _)| *priority)
+                .collect();
+            
+            for admitted in due {
+                let tx = queue.waiting.remove(&admitted).unwrap();
+                let priority = self.priority(&tx, queue.weights[&tx.id]);
+                queue.enqueue(priority, tx);
+            }
+            self.publish_head(shard, &mut queue);
+        }
+    }
+    
+    // The highest-priority transaction across shards, with its weight. Only the shard holding it is locked; if it
+    // left before the lock was taken, that shard has published its new best already, so looking again finds it
+    pub(crate) fn peek(&self) -> Option<(Transaction, usize)> {
+        loop {
+            let (priority, shard) = self.heads.lock().unwrap().first_key_value().map(|(p, shard)| (*p, *shard))?;
+            let queue = self.shards[shard].lock().unwrap();
+            if let Some(tx) = queue.ordered.get(&priority) {
+                return Some((tx.clone(), queue.weights[&tx.id]));
+            }
+        }
+    }
+    
+    pub(crate) fn remove(&self, id: &Uuid) -> Option<Transaction> {
+        let removed = self.shards.iter().enumerate().find_map(|(i, shard)| {
+            let mut queue = shard.lock().unwrap();
+            let removed = queue.remove(id)?;
+            self.publish_head(i, &mut queue);
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+            Some(removed)
+        });
+        if removed.is_some() {
+            self.room.notify_waiters();
+        }
+        removed
+    }
+    
+    // Takes out everything a block at `height` stamped `timestamp` could no longer include
+    pub(crate) fn remove_expired(&self, height: u64, timestamp: DateTime<Utc>) -> Vec<Transaction> {
+        let mut expired = Vec::new();
+        let mut queues = self.lock_all();
+        for (shard, queue) in queues.iter_mut().enumerate() {
+            let ids: Vec<Uuid> = queue.all()
+                .filter(|tx| tx.is_expired_at(height, timestamp))
+                .map(|tx| tx.id)
+                .collect();
+            expired.extend(ids.iter().filter_map(|id| queue.remove(id)));
+            self.publish_head(shard, queue);
+        }
+        self.pending.fetch_sub(expired.len(), Ordering::Relaxed);
+        drop(queues);
+        self.room.notify_waiters();
+        expired
+    }
+    
+    pub(crate) fn drain(&self) -> Vec<Transaction> {
+        let mut queues = self.lock_all();
+        let mut ordered: Vec<(Priority, Transaction)> = Vec::new();
+        let mut waiting = Vec::new();
+        for queue in queues.iter_mut() {
+            let queue = std::mem::take(&mut **queue);
+            ordered.extend(queue.ordered);
+            waiting.extend(queue.waiting.into_values());
+        }
+        self.heads.lock().unwrap().clear();
+        self.pending.fetch_sub(ordered.len() + waiting.len(), Ordering::Relaxed);
+        drop(queues);
+        ordered.sort_unstable_by_key(|(priority, _)| *priority);
+        self.room.notify_waiters();
+        ordered.into_iter().map(|(_, tx)| tx).chain(waiting).collect()
+    }
+}
+
+// Highest fee rate first, but each sender's transactions stay in the order they were queued
+pub(crate) fn order_by_fee_rate(transactions: Vec<Transaction>) -> Vec<Transaction> {
+    let mut by_sender: HashMap<String, VecDeque<(usize, Transaction)>> = HashMap::new();
+    for (position, tx) in transactions.into_iter().enumerate() {
+        by_sender.entry(tx.from.clone()).or_default().push_back((position, tx));
+    }
+    
+    let mut heads = BinaryHeap::new();
+    for (sender, queue) in &by_sender {
+        let (position, tx) = &queue[0];
+        heads.push((tx.fee_rate(), Reverse(*position), sender.clone()));
+    }
+    
+    let mut ordered = Vec::new();
+    while let Some((_, _, sender)) = heads.pop() {
+        let queue = by_sender.get_mut(&sender).unwrap();
+        ordered.push(queue.pop_front().unwrap().1);
+        if let Some((position, tx)) = queue.front() {
+            heads.push((tx.fee_rate(), Reverse(*position), sender));
+        }
+    }
+    ordered
+}
\ No newline at end of file
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{BlockHeader, Transaction};
+
+// Leaves and inner nodes are hashed under different tags, so an inner node can't pass for a transaction
+const LEAF_TAG: u8 = 0;
+const NODE_TAG: u8 = 1;
+
+// Where a transaction sits in its block's tree and the hashes needed to climb from it to the root
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MerkleProof {
+    pub index: usize,
+    pub leaves: usize,
+    // Sibling of each node on the path, leaf first; the last node of an odd level has none and moves up as it is
+    pub siblings: Vec<String>,
+}
+
+type Hash = [u8; 32];
+
+// Leaves and nodes hash the raw 32 bytes below them; only the root and proof siblings are written out in hex
+fn leaf(tx_digest: &Hash) -> Hash {
+    Sha256::new().chain_update([LEAF_TAG]).chain_update(tx_digest).finalize().into()
+}
+
+fn node(left: &Hash, right: &Hash) -> Hash {
+    Sha256::new().chain_update([NODE_TAG]).chain_update(left).chain_update(right).finalize().into()
+}
+
+fn parents(level: &[Hash]) -> Vec<Hash> {
+    level.chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node(left, right),
+            [last] => *last,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+// The digest of nothing for an empty block, as the genesis block has always committed to
+pub fn root(tx_digests: &[[u8; 32]]) -> String {
+    let mut level: Vec<Hash> = tx_digests.iter().map(leaf).collect();
+    if level.is_empty() {
+        return format!("{:x}", Sha256::new().finalize());
+    }
+    
+    while level.len() > 1 {
+        level = parents(&level);
+    }
+    hex::encode(level[0])
+}
+
+pub fn proof(tx_digests: &[[u8; 32]], index: usize) -> Option<MerkleProof> {
+    let mut level: Vec<Hash> = tx_digests.iter().map(leaf).collect();
+    if index >= level.len() {
+        return None;
+    }
+    
+    let mut siblings = Vec::new();
+    let mut position = index;
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(position ^ 1) {
+            siblings.push(hex::encode(sibling));
+        }
+        level = parents(&level);
+        position /= 2;
+    }
+    
+    Some(MerkleProof { index, leaves: tx_digests.len(), siblings })
+}
+
+// Whether `tx` is in the block `header` describes, for a light client holding headers only; the header must be
+// one the client already trusts, e.g. on a chain it validated
+pub fn verify_inclusion_proof(header: &BlockHeader, tx: &Transaction, proof: &MerkleProof) -> bool {
+    if header.hash != header.calculate_hash() || proof.index >= proof.leaves {
+        return false;
+    }
+    
+    let mut siblings = proof.siblings.iter();
+    let mut current = leaf(&tx.digest());
+    let (mut position, mut width) = (proof.index, proof.leaves);
+    while width > 1 {
+        if position % 2 == 1 || position + 1 < width {
+            let mut sibling = [0; 32];
+            match siblings.next().map(|hex| hex::decode_to_slice(hex, &mut sibling)) {
+                Some(Ok(())) => {}
+                _ => return false,
+            }
+            current = match position % 2 {
+                1 => node(&sibling, &current),
+                _ => node(&current, &sibling),
+            };
+        }
+        position /= 2;
+        width = width.div_ceil(2);
+    }
+    
+    siblings.next().is_none() && hex::encode(current) == header.merkle_root
+}
\ No newline at end of file
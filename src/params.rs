@@ -0,0 +1,452 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::epoch::{EpochLength, EpochSchedule};
+use crate::{Block, LedgerError, Result, Transaction, TransactionKind};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DifficultyAdjustment {
+    pub target_block_interval_ms: u64,
+    // Difficulty is recalculated at heights that are multiples of this
+    pub window: u64,
+    pub min_difficulty: usize,
+    pub max_difficulty: usize,
+}
+
+impl Default for DifficultyAdjustment {
+    fn default() -> Self {
+        Self {
+            target_block_interval_ms: 1_000,
+            window: 10,
+            min_difficulty: 1,
+            max_difficulty: 8,
+        }
+    }
+}
+
+impl DifficultyAdjustment {
+    // Each difficulty step is 16x the work, so only move once blocks are off target by more than 4x
+    pub fn retarget(&self, current: usize, first: &Block, last: &Block) -> usize {
+        let intervals = last.header.height.saturating_sub(first.header.height);
+        if intervals == 0 {
+            return current;
+        }
+        
+        let elapsed = (last.header.timestamp - first.header.timestamp).num_milliseconds().max(0) as u64;
+        let actual = elapsed / intervals;
+        let target = self.target_block_interval_ms;
+        
+        let next = if actual.saturating_mul(4) < target {
+            current + 1
+        } else if actual > target.saturating_mul(4) {
+            current.saturating_sub(1)
+        } else {
+            current
+        };
+        
+        next.clamp(self.min_difficulty, self.max_difficulty)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlashingParams {
+    // Share of every bond to the validator that is burned, in basis points
+    pub double_sign_penalty_bps: u64,
+    pub downtime_penalty_bps: u64,
+    // Blocks a validator must go without producing before it can be reported
+    pub downtime_window: u64,
+    pub jail_blocks: u64,
+}
+
+impl Default for SlashingParams {
+    fn default() -> Self {
+        Self {
+            double_sign_penalty_bps: 500,
+            downtime_penalty_bps: 100,
+            downtime_window: 100,
+            jail_blocks: 1_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum TimestampOrdering {
+    // Each block must be later than its parent
+    #[default]
+    AfterParent,
+    // Each block must be later than the median of the last `window` blocks, so one producer's fast
+    // clock can't push every later timestamp forward
+    MedianTimePast {
+        window: usize,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlockTimeRules {
+    #[serde(default)]
+    pub ordering: TimestampOrdering,
+    // How far ahead of the local clock a block may be; such blocks are refused until the clock catches up
+    #[serde(default)]
+    pub max_future_drift_ms: Option<u64>,
+    // Nominal time between blocks; when set, each header records how many slots went by without a block since its
+    // parent, so a chain that skips empty blocks still shows where it was idle
+    #[serde(default)]
+    pub slot_ms: Option<u64>,
+}
+
+impl Default for BlockTimeRules {
+    fn default() -> Self {
+        Self {
+            ordering: TimestampOrdering::AfterParent,
+            max_future_drift_ms: Some(15_000),
+            slot_ms: None,
+        }
+    }
+}
+
+impl BlockTimeRules {
+    // Whole slots between the parent's and the block's own, which must be `BlockHeader::skipped_slots`
+    pub fn skipped_slots(&self, parent: DateTime<Utc>, timestamp: DateTime<Utc>) -> u64 {
+        let Some(slot) = self.slot_ms else {
+            return 0;
+        };
+        let elapsed = (timestamp - parent).num_milliseconds().max(0) as u64;
+        (elapsed / slot).saturating_sub(1)
+    }
+}
+
+// The one account allowed to mint and burn, and the limits it works within
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IssuancePolicy {
+    pub issuer: String,
+    // Mint and burn transactions must be signed with this key
+    pub public_key: String,
+    #[serde(default)]
+    pub max_supply: Option<u64>,
+    #[serde(default)]
+    pub max_mint_per_block: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum FeeDestination {
+    // Credited to the account behind the producer's key; burned when there is none, e.g. under proof-of-work
+    #[default]
+    Producer,
+    Burn,
+}
+
+// New supply paid out through each block's coinbase, halving every `halving_interval` blocks
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct EmissionSchedule {
+    pub initial_reward: u64,
+    pub halving_interval: u64,
+}
+
+impl EmissionSchedule {
+    pub fn reward_at(&self, height: u64) -> u64 {
+        let halvings = height / self.halving_interval;
+        if halvings >= 64 {
+            return 0;
+        }
+        self.initial_reward >> halvings
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChainParams {
+    pub max_block_transactions: usize,
+    // Encoded size of a block's transactions; headers are small and bounded
+    #[serde(default = "default_max_block_bytes")]
+    pub max_block_bytes: usize,
+    // Sum of the gas limits of a block's contract calls
+    #[serde(default = "default_max_block_gas")]
+    pub max_block_gas: u64,
+    // Fixed difficulty, or the starting one when adjustment is enabled
+    pub difficulty: usize,
+    #[serde(default)]
+    pub difficulty_adjustment: Option<DifficultyAdjustment>,
+    #[serde(default)]
+    pub slashing: SlashingParams,
+    // Confirmations after which depth-based engines treat a block as final
+    #[serde(default = "default_finality_depth")]
+    pub finality_depth: u64,
+    // Validators vote on every block at a multiple of this height; two thirds of the stake finalizes it
+    #[serde(default)]
+    pub checkpoint_interval: Option<u64>,
+    #[serde(default)]
+    pub epochs: Option<EpochSchedule>,
+    #[serde(default)]
+    pub block_time: BlockTimeRules,
+    #[serde(default)]
+    pub fee_destination: FeeDestination,
+    // Largest `data` payload a single transaction may carry
+    #[serde(default = "default_max_transaction_data")]
+    pub max_transaction_data: usize,
+    // Sum of the payloads of a block's transactions, within `max_block_bytes`
+    #[serde(default = "default_max_block_data")]
+    pub max_block_data: usize,
+    // Least fee a transaction pays per byte of its payload
+    #[serde(default = "default_data_fee_per_byte")]
+    pub data_fee_per_byte: u64,
+    // Supply is fixed after genesis when unset
+    #[serde(default)]
+    pub issuance: Option<IssuancePolicy>,
+    // When set, every block opens with a coinbase; fees then reach the producer only through it
+    #[serde(default)]
+    pub emission: Option<EmissionSchedule>,
+}
+
+fn default_finality_depth() -> u64 {
+    6
+}
+
+fn default_max_block_bytes() -> usize {
+    1 << 20
+}
+
+fn default_max_block_gas() -> u64 {
+    50_000_000
+}
+
+fn default_max_transaction_data() -> usize {
+    4 << 10
+}
+
+fn default_max_block_data() -> usize {
+    256 << 10
+}
+
+fn default_data_fee_per_byte() -> u64 {
+    1
+}
+
+impl Default for ChainParams {
+    fn default() -> Self {
+        Self {
+            max_block_transactions: 5_000,
+            max_block_bytes: default_max_block_bytes(),
+            max_block_gas: default_max_block_gas(),
+            difficulty: 2,
+            difficulty_adjustment: None,
+            slashing: SlashingParams::default(),
+            finality_depth: default_finality_depth(),
+            checkpoint_interval: None,
+            epochs: None,
+            block_time: BlockTimeRules::default(),
+            fee_destination: FeeDestination::default(),
+            max_transaction_data: default_max_transaction_data(),
+            max_block_data: default_max_block_data(),
+            data_fee_per_byte: default_data_fee_per_byte(),
+            issuance: None,
+            emission: None,
+        }
+    }
+}
+
+impl ChainParams {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_block_transactions == 0 || self.max_block_bytes == 0 || self.max_block_gas == 0 {
+            return Err(LedgerError::InvalidParameters(
+                "Blocks must allow at least one transaction".to_string(),
+            ));
+        }
+        
+        if self.max_transaction_data > self.max_block_data {
+            return Err(LedgerError::InvalidParameters(
+                "A transaction's payload limit cannot exceed the block's".to_string(),
+            ));
+        }
+        
+        if let Some(issuance) = &self.issuance {
+            if issuance.issuer.is_empty() || issuance.public_key.is_empty() || issuance.max_mint_per_block == Some(0) {
+                return Err(LedgerError::InvalidParameters(
+                    "Issuance needs an issuer, its key and a non-zero mint limit".to_string(),
+                ));
+            }
+        }
+        
+        if let Some(emission) = &self.emission {
+            if emission.halving_interval == 0 || self.max_block_transactions < 2 {
+                return Err(LedgerError::InvalidParameters(
+                    "Emission needs a halving interval and room for a transaction beside the coinbase".to_string(),
+                ));
+            }
+        }
+        
+        // A SHA-256 hex digest only has 64 characters
+        if self.difficulty > 64 {
+            return Err(LedgerError::InvalidParameters(
+                "Difficulty cannot exceed 64".to_string(),
+            ));
+        }
+        
+        let slashing = &self.slashing;
+        if slashing.double_sign_penalty_bps > 10_000 || slashing.downtime_penalty_bps > 10_000 {
+            return Err(LedgerError::InvalidParameters(
+                "Slashing penalties cannot exceed 10000 basis points".to_string(),
+            ));
+        }
+        
+        if slashing.downtime_window == 0 {
+            return Err(LedgerError::InvalidParameters(
+                "Downtime window must be at least one block".to_string(),
+            ));
+        }
+        
+        if self.checkpoint_interval == Some(0) {
+            return Err(LedgerError::InvalidParameters(
+                "Checkpoint interval must be at least one block".to_string(),
+            ));
+        }
+        
+        if self.block_time.slot_ms == Some(0) {
+            return Err(LedgerError::InvalidParameters(
+                "Block slots must be at least a millisecond long".to_string(),
+            ));
+        }
+        
+        if self.block_time.ordering == (TimestampOrdering::MedianTimePast { window: 0 }) {
+            return Err(LedgerError::InvalidParameters(
+                "Median-time-past window must be at least one block".to_string(),
+            ));
+        }
+        
+        if let Some(schedule) = &self.epochs {
+            let empty = matches!(schedule.length, EpochLength::Blocks(0) | EpochLength::Millis(0));
+            if empty || schedule.epochs_per_era == 0 {
+                return Err(LedgerError::InvalidParameters(
+                    "Epochs and eras must have a non-zero length".to_string(),
+                ));
+            }
+        }
+        
+        if let Some(adjustment) = &self.difficulty_adjustment {
+            if adjustment.target_block_interval_ms == 0 || adjustment.window < 2 {
+                return Err(LedgerError::InvalidParameters(
+                    "Difficulty adjustment needs a target interval and a window of at least two blocks".to_string(),
+                ));
+            }
+            
+            if adjustment.min_difficulty > adjustment.max_difficulty || adjustment.max_difficulty > 64 {
+                return Err(LedgerError::InvalidParameters(
+                    "Difficulty adjustment bounds must satisfy min <= max <= 64".to_string(),
+                ));
+            }
+            
+            if !(adjustment.min_difficulty..=adjustment.max_difficulty).contains(&self.difficulty) {
+                return Err(LedgerError::InvalidParameters(
+                    "Starting difficulty must lie within the adjustment bounds".to_string(),
+                ));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    // Limits every node enforces on blocks it receives, whichever engine produced them
+    pub fn check_block_limits(&self, block: &Block) -> Result<()> {
+        if block.transactions.len() > self.max_block_transactions {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Block carries {} transactions, above the limit of {}",
+                block.transactions.len(), self.max_block_transactions,
+            )));
+        }
+        
+        let size = block.body_size();
+        if size > self.max_block_bytes {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Block body is {} bytes, above the limit of {}",
+                size, self.max_block_bytes,
+            )));
+        }
+        
+        for tx in &block.transactions {
+            self.check_data(tx).map_err(|e| {
+                LedgerError::BlockValidationFailed(format!("Transaction {}: {}", tx.id, e))
+            })?;
+        }
+        
+        let data = block.transactions.iter().map(|tx| tx.data.len()).sum::<usize>();
+        if data > self.max_block_data {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Block carries {} bytes of payload, above the limit of {}",
+                data, self.max_block_data,
+            )));
+        }
+        
+        let gas = block.transactions.iter().fold(0u64, |gas, tx| gas.saturating_add(tx.gas_limit()));
+        if gas > self.max_block_gas {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Block calls may burn {} gas, above the limit of {}",
+                gas, self.max_block_gas,
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    // Under an emission schedule, the block opens with its only coinbase, paying exactly the reward and, unless fees
+    // are burned, the other transactions' fees; without one, blocks carry no coinbase
+    pub fn check_coinbase(&self, block: &Block) -> Result<()> {
+        let coinbases = block.transactions.iter()
+            .filter(|tx| matches!(tx.kind, TransactionKind::Coinbase { .. }))
+            .count();
+        if self.emission.is_none() {
+            if coinbases > 0 {
+                return Err(LedgerError::BlockValidationFailed(
+                    "Blocks carry no coinbase without an emission schedule".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+        
+        let first = block.transactions.first().filter(|tx| matches!(tx.kind, TransactionKind::Coinbase { .. }));
+        let (Some(coinbase), 1) = (first, coinbases) else {
+            return Err(LedgerError::BlockValidationFailed(
+                "Blocks must open with exactly one coinbase".to_string(),
+            ));
+        };
+        
+        let height = block.header.height;
+        if coinbase.kind != (TransactionKind::Coinbase { height }) {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Coinbase {} is not for height {}",
+                coinbase.id, height,
+            )));
+        }
+        
+        let expected = self.coinbase_amount(height, &block.transactions[1..]);
+        if coinbase.amount != expected {
+            return Err(LedgerError::BlockValidationFailed(format!(
+                "Coinbase {} pays {}, not the {} due at height {}",
+                coinbase.id, coinbase.amount, expected, height,
+            )));
+        }
+        Ok(())
+    }
+    
+    // What the coinbase of a block at `height` carrying `transactions` pays
+    pub fn coinbase_amount(&self, height: u64, transactions: &[Transaction]) -> u64 {
+        let reward = self.emission.map(|emission| emission.reward_at(height)).unwrap_or(0);
+        if self.fee_destination == FeeDestination::Burn {
+            return reward;
+        }
+        transactions.iter().fold(reward, |total, tx| total.saturating_add(tx.fee))
+    }
+    
+    // Payload bounds and pricing for one transaction
+    pub fn check_data(&self, tx: &Transaction) -> std::result::Result<(), String> {
+        if tx.data.len() > self.max_transaction_data {
+            return Err(format!(
+                "Payload is {} bytes, above the limit of {}",
+                tx.data.len(), self.max_transaction_data,
+            ));
+        }
+        
+        let minimum = (tx.data.len() as u64).saturating_mul(self.data_fee_per_byte);
+        if tx.fee < minimum {
+            return Err(format!("A {}-byte payload needs a fee of at least {}", tx.data.len(), minimum));
+        }
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,158 @@
+use std::collections::VecDeque;
+use std::sync::RwLock;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+const MAX_RECORDED_VIOLATIONS: usize = 32;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Violation {
+    InvalidBlock,
+    BadSignature,
+    OversizedMessage,
+}
+
+#[derive(Debug, Clone)]
+pub struct PeerPolicy {
+    // Misbehaviour score at which a peer is banned
+    pub ban_threshold: u32,
+    pub ban_duration: Duration,
+    pub invalid_block_penalty: u32,
+    pub bad_signature_penalty: u32,
+    pub oversized_message_penalty: u32,
+}
+
+impl Default for PeerPolicy {
+    fn default() -> Self {
+        Self {
+            ban_threshold: 100,
+            ban_duration: Duration::from_secs(600),
+            invalid_block_penalty: 50,
+            bad_signature_penalty: 100,
+            oversized_message_penalty: 25,
+        }
+    }
+}
+
+impl PeerPolicy {
+    fn penalty(&self, violation: Violation) -> u32 {
+        match violation {
+            Violation::InvalidBlock => self.invalid_block_penalty,
+            Violation::BadSignature => self.bad_signature_penalty,
+            Violation::OversizedMessage => self.oversized_message_penalty,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ViolationReport {
+    pub at: DateTime<Utc>,
+    pub violation: Violation,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PeerRecord {
+    pub peer: String,
+    pub score: u32,
+    // Most recent first
+    pub violations: VecDeque<ViolationReport>,
+    pub banned_until: Option<DateTime<Utc>>,
+    pub ban_reason: Option<String>,
+}
+
+impl PeerRecord {
+    fn new(peer: &str) -> Self {
+        Self {
+            peer: peer.to_string(),
+            score: 0,
+            violations: VecDeque::new(),
+            banned_until: None,
+            ban_reason: None,
+        }
+    }
+    
+    pub fn is_banned(&self) -> bool {
+        self.banned_until.is_some_and(|until| Utc::now() < until)
+    }
+}
+
+// Misbehaviour scores for the peers blocks arrive from, kept by `import_block_from`, which the simulated network
+// delivers every block through, and banned or unbanned by hand through `AdminApi`. Transports should drop banned peers
+#[derive(Debug, Default)]
+pub struct PeerRegistry {
+    policy: RwLock<PeerPolicy>,
+    peers: DashMap<String, PeerRecord>,
+}
+
+impl PeerRegistry {
+    pub fn new(policy: PeerPolicy) -> Self {
+        Self {
+            policy: RwLock::new(policy),
+            peers: DashMap::new(),
+        }
+    }
+    
+    pub fn set_policy(&self, policy: PeerPolicy) {
+        *self.policy.write().unwrap() = policy;
+    }
+    
+    pub fn is_banned(&self, peer: &str) -> bool {
+        self.peers.get(peer).is_some_and(|record| record.is_banned())
+    }
+    
+    pub fn peer(&self, peer: &str) -> Option<PeerRecord> {
+        self.peers.get(peer).map(|record| record.clone())
+    }
+    
+    pub fn list(&self) -> Vec<PeerRecord> {
+        let mut peers: Vec<PeerRecord> = self.peers.iter().map(|record| record.clone()).collect();
+        peers.sort_by(|a, b| a.peer.cmp(&b.peer));
+        peers
+    }
+    
+    // Returns true when this violation gets the peer banned
+    pub fn report(&self, peer: &str, violation: Violation, detail: &str) -> bool {
+        let policy = self.policy.read().unwrap().clone();
+        let mut record = self.peers.entry(peer.to_string()).or_insert_with(|| PeerRecord::new(peer));
+        
+        record.violations.push_front(ViolationReport {
+            at: Utc::now(),
+            violation,
+            detail: detail.to_string(),
+        });
+        record.violations.truncate(MAX_RECORDED_VIOLATIONS);
+        record.score = record.score.saturating_add(policy.penalty(violation));
+        
+        if record.score < policy.ban_threshold || record.is_banned() {
+            return false;
+        }
+        
+        warn!("Banning peer {} after {:?}: {}", peer, violation, detail);
+        record.score = 0;
+        record.banned_until = Some(Utc::now() + policy.ban_duration);
+        record.ban_reason = Some(format!("{:?}: {}", violation, detail));
+        true
+    }
+    
+    pub fn ban(&self, peer: &str, duration: Duration, reason: &str) {
+        let mut record = self.peers.entry(peer.to_string()).or_insert_with(|| PeerRecord::new(peer));
+        record.banned_until = Some(Utc::now() + duration);
+        record.ban_reason = Some(reason.to_string());
+    }
+    
+    // Lifts a ban and clears the score; the violation history is kept
+    pub fn unban(&self, peer: &str) -> bool {
+        let Some(mut record) = self.peers.get_mut(peer) else {
+            return false;
+        };
+        
+        record.score = 0;
+        record.banned_until = None;
+        record.ban_reason = None;
+        true
+    }
+}
\ No newline at end of file
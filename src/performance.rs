@@ -1,7 +1,17 @@
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
-use std::collections::VecDeque;
-use tokio::sync::RwLock;
+use arc_swap::ArcSwap;
+
+// Batches the average batch time is taken over
+const BATCH_WINDOW: usize = 100;
+
+// Longest latency told apart from longer ones; anything slower is counted as this
+const MAX_TRACKED_LATENCY: Duration = Duration::from_secs(3_600);
+
+// Latencies below this many microseconds get a bucket each; above it, every doubling is split into this many
+// buckets over two, so a percentile is read off within 1% of the true value
+const SUB_BUCKETS: u64 = 256;
 
 #[derive(Debug, Clone)]
 pub struct PerformanceStats {
@@ -9,45 +19,172 @@ pub struct PerformanceStats {
     pub transactions_per_second: f64,
     pub average_batch_time: Duration,
     pub peak_tps: f64,
+    // Blocks handed to the mining pool and not yet sealed
+    pub mining_queue_depth: usize,
+    // Hashes tried per second of mining, over every block mined with proof of work
+    pub hash_rate: f64,
+    // From admission to the block that included it, over every transaction produced here
+    pub inclusion_latency: LatencyPercentiles,
+    // The same, over the transactions of the latest block produced
+    pub batch_latency: LatencyPercentiles,
+}
+
+// A latency distribution; percentiles are the upper bound of the bucket they fall in, the maximum is exact
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LatencyPercentiles {
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+// Log-linear buckets of microseconds that any number of threads record into without waiting on each other
+struct LatencyHistogram {
+    buckets: Vec<AtomicU64>,
+    max: AtomicU64,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        let buckets = Self::bucket(MAX_TRACKED_LATENCY.as_micros() as u64) + 1;
+        Self {
+            buckets: (0..buckets).map(|_| AtomicU64::new(0)).collect(),
+            max: AtomicU64::new(0),
+        }
+    }
+    
+    fn bucket(micros: u64) -> usize {
+        if micros < SUB_BUCKETS {
+            return micros as usize;
+        }
+        // How many times `micros` is halved to fall in the top half of the linear range
+        let shift = (u64::BITS - micros.leading_zeros()) - SUB_BUCKETS.trailing_zeros();
+        let half = SUB_BUCKETS / 2;
+        (SUB_BUCKETS + (shift as u64 - 1) * half + ((micros >> shift) - half)) as usize
+    }
+    
+    // The largest latency that falls in `bucket`
+    fn upper_bound(bucket: usize) -> u64 {
+        let bucket = bucket as u64;
+        if bucket < SUB_BUCKETS {
+            return bucket;
+        }
+        let half = SUB_BUCKETS / 2;
+        let shift = (bucket - SUB_BUCKETS) / half + 1;
+        let sub = (bucket - SUB_BUCKETS) % half + half;
+        ((sub + 1) << shift) - 1
+    }
+    
+    // Returns the latency as counted, in microseconds
+    fn record(&self, latency: Duration) -> u64 {
+        let micros = latency.min(MAX_TRACKED_LATENCY).as_micros() as u64;
+        self.buckets[Self::bucket(micros)].fetch_add(1, Ordering::Relaxed);
+        self.max.fetch_max(micros, Ordering::Relaxed);
+        micros
+    }
+    
+    // Counts keep arriving while they are summed, so a read taken mid-batch may count part of it
+    fn percentiles(&self) -> LatencyPercentiles {
+        let counts: Vec<u64> = self.buckets.iter().map(|bucket| bucket.load(Ordering::Relaxed)).collect();
+        let total: u64 = counts.iter().sum();
+        let max = self.max.load(Ordering::Relaxed);
+        let at = |quantile: f64| {
+            let rank = ((quantile * total as f64).ceil() as u64).max(1);
+            let mut seen = 0;
+            let bucket = counts.iter().position(|count| {
+                seen += count;
+                seen >= rank
+            });
+            Duration::from_micros(bucket.map_or(0, Self::upper_bound).min(max))
+        };
+        LatencyPercentiles { p50: at(0.5), p95: at(0.95), p99: at(0.99), max: Duration::from_micros(max) }
+    }
+    
+    // What `percentiles` would read off a histogram holding just these latencies, without building one
+    fn percentiles_of(mut micros: Vec<u64>) -> LatencyPercentiles {
+        micros.sort_unstable();
+        let max = micros.last().copied().unwrap_or(0);
+        let at = |quantile: f64| {
+            let rank = ((quantile * micros.len() as f64).ceil() as usize).max(1);
+            let bucket = micros.get(rank - 1).map(|&latency| Self::bucket(latency));
+            Duration::from_micros(bucket.map_or(0, Self::upper_bound).min(max))
+        };
+        LatencyPercentiles { p50: at(0.5), p95: at(0.95), p99: at(0.99), max: Duration::from_micros(max) }
+    }
 }
 
+// Held while a batch waits to be mined or its block is mined; dropping it, even on a panic or a cancelled producer, lowers the gauge again
+pub struct MiningGuard(Arc<PerformanceMonitor>);
+
+impl Drop for MiningGuard {
+    fn drop(&mut self) {
+        self.0.mining.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// Recording never waits and reading never blocks, so both are safe from any thread or runtime
 pub struct PerformanceMonitor {
-    stats: Arc<RwLock<PerformanceData>>,
+    started: Instant,
+    total_transactions: AtomicU64,
+    // Bit pattern of an f64; for non-negative floats it orders the same way as the value
+    peak_tps: AtomicU64,
+    // The last `BATCH_WINDOW` batch times in nanoseconds, written round-robin
+    batch_times: Vec<AtomicU64>,
+    batches: AtomicUsize,
+    mining: AtomicUsize,
+    hashes: AtomicU64,
+    mining_nanos: AtomicU64,
+    inclusion_latency: LatencyHistogram,
+    batch_latency: ArcSwap<LatencyPercentiles>,
 }
 
-struct PerformanceData {
-    total_transactions: u64,
-    batch_times: VecDeque<Duration>,
-    batch_sizes: VecDeque<usize>,
-    peak_tps: f64,
-    last_reset: Instant,
+impl Default for PerformanceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PerformanceMonitor {
     pub fn new() -> Self {
         Self {
-            stats: Arc::new(RwLock::new(PerformanceData {
-                total_transactions: 0,
-                batch_times: VecDeque::new(),
-                batch_sizes: VecDeque::new(),
-                peak_tps: 0.0,
-                last_reset: Instant::now(),
-            })),
+            started: Instant::now(),
+            total_transactions: AtomicU64::new(0),
+            peak_tps: AtomicU64::new(0f64.to_bits()),
+            batch_times: (0..BATCH_WINDOW).map(|_| AtomicU64::new(0)).collect(),
+            batches: AtomicUsize::new(0),
+            mining: AtomicUsize::new(0),
+            hashes: AtomicU64::new(0),
+            mining_nanos: AtomicU64::new(0),
+            inclusion_latency: LatencyHistogram::new(),
+            batch_latency: ArcSwap::from_pointee(LatencyPercentiles::default()),
         }
     }
     
-    pub async fn record_batch(&self, batch_size: usize, processing_time: Duration) {
-        let mut data = self.stats.write().await;
-        
-        data.total_transactions += batch_size as u64;
-        data.batch_times.push_back(processing_time);
-        data.batch_sizes.push_back(batch_size);
-        
-        // Keep only last 100 batches for rolling average
-        if data.batch_times.len() > 100 {
-            data.batch_times.pop_front();
-            data.batch_sizes.pop_front();
-        }
+    // Counts a block in `mining_queue_depth` until the returned guard is dropped
+    pub fn mining_started(self: &Arc<Self>) -> MiningGuard {
+        self.mining.fetch_add(1, Ordering::Relaxed);
+        MiningGuard(Arc::clone(self))
+    }
+    
+    pub fn mining_queue_depth(&self) -> usize {
+        self.mining.load(Ordering::Relaxed)
+    }
+    
+    pub fn record_mining(&self, hashes: u64, mining_time: Duration) {
+        self.hashes.fetch_add(hashes, Ordering::Relaxed);
+        self.mining_nanos.fetch_add(mining_time.as_nanos() as u64, Ordering::Relaxed);
+    }
+    
+    // How long each transaction of a freshly produced block waited for it
+    pub fn record_inclusions(&self, latencies: impl IntoIterator<Item = Duration>) {
+        let batch: Vec<u64> = latencies.into_iter().map(|latency| self.inclusion_latency.record(latency)).collect();
+        self.batch_latency.store(Arc::new(LatencyHistogram::percentiles_of(batch)));
+    }
+    
+    pub fn record_batch(&self, batch_size: usize, processing_time: Duration) {
+        self.total_transactions.fetch_add(batch_size as u64, Ordering::Relaxed);
+        let slot = self.batches.fetch_add(1, Ordering::Relaxed) % BATCH_WINDOW;
+        self.batch_times[slot].store(processing_time.as_nanos() as u64, Ordering::Relaxed);
         
         // Calculate current TPS
         let current_tps = if processing_time.as_secs_f64() > 0.0 {
@@ -56,36 +193,42 @@ impl PerformanceMonitor {
             0.0
         };
         
-        if current_tps > data.peak_tps {
-            data.peak_tps = current_tps;
-        }
+        self.peak_tps.fetch_max(current_tps.to_bits(), Ordering::Relaxed);
     }
     
     pub fn get_stats(&self) -> PerformanceStats {
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                let data = self.stats.read().await;
-                
-                let total_time = data.last_reset.elapsed();
-                let overall_tps = if total_time.as_secs_f64() > 0.0 {
-                    data.total_transactions as f64 / total_time.as_secs_f64()
-                } else {
-                    0.0
-                };
-                
-                let avg_batch_time = if !data.batch_times.is_empty() {
-                    data.batch_times.iter().sum::<Duration>() / data.batch_times.len() as u32
-                } else {
-                    Duration::from_millis(0)
-                };
-                
-                PerformanceStats {
-                    total_transactions: data.total_transactions,
-                    transactions_per_second: overall_tps,
-                    average_batch_time: avg_batch_time,
-                    peak_tps: data.peak_tps,
-                }
-            })
-        })
+        let total_transactions = self.total_transactions.load(Ordering::Relaxed);
+        let total_time = self.started.elapsed();
+        let overall_tps = if total_time.as_secs_f64() > 0.0 {
+            total_transactions as f64 / total_time.as_secs_f64()
+        } else {
+            0.0
+        };
+        
+        let window = self.batches.load(Ordering::Relaxed).min(BATCH_WINDOW);
+        let avg_batch_time = if window > 0 {
+            let nanos: u64 = self.batch_times[..window].iter().map(|time| time.load(Ordering::Relaxed)).sum();
+            Duration::from_nanos(nanos / window as u64)
+        } else {
+            Duration::from_millis(0)
+        };
+        
+        let mining_time = Duration::from_nanos(self.mining_nanos.load(Ordering::Relaxed));
+        let hash_rate = if mining_time.as_secs_f64() > 0.0 {
+            self.hashes.load(Ordering::Relaxed) as f64 / mining_time.as_secs_f64()
+        } else {
+            0.0
+        };
+        
+        PerformanceStats {
+            total_transactions,
+            transactions_per_second: overall_tps,
+            average_batch_time: avg_batch_time,
+            peak_tps: f64::from_bits(self.peak_tps.load(Ordering::Relaxed)),
+            mining_queue_depth: self.mining_queue_depth(),
+            hash_rate,
+            inclusion_latency: self.inclusion_latency.percentiles(),
+            batch_latency: **self.batch_latency.load(),
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,135 @@
+use std::sync::RwLock;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::consensus::{self, Consensus};
+use crate::keys::Keypair;
+use crate::params::ChainParams;
+use crate::{Block, LedgerError, Result};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PoaConfig {
+    // Public keys of the authorities, in proposing order
+    pub validators: Vec<String>,
+    // How long an authority has to produce its block before the next one may take the slot
+    #[serde(default)]
+    pub round_timeout_ms: Option<u64>,
+}
+
+impl PoaConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.validators.is_empty() {
+            return Err(LedgerError::InvalidParameters(
+                "Proof-of-authority needs at least one validator".to_string(),
+            ));
+        }
+        
+        if self.round_timeout_ms == Some(0) {
+            return Err(LedgerError::InvalidParameters(
+                "Round timeout must be greater than zero".to_string(),
+            ));
+        }
+        
+        let mut seen = std::collections::HashSet::new();
+        if !self.validators.iter().all(|key| seen.insert(key)) {
+            return Err(LedgerError::InvalidParameters(
+                "Validator set contains duplicate keys".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+}
+
+pub struct ProofOfAuthority {
+    config: RwLock<PoaConfig>,
+    // Keys this node can sign with; a single-node test network may hold every authority's key
+    local_keys: Vec<Keypair>,
+}
+
+impl ProofOfAuthority {
+    pub fn new(config: PoaConfig, local_keys: Vec<Keypair>) -> Result<Self> {
+        config.validate()?;
+        
+        Ok(Self {
+            config: RwLock::new(config),
+            local_keys,
+        })
+    }
+    
+    pub fn validators(&self) -> Vec<String> {
+        self.config.read().unwrap().validators.clone()
+    }
+    
+    // Swaps in a new validator set; takes effect from the next block
+    pub fn reload(&self, config: PoaConfig) -> Result<()> {
+        config.validate()?;
+        info!("Reloaded proof-of-authority validator set ({} validators)", config.validators.len());
+        *self.config.write().unwrap() = config;
+        Ok(())
+    }
+    
+    pub fn proposer_for(&self, height: u64) -> String {
+        self.proposer_for_round(height, 0)
+    }
+    
+    // Each timed-out round hands the slot to the next authority in order
+    pub fn proposer_for_round(&self, height: u64, round: u64) -> String {
+        let config = self.config.read().unwrap();
+        let index = ((height + round) % config.validators.len() as u64) as usize;
+        config.validators[index].clone()
+    }
+    
+    fn round_at(&self, parent: &Block, now: DateTime<Utc>) -> u64 {
+        consensus::round_at(parent, now, self.config.read().unwrap().round_timeout_ms)
+    }
+    
+    fn local_key_for(&self, height: u64, round: u64) -> Option<&Keypair> {
+        let proposer = self.proposer_for_round(height, round);
+        self.local_keys.iter().find(|key| key.public_key() == proposer)
+    }
+}
+
+impl Consensus for ProofOfAuthority {
+    fn name(&self) -> &'static str {
+        "proof-of-authority"
+    }
+    
+    fn can_propose(&self, parent: &Block) -> bool {
+        let round = self.round_at(parent, crate::clock::now());
+        self.local_key_for(parent.header.height + 1, round).is_some()
+    }
+    
+    fn propose(&self, parent: &Block, mut block: Block, _params: &ChainParams) -> Result<Block> {
+        let height = parent.header.height + 1;
+        block.header.round = self.round_at(parent, block.header.timestamp);
+        
+        let keypair = self.local_key_for(height, block.header.round).ok_or_else(|| {
+            LedgerError::Consensus(format!("Not the scheduled proposer for height {}", height))
+        })?;
+        
+        block.sign(keypair);
+        Ok(block)
+    }
+    
+    fn validate(&self, block: &Block, parent: &Block, _params: &ChainParams) -> Result<()> {
+        block.validate(Some(parent))?;
+        consensus::check_round(block, parent, self.config.read().unwrap().round_timeout_ms)?;
+        
+        let expected = self.proposer_for_round(block.header.height, block.header.round);
+        if block.header.producer.as_ref() != Some(&expected) {
+            return Err(LedgerError::BlockValidationFailed(
+                "Block was not produced by the scheduled authority".to_string(),
+            ));
+        }
+        
+        if !block.verify_producer_signature() {
+            return Err(LedgerError::BlockValidationFailed(
+                "Invalid block producer signature".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+}
\ No newline at end of file
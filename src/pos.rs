@@ -0,0 +1,156 @@
+use std::sync::{Arc, OnceLock};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::consensus::{self, Consensus};
+use crate::keys::{self, Keypair};
+use crate::params::ChainParams;
+use crate::staking::{StakeRegistry, Validator};
+use crate::{Block, LedgerError, Result};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum Election {
+    // Seeded by the parent hash, which the parent's producer can grind by reordering transactions
+    #[default]
+    ParentHash,
+    // Seeded by a VRF output chained from block to block and proven against the producer's VRF key
+    Vrf,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PosConfig {
+    // Minted for each block and shared across the producer's bonds
+    pub block_reward: u64,
+    #[serde(default)]
+    pub election: Election,
+    // How long an elected validator has to produce before the slot is drawn again
+    #[serde(default)]
+    pub round_timeout_ms: Option<u64>,
+}
+
+pub struct ProofOfStake {
+    config: PosConfig,
+    local_keys: Vec<Keypair>,
+    stakes: OnceLock<Arc<StakeRegistry>>,
+}
+
+impl ProofOfStake {
+    pub fn new(config: PosConfig, local_keys: Vec<Keypair>) -> Self {
+        Self {
+            config,
+            local_keys,
+            stakes: OnceLock::new(),
+        }
+    }
+    
+    // Stake-weighted draw from a seed every node derives the same way from the parent
+    pub fn proposer_for(&self, parent: &Block) -> Option<Validator> {
+        self.proposer_for_round(parent, 0)
+    }
+    
+    // Later rounds redraw from the same seed, so a timed-out validator's slot passes on by stake too
+    pub fn proposer_for_round(&self, parent: &Block, round: u64) -> Option<Validator> {
+        let stakes = self.stakes.get()?;
+        let source = match self.config.election {
+            Election::Vrf => parent.header.vrf_output.as_ref().unwrap_or(&parent.header.hash),
+            Election::ParentHash => &parent.header.hash,
+        };
+        let digest = match round {
+            0 => Sha256::digest(source.as_bytes()),
+            round => Sha256::digest(format!("{}:{}", source, round).as_bytes()),
+        };
+        let seed = u64::from_le_bytes(digest[..8].try_into().unwrap());
+        stakes.select(seed, parent.header.height + 1)
+    }
+    
+    // Chains each output to the last so no producer can steer the next election
+    fn vrf_input(parent: &Block) -> Vec<u8> {
+        let previous = parent.header.vrf_output.as_ref().unwrap_or(&parent.header.hash);
+        format!("{}:{}", previous, parent.header.height + 1).into_bytes()
+    }
+    
+    fn local_key_for(&self, parent: &Block, round: u64) -> Option<&Keypair> {
+        let proposer = self.proposer_for_round(parent, round)?;
+        self.local_keys.iter().find(|key| key.public_key() == proposer.public_key)
+    }
+}
+
+impl Consensus for ProofOfStake {
+    fn name(&self) -> &'static str {
+        "proof-of-stake"
+    }
+    
+    fn attach_stakes(&self, stakes: Arc<StakeRegistry>) {
+        let _ = self.stakes.set(stakes);
+    }
+    
+    fn can_propose(&self, parent: &Block) -> bool {
+        let round = consensus::round_at(parent, crate::clock::now(), self.config.round_timeout_ms);
+        self.local_key_for(parent, round).is_some()
+    }
+    
+    fn propose(&self, parent: &Block, mut block: Block, _params: &ChainParams) -> Result<Block> {
+        block.header.round = consensus::round_at(parent, block.header.timestamp, self.config.round_timeout_ms);
+        
+        let keypair = self.local_key_for(parent, block.header.round).ok_or_else(|| {
+            LedgerError::Consensus(format!("Not the elected proposer for height {}", parent.header.height + 1))
+        })?;
+        
+        if self.config.election == Election::Vrf {
+            let (output, proof) = keypair.vrf_prove(&Self::vrf_input(parent));
+            block.header.vrf_output = Some(output);
+            block.header.vrf_proof = Some(proof);
+        }
+        
+        block.sign(keypair);
+        Ok(block)
+    }
+    
+    fn validate(&self, block: &Block, parent: &Block, _params: &ChainParams) -> Result<()> {
+        block.validate(Some(parent))?;
+        consensus::check_round(block, parent, self.config.round_timeout_ms)?;
+        
+        let expected = self.proposer_for_round(parent, block.header.round).ok_or_else(|| {
+            LedgerError::Consensus("No validator has stake bonded".to_string())
+        })?;
+        if block.header.producer.as_ref() != Some(&expected.public_key) {
+            return Err(LedgerError::BlockValidationFailed(
+                "Block was not produced by the elected validator".to_string(),
+            ));
+        }
+        
+        if !block.verify_producer_signature() {
+            return Err(LedgerError::BlockValidationFailed(
+                "Invalid block producer signature".to_string(),
+            ));
+        }
+        
+        if self.config.election == Election::Vrf {
+            let vrf_key = expected.vrf_key.as_ref().ok_or_else(|| {
+                LedgerError::Consensus(format!("Validator {} has no registered VRF key", expected.address))
+            })?;
+            
+            let proven = match (&block.header.vrf_output, &block.header.vrf_proof) {
+                (Some(output), Some(proof)) => keys::verify_vrf(vrf_key, &Self::vrf_input(parent), output, proof),
+                _ => false,
+            };
+            if !proven {
+                return Err(LedgerError::BlockValidationFailed(
+                    "Missing or invalid VRF proof".to_string(),
+                ));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    fn block_rewards(&self, block: &Block) -> Vec<(String, u64)> {
+        let (Some(stakes), Some(producer)) = (self.stakes.get(), &block.header.producer) else {
+            return Vec::new();
+        };
+        
+        stakes.validator_by_key(producer)
+            .map(|validator| stakes.reward_shares(&validator.address, self.config.block_reward))
+            .unwrap_or_default()
+    }
+}
\ No newline at end of file
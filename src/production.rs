@@ -0,0 +1,100 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::{LedgerError, Result};
+
+// When the background processor turns pending transactions into a block; any trigger that fires produces one
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProductionPolicy {
+    // Produce once this many transactions are waiting
+    pub batch_threshold: Option<usize>,
+    // Produce once this long has passed since the last block, if anything is waiting
+    pub max_interval: Option<Duration>,
+    // Produce every block slot even with nothing waiting, rather than skip empty slots and leave the gap to the next
+    // block's `skipped_slots`; only has an effect on chains with a `slot_ms`
+    #[serde(default)]
+    pub produce_empty: bool,
+}
+
+impl Default for ProductionPolicy {
+    // Produce on every poll that finds work, as the processor always has
+    fn default() -> Self {
+        Self {
+            batch_threshold: Some(1),
+            max_interval: None,
+            produce_empty: false,
+        }
+    }
+}
+
+impl ProductionPolicy {
+    // Leaves block production to explicit `produce_block_now` calls
+    pub fn manual() -> Self {
+        Self {
+            batch_threshold: None,
+            max_interval: None,
+            produce_empty: false,
+        }
+    }
+    
+    pub fn validate(&self) -> Result<()> {
+        if self.batch_threshold == Some(0) || self.max_interval == Some(Duration::ZERO) {
+            return Err(LedgerError::InvalidParameters(
+                "Production triggers must be positive; use None to disable one".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    pub fn should_produce(&self, pending: usize, since_last_block: Duration, slot: Option<Duration>) -> bool {
+        if pending == 0 {
+            return self.produce_empty && slot.is_some_and(|slot| since_last_block >= slot);
+        }
+        
+        let by_count = self.batch_threshold.is_some_and(|threshold| pending >= threshold);
+        let by_time = self.max_interval.is_some_and(|interval| since_last_block >= interval);
+        by_count || by_time
+    }
+}
+
+// How the background processor sizes batches: doubled while a full batch is still waiting after a block, up to
+// `max_batch`, and halved whenever producing a block took longer than `target_latency`. A batch size set through
+// the admin API holds until a config is set again
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProcessorConfig {
+    pub target_latency: Duration,
+    pub max_batch: usize,
+}
+
+impl Default for ProcessorConfig {
+    fn default() -> Self {
+        Self {
+            target_latency: Duration::from_millis(500),
+            max_batch: 5_000,
+        }
+    }
+}
+
+impl ProcessorConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_batch == 0 || self.target_latency == Duration::ZERO {
+            return Err(LedgerError::InvalidParameters(
+                "Batches and their latency target must be positive".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    // The batch after one of `current` took `latency` to produce and left `backlog` transactions waiting
+    pub fn next_batch_size(&self, current: usize, latency: Duration, backlog: usize) -> usize {
+        if latency > self.target_latency {
+            return (current / 2).clamp(1, self.max_batch);
+        }
+        match backlog >= current {
+            true => current.saturating_mul(2).min(self.max_batch),
+            false => current.min(self.max_batch),
+        }
+    }
+}
\ No newline at end of file
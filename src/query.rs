@@ -0,0 +1,150 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use crate::analytics::BalanceSummary;
+use crate::{Block, DistributedLedger, LedgerError, Result};
+
+// Blocks copied per lock acquisition; small enough that block production never waits long
+const SCAN_CHUNK: usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct QueryBudget {
+    pub max_duration: Duration,
+    pub max_blocks: Option<usize>,
+    // Scans stop at the finalized height unless this is set
+    pub include_unfinalized: bool,
+}
+
+impl Default for QueryBudget {
+    fn default() -> Self {
+        Self {
+            max_duration: Duration::from_secs(5),
+            max_blocks: None,
+            include_unfinalized: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+    
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+pub struct QueryExecutor {
+    ledger: DistributedLedger,
+    permits: Arc<Semaphore>,
+}
+
+impl QueryExecutor {
+    pub fn new(ledger: DistributedLedger, max_concurrent_queries: usize) -> Self {
+        Self {
+            ledger,
+            permits: Arc::new(Semaphore::new(max_concurrent_queries.max(1))),
+        }
+    }
+    
+    // Kept up to date as blocks apply, so unlike scans these take no permit
+    pub fn rich_list(&self, n: usize) -> Vec<(String, u64)> {
+        self.ledger.rich_list(n)
+    }
+    
+    pub fn balance_summary(&self) -> BalanceSummary {
+        self.ledger.balance_summary()
+    }
+    
+    // Folds over blocks in `from_height..=to_height` (or up to the tip) on a blocking thread
+    pub async fn scan<T, F>(
+        &self,
+        from_height: u64,
+        to_height: Option<u64>,
+        budget: QueryBudget,
+        cancel: CancellationToken,
+        init: T,
+        mut fold: F,
+    ) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnMut(T, u64, &Block) -> T + Send + 'static,
+    {
+        let permit = Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .map_err(anyhow::Error::from)?;
+        let ledger = self.ledger.clone();
+        
+        let to_height = match budget.include_unfinalized {
+            true => to_height,
+            false => {
+                let finalized = ledger.finalized_height().await;
+                Some(to_height.map_or(finalized, |to| to.min(finalized)))
+            }
+        };
+        if to_height.is_some_and(|to| to < from_height) {
+            return Ok(init);
+        }
+        
+        let handle = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let started = Instant::now();
+            let mut acc = init;
+            let mut scanned = 0usize;
+            let mut height = from_height;
+            
+            loop {
+                let remaining = to_height.map(|to| (to + 1).saturating_sub(height) as usize);
+                let chunk_size = remaining.map_or(SCAN_CHUNK, |r| r.min(SCAN_CHUNK));
+                if chunk_size == 0 {
+                    break;
+                }
+                
+                let chunk = ledger.read_blocks_blocking(height as usize, chunk_size);
+                if chunk.is_empty() {
+                    break;
+                }
+                
+                for block in &chunk {
+                    if cancel.is_cancelled() {
+                        return Err(LedgerError::QueryAborted("Query was cancelled".to_string()));
+                    }
+                    
+                    if started.elapsed() > budget.max_duration {
+                        return Err(LedgerError::QueryAborted(format!(
+                            "Time budget of {:?} exhausted after {} blocks",
+                            budget.max_duration,
+                            scanned,
+                        )));
+                    }
+                    
+                    if budget.max_blocks.is_some_and(|max| scanned >= max) {
+                        return Err(LedgerError::QueryAborted(format!(
+                            "Block budget of {} exhausted",
+                            scanned,
+                        )));
+                    }
+                    
+                    acc = fold(acc, height, block);
+                    scanned += 1;
+                    height += 1;
+                }
+            }
+            
+            Ok(acc)
+        });
+        
+        handle.await.map_err(anyhow::Error::from)?
+    }
+}
\ No newline at end of file
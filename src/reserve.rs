@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::keys::{self, Keypair};
+use crate::state::{verify_balance_proof, BalanceProof};
+use crate::BlockHeader;
+
+// A custodian's signed statement of what its accounts held, each balance proven against the state root of a
+// finalized header; balances are as the block before `header` left them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReserveReport {
+    pub chain_id: String,
+    pub header: BlockHeader,
+    pub proofs: Vec<BalanceProof>,
+    pub total: u64,
+    pub generated_at: DateTime<Utc>,
+    // Public key of whoever vouches for the accounts
+    pub signer: String,
+    pub signature: String,
+}
+
+impl ReserveReport {
+    pub(crate) fn new(chain_id: &str, header: BlockHeader, proofs: Vec<BalanceProof>, keypair: &Keypair) -> Self {
+        let mut report = Self {
+            chain_id: chain_id.to_string(),
+            header,
+            total: proofs.iter().map(|proof| proof.balance).sum(),
+            proofs,
+            generated_at: crate::clock::now(),
+            signer: keypair.public_key(),
+            signature: String::new(),
+        };
+        report.signature = keypair.sign(&report.digest());
+        report
+    }
+    
+    // Everything the signature covers; the proofs themselves are checked against the header instead
+    fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.chain_id.as_bytes());
+        hasher.update(self.header.hash.as_bytes());
+        for proof in &self.proofs {
+            hasher.update(proof.address.as_bytes());
+            hasher.update(proof.balance.to_le_bytes());
+        }
+        hasher.update(self.total.to_le_bytes());
+        hasher.update(self.generated_at.timestamp_millis().to_le_bytes());
+        hasher.finalize().into()
+    }
+}
+
+// Whether the report is signed by its signer, adds up, and proves every balance against its header. As with balance
+// proofs the auditor must separately trust the header, e.g. by matching its hash on a node of its own
+pub fn verify_reserve_report(report: &ReserveReport) -> bool {
+    keys::verify_signature(&report.signer, &report.digest(), &report.signature)
+        && report.proofs.iter().map(|proof| proof.balance as u128).sum::<u128>() == report.total as u128
+        && report.proofs.iter().all(|proof| verify_balance_proof(&report.header, proof))
+}
\ No newline at end of file
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{LedgerError, Result};
+
+// How much of the chain a secondary index covers
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum Retention {
+    #[default]
+    Full,
+    // The latest this many blocks; older ones are dropped as the chain grows
+    Recent(u64),
+    Disabled,
+}
+
+impl Retention {
+    pub fn is_enabled(&self) -> bool {
+        *self != Retention::Disabled
+    }
+    
+    // Lowest height kept with the tip at `tip`, or None if nothing is
+    pub fn floor(&self, tip: u64) -> Option<u64> {
+        match self {
+            Retention::Full => Some(0),
+            Retention::Recent(blocks) => Some((tip + 1).saturating_sub(*blocks)),
+            Retention::Disabled => None,
+        }
+    }
+    
+    // The height that leaves the index once the block at `tip` is added
+    pub(crate) fn expired(&self, tip: u64) -> Option<u64> {
+        match self {
+            Retention::Recent(blocks) => tip.checked_sub(*blocks),
+            _ => None,
+        }
+    }
+}
+
+// Which optional indexes this node keeps, trading query reach for memory. The transaction id index is not among
+// them: it doubles as replay protection, so it always covers the whole chain
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct IndexRetention {
+    // Account histories
+    pub addresses: Retention,
+    // Memo prefix and tag search
+    pub memos: Retention,
+    // Balances as past blocks left them, behind balance lookups by height, state diffs, balance proofs and the
+    // balances shown in account histories. Blocks that could still be reorganised are kept whatever this says, and
+    // unlike the indexes, what was pruned doesn't come back when the window is widened
+    #[serde(default)]
+    pub state: Retention,
+}
+
+impl IndexRetention {
+    pub fn validate(&self) -> Result<()> {
+        if [self.addresses, self.memos, self.state].contains(&Retention::Recent(0)) {
+            return Err(LedgerError::InvalidParameters(
+                "Index windows must be positive; use Disabled to drop an index".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+}
\ No newline at end of file
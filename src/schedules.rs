@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const SCHEDULE_MODULE: &str = "native:schedule";
+
+// A recurring payment the payer registered on chain; every `interval` epochs the ledger moves `amount` to the recipient
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StandingOrder {
+    // The registering transaction's id
+    pub id: Uuid,
+    pub payer: String,
+    pub recipient: String,
+    pub amount: u64,
+    pub interval: u64,
+    pub created_at: u64,
+    // Epoch the next payment falls due
+    pub next_due: u64,
+    pub payments: u64,
+    // Installments skipped because the payer couldn't cover them
+    pub missed: u64,
+    pub cancelled_at: Option<u64>,
+}
+
+impl StandingOrder {
+    pub fn is_active(&self) -> bool {
+        self.cancelled_at.is_none()
+    }
+}
+
+// One standing order's turn at an epoch boundary, kept in the epoch record so a reorg can undo it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Installment {
+    pub order: Uuid,
+    pub paid: bool,
+    pub previous_due: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct StandingOrders {
+    orders: RwLock<BTreeMap<Uuid, StandingOrder>>,
+}
+
+impl StandingOrders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn get(&self, id: Uuid) -> Option<StandingOrder> {
+        self.orders.read().unwrap().get(&id).cloned()
+    }
+    
+    // Active orders paid by or to `address`
+    pub fn active_for(&self, address: &str) -> Vec<StandingOrder> {
+        self.orders.read().unwrap()
+            .values()
+            .filter(|order| order.is_active() && (order.payer == address || order.recipient == address))
+            .cloned()
+            .collect()
+    }
+    
+    // Active orders due by `epoch`, by id so every node pays them in the same order
+    pub fn due(&self, epoch: u64) -> Vec<StandingOrder> {
+        self.orders.read().unwrap()
+            .values()
+            .filter(|order| order.is_active() && order.next_due <= epoch)
+            .cloned()
+            .collect()
+    }
+    
+    pub(crate) fn register(&self, order: StandingOrder) {
+        self.orders.write().unwrap().insert(order.id, order);
+    }
+    
+    pub(crate) fn unregister(&self, id: Uuid) {
+        self.orders.write().unwrap().remove(&id);
+    }
+    
+    pub(crate) fn cancel(&self, id: Uuid, height: u64) {
+        if let Some(order) = self.orders.write().unwrap().get_mut(&id) {
+            order.cancelled_at = Some(height);
+        }
+    }
+    
+    pub(crate) fn reinstate(&self, id: Uuid) {
+        if let Some(order) = self.orders.write().unwrap().get_mut(&id) {
+            order.cancelled_at = None;
+        }
+    }
+    
+    pub(crate) fn settle(&self, id: Uuid, epoch: u64, paid: bool) {
+        if let Some(order) = self.orders.write().unwrap().get_mut(&id) {
+            order.next_due = epoch + order.interval;
+            match paid {
+                true => order.payments += 1,
+                false => order.missed += 1,
+            }
+        }
+    }
+    
+    pub(crate) fn unsettle(&self, installment: &Installment) {
+        if let Some(order) = self.orders.write().unwrap().get_mut(&installment.order) {
+            order.next_due = installment.previous_due;
+            match installment.paid {
+                true => order.payments -= 1,
+                false => order.missed -= 1,
+            }
+        }
+    }
+}
\ No newline at end of file
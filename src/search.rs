@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use crate::labels::{AddressLabel, LabelSet};
+use crate::{Block, Transaction};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SearchResult {
+    Block {
+        height: u64,
+        block: Block,
+    },
+    Transaction {
+        transaction: Transaction,
+        // `None` while the transaction is still pending
+        block_height: Option<u64>,
+        // Of the accounts it sends from or pays into
+        #[serde(default)]
+        labels: LabelSet,
+    },
+    Address {
+        address: String,
+        balance: u64,
+        #[serde(default)]
+        label: Option<AddressLabel>,
+    },
+}
\ No newline at end of file
@@ -0,0 +1,263 @@
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::BinaryHeap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Duration, Utc};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::clock::SIMULATED;
+use crate::consensus::Consensus;
+use crate::fork::BlockImport;
+use crate::genesis::GenesisConfig;
+use crate::search::SearchResult;
+use crate::{Block, DistributedLedger, LedgerError, Result, Transaction};
+
+pub use crate::rng::SimRng;
+
+// The virtual clock and id generator seen by code running inside the simulation
+#[derive(Debug)]
+pub struct SimEnv {
+    start: DateTime<Utc>,
+    elapsed_ms: AtomicU64,
+    rng: Mutex<SimRng>,
+}
+
+impl SimEnv {
+    pub fn now(&self) -> DateTime<Utc> {
+        self.start + Duration::milliseconds(self.elapsed_ms.load(Ordering::SeqCst) as i64)
+    }
+    
+    pub fn next_id(&self) -> Uuid {
+        let mut rng = self.rng.lock().unwrap();
+        let bytes = ((rng.next_u64() as u128) << 64) | rng.next_u64() as u128;
+        uuid::Builder::from_random_bytes(bytes.to_le_bytes()).into_uuid()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    pub seed: u64,
+    pub min_latency_ms: u64,
+    pub max_latency_ms: u64,
+    pub start_time: DateTime<Utc>,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            min_latency_ms: 10,
+            max_latency_ms: 100,
+            start_time: DateTime::UNIX_EPOCH + Duration::days(1),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Delivery {
+    at_ms: u64,
+    // Breaks ties between deliveries due at the same instant in send order
+    sequence: u64,
+    from: usize,
+    to: usize,
+    block: Block,
+}
+
+impl PartialEq for Delivery {
+    fn eq(&self, other: &Self) -> bool {
+        (self.at_ms, self.sequence) == (other.at_ms, other.sequence)
+    }
+}
+
+impl Eq for Delivery {}
+
+impl PartialOrd for Delivery {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Reversed so the heap pops the earliest delivery first
+impl Ord for Delivery {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        (other.at_ms, other.sequence).cmp(&(self.at_ms, self.sequence))
+    }
+}
+
+// In-process nodes over an in-memory network; the same seed and the same calls give the same chains
+pub struct Simulation {
+    config: SimConfig,
+    env: Arc<SimEnv>,
+    nodes: Vec<DistributedLedger>,
+    in_flight: BinaryHeap<Delivery>,
+    sent: u64,
+    // Partition group of each node; blocks only cross between nodes in the same group
+    groups: Vec<usize>,
+}
+
+impl Simulation {
+    pub fn new(config: SimConfig, genesis: GenesisConfig, engines: Vec<Arc<dyn Consensus>>) -> Result<Self> {
+        if config.min_latency_ms > config.max_latency_ms {
+            return Err(LedgerError::InvalidParameters(
+                "Minimum latency cannot exceed the maximum".to_string(),
+            ));
+        }
+        
+        let env = Arc::new(SimEnv {
+            start: config.start_time,
+            elapsed_ms: AtomicU64::new(0),
+            rng: Mutex::new(SimRng::new(config.seed)),
+        });
+        let nodes = engines.into_iter()
+            .map(|engine| DistributedLedger::with_consensus(genesis.clone(), engine))
+            .collect::<Result<Vec<_>>>()?;
+        let groups = vec![0; nodes.len()];
+        
+        Ok(Self { config, env, nodes, in_flight: BinaryHeap::new(), sent: 0, groups })
+    }
+    
+    pub fn node(&self, index: usize) -> &DistributedLedger {
+        &self.nodes[index]
+    }
+    
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    
+    pub fn now(&self) -> DateTime<Utc> {
+        self.env.now()
+    }
+    
+    // Runs `f` against the virtual clock and seeded ids, e.g. to build transactions
+    pub fn within<T>(&self, f: impl FnOnce() -> T) -> T {
+        SIMULATED.sync_scope(Arc::clone(&self.env), f)
+    }
+    
+    async fn scoped<F: Future>(&self, future: F) -> F::Output {
+        SIMULATED.scope(Arc::clone(&self.env), future).await
+    }
+    
+    pub fn advance(&self, millis: u64) {
+        self.env.elapsed_ms.fetch_add(millis, Ordering::SeqCst);
+    }
+    
+    // Splits the network; nodes left out of every group are isolated on their own
+    pub fn partition(&mut self, groups: &[&[usize]]) {
+        for (index, group) in self.groups.iter_mut().enumerate() {
+            *group = groups.iter()
+                .position(|members| members.contains(&index))
+                .unwrap_or(groups.len() + index);
+        }
+    }
+    
+    pub fn heal(&mut self) {
+        self.groups.iter_mut().for_each(|group| *group = 0);
+    }
+    
+    fn connected(&self, from: usize, to: usize) -> bool {
+        self.groups[from] == self.groups[to]
+    }
+    
+    pub async fn submit(&self, node: usize, transaction: Transaction) -> Result<()> {
+        self.scoped(self.nodes[node].add_transaction(transaction)).await
+    }
+    
+    // Has `node` produce a block from its pending transactions and gossip it
+    pub async fn produce(&mut self, node: usize) -> Result<Option<Block>> {
+        let ledger = &self.nodes[node];
+        let before = ledger.get_latest_block().await.header.hash;
+        self.scoped(ledger.produce_block_now()).await?;
+        
+        let tip = ledger.get_latest_block().await;
+        if tip.header.hash == before {
+            return Ok(None);
+        }
+        
+        self.gossip(node, tip.clone());
+        Ok(Some(tip))
+    }
+    
+    // Sends `block` from `node` to every other node, whether or not `node` holds it, as a faulty one might
+    pub fn gossip(&mut self, node: usize, block: Block) {
+        for peer in 0..self.nodes.len() {
+            if peer != node {
+                self.send(node, peer, block.clone());
+            }
+        }
+    }
+    
+    fn send(&mut self, from: usize, to: usize, block: Block) {
+        if !self.connected(from, to) {
+            return;
+        }
+        
+        let latency = self.env.rng.lock().unwrap().range(self.config.min_latency_ms, self.config.max_latency_ms);
+        self.sent += 1;
+        self.in_flight.push(Delivery {
+            at_ms: self.env.elapsed_ms.load(Ordering::SeqCst) + latency,
+            sequence: self.sent,
+            from,
+            to,
+            block,
+        });
+    }
+    
+    // Delivers the next block in flight, advancing the clock to its arrival; false once the network is quiet
+    pub async fn step(&mut self) -> Result<bool> {
+        let Some(delivery) = self.in_flight.pop() else {
+            return Ok(false);
+        };
+        self.env.elapsed_ms.fetch_max(delivery.at_ms, Ordering::SeqCst);
+        
+        // Partitions also cut off what was already on the wire
+        if !self.connected(delivery.from, delivery.to) {
+            return Ok(true);
+        }
+        
+        let receiver = &self.nodes[delivery.to];
+        let peer = format!("node-{}", delivery.from);
+        match self.scoped(receiver.import_block_from(&peer, delivery.block)).await {
+            // Ask the sender for the gap, as a syncing peer would
+            Ok(BlockImport::Orphaned { missing_ancestor }) => {
+                let sender = &self.nodes[delivery.from];
+                if let Some(SearchResult::Block { block, .. }) = sender.search(&missing_ancestor).await {
+                    self.send(delivery.from, delivery.to, block);
+                }
+            }
+            Ok(_) | Err(LedgerError::DuplicateBlock) => {}
+            Err(e) => warn!("Node {} rejected a block from node {}: {}", delivery.to, delivery.from, e),
+        }
+        
+        Ok(true)
+    }
+    
+    pub async fn run_until_idle(&mut self) -> Result<()> {
+        while self.step().await? {}
+        Ok(())
+    }
+    
+    // Delivers everything due in the next `millis`, then moves the clock to the end of that window
+    pub async fn run_for(&mut self, millis: u64) -> Result<()> {
+        let until = self.env.elapsed_ms.load(Ordering::SeqCst) + millis;
+        while self.in_flight.peek().is_some_and(|delivery| delivery.at_ms <= until) {
+            self.step().await?;
+        }
+        
+        self.env.elapsed_ms.fetch_max(until, Ordering::SeqCst);
+        Ok(())
+    }
+    
+    pub async fn converged(&self) -> bool {
+        let mut tips = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            tips.push(node.get_latest_block().await.header.hash);
+        }
+        tips.windows(2).all(|pair| pair[0] == pair[1])
+    }
+}
\ No newline at end of file
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{BlockHeader, LedgerError, Result};
+
+pub const SLASH_MODULE: &str = "native:slash";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Evidence {
+    // Headers of two different blocks at one height signed by the same producer
+    DoubleSign {
+        first: Box<BlockHeader>,
+        second: Box<BlockHeader>,
+    },
+    // A bonded validator produced none of the main-chain blocks in this range
+    Downtime {
+        from_height: u64,
+        to_height: u64,
+    },
+}
+
+impl Evidence {
+    // Checks what can be verified without chain state
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Evidence::DoubleSign { first, second } => {
+                if first.height != second.height || first.hash == second.hash {
+                    return Err(LedgerError::InvalidTransaction(
+                        "Double-sign evidence needs two different blocks at the same height".to_string(),
+                    ));
+                }
+                
+                if first.producer.is_none() || first.producer != second.producer {
+                    return Err(LedgerError::InvalidTransaction(
+                        "Double-sign evidence blocks must share a producer".to_string(),
+                    ));
+                }
+                
+                let authentic = [first, second].iter().all(|block| {
+                    block.hash == block.calculate_hash() && block.verify_producer_signature()
+                });
+                if !authentic {
+                    return Err(LedgerError::InvalidTransaction(
+                        "Double-sign evidence contains a tampered block".to_string(),
+                    ));
+                }
+            }
+            Evidence::Downtime { from_height, to_height } => {
+                if from_height > to_height {
+                    return Err(LedgerError::InvalidTransaction(
+                        "Downtime range is empty".to_string(),
+                    ));
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    pub fn reason(&self) -> SlashReason {
+        match self {
+            Evidence::DoubleSign { .. } => SlashReason::DoubleSign,
+            Evidence::Downtime { .. } => SlashReason::Downtime,
+        }
+    }
+    
+    // Height the offence is pinned to, so the same misbehaviour can only be punished once
+    pub fn offense_height(&self) -> u64 {
+        match self {
+            Evidence::DoubleSign { first, .. } => first.height,
+            Evidence::Downtime { from_height, .. } => *from_height,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum SlashReason {
+    DoubleSign,
+    Downtime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlashRecord {
+    pub validator: String,
+    pub reason: SlashReason,
+    pub offense_height: u64,
+    // Height of the block carrying the evidence
+    pub block_height: u64,
+    pub evidence_transaction: Uuid,
+    // Amount burned from each staker's bond
+    pub penalties: Vec<(String, u64)>,
+    pub jailed_until: u64,
+    pub(crate) previous_jail: Option<u64>,
+}
+
+impl SlashRecord {
+    pub fn total_penalty(&self) -> u64 {
+        self.penalties.iter().map(|(_, amount)| amount).sum()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,257 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::params::SlashingParams;
+use crate::slashing::{Evidence, SlashReason, SlashRecord};
+
+pub const STAKE_MODULE: &str = "native:stake";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Validator {
+    pub address: String,
+    pub public_key: String,
+    // Bonded amount per staker; the validator's own bond is under its address
+    pub bonds: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub vrf_key: Option<String>,
+    #[serde(default)]
+    pub registered_at: u64,
+    // Excluded from proposer selection below this height
+    #[serde(default)]
+    pub jailed_until: Option<u64>,
+}
+
+impl Validator {
+    pub fn total_stake(&self) -> u64 {
+        self.bonds.values().sum()
+    }
+    
+    pub fn self_bond(&self) -> u64 {
+        self.bonds.get(&self.address).copied().unwrap_or(0)
+    }
+    
+    pub fn is_jailed(&self, height: u64) -> bool {
+        self.jailed_until.is_some_and(|until| height < until)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct StakeRegistry {
+    // Ordered by address so stake-weighted selection is identical on every node
+    validators: RwLock<BTreeMap<String, Validator>>,
+    slashes: RwLock<Vec<SlashRecord>>,
+}
+
+impl StakeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn contains(&self, address: &str) -> bool {
+        self.validators.read().unwrap().contains_key(address)
+    }
+    
+    pub fn validator(&self, address: &str) -> Option<Validator> {
+        self.validators.read().unwrap().get(address).cloned()
+    }
+    
+    pub fn validator_by_key(&self, public_key: &str) -> Option<Validator> {
+        self.validators.read().unwrap()
+            .values()
+            .find(|validator| validator.public_key == public_key)
+            .cloned()
+    }
+    
+    // Validators with stake bonded, in address order
+    pub fn active_validators(&self) -> Vec<Validator> {
+        self.validators.read().unwrap()
+            .values()
+            .filter(|validator| validator.total_stake() > 0)
+            .cloned()
+            .collect()
+    }
+    
+    pub fn total_stake(&self) -> u64 {
+        self.validators.read().unwrap().values().map(Validator::total_stake).sum()
+    }
+    
+    pub fn bonded(&self, validator: &str, staker: &str) -> u64 {
+        self.validators.read().unwrap()
+            .get(validator)
+            .and_then(|validator| validator.bonds.get(staker).copied())
+            .unwrap_or(0)
+    }
+    
+    // Picks an unjailed validator for `height` with probability proportional to its stake
+    pub fn select(&self, seed: u64, height: u64) -> Option<Validator> {
+        let validators = self.validators.read().unwrap();
+        let eligible: Vec<&Validator> = validators.values()
+            .filter(|validator| !validator.is_jailed(height))
+            .collect();
+        let total: u64 = eligible.iter().map(|validator| validator.total_stake()).sum();
+        if total == 0 {
+            return None;
+        }
+        
+        let mut point = seed % total;
+        for validator in eligible {
+            let stake = validator.total_stake();
+            if point < stake {
+                return Some(validator.clone());
+            }
+            point -= stake;
+        }
+        
+        None
+    }
+    
+    // Splits a reward across a validator's bonds pro rata; rounding dust goes to the validator
+    pub fn reward_shares(&self, validator: &str, reward: u64) -> Vec<(String, u64)> {
+        let Some(validator) = self.validator(validator) else {
+            return Vec::new();
+        };
+        
+        let total = validator.total_stake();
+        if total == 0 || reward == 0 {
+            return Vec::new();
+        }
+        
+        let mut shares: Vec<(String, u64)> = validator.bonds.iter()
+            .map(|(staker, bond)| {
+                let share = reward as u128 * *bond as u128 / total as u128;
+                (staker.clone(), share as u64)
+            })
+            .collect();
+        
+        let dust = reward - shares.iter().map(|(_, share)| share).sum::<u64>();
+        match shares.iter_mut().find(|(staker, _)| staker == &validator.address) {
+            Some((_, share)) => *share += dust,
+            None => shares.push((validator.address.clone(), dust)),
+        }
+        
+        shares.retain(|(_, share)| *share > 0);
+        shares
+    }
+    
+    pub fn slash_history(&self, validator: &str) -> Vec<SlashRecord> {
+        self.slashes.read().unwrap()
+            .iter()
+            .filter(|record| record.validator == validator)
+            .cloned()
+            .collect()
+    }
+    
+    pub fn is_slashed_for(&self, validator: &str, reason: SlashReason, offense_height: u64) -> bool {
+        self.slashes.read().unwrap().iter().any(|record| {
+            record.validator == validator && record.reason == reason && record.offense_height == offense_height
+        })
+    }
+    
+    // Registers the validator on its first stake; later stakes keep the original key
+    pub(crate) fn register(&self, address: &str, public_key: &str, vrf_key: Option<&str>, height: u64) {
+        self.validators.write().unwrap()
+            .entry(address.to_string())
+            .or_insert_with(|| Validator {
+                address: address.to_string(),
+                public_key: public_key.to_string(),
+                bonds: BTreeMap::new(),
+                vrf_key: vrf_key.map(str::to_string),
+                registered_at: height,
+                jailed_until: None,
+            });
+    }
+    
+    // Burns a share of every bond to the validator and jails it
+    pub(crate) fn slash(
+        &self,
+        validator: &str,
+        evidence: &Evidence,
+        evidence_transaction: Uuid,
+        block_height: u64,
+        params: &SlashingParams,
+    ) {
+        let penalty_bps = match evidence.reason() {
+            SlashReason::DoubleSign => params.double_sign_penalty_bps,
+            SlashReason::Downtime => params.downtime_penalty_bps,
+        };
+        
+        let mut validators = self.validators.write().unwrap();
+        let Some(validator) = validators.get_mut(validator) else {
+            return;
+        };
+        
+        let mut penalties = Vec::new();
+        for (staker, bond) in validator.bonds.iter_mut() {
+            let penalty = (*bond as u128 * penalty_bps as u128 / 10_000) as u64;
+            if penalty > 0 {
+                *bond -= penalty;
+                penalties.push((staker.clone(), penalty));
+            }
+        }
+        validator.bonds.retain(|_, bond| *bond > 0);
+        
+        let previous_jail = validator.jailed_until;
+        let jailed_until = previous_jail.unwrap_or(0).max(block_height + params.jail_blocks);
+        validator.jailed_until = Some(jailed_until);
+        
+        self.slashes.write().unwrap().push(SlashRecord {
+            validator: validator.address.clone(),
+            reason: evidence.reason(),
+            offense_height: evidence.offense_height(),
+            block_height,
+            evidence_transaction,
+            penalties,
+            jailed_until,
+            previous_jail,
+        });
+    }
+    
+    pub(crate) fn revert_slash(&self, evidence_transaction: Uuid) {
+        let mut slashes = self.slashes.write().unwrap();
+        let Some(index) = slashes.iter().position(|record| record.evidence_transaction == evidence_transaction) else {
+            return;
+        };
+        let record = slashes.remove(index);
+        
+        if let Some(validator) = self.validators.write().unwrap().get_mut(&record.validator) {
+            for (staker, penalty) in &record.penalties {
+                *validator.bonds.entry(staker.clone()).or_insert(0) += penalty;
+            }
+            validator.jailed_until = record.previous_jail;
+        }
+    }
+    
+    // Removes a validator admitted by governance when that admission is reverted
+    pub(crate) fn deregister(&self, address: &str) {
+        self.validators.write().unwrap().remove(address);
+    }
+    
+    // Returns the jail it replaced
+    pub(crate) fn set_jail(&self, address: &str, jailed_until: Option<u64>) -> Option<u64> {
+        let mut validators = self.validators.write().unwrap();
+        let validator = validators.get_mut(address)?;
+        std::mem::replace(&mut validator.jailed_until, jailed_until)
+    }
+    
+    pub(crate) fn bond(&self, validator: &str, staker: &str, amount: u64) {
+        if let Some(validator) = self.validators.write().unwrap().get_mut(validator) {
+            *validator.bonds.entry(staker.to_string()).or_insert(0) += amount;
+        }
+    }
+    
+    pub(crate) fn unbond(&self, validator: &str, staker: &str, amount: u64) {
+        let mut validators = self.validators.write().unwrap();
+        let Some(validator) = validators.get_mut(validator) else {
+            return;
+        };
+        
+        if let Some(bond) = validator.bonds.get_mut(staker) {
+            *bond = bond.saturating_sub(amount);
+            if *bond == 0 {
+                validator.bonds.remove(staker);
+            }
+        }
+    }
+}
\ No newline at end of file
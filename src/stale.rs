@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+use std::sync::RwLock;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::Block;
+
+const MAX_STALE_BLOCKS: usize = 1_024;
+
+// A valid block that lost a fork race, either on arrival or when a reorg took it off the main chain
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StaleBlock {
+    pub hash: String,
+    pub height: u64,
+    pub producer: Option<String>,
+    // Local time the block went stale, not the block's own timestamp
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct StaleStats {
+    pub recorded: usize,
+    pub last_hour: usize,
+    // Share of the blocks seen in the last hour that went stale
+    pub stale_rate: f64,
+}
+
+#[derive(Default)]
+pub struct StaleTracker {
+    // Oldest first, capped at MAX_STALE_BLOCKS
+    blocks: RwLock<VecDeque<StaleBlock>>,
+}
+
+impl StaleTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn list(&self) -> Vec<StaleBlock> {
+        self.blocks.read().unwrap().iter().cloned().collect()
+    }
+    
+    pub fn is_stale(&self, hash: &str) -> bool {
+        self.blocks.read().unwrap().iter().any(|block| block.hash == hash)
+    }
+    
+    // `main_chain_last_hour` is how many main-chain blocks were produced over the same window
+    pub fn stats(&self, main_chain_last_hour: usize) -> StaleStats {
+        let blocks = self.blocks.read().unwrap();
+        let since = crate::clock::now() - Duration::hours(1);
+        let last_hour = blocks.iter().filter(|block| block.recorded_at >= since).count();
+        let seen = last_hour + main_chain_last_hour;
+        
+        StaleStats {
+            recorded: blocks.len(),
+            last_hour,
+            stale_rate: if seen == 0 { 0.0 } else { last_hour as f64 / seen as f64 },
+        }
+    }
+    
+    pub(crate) fn record(&self, block: &Block) {
+        let mut blocks = self.blocks.write().unwrap();
+        if blocks.iter().any(|stale| stale.hash == block.header.hash) {
+            return;
+        }
+        
+        if blocks.len() == MAX_STALE_BLOCKS {
+            blocks.pop_front();
+        }
+        blocks.push_back(StaleBlock {
+            hash: block.header.hash.clone(),
+            height: block.header.height,
+            producer: block.header.producer.clone(),
+            recorded_at: crate::clock::now(),
+        });
+    }
+    
+    // For a block that made it back onto the main chain, or turned out to be invalid
+    pub(crate) fn remove(&self, hash: &str) {
+        self.blocks.write().unwrap().retain(|block| block.hash != hash);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,426 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use dashmap::iter::Iter;
+use dashmap::mapref::entry::Entry;
+use dashmap::mapref::one::{Ref, RefMut};
+use dashmap::{DashMap, DashSet};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::BlockHeader;
+
+// As in the transaction tree, a branch can't pass for an account
+const LEAF_TAG: u8 = 0;
+const BRANCH_TAG: u8 = 1;
+
+type Hash = [u8; 32];
+
+// What an empty subtree hashes to; no leaf or branch digest can be all zeros in practice
+const EMPTY: Hash = [0; 32];
+
+// A binary trie over the hashed addresses of funded accounts. A subtree holding a single account collapses into its
+// leaf, so the shape, and the root, depend on nothing but the balances. Updates copy the path they change and share
+// the rest, so older roots stay readable
+#[derive(Debug)]
+enum Node {
+    Empty,
+    Leaf { key: Hash, balance: u64, hash: Hash },
+    Branch { left: Arc<Node>, right: Arc<Node>, hash: Hash },
+}
+
+fn key(address: &str) -> Hash {
+    Sha256::digest(address.as_bytes()).into()
+}
+
+// Bit `depth` of the key picks the branch at that depth, most significant first
+fn bit(key: &Hash, depth: usize) -> bool {
+    key[depth / 8] >> (7 - depth % 8) & 1 == 1
+}
+
+fn leaf_hash(key: &Hash, balance: u64) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_TAG]);
+    hasher.update(key);
+    hasher.update(balance.to_le_bytes());
+    hasher.finalize().into()
+}
+
+fn branch_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([BRANCH_TAG]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn decode(hex: &str) -> Option<Hash> {
+    hex::decode(hex).ok()?.try_into().ok()
+}
+
+impl Node {
+    fn hash(&self) -> Hash {
+        match self {
+            Node::Empty => EMPTY,
+            Node::Leaf { hash, .. } | Node::Branch { hash, .. } => *hash,
+        }
+    }
+    
+    fn leaf(key: Hash, balance: u64) -> Arc<Node> {
+        Arc::new(Node::Leaf { key, balance, hash: leaf_hash(&key, balance) })
+    }
+    
+    // Joins two subtrees, collapsing a lone leaf into the parent's place
+    fn branch(left: Arc<Node>, right: Arc<Node>) -> Arc<Node> {
+        match (left.as_ref(), right.as_ref()) {
+            (Node::Empty, Node::Empty | Node::Leaf { .. }) => right,
+            (Node::Leaf { .. }, Node::Empty) => left,
+            _ => {
+                let hash = branch_hash(&left.hash(), &right.hash());
+                Arc::new(Node::Branch { left, right, hash })
+            }
+        }
+    }
+    
+    fn get(node: &Arc<Node>, key: &Hash) -> u64 {
+        let mut current = node;
+        let mut depth = 0;
+        loop {
+            match current.as_ref() {
+                Node::Empty => return 0,
+                Node::Leaf { key: found, balance, .. } => return if found == key { *balance } else { 0 },
+                Node::Branch { left, right, .. } => {
+                    current = if bit(key, depth) { right } else { left };
+                    depth += 1;
+                }
+            }
+        }
+    }
+    
+    // Sets the balance under `key`, removing the account at zero
+    fn set(node: &Arc<Node>, key: &Hash, balance: u64, depth: usize) -> Arc<Node> {
+        match node.as_ref() {
+            Node::Empty if balance == 0 => Arc::clone(node),
+            Node::Empty => Node::leaf(*key, balance),
+            Node::Leaf { key: found, .. } if found == key => match balance {
+                0 => Arc::new(Node::Empty),
+                balance => Node::leaf(*key, balance),
+            },
+            Node::Leaf { .. } if balance == 0 => Arc::clone(node),
+            Node::Leaf { key: found, .. } => Self::split(Arc::clone(node), found, Node::leaf(*key, balance), key, depth),
+            Node::Branch { left, right, .. } => match bit(key, depth) {
+                true => Self::branch(Arc::clone(left), Self::set(right, key, balance, depth + 1)),
+                false => Self::branch(Self::set(left, key, balance, depth + 1), Arc::clone(right)),
+            },
+        }
+    }
+    
+    // Balances before and after of every account in subtrees that differ between the two trees
+    fn diff(before: &Arc<Node>, after: &Arc<Node>, balances: &mut HashMap<Hash, (u64, u64)>) {
+        if before.hash() == after.hash() {
+            return;
+        }
+        
+        match (before.as_ref(), after.as_ref()) {
+            (Node::Branch { left: before_left, right: before_right, .. }, Node::Branch { left, right, .. }) => {
+                Self::diff(before_left, left, balances);
+                Self::diff(before_right, right, balances);
+            }
+            _ => {
+                before.for_each_leaf(&mut |key, balance| balances.entry(key).or_default().0 = balance);
+                after.for_each_leaf(&mut |key, balance| balances.entry(key).or_default().1 = balance);
+            }
+        }
+    }
+    
+    fn for_each_leaf(&self, visit: &mut impl FnMut(Hash, u64)) {
+        match self {
+            Node::Empty => {}
+            Node::Leaf { key, balance, .. } => visit(*key, *balance),
+            Node::Branch { left, right, .. } => {
+                left.for_each_leaf(visit);
+                right.for_each_leaf(visit);
+            }
+        }
+    }
+    
+    // Branches down to the first bit the two keys differ on
+    fn split(a: Arc<Node>, a_key: &Hash, b: Arc<Node>, b_key: &Hash, depth: usize) -> Arc<Node> {
+        match (bit(a_key, depth), bit(b_key, depth)) {
+            (false, true) => Self::branch(a, b),
+            (true, false) => Self::branch(b, a),
+            (true, true) => Self::branch(Arc::new(Node::Empty), Self::split(a, a_key, b, b_key, depth + 1)),
+            (false, false) => Self::branch(Self::split(a, a_key, b, b_key, depth + 1), Arc::new(Node::Empty)),
+        }
+    }
+}
+
+// An account's balance as committed by the state root of the header at `height`, i.e. as the block before it left
+// the chain. A zero balance is proven by the path ending in an empty subtree or another account's leaf
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BalanceProof {
+    pub address: String,
+    pub balance: u64,
+    pub height: u64,
+    // Sibling of each node on the path, root first
+    pub siblings: Vec<String>,
+    // The hashed address and balance of the leaf the path ends in, if any
+    pub leaf: Option<(String, u64)>,
+}
+
+// Whether the proof's balance is the one `header` commits to, for a client holding headers only; as with inclusion
+// proofs, the header must be one the client already trusts
+pub fn verify_balance_proof(header: &BlockHeader, proof: &BalanceProof) -> bool {
+    if header.hash != header.calculate_hash() || header.height != proof.height {
+        return false;
+    }
+    
+    let key = key(&proof.address);
+    let mut current = match &proof.leaf {
+        Some((found, balance)) => {
+            let Some(found) = decode(found) else {
+                return false;
+            };
+            let expected = if found == key { *balance } else { 0 };
+            if *balance == 0 || proof.balance != expected {
+                return false;
+            }
+            leaf_hash(&found, *balance)
+        }
+        None if proof.balance != 0 => return false,
+        None => EMPTY,
+    };
+    
+    for (depth, sibling) in proof.siblings.iter().enumerate().rev() {
+        let Some(sibling) = decode(sibling) else {
+            return false;
+        };
+        current = match bit(&key, depth) {
+            true => branch_hash(&sibling, &current),
+            false => branch_hash(&current, &sibling),
+        };
+    }
+    
+    hex::encode(current) == header.state_root
+}
+
+// An account whose balance changed, between two heights or by a block and its revert
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BalanceChange {
+    pub address: String,
+    pub before: u64,
+    pub after: u64,
+}
+
+impl BalanceChange {
+    pub fn delta(&self) -> i128 {
+        self.after as i128 - self.before as i128
+    }
+}
+
+// Committed balances, noting every account written to since the state tree last took them in, so a block only
+// costs as much as the accounts it touches. Anything that can change a balance goes through here
+#[derive(Debug, Default)]
+pub(crate) struct Balances {
+    balances: DashMap<String, u64>,
+    written: DashSet<String>,
+}
+
+impl Balances {
+    pub(crate) fn get(&self, address: &str) -> Option<Ref<'_, String, u64>> {
+        self.balances.get(address)
+    }
+    
+    pub(crate) fn iter(&self) -> Iter<'_, String, u64> {
+        self.balances.iter()
+    }
+    
+    pub(crate) fn get_mut(&self, address: &str) -> Option<RefMut<'_, String, u64>> {
+        self.mark(address);
+        self.balances.get_mut(address)
+    }
+    
+    pub(crate) fn entry(&self, address: String) -> Entry<'_, String, u64> {
+        self.mark(&address);
+        self.balances.entry(address)
+    }
+    
+    pub(crate) fn insert(&self, address: String, balance: u64) -> Option<u64> {
+        self.mark(&address);
+        self.balances.insert(address, balance)
+    }
+    
+    fn mark(&self, address: &str) {
+        if !self.written.contains(address) {
+            self.written.insert(address.to_string());
+        }
+    }
+    
+    // The balances written to since the last call, as they stand now
+    pub(crate) fn take_written(&self) -> Vec<(String, u64)> {
+        let written: Vec<String> = self.written.iter().map(|address| address.key().clone()).collect();
+        self.written.clear();
+        written.into_iter()
+            .map(|address| {
+                let balance = self.balances.get(&address).map_or(0, |balance| *balance);
+                (address, balance)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Versions {
+    // Height of the oldest tree still kept; older ones are pruned with `StateTree::prune_below`
+    first: u64,
+    // The tree after each kept block, oldest first
+    roots: VecDeque<Arc<Node>>,
+    // What each kept block changed, as the balances before it, so a revert can put them back
+    undo: VecDeque<Vec<(String, u64)>>,
+    // Every address the tree has held, by key, since leaves only carry the key
+    addresses: HashMap<Hash, String>,
+}
+
+impl Versions {
+    fn root(&self, height: u64) -> Option<&Arc<Node>> {
+        self.roots.get(height.checked_sub(self.first)? as usize)
+    }
+}
+
+// Account balances after each main-chain block, authenticated by a root that the next block's header commits to
+#[derive(Debug, Default)]
+pub struct StateTree {
+    versions: RwLock<Versions>,
+}
+
+impl StateTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // Root of the latest committed balances, which the next block must carry
+    pub fn root(&self) -> String {
+        let versions = self.versions.read().unwrap();
+        hex::encode(versions.roots.back().map_or(EMPTY, |root| root.hash()))
+    }
+    
+    // None for heights not reached yet or already pruned, as for the other lookups by height
+    pub fn root_at(&self, height: u64) -> Option<String> {
+        self.versions.read().unwrap().root(height).map(|root| hex::encode(root.hash()))
+    }
+    
+    // Balances as the block at `height` left them
+    pub fn balance_at(&self, address: &str, height: u64) -> Option<u64> {
+        let versions = self.versions.read().unwrap();
+        versions.root(height).map(|root| Node::get(root, &key(address)))
+    }
+    
+    // Every nonzero balance as the block at `height` left them, by address
+    pub fn balances_at(&self, height: u64) -> Option<HashMap<String, u64>> {
+        let versions = self.versions.read().unwrap();
+        let mut balances = HashMap::new();
+        versions.root(height)?.for_each_leaf(&mut |key, balance| {
+            if let Some(address) = versions.addresses.get(&key) {
+                balances.insert(address.clone(), balance);
+            }
+        });
+        Some(balances)
+    }
+    
+    // Accounts whose balance differs between the states after blocks `from` and `to`, by address; subtrees the two
+    // roots share are skipped without being walked
+    pub fn diff(&self, from: u64, to: u64) -> Option<Vec<BalanceChange>> {
+        let versions = self.versions.read().unwrap();
+        let (before, after) = (versions.root(from)?, versions.root(to)?);
+        
+        let mut balances = HashMap::new();
+        Node::diff(before, after, &mut balances);
+        let mut changes: Vec<_> = balances.into_iter()
+            .filter(|(_, (before, after))| before != after)
+            .filter_map(|(key, (before, after))| {
+                let address = versions.addresses.get(&key)?.clone();
+                Some(BalanceChange { address, before, after })
+            })
+            .collect();
+        changes.sort_by(|a, b| a.address.cmp(&b.address));
+        Some(changes)
+    }
+    
+    // Proves the balance under the root of the block at `height - 1`, which the header at `height` commits to
+    pub fn prove(&self, address: &str, height: u64) -> Option<BalanceProof> {
+        let versions = self.versions.read().unwrap();
+        let mut current = versions.root(height.checked_sub(1)?)?;
+        let key = key(address);
+        
+        let mut siblings = Vec::new();
+        let leaf = loop {
+            match current.as_ref() {
+                Node::Empty => break None,
+                Node::Leaf { key, balance, .. } => break Some((hex::encode(key), *balance)),
+                Node::Branch { left, right, .. } => {
+                    let (next, sibling) = match bit(&key, siblings.len()) {
+                        true => (right, left),
+                        false => (left, right),
+                    };
+                    siblings.push(hex::encode(sibling.hash()));
+                    current = next;
+                }
+            }
+        };
+        
+        let balance = match &leaf {
+            Some((found, balance)) if *found == hex::encode(key) => *balance,
+            _ => 0,
+        };
+        Some(BalanceProof { address: address.to_string(), balance, height, siblings, leaf })
+    }
+    
+    // Records the balances after the next block, given at least every account it changed; accounts whose balance
+    // is unchanged are skipped
+    pub(crate) fn commit(&self, balances: impl IntoIterator<Item = (String, u64)>) -> Vec<BalanceChange> {
+        let mut versions = self.versions.write().unwrap();
+        let mut root = versions.roots.back().cloned().unwrap_or_else(|| Arc::new(Node::Empty));
+        
+        let mut changes = Vec::new();
+        for (address, balance) in balances {
+            let key = key(&address);
+            let previous = Node::get(&root, &key);
+            if previous == balance {
+                continue;
+            }
+            
+            root = Node::set(&root, &key, balance, 0);
+            versions.addresses.entry(key).or_insert_with(|| address.clone());
+            changes.push(BalanceChange { address, before: previous, after: balance });
+        }
+        
+        let undo = changes.iter().map(|change| (change.address.clone(), change.before)).collect();
+        versions.roots.push_back(root);
+        versions.undo.push_back(undo);
+        changes
+    }
+    
+    // Drops the latest block's balances when it leaves the main chain
+    pub(crate) fn revert(&self) -> Vec<BalanceChange> {
+        let mut versions = self.versions.write().unwrap();
+        let Some(reverted) = versions.roots.pop_back() else {
+            return Vec::new();
+        };
+        
+        versions.undo.pop_back().unwrap_or_default().into_iter()
+            .map(|(address, previous)| {
+                let current = Node::get(&reverted, &key(&address));
+                BalanceChange { address, before: current, after: previous }
+            })
+            .collect()
+    }
+    
+    // Forgets the trees of blocks below `height`, which must be too deep to revert; the latest is always kept
+    pub(crate) fn prune_below(&self, height: u64) {
+        let mut versions = self.versions.write().unwrap();
+        while versions.first < height && versions.roots.len() > 1 {
+            versions.roots.pop_front();
+            versions.undo.pop_front();
+            versions.first += 1;
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,85 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+
+use crate::{Block, TransactionKind};
+
+// Mempool samples kept, one per appended block
+pub const DEPTH_SAMPLES: usize = 1024;
+
+// Activity over the latest main-chain blocks; see `DistributedLedger::chain_stats`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChainStats {
+    pub from_height: u64,
+    pub to_height: u64,
+    // Mean time from each block's parent to the block
+    pub average_interval_ms: f64,
+    // Coinbases aside
+    pub average_transactions: f64,
+    // Share of blocks carrying nothing but, at most, a coinbase
+    pub empty_ratio: f64,
+    // Transactions still pooled once each block was appended, by height; only blocks this node appended
+    pub mempool_depth: Vec<(u64, usize)>,
+    pub difficulty: Vec<(u64, usize)>,
+}
+
+impl ChainStats {
+    // `blocks` is the window preceded by its first block's parent
+    pub(crate) fn over(blocks: &[Arc<Block>], mempool_depth: Vec<(u64, usize)>) -> Self {
+        let window = &blocks[1.min(blocks.len())..];
+        let intervals = blocks.windows(2)
+            .map(|pair| (pair[1].header.timestamp - pair[0].header.timestamp).num_milliseconds() as f64)
+            .collect::<Vec<_>>();
+        let transactions = window.iter()
+            .map(|block| {
+                block.transactions.iter()
+                    .filter(|tx| !matches!(tx.kind, TransactionKind::Coinbase { .. }))
+                    .count()
+            })
+            .collect::<Vec<_>>();
+        
+        let mean = |total: f64| if window.is_empty() { 0.0 } else { total / window.len() as f64 };
+        ChainStats {
+            from_height: window.first().map_or(0, |block| block.header.height),
+            to_height: window.last().map_or(0, |block| block.header.height),
+            average_interval_ms: mean(intervals.iter().sum()),
+            average_transactions: mean(transactions.iter().sum::<usize>() as f64),
+            empty_ratio: mean(transactions.iter().filter(|count| **count == 0).count() as f64),
+            mempool_depth,
+            difficulty: window.iter().map(|block| (block.header.height, block.header.difficulty)).collect(),
+        }
+    }
+}
+
+// Pool size after each block this node appended, oldest first
+#[derive(Debug, Default)]
+pub struct MempoolDepth {
+    samples: Mutex<VecDeque<(u64, usize)>>,
+}
+
+impl MempoolDepth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn since(&self, height: u64) -> Vec<(u64, usize)> {
+        self.samples.lock().unwrap()
+            .iter()
+            .filter(|(sampled, _)| *sampled >= height)
+            .copied()
+            .collect()
+    }
+    
+    // Samples at or above `height` came from blocks a reorg has since replaced
+    pub(crate) fn record(&self, height: u64, depth: usize) {
+        let mut samples = self.samples.lock().unwrap();
+        while samples.back().is_some_and(|(sampled, _)| *sampled >= height) {
+            samples.pop_back();
+        }
+        
+        if samples.len() == DEPTH_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back((height, depth));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,57 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::Transaction;
+
+// Rejections remembered before the oldest are forgotten
+pub const REJECTION_CAPACITY: usize = 10_000;
+
+// Where a transaction stands from this node's point of view
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TransactionStatus {
+    Pending,
+    Included { height: u64, block_hash: String },
+    // Included in a block that can no longer be reorganised away
+    Finalized { height: u64, block_hash: String },
+    // Admitted, then dropped before any block took it
+    Rejected { reason: String },
+    Unknown,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    by_id: HashMap<Uuid, (Transaction, String)>,
+    order: VecDeque<Uuid>,
+}
+
+// The latest pooled transactions dropped without being committed, with why
+#[derive(Debug, Default)]
+pub struct Rejections {
+    entries: Mutex<Entries>,
+}
+
+impl Rejections {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn get(&self, id: Uuid) -> Option<(Transaction, String)> {
+        self.entries.lock().unwrap().by_id.get(&id).cloned()
+    }
+    
+    pub(crate) fn record(&self, transaction: Transaction, reason: String) {
+        let mut entries = self.entries.lock().unwrap();
+        let id = transaction.id;
+        if entries.by_id.insert(id, (transaction, reason)).is_none() {
+            entries.order.push_back(id);
+        }
+        
+        while entries.order.len() > REJECTION_CAPACITY {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.by_id.remove(&oldest);
+            }
+        }
+    }
+}
\ No newline at end of file
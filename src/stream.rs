@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Transaction;
+
+// Blocks read per lock acquisition while streaming, as for query scans
+pub const STREAM_CHUNK: usize = 64;
+
+// Which committed transactions `DistributedLedger::iter_transactions` yields
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TransactionFilter {
+    pub from_height: u64,
+    // Up to the tip when the stream reaches it if unset
+    pub to_height: Option<u64>,
+    // Only transactions sending from or paying into this account
+    pub address: Option<String>,
+}
+
+impl TransactionFilter {
+    pub fn matches(&self, tx: &Transaction) -> bool {
+        match &self.address {
+            Some(address) => crate::history::parties(tx).contains(address.as_str()),
+            None => true,
+        }
+    }
+}
\ No newline at end of file
@@ -2,50 +2,729 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
+use crate::anchor::Anchor;
+use crate::bundle::{BundlePosition, MAX_BUNDLE_SIZE};
+use crate::checkpoint::CheckpointVote;
+use crate::governance::ProposalAction;
+use crate::keys::{self, Keypair};
+use crate::limits::SpendingLimit;
+use crate::utxo::OutPoint;
+use crate::vesting::VestingSchedule;
+use crate::slashing::Evidence;
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub enum TransactionKind {
+    #[default]
+    Transfer,
+    Anchor(Anchor),
+    // Staking kinds name the validator in `to`; a stake registers the sender as a validator
+    Stake {
+        #[serde(default)]
+        vrf_key: Option<String>,
+    },
+    Delegate,
+    Unstake,
+    // Reports misbehaviour by the validator named in `to`
+    Evidence(Evidence),
+    // Cast by the validator in `from`, signed with its registered key
+    CheckpointVote(CheckpointVote),
+    // Governance kinds are also cast by a signing validator in `from`
+    Propose(ProposalAction),
+    Vote {
+        proposal: Uuid,
+        approve: bool,
+    },
+    // Pays every output from the sender at once; `amount` is their total and `to` is left empty
+    Payout(Vec<Payment>),
+    // The sender pays `amount` to `to`, who pays `counter_amount` back; both have to sign
+    Swap {
+        counter_amount: u64,
+    },
+    // Deploys WASM bytecode to `ContractStore::address_for` this transaction, endowed with `amount`
+    Deploy {
+        code: Vec<u8>,
+    },
+    // Runs the exported `method` of the contract in `to`, sending it `amount`
+    Call {
+        method: String,
+        input: Vec<u8>,
+        gas_limit: u64,
+    },
+    // Registers a standing order paying `amount` to `to` every `interval` epochs, starting `interval` epochs out
+    Schedule {
+        amount: u64,
+        interval: u64,
+    },
+    // Stops a standing order; only its payer can
+    CancelSchedule {
+        schedule: Uuid,
+    },
+    // Supply changes by the issuer in `ChainParams::issuance`: a mint creates `amount` for `to`, a burn destroys
+    // `amount` of the issuer's own balance
+    Mint,
+    Burn,
+    // Replaces the sender's own spending limit; an unlimited one lifts it
+    SetSpendingLimit(SpendingLimit),
+    // Consumes the sender's coins in `inputs` and creates one coin per output, change included, under the UTXO
+    // model; `amount` is the outputs' total and the inputs must cover it plus the fee exactly
+    Spend {
+        inputs: Vec<OutPoint>,
+        outputs: Vec<Payment>,
+    },
+    // Transfers `amount` to `to` locked under the schedule; it counts towards their balance but only vested funds
+    // can leave it
+    Vest(VestingSchedule),
+    // Lets the spender in `to` move up to `allowance` of the sender's balance, replacing any earlier allowance; zero
+    // revokes it
+    Approve {
+        allowance: u64,
+    },
+    // The spender in `from` moves `amount` of `owner`'s balance to `to`, within the allowance; the spender pays the fee
+    TransferFrom {
+        owner: String,
+    },
+    // Locks `amount` for `to` behind `hash`, a `htlc::hashlock` digest: anyone revealing its preimage before height
+    // `timeout` releases the funds to `to`, and from `timeout` on only the sender can take them back
+    HashLock {
+        hash: String,
+        timeout: u64,
+    },
+    // Releases `lock` to its recipient; the sender can be anyone, who pays the fee
+    Claim {
+        lock: Uuid,
+        preimage: Vec<u8>,
+    },
+    // Returns a timed-out `lock` to the sender that created it
+    Refund {
+        lock: Uuid,
+    },
+    // Created by the producer of block `height` as its first transaction, paying `to` the emission schedule's reward
+    // plus the block's fees; it has no sender and is never accepted from the mempool
+    Coinbase {
+        height: u64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Payment {
+    pub to: String,
+    pub amount: u64,
+}
+
+// A block height or block timestamp bounding when a transaction may be included
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum BlockBound {
+    Height(u64),
+    Time(DateTime<Utc>),
+}
+
+impl BlockBound {
+    pub fn is_reached(&self, height: u64, timestamp: DateTime<Utc>) -> bool {
+        match self {
+            BlockBound::Height(bound) => height >= *bound,
+            BlockBound::Time(bound) => timestamp >= *bound,
+        }
+    }
+    
+    pub fn has_passed(&self, height: u64, timestamp: DateTime<Utc>) -> bool {
+        match self {
+            BlockBound::Height(bound) => height > *bound,
+            BlockBound::Time(bound) => timestamp > *bound,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Authorization {
+    pub public_key: String,
+    pub signature: String,
+}
+
+// Every field is covered by the digest that `sign` signs, so the `with_*` builders below re-derive it and must be
+// called before `sign`
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Transaction {
     pub id: Uuid,
     pub from: String,
     pub to: String,
     pub amount: u64,
+    // Paid by the sender on top of `amount`, to the block producer or burned
+    #[serde(default)]
+    pub fee: u64,
+    #[serde(default)]
+    pub kind: TransactionKind,
     pub timestamp: DateTime<Utc>,
     pub signature: String,
+    #[serde(default)]
+    pub authorization: Option<Authorization>,
+    // Network the transaction is valid on; must match the genesis `chain_id`
+    #[serde(default)]
+    pub chain_id: String,
+    // Last block that may include the transaction; unset transactions never expire
+    #[serde(default)]
+    pub valid_until: Option<BlockBound>,
+    // First block that may include it; until then it waits in the mempool
+    #[serde(default)]
+    pub not_before: Option<BlockBound>,
+    // The counterparty's signature over the same digest, for swaps
+    #[serde(default)]
+    pub counter_authorization: Option<Authorization>,
+    // Opaque application payload, e.g. a document hash to notarize; priced per byte through the fee
+    #[serde(default)]
+    pub data: Vec<u8>,
+    // Pending transaction from the same sender this one takes the place of, if it pays a higher fee; later rounds
+    // name the same original, so whichever commits rules out the rest
+    #[serde(default)]
+    pub replaces: Option<Uuid>,
+    // Set on members of a bundle, which commit together or not at all
+    #[serde(default)]
+    pub bundle: Option<BundlePosition>,
 }
 
 impl Transaction {
     pub fn new(from: String, to: String, amount: u64) -> Self {
-        let id = Uuid::new_v4();
-        let timestamp = Utc::now();
-        let signature = Self::calculate_signature(&id, &from, &to, amount, &timestamp);
-        
-        Self {
-            id,
+        Self::with_kind(from, to, amount, TransactionKind::Transfer)
+    }
+    
+    pub fn anchor(from: String, anchor: Anchor) -> Self {
+        Self::with_kind(from, String::new(), 0, TransactionKind::Anchor(anchor))
+    }
+    
+    // Bonds `amount` as the sender's own validator stake; must be signed by the validator's key
+    pub fn stake(validator: String, amount: u64) -> Self {
+        Self::with_kind(validator.clone(), validator, amount, TransactionKind::Stake { vrf_key: None })
+    }
+    
+    // As `stake`, also registering the key used to prove VRF proposer elections
+    pub fn stake_with_vrf_key(validator: String, amount: u64, vrf_key: String) -> Self {
+        let kind = TransactionKind::Stake { vrf_key: Some(vrf_key) };
+        Self::with_kind(validator.clone(), validator, amount, kind)
+    }
+    
+    pub fn delegate(from: String, validator: String, amount: u64) -> Self {
+        Self::with_kind(from, validator, amount, TransactionKind::Delegate)
+    }
+    
+    pub fn unstake(from: String, validator: String, amount: u64) -> Self {
+        Self::with_kind(from, validator, amount, TransactionKind::Unstake)
+    }
+    
+    pub fn evidence(reporter: String, validator: String, evidence: Evidence) -> Self {
+        Self::with_kind(reporter, validator, 0, TransactionKind::Evidence(evidence))
+    }
+    
+    pub fn checkpoint_vote(validator: String, height: u64, hash: String) -> Self {
+        Self::with_kind(validator, String::new(), 0, TransactionKind::CheckpointVote(CheckpointVote { height, hash }))
+    }
+    
+    pub fn propose(validator: String, action: ProposalAction) -> Self {
+        Self::with_kind(validator, String::new(), 0, TransactionKind::Propose(action))
+    }
+    
+    pub fn vote(validator: String, proposal: Uuid, approve: bool) -> Self {
+        Self::with_kind(validator, String::new(), 0, TransactionKind::Vote { proposal, approve })
+    }
+    
+    // All or nothing: the block either applies every payment or none of them
+    pub fn payout(from: String, payments: Vec<Payment>) -> Self {
+        let total = payments.iter().fold(0u64, |total, payment| total.saturating_add(payment.amount));
+        Self::with_kind(from, String::new(), total, TransactionKind::Payout(payments))
+    }
+    
+    // Settles both legs or neither; `countersign` it with the counterparty's key as well as `sign`
+    pub fn swap(from: String, to: String, amount: u64, counter_amount: u64) -> Self {
+        Self::with_kind(from, to, amount, TransactionKind::Swap { counter_amount })
+    }
+    
+    pub fn deploy(from: String, code: Vec<u8>) -> Self {
+        Self::with_kind(from, String::new(), 0, TransactionKind::Deploy { code })
+    }
+    
+    pub fn call(from: String, contract: String, amount: u64, method: String, input: Vec<u8>, gas_limit: u64) -> Self {
+        Self::with_kind(from, contract, amount, TransactionKind::Call { method, input, gas_limit })
+    }
+    
+    pub fn schedule(from: String, to: String, amount: u64, interval: u64) -> Self {
+        Self::with_kind(from, to, 0, TransactionKind::Schedule { amount, interval })
+    }
+    
+    pub fn cancel_schedule(from: String, schedule: Uuid) -> Self {
+        Self::with_kind(from, String::new(), 0, TransactionKind::CancelSchedule { schedule })
+    }
+    
+    // Takes the place of `original` and moves nothing, so only the fee is spent; give it a higher fee than the original
+    pub fn cancellation(from: String, original: Uuid) -> Self {
+        Self::with_kind(from.clone(), from, 0, TransactionKind::Transfer).replacing(original)
+    }
+    
+    pub fn mint(issuer: String, to: String, amount: u64) -> Self {
+        Self::with_kind(issuer, to, amount, TransactionKind::Mint)
+    }
+    
+    pub fn burn(issuer: String, amount: u64) -> Self {
+        Self::with_kind(issuer, String::new(), amount, TransactionKind::Burn)
+    }
+    
+    pub fn set_spending_limit(from: String, limit: SpendingLimit) -> Self {
+        Self::with_kind(from, String::new(), 0, TransactionKind::SetSpendingLimit(limit))
+    }
+    
+    pub fn spend(from: String, inputs: Vec<OutPoint>, outputs: Vec<Payment>) -> Self {
+        let total = outputs.iter().fold(0u64, |total, output| total.saturating_add(output.amount));
+        Self::with_kind(from, String::new(), total, TransactionKind::Spend { inputs, outputs })
+    }
+    
+    pub fn vest(from: String, to: String, amount: u64, schedule: VestingSchedule) -> Self {
+        Self::with_kind(from, to, amount, TransactionKind::Vest(schedule))
+    }
+    
+    pub fn approve(owner: String, spender: String, allowance: u64) -> Self {
+        Self::with_kind(owner, spender, 0, TransactionKind::Approve { allowance })
+    }
+    
+    pub fn revoke_allowance(owner: String, spender: String) -> Self {
+        Self::approve(owner, spender, 0)
+    }
+    
+    pub fn transfer_from(spender: String, owner: String, to: String, amount: u64) -> Self {
+        Self::with_kind(spender, to, amount, TransactionKind::TransferFrom { owner })
+    }
+    
+    pub fn hash_lock(from: String, to: String, amount: u64, hash: String, timeout: u64) -> Self {
+        Self::with_kind(from, to, amount, TransactionKind::HashLock { hash, timeout })
+    }
+    
+    pub fn claim(from: String, lock: Uuid, preimage: Vec<u8>) -> Self {
+        Self::with_kind(from, String::new(), 0, TransactionKind::Claim { lock, preimage })
+    }
+    
+    pub fn refund(from: String, lock: Uuid) -> Self {
+        Self::with_kind(from, String::new(), 0, TransactionKind::Refund { lock })
+    }
+    
+    pub fn coinbase(height: u64, to: String, amount: u64) -> Self {
+        Self::with_kind(String::new(), to, amount, TransactionKind::Coinbase { height })
+    }
+    
+    fn with_kind(from: String, to: String, amount: u64, kind: TransactionKind) -> Self {
+        let mut transaction = Self {
+            id: crate::clock::new_id(),
             from,
             to,
             amount,
-            timestamp,
-            signature,
-        }
+            fee: 0,
+            kind,
+            timestamp: crate::clock::now(),
+            signature: String::new(),
+            authorization: None,
+            chain_id: String::new(),
+            valid_until: None,
+            not_before: None,
+            counter_authorization: None,
+            data: Vec::new(),
+            replaces: None,
+            bundle: None,
+        };
+        
+        transaction.signature = transaction.calculate_signature();
+        transaction
     }
     
-    fn calculate_signature(
-        id: &Uuid,
-        from: &str,
-        to: &str,
-        amount: u64,
-        timestamp: &DateTime<Utc>,
-    ) -> String {
+    // Binds the transaction to a network
+    pub fn for_chain(mut self, chain_id: &str) -> Self {
+        self.chain_id = chain_id.to_string();
+        self.signature = self.calculate_signature();
+        self
+    }
+    
+    pub fn with_fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self.signature = self.calculate_signature();
+        self
+    }
+    
+    pub fn with_valid_until(mut self, valid_until: BlockBound) -> Self {
+        self.valid_until = Some(valid_until);
+        self.signature = self.calculate_signature();
+        self
+    }
+    
+    pub fn with_not_before(mut self, not_before: BlockBound) -> Self {
+        self.not_before = Some(not_before);
+        self.signature = self.calculate_signature();
+        self
+    }
+    
+    pub fn with_data(mut self, data: Vec<u8>) -> Self {
+        self.data = data;
+        self.signature = self.calculate_signature();
+        self
+    }
+    
+    pub fn replacing(mut self, original: Uuid) -> Self {
+        self.replaces = Some(original);
+        self.signature = self.calculate_signature();
+        self
+    }
+    
+    // See `bundle::bundle`
+    pub fn in_bundle(mut self, position: BundlePosition) -> Self {
+        self.bundle = Some(position);
+        self.signature = self.calculate_signature();
+        self
+    }
+    
+    pub fn is_cancellation(&self) -> bool {
+        self.replaces.is_some() && self.kind == TransactionKind::Transfer && self.from == self.to && self.amount == 0
+    }
+    
+    // Whether a block at `height` stamped `timestamp` is too early to include the transaction
+    pub fn is_locked_at(&self, height: u64, timestamp: DateTime<Utc>) -> bool {
+        self.not_before.is_some_and(|not_before| !not_before.is_reached(height, timestamp))
+    }
+    
+    // Whether a block at `height` stamped `timestamp` is too late to include the transaction
+    pub fn is_expired_at(&self, height: u64, timestamp: DateTime<Utc>) -> bool {
+        self.valid_until.is_some_and(|valid_until| valid_until.has_passed(height, timestamp))
+    }
+    
+    // Signs the content digest, which already commits to every other field
+    pub fn sign(&mut self, keypair: &Keypair) {
+        self.authorization = Some(Authorization {
+            public_key: keypair.public_key(),
+            signature: keypair.sign(self.signature.as_bytes()),
+        });
+    }
+    
+    pub fn countersign(&mut self, keypair: &Keypair) {
+        self.counter_authorization = Some(Authorization {
+            public_key: keypair.public_key(),
+            signature: keypair.sign(self.signature.as_bytes()),
+        });
+    }
+    
+    fn calculate_signature(&self) -> String {
         let mut hasher = Sha256::new();
-        hasher.update(id.as_bytes());
-        hasher.update(from.as_bytes());
-        hasher.update(to.as_bytes());
-        hasher.update(amount.to_le_bytes());
-        hasher.update(timestamp.timestamp().to_le_bytes());
+        hasher.update(self.chain_id.as_bytes());
+        hasher.update(self.id.as_bytes());
+        hasher.update(self.from.as_bytes());
+        hasher.update(self.to.as_bytes());
+        hasher.update(self.amount.to_le_bytes());
+        hasher.update(self.fee.to_le_bytes());
+        hasher.update(serde_json::to_vec(&self.kind).unwrap());
+        hasher.update(self.timestamp.timestamp().to_le_bytes());
+        // Tagged, so the same bound can't be read as the other kind
+        if let Some(valid_until) = &self.valid_until {
+            hasher.update(b"valid_until");
+            hasher.update(serde_json::to_vec(valid_until).unwrap());
+        }
+        if let Some(not_before) = &self.not_before {
+            hasher.update(b"not_before");
+            hasher.update(serde_json::to_vec(not_before).unwrap());
+        }
+        if let Some(original) = &self.replaces {
+            hasher.update(b"replaces");
+            hasher.update(original.as_bytes());
+        }
+        if let Some(position) = &self.bundle {
+            hasher.update(b"bundle");
+            hasher.update(position.bundle.as_bytes());
+            hasher.update(position.index.to_le_bytes());
+            hasher.update(position.size.to_le_bytes());
+        }
+        if !self.data.is_empty() {
+            hasher.update(b"data");
+            hasher.update((self.data.len() as u64).to_le_bytes());
+            hasher.update(&self.data);
+        }
         format!("{:x}", hasher.finalize())
     }
     
     pub fn validate(&self) -> crate::Result<()> {
+        if crate::contracts::ContractStore::is_contract(&self.from) {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Contracts only pay out from inside their own calls".to_string(),
+            ));
+        }
+        
+        if self.fee > 0 && self.from.is_empty() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Fees must be paid by a sender".to_string(),
+            ));
+        }
+        
+        if self.replaces.is_some_and(|original| original == self.id || self.from.is_empty()) {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Replacements need a sender and must name another transaction".to_string(),
+            ));
+        }
+        
+        if let Some(position) = &self.bundle {
+            if position.index >= position.size || position.size > MAX_BUNDLE_SIZE || self.replaces.is_some() {
+                return Err(crate::LedgerError::InvalidTransaction(format!(
+                    "Bundles hold up to {} transactions, none of them replacements",
+                    MAX_BUNDLE_SIZE,
+                )));
+            }
+        }
+        
+        match &self.kind {
+            TransactionKind::Transfer if self.is_cancellation() => {}
+            TransactionKind::Transfer | TransactionKind::Vest(_) => self.validate_transfer()?,
+            TransactionKind::Anchor(anchor) => self.validate_anchor(anchor)?,
+            TransactionKind::Stake { .. } | TransactionKind::Delegate | TransactionKind::Unstake => {
+                self.validate_staking()?
+            }
+            TransactionKind::Evidence(evidence) => self.validate_evidence(evidence)?,
+            TransactionKind::CheckpointVote(vote) => self.validate_checkpoint_vote(vote)?,
+            TransactionKind::Propose(action) => {
+                self.validate_governance()?;
+                action.validate()?
+            }
+            TransactionKind::Vote { .. } => self.validate_governance()?,
+            TransactionKind::Payout(payments) => self.validate_payout(payments)?,
+            TransactionKind::Swap { counter_amount } => self.validate_swap(*counter_amount)?,
+            TransactionKind::Deploy { code } => self.validate_deploy(code)?,
+            TransactionKind::Call { method, gas_limit, .. } => self.validate_call(method, *gas_limit)?,
+            TransactionKind::Schedule { amount, interval } => self.validate_schedule(*amount, *interval)?,
+            TransactionKind::Mint | TransactionKind::Burn => self.validate_issuance()?,
+            TransactionKind::CancelSchedule { .. } => {
+                if self.from.is_empty() || self.amount != 0 || !self.to.is_empty() {
+                    return Err(crate::LedgerError::InvalidTransaction(
+                        "Cancellations need a payer and cannot transfer value".to_string(),
+                    ));
+                }
+            }
+            TransactionKind::Spend { inputs, outputs } => self.validate_spend(inputs, outputs)?,
+            TransactionKind::Approve { .. } => {
+                if self.from.is_empty() || self.to.is_empty() || self.from == self.to || self.amount != 0 {
+                    return Err(crate::LedgerError::InvalidTransaction(
+                        "Approvals need an owner and a different spender, and cannot transfer value".to_string(),
+                    ));
+                }
+            }
+            TransactionKind::TransferFrom { owner } => {
+                self.validate_transfer()?;
+                if owner.is_empty() || *owner == self.from {
+                    return Err(crate::LedgerError::InvalidTransaction(
+                        "Delegated transfers need an owner other than the spender".to_string(),
+                    ));
+                }
+            }
+            TransactionKind::SetSpendingLimit(_) => {
+                if self.from.is_empty() || self.amount != 0 || !self.to.is_empty() {
+                    return Err(crate::LedgerError::InvalidTransaction(
+                        "Spending limits need an account and cannot transfer value".to_string(),
+                    ));
+                }
+            }
+            TransactionKind::HashLock { hash, timeout } => {
+                self.validate_transfer()?;
+                let digest = hash.len() == 64 && hash.bytes().all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'));
+                if !digest || *timeout == 0 {
+                    return Err(crate::LedgerError::InvalidTransaction(
+                        "Hash locks need a lowercase hex SHA-256 digest and a timeout height".to_string(),
+                    ));
+                }
+            }
+            TransactionKind::Claim { .. } | TransactionKind::Refund { .. } => {
+                if self.from.is_empty() || self.amount != 0 || !self.to.is_empty() {
+                    return Err(crate::LedgerError::InvalidTransaction(
+                        "Claims and refunds need a sender and move only the locked funds".to_string(),
+                    ));
+                }
+            }
+            TransactionKind::Coinbase { .. } => {
+                let attached = self.authorization.is_some() || self.bundle.is_some() || !self.data.is_empty();
+                if !self.from.is_empty() || self.to.is_empty() || attached {
+                    return Err(crate::LedgerError::InvalidTransaction(
+                        "Coinbases pay a recipient from no sender, unsigned and with no payload".to_string(),
+                    ));
+                }
+            }
+        }
+        
+        if self.counter_authorization.is_some() && !matches!(self.kind, TransactionKind::Swap { .. }) {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Only swaps are countersigned".to_string(),
+            ));
+        }
+        
+        // Verify signature
+        if self.signature != self.calculate_signature() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Invalid transaction signature".to_string(),
+            ));
+        }
+        
+        for auth in self.authorization.iter().chain(&self.counter_authorization) {
+            if !keys::verify_signature(&auth.public_key, self.signature.as_bytes(), &auth.signature) {
+                return Err(crate::LedgerError::InvalidTransaction(
+                    "Invalid transaction authorization".to_string(),
+                ));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    fn validate_payout(&self, payments: &[Payment]) -> crate::Result<()> {
+        if self.from.is_empty() || !self.to.is_empty() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Payouts need a sender and name recipients only in their payments".to_string(),
+            ));
+        }
+        
+        if payments.is_empty() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Payout has no payments".to_string(),
+            ));
+        }
+        
+        let mut total = 0u64;
+        for payment in payments {
+            if payment.amount == 0 || payment.to.is_empty() || payment.to == self.from {
+                return Err(crate::LedgerError::InvalidTransaction(format!(
+                    "Invalid payment of {} to '{}'",
+                    payment.amount, payment.to,
+                )));
+            }
+            
+            total = total.checked_add(payment.amount).ok_or_else(|| {
+                crate::LedgerError::InvalidTransaction("Payout total overflows".to_string())
+            })?;
+        }
+        
+        if total != self.amount {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Payout amount must equal the sum of its payments".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    fn validate_spend(&self, inputs: &[OutPoint], outputs: &[Payment]) -> crate::Result<()> {
+        if self.from.is_empty() || !self.to.is_empty() || self.authorization.is_none() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Spends must be signed by the owner of their inputs".to_string(),
+            ));
+        }
+        
+        let distinct: std::collections::HashSet<_> = inputs.iter().collect();
+        if inputs.is_empty() || distinct.len() != inputs.len() || outputs.is_empty() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Spends need distinct inputs and at least one output".to_string(),
+            ));
+        }
+        
+        if outputs.len() > u32::MAX as usize || outputs.iter().any(|output| output.amount == 0 || output.to.is_empty()) {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Every output needs an owner and a non-zero amount".to_string(),
+            ));
+        }
+        
+        let total = outputs.iter().try_fold(0u64, |total, output| total.checked_add(output.amount));
+        if total != Some(self.amount) {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Spend amount must equal the sum of its outputs".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    fn validate_deploy(&self, code: &[u8]) -> crate::Result<()> {
+        if self.from.is_empty() || !self.to.is_empty() || code.is_empty() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Deploys need a sender and bytecode, and no recipient".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    fn validate_call(&self, method: &str, gas_limit: u64) -> crate::Result<()> {
+        if self.from.is_empty() || !crate::contracts::ContractStore::is_contract(&self.to) {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Calls need a sender and a contract address".to_string(),
+            ));
+        }
+        
+        if method.is_empty() || gas_limit == 0 {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Calls need a method and a gas limit".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    fn validate_issuance(&self) -> crate::Result<()> {
+        if self.amount == 0 {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Amount must be greater than zero".to_string(),
+            ));
+        }
+        
+        // Mints need a recipient; burns only touch the issuer
+        let recipient = self.kind == TransactionKind::Mint;
+        if self.from.is_empty() || self.to.is_empty() == recipient {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Mints need an issuer and a recipient, burns only an issuer".to_string(),
+            ));
+        }
+        
+        if self.authorization.is_none() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Supply changes must be signed by the issuer's key".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    fn validate_schedule(&self, amount: u64, interval: u64) -> crate::Result<()> {
+        if self.amount != 0 {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Standing orders move nothing when registered".to_string(),
+            ));
+        }
+        
+        if amount == 0 || interval == 0 {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Standing orders need an amount and an interval greater than zero".to_string(),
+            ));
+        }
+        
+        if self.from.is_empty() || self.to.is_empty() || self.from == self.to {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Standing orders need a payer and a different recipient".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    fn validate_swap(&self, counter_amount: u64) -> crate::Result<()> {
+        self.validate_transfer()?;
+        
+        if counter_amount == 0 {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Both legs of a swap must be greater than zero".to_string(),
+            ));
+        }
+        
+        if self.counter_authorization.is_none() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Swaps must be countersigned by the counterparty".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    fn validate_transfer(&self) -> crate::Result<()> {
         if self.amount == 0 {
             return Err(crate::LedgerError::InvalidTransaction(
                 "Amount must be greater than zero".to_string(),
@@ -64,27 +743,170 @@ impl Transaction {
             ));
         }
         
-        // Verify signature
-        let expected_signature = Self::calculate_signature(
-            &self.id,
-            &self.from,
-            &self.to,
-            self.amount,
-            &self.timestamp,
-        );
+        Ok(())
+    }
+    
+    fn validate_anchor(&self, anchor: &Anchor) -> crate::Result<()> {
+        if self.from.is_empty() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Anchor submitter cannot be empty".to_string(),
+            ));
+        }
         
-        if self.signature != expected_signature {
+        if self.amount != 0 || !self.to.is_empty() {
             return Err(crate::LedgerError::InvalidTransaction(
-                "Invalid transaction signature".to_string(),
+                "Anchor transactions cannot transfer value".to_string(),
+            ));
+        }
+        
+        anchor.validate()
+    }
+    
+    fn validate_staking(&self) -> crate::Result<()> {
+        if self.amount == 0 {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Amount must be greater than zero".to_string(),
+            ));
+        }
+        
+        if self.from.is_empty() || self.to.is_empty() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Staker and validator cannot be empty".to_string(),
+            ));
+        }
+        
+        match self.kind {
+            TransactionKind::Stake { .. } if self.from != self.to => Err(crate::LedgerError::InvalidTransaction(
+                "Validators can only stake for themselves; use a delegation".to_string(),
+            )),
+            TransactionKind::Stake { .. } if self.authorization.is_none() => Err(crate::LedgerError::InvalidTransaction(
+                "Stake must be signed by the validator's key".to_string(),
+            )),
+            TransactionKind::Delegate if self.from == self.to => Err(crate::LedgerError::InvalidTransaction(
+                "Validators bond their own funds with a stake".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+    
+    fn validate_evidence(&self, evidence: &Evidence) -> crate::Result<()> {
+        if self.from.is_empty() || self.to.is_empty() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Evidence needs a reporter and an accused validator".to_string(),
+            ));
+        }
+        
+        if self.amount != 0 {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Evidence transactions cannot transfer value".to_string(),
+            ));
+        }
+        
+        evidence.validate()
+    }
+    
+    fn validate_checkpoint_vote(&self, vote: &CheckpointVote) -> crate::Result<()> {
+        if self.from.is_empty() || vote.hash.is_empty() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Checkpoint votes need a validator and a block hash".to_string(),
+            ));
+        }
+        
+        if self.amount != 0 || !self.to.is_empty() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Checkpoint votes cannot transfer value".to_string(),
+            ));
+        }
+        
+        if self.authorization.is_none() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Checkpoint votes must be signed by the validator's key".to_string(),
             ));
         }
         
         Ok(())
     }
     
-    pub fn hash(&self) -> String {
+    fn validate_governance(&self) -> crate::Result<()> {
+        if self.from.is_empty() || self.amount != 0 || !self.to.is_empty() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Governance transactions need a validator and cannot transfer value".to_string(),
+            ));
+        }
+        
+        if self.authorization.is_none() {
+            return Err(crate::LedgerError::InvalidTransaction(
+                "Governance transactions must be signed by the validator's key".to_string(),
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    // Bytes on the wire, as framed by IPC
+    pub fn encoded_size(&self) -> usize {
+        bincode::serialized_size(self).map_or(usize::MAX, |size| size as usize)
+    }
+    
+    // What fee rates and block size limits measure a transaction by: the size of its canonical encoding, which
+    // every node computes the same
+    pub fn weight(&self) -> usize {
+        self.encoded_size()
+    }
+    
+    // Gas a call may burn; zero for everything else
+    pub fn gas_limit(&self) -> u64 {
+        match self.kind {
+            TransactionKind::Call { gas_limit, .. } => gas_limit,
+            _ => 0,
+        }
+    }
+    
+    // Fee per byte, in millionths so rates can be compared exactly
+    pub fn fee_rate(&self) -> u128 {
+        self.fee_rate_at(self.weight())
+    }
+    
+    // `fee_rate` for a caller that kept the weight, e.g. from `digest_and_weight`
+    pub fn fee_rate_at(&self, weight: usize) -> u128 {
+        self.fee as u128 * 1_000_000 / weight.max(1) as u128
+    }
+    
+    // Over the canonical encoding, streamed into the hasher rather than built up first. Every field is public, so an
+    // edited transaction must hash afresh; blocks and the mempool keep what they worked out instead
+    pub fn digest(&self) -> [u8; 32] {
         let mut hasher = Sha256::new();
-        hasher.update(serde_json::to_string(self).unwrap().as_bytes());
-        format!("{:x}", hasher.finalize())
+        bincode::serialize_into(&mut hasher, self).unwrap();
+        hasher.finalize().into()
+    }
+    
+    // `digest` and `weight` from one pass over the encoding, for callers that keep both
+    pub fn digest_and_weight(&self) -> ([u8; 32], usize) {
+        let mut encoding = Counted { hasher: Sha256::new(), bytes: 0 };
+        bincode::serialize_into(&mut encoding, self).unwrap();
+        (encoding.hasher.finalize().into(), encoding.bytes)
+    }
+    
+    // `digest` written out in hex, as shown to users
+    pub fn hash(&self) -> String {
+        hex::encode(self.digest())
+    }
+}
+
+// The hasher a transaction is encoded into, counting the bytes that pass through
+struct Counted {
+    hasher: Sha256,
+    bytes: usize,
+}
+
+impl std::io::Write for Counted {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.hasher.update(buf);
+        self.bytes += buf.len();
+        Ok(buf.len())
     }
-}
\ No newline at end of file
+    
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// How value is held. Under `Utxo`, genesis funds become coins and only spends and fee-less anchors are accepted;
+// staking and the other account-based kinds are refused, and fees are burned since no coin receives them
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum LedgerModel {
+    #[default]
+    Account,
+    Utxo,
+}
+
+// Output `index` of transaction `tx`; genesis coins use the genesis block id
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OutPoint {
+    pub tx: Uuid,
+    pub index: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Coin {
+    pub owner: String,
+    pub amount: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct UtxoSet {
+    coins: DashMap<OutPoint, Coin>,
+    // Coins each committed spend consumed, so a reorg can restore them
+    spent: DashMap<Uuid, Vec<(OutPoint, Coin)>>,
+}
+
+impl UtxoSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn get(&self, outpoint: &OutPoint) -> Option<Coin> {
+        self.coins.get(outpoint).map(|coin| coin.value().clone())
+    }
+    
+    pub fn owned_by(&self, owner: &str) -> Vec<(OutPoint, Coin)> {
+        let mut coins: Vec<_> = self.coins.iter()
+            .filter(|coin| coin.owner == owner)
+            .map(|coin| (*coin.key(), coin.value().clone()))
+            .collect();
+        coins.sort_by_key(|(outpoint, _)| *outpoint);
+        coins
+    }
+    
+    pub fn len(&self) -> usize {
+        self.coins.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.coins.is_empty()
+    }
+    
+    pub(crate) fn create(&self, outpoint: OutPoint, coin: Coin) {
+        self.coins.insert(outpoint, coin);
+    }
+    
+    pub(crate) fn destroy(&self, outpoint: &OutPoint) {
+        self.coins.remove(outpoint);
+    }
+    
+    pub(crate) fn spend(&self, tx: Uuid, inputs: &[OutPoint]) {
+        let consumed = inputs.iter()
+            .filter_map(|outpoint| self.coins.remove(outpoint))
+            .collect();
+        self.spent.insert(tx, consumed);
+    }
+    
+    pub(crate) fn unspend(&self, tx: Uuid) {
+        if let Some((_, consumed)) = self.spent.remove(&tx) {
+            for (outpoint, coin) in consumed {
+                self.coins.insert(outpoint, coin);
+            }
+        }
+    }
+}
\ No newline at end of file
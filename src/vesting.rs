@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Nothing unlocks before height `cliff`; from there the grant unlocks linearly over `duration` blocks
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct VestingSchedule {
+    pub cliff: u64,
+    pub duration: u64,
+}
+
+impl VestingSchedule {
+    pub fn unlocked(&self, amount: u64, height: u64) -> u64 {
+        if height < self.cliff {
+            return 0;
+        }
+        
+        let elapsed = height - self.cliff;
+        if elapsed >= self.duration {
+            return amount;
+        }
+        (amount as u128 * elapsed as u128 / self.duration as u128) as u64
+    }
+    
+    pub fn locked(&self, amount: u64, height: u64) -> u64 {
+        amount - self.unlocked(amount, height)
+    }
+}
+
+// Funds credited to `account` that only become spendable on `schedule`; `id` is the granting transaction, or the
+// genesis block for allocations
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VestingGrant {
+    pub id: Uuid,
+    pub account: String,
+    pub amount: u64,
+    pub schedule: VestingSchedule,
+}
+
+// An account's balance split by what its grants still lock at some height
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct VestingBalance {
+    pub locked: u64,
+    pub liquid: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct Vesting {
+    grants: RwLock<HashMap<String, Vec<VestingGrant>>>,
+}
+
+impl Vesting {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn grants(&self, account: &str) -> Vec<VestingGrant> {
+        self.grants.read().unwrap().get(account).cloned().unwrap_or_default()
+    }
+    
+    pub fn locked(&self, account: &str, height: u64) -> u64 {
+        self.grants.read().unwrap()
+            .get(account)
+            .map(|grants| grants.iter().map(|grant| grant.schedule.locked(grant.amount, height)).sum())
+            .unwrap_or(0)
+    }
+    
+    pub(crate) fn grant(&self, grant: VestingGrant) {
+        self.grants.write().unwrap().entry(grant.account.clone()).or_default().push(grant);
+    }
+    
+    pub(crate) fn revoke(&self, account: &str, id: Uuid) {
+        let mut grants = self.grants.write().unwrap();
+        if let Some(held) = grants.get_mut(account) {
+            held.retain(|grant| grant.id != id);
+            if held.is_empty() {
+                grants.remove(account);
+            }
+        }
+    }
+}
\ No newline at end of file
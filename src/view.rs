@@ -0,0 +1,69 @@
+use std::ops::RangeBounds;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::chain::Chain;
+use crate::history::{AccountEntry, Page};
+use crate::status::TransactionStatus;
+use crate::{Block, DistributedLedger, Transaction};
+
+// The chain, committed balances and transaction pool as they stood at one instant, for a run of queries that must
+// agree with each other; see `DistributedLedger::read_view`. Blocks keep applying while a view is held; the view
+// just doesn't see them
+pub struct ReadView<'a> {
+    ledger: &'a DistributedLedger,
+    blocks: Arc<Chain>,
+    pending: Vec<Transaction>,
+}
+
+impl<'a> ReadView<'a> {
+    pub(crate) fn new(
+        ledger: &'a DistributedLedger,
+        blocks: Arc<Chain>,
+        pending: Vec<Transaction>,
+    ) -> Self {
+        Self { ledger, blocks, pending }
+    }
+    
+    pub fn height(&self) -> u64 {
+        self.blocks.len() as u64 - 1
+    }
+    
+    pub fn tip(&self) -> &Block {
+        self.blocks.last().unwrap()
+    }
+    
+    pub fn block(&self, height: u64) -> Option<&Block> {
+        self.blocks.get(height as usize).map(Arc::as_ref)
+    }
+    
+    // Committed balance as of the view's tip, zero for an account that holds nothing; `None` once the state at that tip
+    // has been pruned, which a view held while the chain moves well past it can see
+    pub fn balance(&self, address: &str) -> Option<u64> {
+        self.ledger.state().balance_at(address, self.height())
+    }
+    
+    // Pooled transactions in processing order
+    pub fn pending(&self) -> &[Transaction] {
+        &self.pending
+    }
+    
+    pub fn pending_for(&self, address: &str) -> Vec<&Transaction> {
+        self.pending.iter().filter(|tx| tx.from == address).collect()
+    }
+    
+    // As `DistributedLedger::get_transaction`, except that rejections are the only part read live
+    pub fn transaction(&self, id: Uuid) -> (Option<Transaction>, TransactionStatus) {
+        if let Some((tx, status)) = self.ledger.committed_transaction(&self.blocks, id) {
+            return (Some(tx), status);
+        }
+        match self.pending.iter().find(|tx| tx.id == id) {
+            Some(tx) => (Some(tx.clone()), TransactionStatus::Pending),
+            None => self.ledger.rejected_transaction(id),
+        }
+    }
+    
+    pub fn account_history(&self, address: &str, range: impl RangeBounds<u64>, page: Page) -> Vec<AccountEntry> {
+        self.ledger.account_history(&self.blocks, address, range, page)
+    }
+}
\ No newline at end of file
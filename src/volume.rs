@@ -0,0 +1,117 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Block, TransactionKind};
+
+const HOUR_SECONDS: i64 = 3600;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TimeBucket {
+    Hour,
+    Day,
+}
+
+impl TimeBucket {
+    fn hours(&self) -> i64 {
+        match self {
+            TimeBucket::Hour => 1,
+            TimeBucket::Day => 24,
+        }
+    }
+}
+
+// Main-chain transactions whose block was timestamped within the bucket starting at `start`, in UTC
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VolumeBucket {
+    pub start: DateTime<Utc>,
+    pub volume: u128,
+    pub transactions: u64,
+}
+
+// Volume and transaction count by hour since the epoch
+type Totals = BTreeMap<i64, (u128, u64)>;
+
+#[derive(Debug, Default)]
+struct Volumes {
+    all: Totals,
+    by_address: HashMap<String, Totals>,
+}
+
+// Hourly transfer totals for the whole chain and for each account, updated as blocks join and leave the main chain
+// so reports never scan it; coinbases are issuance rather than transfers and are left out
+#[derive(Debug, Default)]
+pub struct VolumeIndex {
+    volumes: RwLock<Volumes>,
+}
+
+impl VolumeIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // Oldest bucket first, skipping buckets without transactions; all accounts unless `address` is given
+    pub fn aggregate(&self, address: Option<&str>, bucket: TimeBucket) -> Vec<VolumeBucket> {
+        let volumes = self.volumes.read().unwrap();
+        let totals = match address {
+            Some(address) => match volumes.by_address.get(address) {
+                Some(totals) => totals,
+                None => return Vec::new(),
+            },
+            None => &volumes.all,
+        };
+        
+        let mut buckets: Vec<VolumeBucket> = Vec::new();
+        for (hour, (volume, transactions)) in totals {
+            let start = hour.div_euclid(bucket.hours()) * bucket.hours() * HOUR_SECONDS;
+            match buckets.last_mut() {
+                Some(last) if last.start.timestamp() == start => {
+                    last.volume += volume;
+                    last.transactions += transactions;
+                }
+                _ => buckets.push(VolumeBucket {
+                    start: DateTime::from_timestamp(start, 0).unwrap_or_default(),
+                    volume: *volume,
+                    transactions: *transactions,
+                }),
+            }
+        }
+        buckets
+    }
+    
+    pub(crate) fn insert(&self, block: &Block) {
+        self.apply(block, |totals, hour, amount| {
+            let entry = totals.entry(hour).or_default();
+            entry.0 += amount as u128;
+            entry.1 += 1;
+        });
+    }
+    
+    pub(crate) fn remove(&self, block: &Block) {
+        self.apply(block, |totals, hour, amount| {
+            if let Some(entry) = totals.get_mut(&hour) {
+                entry.0 = entry.0.saturating_sub(amount as u128);
+                entry.1 = entry.1.saturating_sub(1);
+                if entry.1 == 0 {
+                    totals.remove(&hour);
+                }
+            }
+        });
+    }
+    
+    fn apply(&self, block: &Block, update: impl Fn(&mut Totals, i64, u64)) {
+        let hour = block.header.timestamp.timestamp().div_euclid(HOUR_SECONDS);
+        let mut volumes = self.volumes.write().unwrap();
+        for tx in block.transactions.iter().filter(|tx| !matches!(tx.kind, TransactionKind::Coinbase { .. })) {
+            update(&mut volumes.all, hour, tx.amount);
+            for party in crate::history::parties(tx) {
+                let totals = volumes.by_address.entry(party.to_string()).or_default();
+                update(totals, hour, tx.amount);
+                if totals.is_empty() {
+                    volumes.by_address.remove(party);
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
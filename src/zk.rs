@@ -0,0 +1,35 @@
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Groth16, Proof, VerifyingKey};
+use ark_serialize::CanonicalDeserialize;
+
+use crate::{LedgerError, Result};
+
+// Verifies a Groth16 proof over BN254. Keys, proofs and public inputs use arkworks'
+// compressed canonical encoding.
+pub fn verify_groth16(
+    verifying_key: &[u8],
+    proof: &[u8],
+    public_inputs: &[Vec<u8>],
+) -> Result<bool> {
+    let vk = VerifyingKey::<Bn254>::deserialize_compressed(verifying_key)
+        .map_err(|e| LedgerError::InvalidProof(format!("Malformed verifying key: {}", e)))?;
+    let proof = Proof::<Bn254>::deserialize_compressed(proof)
+        .map_err(|e| LedgerError::InvalidProof(format!("Malformed proof: {}", e)))?;
+    
+    let inputs = public_inputs.iter()
+        .map(|input| Fr::deserialize_compressed(input.as_slice()))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| LedgerError::InvalidProof(format!("Malformed public input: {}", e)))?;
+    
+    if inputs.len() + 1 != vk.gamma_abc_g1.len() {
+        return Err(LedgerError::InvalidProof(format!(
+            "Expected {} public inputs, got {}",
+            vk.gamma_abc_g1.len().saturating_sub(1),
+            inputs.len(),
+        )));
+    }
+    
+    let pvk = ark_groth16::prepare_verifying_key(&vk);
+    Groth16::<Bn254>::verify_proof(&pvk, &proof, &inputs)
+        .map_err(|e| LedgerError::InvalidProof(e.to_string()))
+}
\ No newline at end of file
@@ -0,0 +1,64 @@
+use distributed_ledger::{AdminApi, LedgerError};
+
+use crate::common::TestNode;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn batch_size_is_fixed_only_to_a_positive_value() {
+    let node = TestNode::new("it-admin-batch");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    
+    assert!(matches!(admin.set_batch_size("secret", 0), Err(LedgerError::InvalidTransaction(_))));
+    assert!(matches!(admin.set_batch_size("wrong", 3), Err(LedgerError::Unauthorized(_))));
+    assert_ne!(node.ledger.batch_size(), 3);
+    
+    admin.set_batch_size("secret", 3).unwrap();
+    assert_eq!(node.ledger.batch_size(), 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn draining_the_mempool_drops_and_unreserves_everything_pending() {
+    let node = TestNode::new("it-admin-drain");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    node.ledger.add_transaction(node.transfer("alice", "bob", 10)).await.unwrap();
+    node.ledger.add_transaction(node.transfer("charlie", "bob", 20)).await.unwrap();
+    
+    assert!(matches!(admin.drain_mempool("wrong"), Err(LedgerError::Unauthorized(_))));
+    assert_eq!(node.ledger.mempool_size(), 2);
+    
+    assert_eq!(admin.drain_mempool("secret").unwrap(), 2);
+    assert_eq!(node.ledger.mempool_size(), 0);
+    assert_eq!(node.ledger.reserved_balance("alice"), 0);
+    assert_eq!(node.ledger.reserved_balance("charlie"), 0);
+    
+    // Nothing is left for a block to take
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_000);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn refused_snapshot_requests_write_nothing() {
+    let node = TestNode::new("it-admin-snapshot");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    
+    let path = node.path("snapshot.json");
+    assert!(matches!(admin.trigger_snapshot("wrong", &path).await, Err(LedgerError::Unauthorized(_))));
+    assert!(!path.exists());
+    admin.trigger_snapshot("secret", &path).await.unwrap();
+    assert!(path.exists());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_rotated_token_replaces_the_old_one() {
+    let node = TestNode::new("it-admin-rotate");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    
+    assert!(matches!(admin.rotate_token("secret", ""), Err(LedgerError::Unauthorized(_))));
+    assert!(matches!(admin.rotate_token("wrong", "rotated"), Err(LedgerError::Unauthorized(_))));
+    admin.pause_block_production("secret").unwrap();
+    
+    admin.rotate_token("secret", "rotated").unwrap();
+    assert!(matches!(admin.resume_block_production("secret"), Err(LedgerError::Unauthorized(_))));
+    assert!(node.ledger.is_paused());
+    admin.resume_block_production("rotated").unwrap();
+    assert!(!node.ledger.is_paused());
+}
@@ -0,0 +1,59 @@
+use distributed_ledger::{Anchor, LedgerError, SearchResult, Transaction};
+
+use crate::common::TestNode;
+
+fn anchor(node: &TestNode, sequence: u64, state_root: &str) -> Transaction {
+    let anchor = Anchor::new("rollup".to_string(), sequence, state_root.to_string(), "b".repeat(64));
+    let mut tx = Transaction::anchor("alice".to_string(), anchor).for_chain(&node.genesis.chain_id);
+    tx.sign(&node.genesis.dev_keypair("alice").unwrap());
+    tx
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn committed_anchors_come_with_a_proof_checkable_against_their_block() {
+    let node = TestNode::new("it-anchor");
+    assert_eq!(node.ledger.latest_anchor_sequence("rollup"), None);
+    
+    node.ledger.add_transaction(anchor(&node, 0, &"a".repeat(64))).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.latest_anchor_sequence("rollup"), Some(0));
+    
+    // Not final yet, so only handed out on request
+    assert!(node.ledger.get_anchor_proof("rollup", 0, false).await.is_none());
+    let proof = node.ledger.get_anchor_proof("rollup", 0, true).await.unwrap();
+    assert_eq!(proof.block_height, 1);
+    assert_eq!(proof.anchor.state_root, "a".repeat(64));
+    let Some(SearchResult::Block { block, .. }) = node.ledger.search("1").await else {
+        panic!("no block at height 1");
+    };
+    assert!(proof.verify(&block));
+    
+    // Checked against another block, or claiming another root, the proof fails
+    let Some(SearchResult::Block { block: genesis, .. }) = node.ledger.search("0").await else {
+        panic!("no genesis block");
+    };
+    assert!(!proof.verify(&genesis));
+    let mut forged = proof.clone();
+    forged.anchor.state_root = "c".repeat(64);
+    assert!(!forged.verify(&block));
+    assert!(node.ledger.get_anchor_proof("rollup", 1, true).await.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn malformed_or_repeated_anchors_are_refused() {
+    let node = TestNode::new("it-anchor-refused");
+    let malformed = node.ledger.add_transaction(anchor(&node, 0, "not-a-digest")).await;
+    assert!(matches!(malformed, Err(LedgerError::InvalidTransaction(_))));
+    
+    node.ledger.add_transaction(anchor(&node, 0, &"a".repeat(64))).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    
+    // A sequence is anchored once; a later batch takes the next one
+    let repeated = node.ledger.add_transaction(anchor(&node, 0, &"d".repeat(64))).await;
+    assert!(matches!(repeated, Err(LedgerError::InvalidTransaction(_))));
+    let proof = node.ledger.get_anchor_proof("rollup", 0, true).await.unwrap();
+    assert_eq!(proof.anchor.state_root, "a".repeat(64));
+    node.ledger.add_transaction(anchor(&node, 1, &"d".repeat(64))).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.latest_anchor_sequence("rollup"), Some(1));
+}
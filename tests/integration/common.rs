@@ -0,0 +1,71 @@
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use distributed_ledger::{DistributedLedger, GenesisConfig, Transaction};
+
+pub const WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct TestNode {
+    pub ledger: DistributedLedger,
+    pub genesis: GenesisConfig,
+    pub dir: PathBuf,
+}
+
+impl TestNode {
+    pub fn new(seed: &str) -> Self {
+        let genesis = GenesisConfig::dev(seed);
+        let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+        
+        let dir = std::env::temp_dir().join(format!("ledger-it-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        
+        Self { ledger, genesis, dir }
+    }
+    
+    pub async fn start(seed: &str) -> Self {
+        let node = Self::new(seed);
+        node.ledger.start_background_processor().await;
+        node
+    }
+    
+    // Another node built from the same genesis, e.g. to stand in for a restart
+    pub fn sibling(&self) -> DistributedLedger {
+        DistributedLedger::from_genesis(self.genesis.clone()).unwrap()
+    }
+    
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+    
+    pub fn transfer(&self, from: &str, to: &str, amount: u64) -> Transaction {
+        self.transfer_with_fee(from, to, amount, 0)
+    }
+    
+    pub fn transfer_with_fee(&self, from: &str, to: &str, amount: u64, fee: u64) -> Transaction {
+        let mut tx = Transaction::new(from.to_string(), to.to_string(), amount)
+            .for_chain(&self.genesis.chain_id)
+            .with_fee(fee);
+        tx.sign(&self.genesis.dev_keypair(from).unwrap());
+        tx
+    }
+}
+
+impl Drop for TestNode {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+pub async fn wait_for<F, Fut>(mut condition: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let deadline = tokio::time::Instant::now() + WAIT_TIMEOUT;
+    
+    while !condition().await {
+        assert!(tokio::time::Instant::now() < deadline, "condition not met within {:?}", WAIT_TIMEOUT);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use distributed_ledger::{Block, ChainParams, Consensus, DistributedLedger, LedgerError, ProofOfWork, SearchResult};
+
+use crate::common::TestNode;
+
+// Proof-of-work that only proposes, and only lets blocks onto the chain, while its switches allow
+struct SwitchedMiner {
+    proposing: AtomicBool,
+    finalizing: AtomicBool,
+}
+
+impl Consensus for SwitchedMiner {
+    fn name(&self) -> &'static str {
+        "switched-proof-of-work"
+    }
+    
+    fn can_propose(&self, _parent: &Block) -> bool {
+        self.proposing.load(Ordering::SeqCst)
+    }
+    
+    fn propose(&self, parent: &Block, block: Block, params: &ChainParams) -> distributed_ledger::Result<Block> {
+        ProofOfWork.propose(parent, block, params)
+    }
+    
+    fn validate(&self, block: &Block, parent: &Block, params: &ChainParams) -> distributed_ledger::Result<()> {
+        ProofOfWork.validate(block, parent, params)
+    }
+    
+    fn finalize(&self, _block: &Block) -> distributed_ledger::Result<()> {
+        if !self.finalizing.load(Ordering::SeqCst) {
+            return Err(LedgerError::Consensus("Not finalizing".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn engines_decide_when_to_propose_and_can_veto_an_append() {
+    let node = TestNode::new("it-consensus");
+    let engine = Arc::new(SwitchedMiner { proposing: AtomicBool::new(false), finalizing: AtomicBool::new(false) });
+    let ledger = DistributedLedger::with_consensus(node.genesis.clone(), engine.clone()).unwrap();
+    ledger.add_transaction(node.transfer("alice", "bob", 10)).await.unwrap();
+    
+    // Not this node's slot: nothing is produced and the transaction keeps waiting
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!((ledger.chain_height(), ledger.mempool_size()), (0, 1));
+    
+    // A refused append leaves the chain and balances as they were, and the batch queued again
+    engine.proposing.store(true, Ordering::SeqCst);
+    assert!(matches!(ledger.process_transactions(10).await, Err(LedgerError::Consensus(_))));
+    assert_eq!((ledger.chain_height(), ledger.mempool_size()), (0, 1));
+    assert_eq!(ledger.get_balance("bob").await, 1_000_000);
+    
+    engine.finalizing.store(true, Ordering::SeqCst);
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!((ledger.chain_height(), ledger.mempool_size()), (1, 0));
+    assert_eq!(ledger.get_balance("bob").await, 1_000_010);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn proof_of_work_refuses_blocks_off_the_difficulty_target() {
+    let node = TestNode::new("it-consensus-pow");
+    let producer = node.sibling();
+    let difficulty = node.genesis.params.difficulty;
+    let Some(SearchResult::Block { block: genesis, .. }) = node.ledger.search("0").await else {
+        panic!("no genesis block");
+    };
+    
+    let mut easier = Block::child_of(&genesis, Vec::new());
+    easier.mine(difficulty - 1);
+    assert!(matches!(node.ledger.import_block(easier).await, Err(LedgerError::BlockValidationFailed(_))));
+    
+    // Claims the difficulty, but its hash misses the target
+    let mut unmined = Block::child_of(&genesis, Vec::new());
+    while unmined.header.hash.starts_with(&"0".repeat(difficulty)) {
+        unmined.header.nonce += 1;
+        unmined.header.hash = unmined.header.calculate_hash();
+    }
+    assert!(matches!(node.ledger.import_block(unmined).await, Err(LedgerError::BlockValidationFailed(_))));
+    assert_eq!(node.ledger.chain_height(), 0);
+    
+    producer.add_transaction(node.transfer("alice", "bob", 10)).await.unwrap();
+    producer.process_transactions(10).await.unwrap();
+    node.ledger.import_block(producer.get_latest_block().await).await.unwrap();
+    assert_eq!(node.ledger.chain_height(), 1);
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_010);
+}
@@ -0,0 +1,90 @@
+use distributed_ledger::{ContractStore, LedgerError, LogFilter, Transaction};
+
+use crate::common::TestNode;
+
+// Counts its calls, refunds 5 to whoever asks and spins forever on request
+const COUNTER: &str = r#"
+(module
+  (import "env" "storage_read" (func $read (param i32 i32 i32 i32) (result i32)))
+  (import "env" "storage_write" (func $write (param i32 i32 i32 i32)))
+  (import "env" "emit" (func $emit (param i32 i32 i32 i32)))
+  (import "env" "transfer" (func $transfer (param i32 i32 i64) (result i32)))
+  (import "env" "caller" (func $caller (param i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "count")
+  (func (export "increment")
+    (drop (call $read (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 8)))
+    (i64.store (i32.const 16) (i64.add (i64.load (i32.const 16)) (i64.const 1)))
+    (call $write (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 8))
+    (call $emit (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 8)))
+  (func (export "refund")
+    (local $len i32)
+    (local.set $len (call $caller (i32.const 32) (i32.const 64)))
+    (drop (call $transfer (i32.const 32) (local.get $len) (i64.const 5))))
+  (func (export "increment_then_spin")
+    (call $write (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 8))
+    (loop $forever (br $forever))))
+"#;
+
+fn signed(node: &TestNode, from: &str, tx: Transaction) -> Transaction {
+    let mut tx = tx.for_chain(&node.genesis.chain_id);
+    tx.sign(&node.genesis.dev_keypair(from).unwrap());
+    tx
+}
+
+async fn commit(node: &TestNode, tx: Transaction) -> Transaction {
+    node.ledger.add_transaction(tx.clone()).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    tx
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn deployed_contracts_keep_state_pay_out_and_run_out_of_gas() {
+    let node = TestNode::new("it-contracts");
+    let code = wat::parse_str(COUNTER).unwrap();
+    
+    let garbage = signed(&node, "alice", Transaction::deploy("alice".to_string(), vec![0, 1, 2]));
+    assert!(matches!(node.ledger.add_transaction(garbage).await, Err(LedgerError::InvalidTransaction(_))));
+    
+    let deploy = commit(&node, signed(&node, "alice", Transaction::deploy("alice".to_string(), code))).await;
+    let contract = ContractStore::address_for(&deploy);
+    assert!(node.ledger.is_deployed(&contract));
+    
+    let call = |method: &str, amount: u64, gas_limit: u64| {
+        let tx = Transaction::call("bob".to_string(), contract.clone(), amount, method.to_string(), Vec::new(), gas_limit);
+        signed(&node, "bob", tx)
+    };
+    
+    commit(&node, call("increment", 0, 100_000)).await;
+    let second = commit(&node, call("increment", 0, 100_000)).await;
+    assert_eq!(node.ledger.contract_storage(&contract, b"count"), Some(2u64.to_le_bytes().to_vec()));
+    
+    let receipt = node.ledger.contract_receipt(second.id).unwrap();
+    assert!(receipt.success);
+    assert!(receipt.gas_used > 0);
+    let events = node.ledger.get_logs(&LogFilter {
+        address: Some("contract".to_string()),
+        topics: vec![Some("event".to_string()), Some(contract.clone())],
+        include_unfinalized: true,
+        ..LogFilter::default()
+    }).await;
+    assert_eq!(events.len(), 2);
+    
+    // Bob sends 20 in and asks for 5 back
+    commit(&node, call("refund", 20, 100_000)).await;
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_000 - 15);
+    assert_eq!(node.ledger.get_balance(&contract).await, 15);
+    
+    // Out of gas: the write is discarded, the value returned, and the contract stays usable
+    let spin = commit(&node, call("increment_then_spin", 10, 10_000)).await;
+    let receipt = node.ledger.contract_receipt(spin.id).unwrap();
+    assert!(!receipt.success);
+    assert!(receipt.error.is_some() && receipt.gas_used > 9_900);
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_000 - 15);
+    assert_eq!(node.ledger.contract_storage(&contract, b"count"), Some(2u64.to_le_bytes().to_vec()));
+    
+    let stolen = Transaction::new(contract.clone(), "eve".to_string(), 15);
+    assert!(matches!(node.ledger.add_transaction(stolen).await, Err(LedgerError::InvalidTransaction(_))));
+    let missing = Transaction::call("bob".to_string(), "contract:00".to_string(), 0, "increment".to_string(), Vec::new(), 1);
+    assert!(matches!(node.ledger.add_transaction(signed(&node, "bob", missing)).await, Err(LedgerError::InvalidTransaction(_))));
+}
\ No newline at end of file
@@ -0,0 +1,73 @@
+use distributed_ledger::{BlockImport, DifficultyAdjustment, DistributedLedger, GenesisConfig, LedgerError};
+
+use crate::common::TestNode;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn difficulty_rises_when_blocks_come_too_fast_and_peers_enforce_it() {
+    let mut genesis = GenesisConfig::dev("it-difficulty");
+    genesis.params.difficulty = 1;
+    genesis.params.difficulty_adjustment = Some(DifficultyAdjustment {
+        target_block_interval_ms: 60_000,
+        window: 2,
+        min_difficulty: 1,
+        max_difficulty: 3,
+    });
+    
+    let producer = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let follower = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let alice = genesis.dev_keypair("alice").unwrap();
+    
+    let mut difficulties = Vec::new();
+    for amount in 1..=8 {
+        let mut tx = distributed_ledger::Transaction::new("alice".to_string(), "bob".to_string(), amount);
+        tx.sign(&alice);
+        producer.add_transaction(tx).await.unwrap();
+        producer.process_transactions(10).await.unwrap();
+        
+        let block = producer.get_latest_block().await;
+        difficulties.push(block.header.difficulty);
+        assert_eq!(follower.import_block(block).await.unwrap(), BlockImport::Extended);
+    }
+    
+    assert_eq!(difficulties, vec![1, 1, 1, 2, 2, 3, 3, 3]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_block_claiming_a_lower_difficulty() {
+    let node = TestNode::new("it-difficulty-cheat");
+    let rival = node.sibling();
+    
+    rival.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    rival.process_transactions(10).await.unwrap();
+    
+    let mut block = rival.get_latest_block().await;
+    block.mine(1);
+    
+    let result = node.ledger.import_block(block).await;
+    assert!(matches!(result, Err(LedgerError::BlockValidationFailed(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mining_on_several_threads_finds_the_lowest_nonce_and_reports_the_hash_rate() {
+    let mut genesis = GenesisConfig::dev("it-difficulty-threads");
+    genesis.params.difficulty = 3;
+    let producer = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let follower = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let alice = genesis.dev_keypair("alice").unwrap();
+    
+    let mut tx = distributed_ledger::Transaction::new("alice".to_string(), "bob".to_string(), 5);
+    tx.sign(&alice);
+    producer.add_transaction(tx).await.unwrap();
+    producer.process_transactions(10).await.unwrap();
+    let mined = producer.get_latest_block().await;
+    assert!(producer.get_performance_stats().hash_rate > 0.0);
+    
+    for threads in [1, 4] {
+        let mut block = mined.clone();
+        block.header.nonce = 0;
+        let tried = block.mine_with_threads(3, threads);
+        assert!(tried > block.header.nonce);
+        assert_eq!(block.header, mined.header);
+    }
+    assert_eq!(follower.import_block(mined).await.unwrap(), BlockImport::Extended);
+}
\ No newline at end of file
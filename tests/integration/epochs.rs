@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use distributed_ledger::{
+    ChainParams, DistributedLedger, Epoch, EpochEffects, EpochHook, EpochLength, EpochRewards, EpochSchedule,
+    GenesisConfig, SearchResult, StakeRegistry, Transaction,
+};
+
+struct ShrinkBlocksEachEra;
+
+impl EpochHook for ShrinkBlocksEachEra {
+    fn name(&self) -> &'static str {
+        "shrink-blocks"
+    }
+    
+    fn on_epoch(&self, epoch: &Epoch, previous: &Epoch, _stakes: &StakeRegistry, params: &ChainParams) -> EpochEffects {
+        let mut effects = EpochEffects::default();
+        if epoch.opens_era(previous) {
+            effects.params = Some(ChainParams { max_block_transactions: params.max_block_transactions / 2, ..params.clone() });
+        }
+        effects
+    }
+}
+
+fn node(genesis: &GenesisConfig) -> DistributedLedger {
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    ledger.add_epoch_hook(Arc::new(EpochRewards { per_epoch: 400 }));
+    ledger.add_epoch_hook(Arc::new(ShrinkBlocksEachEra));
+    ledger
+}
+
+async fn produce(ledger: &DistributedLedger, genesis: &GenesisConfig, to: &str, blocks: u64) {
+    for amount in 1..=blocks {
+        let mut tx = Transaction::new("eve".into(), to.into(), amount);
+        tx.sign(&genesis.dev_keypair("eve").unwrap());
+        ledger.add_transaction(tx).await.unwrap();
+        ledger.process_transactions(10).await.unwrap();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn epoch_hooks_pay_rewards_and_change_params_and_unwind_on_reorg() {
+    let dev = GenesisConfig::dev("it-epochs");
+    let mut genesis = dev.clone()
+        .with_validator("alice", &dev.dev_keypair("alice").unwrap().public_key(), 1_000)
+        .with_validator("bob", &dev.dev_keypair("bob").unwrap().public_key(), 3_000);
+    genesis.params.finality_depth = 100;
+    genesis.params.epochs = Some(EpochSchedule { length: EpochLength::Blocks(2), epochs_per_era: 2 });
+    let ledger = node(&genesis);
+    let rival = node(&genesis);
+    
+    produce(&ledger, &genesis, "charlie", 1).await;
+    assert_eq!(ledger.current_epoch().unwrap().epoch.number, 0);
+    
+    produce(&ledger, &genesis, "charlie", 3).await;
+    let epoch = ledger.epoch(1).unwrap();
+    assert_eq!(epoch.epoch.start_height, 2);
+    assert_eq!(epoch.validators, vec![("alice".to_string(), 1_000), ("bob".to_string(), 3_000)]);
+    assert_eq!(epoch.credits, vec![("alice".to_string(), 100), ("bob".to_string(), 300)]);
+    
+    let current = ledger.current_epoch().unwrap();
+    assert_eq!((current.epoch.number, current.epoch.era), (2, 1));
+    assert_eq!(ledger.params().max_block_transactions, 2_500);
+    assert_eq!(ledger.get_balance("alice").await, 1_000_200);
+    
+    // A longer rival chain from genesis replaces all four blocks, and their epoch effects with them
+    produce(&rival, &genesis, "diana", 5).await;
+    for height in 1..=5 {
+        let Some(SearchResult::Block { block, .. }) = rival.search(&height.to_string()).await else {
+            panic!("no block at height {}", height);
+        };
+        let _ = ledger.import_block(block).await.unwrap();
+    }
+    
+    assert_eq!(ledger.get_latest_block().await.header.hash, rival.get_latest_block().await.header.hash);
+    assert_eq!(ledger.current_epoch(), rival.current_epoch());
+    assert_eq!(ledger.params(), rival.params());
+    for account in ["alice", "bob", "charlie", "diana"] {
+        assert_eq!(ledger.get_balance(account).await, rival.get_balance(account).await);
+    }
+}
\ No newline at end of file
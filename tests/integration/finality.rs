@@ -0,0 +1,139 @@
+use distributed_ledger::{
+    Block, BlockImport, DistributedLedger, GenesisConfig, LedgerError, LogFilter, SearchResult, StatusUpdate, Transaction,
+    TransactionStatus,
+};
+
+async fn produce(ledger: &DistributedLedger, genesis: &GenesisConfig, amount: u64) {
+    let mut tx = Transaction::new("alice".into(), "bob".into(), amount);
+    tx.sign(&genesis.dev_keypair("alice").unwrap());
+    ledger.add_transaction(tx).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+}
+
+async fn block_at(ledger: &DistributedLedger, height: u64) -> Block {
+    match ledger.search(&height.to_string()).await {
+        Some(SearchResult::Block { block, .. }) => block,
+        other => panic!("no block at height {}: {:?}", height, other),
+    }
+}
+
+async fn block_hash(ledger: &DistributedLedger, height: u64) -> String {
+    block_at(ledger, height).await.header.hash
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reads_stop_at_finality_unless_opted_in_and_deep_forks_are_refused() {
+    let mut genesis = GenesisConfig::dev("it-finality");
+    genesis.params.finality_depth = 2;
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let rival = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    
+    for amount in 1..=3 {
+        produce(&ledger, &genesis, amount).await;
+    }
+    produce(&rival, &genesis, 100).await;
+    
+    assert_eq!(ledger.finalized_height().await, 1);
+    assert!(ledger.is_finalized(&block_hash(&ledger, 1).await).await);
+    assert!(!ledger.is_finalized(&block_hash(&ledger, 2).await).await);
+    
+    let finalized_logs = ledger.get_logs(&LogFilter::default()).await;
+    assert_eq!(finalized_logs.len(), 1);
+    
+    let all_logs = ledger.get_logs(&LogFilter { include_unfinalized: true, ..Default::default() }).await;
+    assert_eq!(all_logs.len(), 3);
+    
+    let fork = rival.get_latest_block().await;
+    let result = ledger.import_block(fork).await;
+    assert!(matches!(result, Err(LedgerError::BlockValidationFailed(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn checkpoint_signed_by_two_thirds_of_stake_finalizes_the_chain_below() {
+    let dev = GenesisConfig::dev("it-checkpoint");
+    let mut genesis = ["alice", "bob", "charlie"].iter().fold(dev.clone(), |genesis, name| {
+        let key = dev.dev_keypair(name).unwrap();
+        genesis.with_validator(name, &key.public_key(), 1_000)
+    });
+    genesis.params.finality_depth = 100;
+    genesis.params.checkpoint_interval = Some(2);
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let rival = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    
+    for amount in 1..=2 {
+        produce(&ledger, &genesis, amount).await;
+    }
+    for amount in 10..=14 {
+        produce(&rival, &genesis, amount).await;
+    }
+    let checkpoint = block_hash(&ledger, 2).await;
+    
+    let vote = |name: &str, height: u64, hash: &str| {
+        let mut tx = Transaction::checkpoint_vote(name.to_string(), height, hash.to_string());
+        tx.sign(&genesis.dev_keypair(name).unwrap());
+        tx
+    };
+    assert!(ledger.add_transaction(vote("alice", 1, &block_hash(&ledger, 1).await)).await.is_err());
+    assert!(ledger.add_transaction(vote("eve", 2, &checkpoint)).await.is_err());
+    
+    ledger.add_transaction(vote("alice", 2, &checkpoint)).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.finalized_height().await, 0);
+    assert!(ledger.add_transaction(vote("alice", 2, &checkpoint)).await.is_err());
+    
+    ledger.add_transaction(vote("bob", 2, &checkpoint)).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.finalized_height().await, 2);
+    
+    let finalized = ledger.latest_final_checkpoint().unwrap();
+    assert_eq!(finalized.hash, checkpoint);
+    assert_eq!(finalized.voted_stake(), 2_000);
+    assert_eq!(ledger.get_latest_block().await.checkpoint_votes().count(), 1);
+    
+    // The rival's longer chain forks from genesis, below the checkpoint, so the rest of it never connects
+    let mut outcomes = Vec::new();
+    for height in 1..=5 {
+        let block = match rival.search(&height.to_string()).await {
+            Some(SearchResult::Block { block, .. }) => block,
+            other => panic!("no block at height {}: {:?}", height, other),
+        };
+        outcomes.push(ledger.import_block(block).await);
+    }
+    assert!(matches!(outcomes[0], Err(LedgerError::BlockValidationFailed(_))));
+    assert!(outcomes[1..].iter().all(|outcome| matches!(outcome, Ok(BlockImport::Orphaned { .. }))));
+    assert_eq!(block_hash(&ledger, 2).await, checkpoint);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn address_subscribers_follow_their_transactions_until_finality() {
+    let mut genesis = GenesisConfig::dev("it-status-feed");
+    genesis.params.finality_depth = 2;
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let mut feed = ledger.subscribe_address("bob");
+    
+    let mut unrelated = Transaction::new("charlie".into(), "diana".into(), 7);
+    unrelated.sign(&genesis.dev_keypair("charlie").unwrap());
+    ledger.add_transaction(unrelated).await.unwrap();
+    for amount in 1..=3 {
+        produce(&ledger, &genesis, amount).await;
+    }
+    
+    let mut expected = Vec::new();
+    for height in 1..=3 {
+        let block = block_at(&ledger, height).await;
+        let tx = block.transactions.iter().find(|tx| tx.to == "bob").unwrap().clone();
+        let included = TransactionStatus::Included { height, block_hash: block.header.hash.clone() };
+        expected.push(StatusUpdate { transaction: tx.clone(), status: TransactionStatus::Pending });
+        expected.push(StatusUpdate { transaction: tx, status: included });
+    }
+    let first = expected[0].transaction.clone();
+    let finalized = TransactionStatus::Finalized { height: 1, block_hash: block_hash(&ledger, 1).await };
+    expected.push(StatusUpdate { transaction: first.clone(), status: finalized.clone() });
+    
+    let mut received = Vec::new();
+    while received.len() < expected.len() {
+        received.push(feed.next().await.unwrap());
+    }
+    assert_eq!(received, expected);
+    assert_eq!(ledger.get_transaction(first.id).await.1, finalized);
+}
\ No newline at end of file
@@ -0,0 +1,58 @@
+use distributed_ledger::{DistributedLedger, GenesisConfig, LedgerError, Transaction};
+
+use crate::common::TestNode;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dev_genesis_funds_named_accounts_with_keys_derived_from_its_seed() {
+    let genesis = GenesisConfig::dev("it-genesis").with_chain_id("it-genesis").with_allocation("treasury", 42);
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    for name in ["alice", "bob", "charlie", "diana", "eve"] {
+        assert_eq!(ledger.get_balance(name).await, 1_000_000);
+    }
+    assert_eq!(ledger.get_balance("treasury").await, 42);
+    
+    // The same config gives the same keys and genesis block; another seed or chain id gives others
+    let again = GenesisConfig::dev("it-genesis").with_chain_id("it-genesis").with_allocation("treasury", 42);
+    let other = GenesisConfig::dev("it-genesis-other").with_chain_id("it-genesis");
+    let key = |genesis: &GenesisConfig| genesis.dev_keypair("alice").unwrap().public_key();
+    assert_eq!(key(&genesis), key(&again));
+    assert_ne!(key(&genesis), key(&other));
+    assert_eq!(genesis.block().header.hash, again.block().header.hash);
+    assert_ne!(genesis.block().header.hash, again.with_chain_id("elsewhere").block().header.hash);
+    assert!(genesis.dev_keypair("treasury").is_none());
+    
+    let mut tx = Transaction::new("alice".to_string(), "bob".to_string(), 10).for_chain("it-genesis");
+    tx.sign(&genesis.dev_keypair("alice").unwrap());
+    ledger.add_transaction(tx).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_balance("bob").await, 1_000_010);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transactions_signed_for_another_chain_or_seed_are_refused() {
+    let node = TestNode::new("it-genesis-keys");
+    let other = GenesisConfig::dev("it-genesis-stranger");
+    
+    let mut foreign_key = Transaction::new("alice".to_string(), "bob".to_string(), 10).for_chain(&node.genesis.chain_id);
+    foreign_key.sign(&other.dev_keypair("alice").unwrap());
+    assert!(matches!(node.ledger.add_transaction(foreign_key).await, Err(LedgerError::InvalidTransaction(_))));
+    
+    let mut foreign_chain = Transaction::new("alice".to_string(), "bob".to_string(), 10).for_chain("elsewhere");
+    foreign_chain.sign(&node.genesis.dev_keypair("alice").unwrap());
+    assert!(matches!(node.ledger.add_transaction(foreign_chain).await, Err(LedgerError::InvalidTransaction(_))));
+    assert_eq!(node.ledger.mempool_size(), 0);
+}
+
+#[test]
+fn inconsistent_genesis_configs_are_refused() {
+    let dev = GenesisConfig::dev("it-genesis-refused");
+    let refused = [
+        dev.clone().with_allocation("alice", 5),
+        dev.clone().with_allocation("", 5),
+        dev.clone().with_validator("alice", "key", 0),
+        dev.clone().with_validator("alice", "key", 10).with_validator("alice", "other", 10),
+    ];
+    for genesis in refused {
+        assert!(matches!(DistributedLedger::from_genesis(genesis), Err(LedgerError::InvalidParameters(_))));
+    }
+}
@@ -0,0 +1,79 @@
+use distributed_ledger::{
+    ChainParams, DistributedLedger, EpochLength, EpochSchedule, GenesisConfig, ProposalAction, ProposalStatus,
+    Transaction,
+};
+
+struct Network {
+    genesis: GenesisConfig,
+    ledger: DistributedLedger,
+}
+
+impl Network {
+    fn new() -> Self {
+        let dev = GenesisConfig::dev("it-governance");
+        let mut genesis = ["alice", "bob", "charlie"].iter().fold(dev.clone(), |genesis, name| {
+            genesis.with_validator(name, &dev.dev_keypair(name).unwrap().public_key(), 1_000)
+        });
+        genesis.params.epochs = Some(EpochSchedule { length: EpochLength::Blocks(2), epochs_per_era: 1 });
+        let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+        Self { genesis, ledger }
+    }
+    
+    async fn submit(&self, signer: &str, mut tx: Transaction) -> distributed_ledger::Result<uuid::Uuid> {
+        tx.sign(&self.genesis.dev_keypair(signer).unwrap());
+        let id = tx.id;
+        self.ledger.add_transaction(tx).await?;
+        self.ledger.process_transactions(10).await?;
+        Ok(id)
+    }
+    
+    // Fills blocks until the next epoch boundary has been crossed
+    async fn next_epoch(&self) {
+        let epoch = self.ledger.current_epoch().unwrap().epoch.number;
+        while self.ledger.current_epoch().unwrap().epoch.number == epoch {
+            self.submit("eve", Transaction::new("eve".into(), "frank".into(), 1)).await.unwrap();
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn proposals_passing_two_thirds_of_stake_take_effect_at_the_next_epoch() {
+    let net = Network::new();
+    let diana = net.genesis.dev_keypair("diana").unwrap();
+    
+    let outsider = Transaction::propose("eve".into(), ProposalAction::RemoveValidator { address: "alice".into() });
+    assert!(net.submit("eve", outsider).await.is_err());
+    
+    let add = ProposalAction::AddValidator { address: "diana".into(), public_key: diana.public_key(), vrf_key: None };
+    let add = net.submit("alice", Transaction::propose("alice".into(), add)).await.unwrap();
+    net.submit("alice", Transaction::vote("alice".into(), add, true)).await.unwrap();
+    net.submit("bob", Transaction::vote("bob".into(), add, true)).await.unwrap();
+    assert!(net.submit("bob", Transaction::vote("bob".into(), add, true)).await.is_err());
+    assert!(!net.ledger.stakes().contains("diana"));
+    
+    net.next_epoch().await;
+    let epoch = net.ledger.current_epoch().unwrap();
+    assert_eq!(net.ledger.proposal(add).unwrap().status, ProposalStatus::Enacted { epoch: epoch.epoch.number });
+    assert_eq!(epoch.decided, vec![add]);
+    assert_eq!(net.ledger.stakes().validator("diana").unwrap().public_key, diana.public_key());
+    assert!(net.submit("charlie", Transaction::vote("charlie".into(), add, true)).await.is_err());
+    
+    let params = ChainParams { max_block_transactions: 100, ..net.ledger.params() };
+    let change = Transaction::propose("bob".into(), ProposalAction::ChangeParams(Box::new(params)));
+    let change = net.submit("bob", change).await.unwrap();
+    net.submit("alice", Transaction::vote("alice".into(), change, false)).await.unwrap();
+    net.submit("charlie", Transaction::vote("charlie".into(), change, false)).await.unwrap();
+    net.next_epoch().await;
+    assert!(matches!(net.ledger.proposal(change).unwrap().status, ProposalStatus::Rejected { .. }));
+    assert_eq!(net.ledger.params().max_block_transactions, 5_000);
+    
+    let remove = Transaction::propose("alice".into(), ProposalAction::RemoveValidator { address: "charlie".into() });
+    let remove = net.submit("alice", remove).await.unwrap();
+    net.submit("alice", Transaction::vote("alice".into(), remove, true)).await.unwrap();
+    net.submit("bob", Transaction::vote("bob".into(), remove, true)).await.unwrap();
+    net.next_epoch().await;
+    assert_eq!(net.ledger.stakes().validator("charlie").unwrap().jailed_until, Some(u64::MAX));
+    
+    let late = Transaction::propose("charlie".into(), ProposalAction::RemoveValidator { address: "bob".into() });
+    assert!(net.submit("charlie", late).await.is_err());
+}
\ No newline at end of file
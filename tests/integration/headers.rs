@@ -0,0 +1,345 @@
+use sha2::{Digest, Sha256};
+
+use distributed_ledger::{
+    verify_balance_proof, verify_inclusion_proof, verify_reserve_report, AddressBloom, Block, BlockHeader, LedgerError,
+    SearchResult, Transaction,
+};
+
+use crate::common::TestNode;
+
+// The merkle root worked out from scratch, from each transaction's hash: tagged leaves over its raw digest and tagged
+// nodes over their children's raw hashes, written out in hex only at the root
+fn root_over_hashes(transactions: &[Transaction]) -> String {
+    let hash = |tag: u8, parts: &[&[u8]]| -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update([tag]);
+        parts.iter().for_each(|part| hasher.update(part));
+        hasher.finalize().to_vec()
+    };
+    let mut level: Vec<Vec<u8>> = transactions.iter()
+        .map(|tx| hash(0, &[&hex::decode(tx.hash()).unwrap()]))
+        .collect();
+    while level.len() > 1 {
+        level = level.chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash(1, &[left, right]),
+                [last] => last.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    hex::encode(&level[0])
+}
+
+// A block of five transfers, as sealed by the node that produced it and as a peer decodes it, without digests
+async fn sealed_and_fetched(node: &TestNode) -> (Block, Block) {
+    for amount in 1..=5 {
+        node.ledger.add_transaction(node.transfer("alice", "bob", amount)).await.unwrap();
+    }
+    node.ledger.process_transactions(10).await.unwrap();
+    let sealed = node.ledger.get_latest_block().await;
+    let fetched: Block = serde_json::from_slice(&serde_json::to_vec(&sealed).unwrap()).unwrap();
+    (sealed, fetched)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn headers_sync_and_validate_ahead_of_their_bodies() {
+    let node = TestNode::new("it-headers");
+    
+    for _ in 0..3 {
+        node.ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+        node.ledger.process_transactions(10).await.unwrap();
+    }
+    
+    let headers = node.ledger.get_headers(0, 10).await;
+    assert_eq!(headers.len(), 4);
+    headers[0].validate(None).unwrap();
+    for pair in headers.windows(2) {
+        pair[1].validate(Some(&pair[0])).unwrap();
+    }
+    
+    let mut tampered = headers[2].clone();
+    tampered.nonce += 1;
+    assert!(tampered.validate(Some(&headers[1])).is_err());
+    
+    // A body only fits the header that commits to it
+    let (header, transactions) = node.ledger.get_latest_block().await.into_parts();
+    let mut swapped = transactions.clone();
+    swapped[0] = node.transfer("alice", "bob", 1);
+    assert!(matches!(Block::from_parts(header.clone(), swapped), Err(LedgerError::BlockValidationFailed(_))));
+    
+    Block::from_parts(header, transactions).unwrap();
+    
+    // A transaction edited in place after it was hashed still changes what the block commits to
+    let mut edited = node.ledger.get_latest_block().await;
+    let hash = edited.transactions[0].hash();
+    edited.transactions[0].amount += 1;
+    assert_ne!(edited.transactions[0].hash(), hash);
+    assert_ne!(edited.calculate_hash(), edited.header.hash);
+    
+    // Mining only rehashes the header from the digests the block was sealed with, so the edit shows up as a body the
+    // header doesn't commit to; sealing again takes it in
+    edited.mine(node.genesis.params.difficulty);
+    let parts = edited.clone().into_parts();
+    assert!(matches!(Block::from_parts(parts.0, parts.1), Err(LedgerError::BlockValidationFailed(_))));
+    edited.seal();
+    assert_eq!(edited.calculate_hash(), edited.header.hash);
+    
+    // A syncing node reassembles each block from a checked header and a separately fetched body
+    let sibling = node.sibling();
+    for header in &headers[1..] {
+        let Some(SearchResult::Block { block, .. }) = node.ledger.search(&header.height.to_string()).await else {
+            panic!("block {} not found", header.height);
+        };
+        let block = Block::from_parts(header.clone(), block.transactions).unwrap();
+        sibling.import_block(block).await.unwrap();
+    }
+    assert_eq!(sibling.get_latest_block().await.header, headers[3]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn light_clients_verify_payments_against_headers_alone() {
+    let node = TestNode::new("it-merkle");
+    
+    // An odd count, so one level of the tree carries its last node up unpaired
+    let transactions: Vec<_> = (1..=5).map(|amount| node.transfer("alice", "bob", amount)).collect();
+    for tx in &transactions {
+        node.ledger.add_transaction(tx.clone()).await.unwrap();
+    }
+    node.ledger.process_transactions(10).await.unwrap();
+    
+    for tx in &transactions {
+        let (header, proof) = node.ledger.inclusion_proof(tx.id).await.unwrap();
+        assert_eq!(proof.leaves, 5);
+        assert!(verify_inclusion_proof(&header, tx, &proof));
+        
+        let mut forged = tx.clone();
+        forged.amount += 1;
+        assert!(!verify_inclusion_proof(&header, &forged, &proof));
+        let mut other = header.clone();
+        other.merkle_root = Block::merkle_root(&transactions[..4]);
+        other.hash = other.calculate_hash();
+        assert!(!verify_inclusion_proof(&other, tx, &proof));
+    }
+    
+    // A proof only holds at its own position
+    let block = node.ledger.get_latest_block().await;
+    let proof = block.merkle_proof(transactions[4].id).unwrap();
+    assert!(!verify_inclusion_proof(&block.header, &transactions[3], &proof));
+    assert!(block.merkle_proof(node.transfer("alice", "bob", 1).id).is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sealed_blocks_commit_to_the_root_their_transaction_hashes_give() {
+    let node = TestNode::new("it-digest-roots");
+    let (sealed, fetched) = sealed_and_fetched(&node).await;
+    
+    let root = root_over_hashes(&sealed.transactions);
+    assert_eq!(sealed.header.merkle_root, root);
+    assert_eq!(Block::merkle_root(&fetched.transactions), root);
+    assert_eq!(sealed.transaction_digests(), fetched.transaction_digests());
+    assert_eq!(sealed.transaction_weights(), fetched.transaction_weights());
+    assert_eq!(sealed.header.weight, sealed.transaction_weights().iter().sum::<usize>() as u64);
+    assert_eq!(sealed.calculate_hash(), sealed.header.hash);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn merkle_proofs_from_kept_digests_match_those_from_the_transactions() {
+    let node = TestNode::new("it-digest-proofs");
+    let (sealed, fetched) = sealed_and_fetched(&node).await;
+    
+    for tx in &sealed.transactions {
+        let proof = sealed.merkle_proof(tx.id).unwrap();
+        assert_eq!(Some(&proof), fetched.merkle_proof(tx.id).as_ref());
+        assert!(verify_inclusion_proof(&sealed.header, tx, &proof));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocks_reassembled_from_parts_keep_the_roots_of_the_sealed_block() {
+    let node = TestNode::new("it-digest-parts");
+    let (sealed, fetched) = sealed_and_fetched(&node).await;
+    
+    let (header, transactions) = fetched.into_parts();
+    let rebuilt = Block::from_parts(header, transactions).unwrap();
+    assert_eq!(rebuilt, sealed);
+    assert_eq!(rebuilt.transaction_digests(), sealed.transaction_digests());
+    for tx in &sealed.transactions {
+        assert_eq!(rebuilt.merkle_proof(tx.id), sealed.merkle_proof(tx.id));
+    }
+    
+    // Mining again rehashes from the digests `from_parts` kept, which are the sealed block's
+    let mut remined = rebuilt.clone();
+    remined.header.nonce = 0;
+    remined.mine(node.genesis.params.difficulty);
+    assert_eq!(remined.header.merkle_root, root_over_hashes(&sealed.transactions));
+    assert_eq!(remined.calculate_hash(), remined.header.hash);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_transaction_edited_after_sealing_fails_validation() {
+    let node = TestNode::new("it-digest-edit");
+    let (sealed, _) = sealed_and_fetched(&node).await;
+    let Some(SearchResult::Block { block: genesis, .. }) = node.ledger.search("0").await else {
+        panic!("no genesis block");
+    };
+    
+    // Mining rehashes the header from the kept digests, so it still commits to the body as sealed
+    let mut edited = sealed.clone();
+    edited.transactions[1].amount += 1;
+    edited.header.nonce = 0;
+    edited.mine(node.genesis.params.difficulty);
+    assert_eq!(edited.header.merkle_root, sealed.header.merkle_root);
+    assert!(matches!(edited.validate(Some(&genesis)), Err(LedgerError::BlockValidationFailed(_))));
+    let proof = edited.merkle_proof(edited.transactions[1].id).unwrap();
+    assert!(!verify_inclusion_proof(&edited.header, &edited.transactions[1], &proof));
+    assert!(matches!(node.sibling().import_block(edited).await, Err(LedgerError::BlockValidationFailed(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn balance_proofs_hold_against_the_state_root_of_each_header() {
+    let node = TestNode::new("it-state");
+    
+    node.ledger.add_transaction(node.transfer("alice", "frank", 10)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    node.ledger.add_transaction(node.transfer("alice", "bob", 5)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    
+    // Each header commits to the balances its parent left: frank is unfunded at height 1 and funded at 2
+    let (first, unfunded) = node.ledger.get_balance_with_proof("frank", 1).await.unwrap();
+    assert_eq!(unfunded.balance, 0);
+    assert!(verify_balance_proof(&first, &unfunded));
+    let (second, funded) = node.ledger.get_balance_with_proof("frank", 2).await.unwrap();
+    assert_eq!(funded.balance, 10);
+    assert!(verify_balance_proof(&second, &funded));
+    assert!(!verify_balance_proof(&first, &funded));
+    
+    let mut inflated = funded.clone();
+    inflated.balance = 1_000;
+    assert!(!verify_balance_proof(&second, &inflated));
+    let mut hidden = funded.clone();
+    hidden.balance = 0;
+    hidden.leaf = None;
+    assert!(!verify_balance_proof(&second, &hidden));
+    
+    for account in ["alice", "bob", "charlie", "diana", "eve"] {
+        let (header, proof) = node.ledger.get_balance_with_proof(account, 2).await.unwrap();
+        assert!(verify_balance_proof(&header, &proof));
+    }
+    let (_, alice) = node.ledger.get_balance_with_proof("alice", 2).await.unwrap();
+    assert_eq!(alice.balance, 999_990);
+    
+    // Genesis commits to nothing, and the tip's balances wait for the next header
+    assert!(node.ledger.get_balance_with_proof("alice", 0).await.is_none());
+    assert!(node.ledger.get_balance_with_proof("alice", 3).await.is_none());
+    
+    // Nodes agree on the root, and refuse a block committing to another
+    let sibling = node.sibling();
+    let mut forged = Block::child_of(&sibling.get_latest_block().await, Vec::new());
+    forged.header.state_root = second.state_root.clone();
+    forged.mine(node.genesis.params.difficulty);
+    assert!(matches!(sibling.import_block(forged).await, Err(LedgerError::BlockValidationFailed(_))));
+    for height in 1..=2 {
+        let Some(SearchResult::Block { block, .. }) = node.ledger.search(&height.to_string()).await else {
+            panic!("block {} not found", height);
+        };
+        sibling.import_block(block).await.unwrap();
+    }
+    assert_eq!(sibling.state_root(), node.ledger.state_root());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reserve_reports_prove_signed_balances_at_a_finalized_height() {
+    let node = TestNode::new("it-reserves");
+    node.ledger.add_transaction(node.transfer("alice", "frank", 250)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    let depth = node.genesis.params.finality_depth;
+    for _ in 0..depth + 1 {
+        node.ledger.add_transaction(node.transfer("bob", "charlie", 1)).await.unwrap();
+        node.ledger.process_transactions(10).await.unwrap();
+    }
+    assert_eq!(node.ledger.finalized_height().await, 2);
+    
+    let custodian = node.genesis.dev_keypair("eve").unwrap();
+    let accounts = vec!["alice".to_string(), "frank".to_string(), "nobody".to_string()];
+    let unfinalized = node.ledger.reserve_report(&accounts, 3, &custodian).await;
+    assert!(matches!(unfinalized, Err(LedgerError::InvalidParameters(_))));
+    
+    let report = node.ledger.reserve_report(&accounts, 2, &custodian).await.unwrap();
+    let Some(SearchResult::Block { block, .. }) = node.ledger.search("2").await else {
+        panic!("missing block 2");
+    };
+    assert_eq!(report.header, block.header);
+    assert_eq!(report.proofs.iter().map(|proof| proof.balance).collect::<Vec<_>>(), vec![999_750, 250, 0]);
+    assert_eq!(report.total, 1_000_000);
+    assert_eq!(report.signer, custodian.public_key());
+    assert!(verify_reserve_report(&report));
+    
+    let mut inflated = report.clone();
+    inflated.proofs[1].balance = 2_500;
+    inflated.total += 2_250;
+    assert!(!verify_reserve_report(&inflated));
+    let mut resigned = report.clone();
+    resigned.signer = node.genesis.dev_keypair("diana").unwrap().public_key();
+    assert!(!verify_reserve_report(&resigned));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn header_blooms_let_account_scans_skip_blocks() {
+    let node = TestNode::new("it-address-bloom");
+    for (from, to) in [("alice", "bob"), ("charlie", "diana"), ("alice", "eve")] {
+        node.ledger.add_transaction(node.transfer(from, to, 1)).await.unwrap();
+        node.ledger.process_transactions(10).await.unwrap();
+    }
+    
+    // A light client holding only headers knows which bodies to fetch for alice
+    let headers = node.ledger.get_headers(1, 10).await;
+    let for_alice: Vec<u64> = headers.iter()
+        .filter(|header| header.address_bloom.may_contain("alice"))
+        .map(|header| header.height)
+        .collect();
+    assert_eq!(for_alice, vec![1, 3]);
+    assert!(headers[1].address_bloom.may_contain("diana"));
+    assert!(headers.iter().all(|header| !header.address_bloom.may_contain("mallory")));
+    
+    let decoded: BlockHeader = serde_json::from_str(&serde_json::to_string(&headers[0]).unwrap()).unwrap();
+    assert_eq!(decoded, headers[0]);
+    
+    // The bloom is committed to like the rest of the header, so it can't be altered to hide a block
+    let (mut header, transactions) = node.ledger.get_latest_block().await.into_parts();
+    header.address_bloom = AddressBloom::default();
+    assert!(matches!(Block::from_parts(header.clone(), transactions), Err(LedgerError::BlockValidationFailed(_))));
+    assert!(header.validate(Some(&headers[1])).is_err());
+}
+#[tokio::test(flavor = "multi_thread")]
+async fn headers_commit_to_the_weight_that_orders_and_packs_their_transactions() {
+    let node = TestNode::new("it-weight");
+    let mut heavy = Transaction::new("alice".to_string(), "bob".to_string(), 1)
+        .for_chain(&node.genesis.chain_id)
+        .with_fee(100)
+        .with_data(vec![7; 64]);
+    heavy.sign(&node.genesis.dev_keypair("alice").unwrap());
+    let light = node.transfer_with_fee("charlie", "bob", 1, 100);
+    assert_eq!(heavy.weight(), heavy.encoded_size());
+    assert!(heavy.weight() > light.weight());
+    assert!(heavy.fee_rate() < light.fee_rate());
+    
+    // The same fee buys the lighter transaction the better rate, so it goes first whatever the arrival order
+    node.ledger.add_transaction(heavy.clone()).await.unwrap();
+    node.ledger.add_transaction(light.clone()).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    let block = node.ledger.get_latest_block().await;
+    let ids: Vec<_> = block.transactions.iter().map(|tx| tx.id).collect();
+    assert_eq!(ids, [light.id, heavy.id]);
+    assert_eq!(block.header.weight, (light.weight() + heavy.weight()) as u64);
+    assert_eq!(block.transaction_weights().into_owned(), [light.weight(), heavy.weight()]);
+    
+    // A header claiming another weight is refused, however well mined
+    let mut misweighed = block.clone();
+    misweighed.header.weight -= 1;
+    misweighed.header.nonce = 0;
+    misweighed.mine(node.genesis.params.difficulty);
+    assert!(matches!(node.sibling().import_block(misweighed.clone()).await, Err(LedgerError::BlockValidationFailed(_))));
+    let (header, transactions) = misweighed.into_parts();
+    assert!(matches!(Block::from_parts(header, transactions), Err(LedgerError::BlockValidationFailed(_))));
+}
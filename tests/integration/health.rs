@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use distributed_ledger::health::serve_probes;
+use distributed_ledger::{AdminApi, HealthConfig, ListenAddr, MempoolLimits};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::common::{wait_for, TestNode};
+
+async fn probe(socket: &Path, path: &str) -> String {
+    let mut stream = tokio::net::UnixStream::connect(socket).await.unwrap();
+    stream.write_all(format!("GET {} HTTP/1.1\r\n\r\n", path).as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn probes_report_liveness_and_readiness_over_http() {
+    let node = TestNode::new("it-health");
+    let socket = node.path("health.sock");
+    serve_probes(node.ledger.clone(), &[ListenAddr::Unix(socket.clone())], HealthConfig::default()).await.unwrap();
+    
+    // Alive before the processor starts, but not ready to take load
+    let report = node.ledger.health(&HealthConfig::default());
+    assert!(report.live && !report.ready && !report.processor_running);
+    assert!(probe(&socket, "/healthz").await.starts_with("HTTP/1.1 200 OK"));
+    let ready = probe(&socket, "/readyz").await;
+    assert!(ready.starts_with("HTTP/1.1 503 Service Unavailable"));
+    assert!(ready.contains("\"processor_running\":false"));
+    assert!(probe(&socket, "/metrics").await.starts_with("HTTP/1.1 404 Not Found"));
+    
+    node.ledger.start_background_processor().await;
+    wait_for(|| async { node.ledger.health(&HealthConfig::default()).ready }).await;
+    assert!(probe(&socket, "/readyz").await.starts_with("HTTP/1.1 200 OK"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_node_with_a_nearly_full_queue_is_alive_but_not_ready() {
+    let node = TestNode::start("it-health-full").await;
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    admin.set_mempool_limits("secret", MempoolLimits { capacity: 2, ..Default::default() }).unwrap();
+    admin.pause_block_production("secret").unwrap();
+    node.ledger.add_transaction(node.transfer("alice", "bob", 10)).await.unwrap();
+    node.ledger.add_transaction(node.transfer("charlie", "bob", 10)).await.unwrap();
+    
+    let report = node.ledger.health(&HealthConfig::default());
+    assert!(report.live && report.paused && !report.ready);
+    assert_eq!((report.pending_transactions, report.queue_utilization), (2, 1.0));
+    
+    admin.resume_block_production("secret").unwrap();
+    wait_for(|| async { node.ledger.health(&HealthConfig::default()).ready }).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn probes_refuse_to_replace_a_file_that_is_not_a_socket() {
+    let node = TestNode::new("it-health-bind");
+    let taken = node.path("health.sock");
+    std::fs::write(&taken, b"not a socket").unwrap();
+    let addrs = [ListenAddr::Unix(node.path("other.sock")), ListenAddr::Unix(taken.clone())];
+    
+    assert!(serve_probes(node.ledger.clone(), &addrs, HealthConfig::default()).await.is_err());
+    assert_eq!(std::fs::read(&taken).unwrap(), b"not a socket");
+    // Nothing is served when any address fails to bind
+    assert!(tokio::net::UnixStream::connect(node.path("other.sock")).await.is_err());
+}
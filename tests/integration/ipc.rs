@@ -0,0 +1,34 @@
+use distributed_ledger::{ipc::serve_ipc, IpcClient};
+
+use crate::common::{wait_for, TestNode};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn client_drives_node_over_unix_socket() {
+    let node = TestNode::start("it-ipc").await;
+    let socket = node.path("ledger.sock");
+    serve_ipc(node.ledger.clone(), &socket).await.unwrap();
+    
+    let mut client = IpcClient::connect(&socket).await.unwrap();
+    let tx = node.transfer("alice", "bob", 25);
+    client.submit_transaction(tx.clone()).await.unwrap();
+    assert!(client.submit_transaction(tx).await.is_err());
+    
+    wait_for(|| async { node.ledger.get_transaction_count().await == 1 }).await;
+    assert_eq!(client.get_balance("bob").await.unwrap(), 1_000_025);
+    assert_eq!(client.get_latest_block().await.unwrap().header.height, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn serving_replaces_a_stale_socket_but_never_a_regular_file() {
+    let node = TestNode::new("it-ipc-path");
+    let file = node.path("ledger.sock");
+    std::fs::write(&file, "not a socket").unwrap();
+    assert!(serve_ipc(node.ledger.clone(), &file).await.is_err());
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "not a socket");
+    
+    // A socket left behind by an earlier listener is cleared for the next one
+    let socket = node.path("stale.sock");
+    drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+    serve_ipc(node.ledger.clone(), &socket).await.unwrap();
+    IpcClient::connect(&socket).await.unwrap();
+}
\ No newline at end of file
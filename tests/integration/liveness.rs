@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use distributed_ledger::{Block, DistributedLedger, GenesisConfig, Keypair, LedgerError, PoaConfig, ProofOfAuthority, Transaction};
+
+use crate::common::wait_for;
+
+fn transfer(keypair: &Keypair, amount: u64) -> Transaction {
+    let mut tx = Transaction::new("alice".to_string(), "bob".to_string(), amount);
+    tx.sign(keypair);
+    tx
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn next_authority_takes_over_when_the_scheduled_one_is_offline() {
+    let genesis = GenesisConfig::dev("it-liveness");
+    let alice = genesis.dev_keypair("alice").unwrap();
+    let bob = genesis.dev_keypair("bob").unwrap();
+    let config = PoaConfig {
+        validators: vec![bob.public_key(), alice.public_key()],
+        round_timeout_ms: Some(300),
+    };
+    
+    // Bob's node never comes up; alice's node gets a turn at height 1 within one rotation
+    let engine = ProofOfAuthority::new(config.clone(), vec![alice.clone()]).unwrap();
+    let node = DistributedLedger::with_consensus(genesis.clone(), Arc::new(engine)).unwrap();
+    let follower = DistributedLedger::with_consensus(genesis.clone(), Arc::new(ProofOfAuthority::new(config, Vec::new()).unwrap())).unwrap();
+    
+    node.add_transaction(transfer(&alice, 5)).await.unwrap();
+    wait_for(|| async {
+        node.process_transactions(10).await.unwrap();
+        node.get_latest_block().await.header.height == 1
+    }).await;
+    let parent = node.get_latest_block().await;
+    follower.import_block(parent.clone()).await.unwrap();
+    
+    // Height 2 is bob's slot
+    node.add_transaction(transfer(&alice, 5)).await.unwrap();
+    node.process_transactions(10).await.unwrap();
+    assert_eq!(node.get_latest_block().await.header.height, 1);
+    
+    // Claiming bob's slot before his timeout is refused by every node
+    let mut early = Block::child_of(&parent, Vec::new());
+    early.header.round = 1;
+    early.sign(&alice);
+    assert!(matches!(follower.import_block(early).await, Err(LedgerError::BlockValidationFailed(_))));
+    
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    wait_for(|| async {
+        node.process_transactions(10).await.unwrap();
+        node.get_latest_block().await.header.height == 2
+    }).await;
+    let block = node.get_latest_block().await;
+    assert!(block.header.round >= 1);
+    assert_eq!(block.header.producer, Some(alice.public_key()));
+    
+    follower.import_block(block).await.unwrap();
+    assert_eq!(follower.get_balance("bob").await, 1_000_010);
+    
+    // Height 3 is alice's own slot, so it goes out in round 0 without waiting
+    node.add_transaction(transfer(&alice, 5)).await.unwrap();
+    node.process_transactions(10).await.unwrap();
+    let block = node.get_latest_block().await;
+    assert_eq!((block.header.height, block.header.round), (3, 0));
+}
\ No newline at end of file
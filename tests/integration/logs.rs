@@ -0,0 +1,85 @@
+use distributed_ledger::{BlockImport, LogFilter, SearchResult};
+
+use crate::common::TestNode;
+
+fn unfinalized(filter: LogFilter) -> LogFilter {
+    LogFilter { include_unfinalized: true, ..filter }
+}
+
+fn topic(value: &str) -> Option<String> {
+    Some(value.to_string())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn logs_filter_by_address_topic_and_height() {
+    let node = TestNode::new("it-logs");
+    node.ledger.add_transaction(node.transfer("alice", "bob", 10)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    node.ledger.add_transaction(node.transfer("charlie", "bob", 20)).await.unwrap();
+    node.ledger.add_transaction(node.transfer("alice", "diana", 30)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    
+    let transfers = node.ledger.get_logs(&unfinalized(LogFilter {
+        address: Some("native:transfer".to_string()),
+        ..Default::default()
+    })).await;
+    assert_eq!(transfers.len(), 3);
+    assert_eq!((transfers[0].block_height, transfers[0].data.clone()), (1, 10u64.to_le_bytes().to_vec()));
+    
+    // Topics match by position, with `None` matching anything
+    let from_alice = node.ledger.get_logs(&unfinalized(LogFilter {
+        topics: vec![topic("transfer"), topic("alice")],
+        ..Default::default()
+    })).await;
+    let heights: Vec<u64> = from_alice.iter().map(|log| log.block_height).collect();
+    assert_eq!(heights, [1, 2]);
+    
+    let to_bob = node.ledger.get_logs(&unfinalized(LogFilter {
+        topics: vec![None, None, topic("bob")],
+        from_height: Some(2),
+        ..Default::default()
+    })).await;
+    assert_eq!(to_bob.len(), 1);
+    assert_eq!(to_bob[0].topics, ["transfer", "charlie", "bob"]);
+    
+    // Topics out of position, an unknown address and an empty range find nothing
+    let misplaced = LogFilter { topics: vec![topic("alice")], ..Default::default() };
+    assert!(node.ledger.get_logs(&unfinalized(misplaced)).await.is_empty());
+    let unknown = LogFilter { address: Some("native:unknown".to_string()), ..Default::default() };
+    assert!(node.ledger.get_logs(&unfinalized(unknown)).await.is_empty());
+    let inverted = LogFilter { from_height: Some(2), to_height: Some(1), ..Default::default() };
+    assert!(node.ledger.get_logs(&unfinalized(inverted)).await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn logs_of_a_reorganized_block_are_dropped_with_it() {
+    let node = TestNode::new("it-logs-reorg");
+    let rival = node.sibling();
+    
+    node.ledger.add_transaction(node.transfer("alice", "bob", 10)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    let by_alice = unfinalized(LogFilter { topics: vec![None, topic("alice")], ..Default::default() });
+    assert_eq!(node.ledger.get_logs(&by_alice).await.len(), 1);
+    
+    rival.add_transaction(node.transfer("charlie", "bob", 20)).await.unwrap();
+    rival.process_transactions(10).await.unwrap();
+    rival.add_transaction(node.transfer("charlie", "diana", 5)).await.unwrap();
+    rival.process_transactions(10).await.unwrap();
+    let mut imports = Vec::new();
+    for height in 1..=2 {
+        let Some(SearchResult::Block { block, .. }) = rival.search(&height.to_string()).await else {
+            panic!("no block at height {}", height);
+        };
+        imports.push(node.ledger.import_block(block).await.unwrap());
+    }
+    assert!(matches!(imports[..], [BlockImport::SideChain, BlockImport::Reorganized(_)]));
+    
+    // Requeued, alice's transfer logs nothing until a block carries it again
+    assert!(node.ledger.get_logs(&by_alice).await.is_empty());
+    let by_charlie = unfinalized(LogFilter { topics: vec![None, topic("charlie")], ..Default::default() });
+    let heights: Vec<u64> = node.ledger.get_logs(&by_charlie).await.iter().map(|log| log.block_height).collect();
+    assert_eq!(heights, [1, 2]);
+    
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.get_logs(&by_alice).await[0].block_height, 3);
+}
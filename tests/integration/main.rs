@@ -0,0 +1,32 @@
+// End-to-end scenarios against a running node; run with `cargo test --features it`
+mod admin;
+mod anchor;
+mod common;
+mod consensus;
+mod contracts;
+mod params;
+mod poa;
+mod processor;
+mod difficulty;
+mod epochs;
+mod finality;
+mod genesis;
+mod governance;
+mod headers;
+mod health;
+mod ipc;
+mod logs;
+mod liveness;
+mod reorg;
+mod schedules;
+mod search;
+mod restart;
+#[cfg(feature = "simulation")]
+mod simulation;
+mod slashing;
+mod staking;
+mod storage;
+mod timestamps;
+mod upgrade;
+#[cfg(feature = "zk")]
+mod zk;
\ No newline at end of file
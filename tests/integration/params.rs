@@ -0,0 +1,43 @@
+use distributed_ledger::{ChainParams, DifficultyAdjustment, DistributedLedger, GenesisConfig, LedgerError, Transaction};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocks_are_produced_within_the_configured_params() {
+    let mut genesis = GenesisConfig::dev("it-params");
+    genesis.params.difficulty = 3;
+    genesis.params.max_block_transactions = 2;
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    assert_eq!(ledger.params(), genesis.params);
+    
+    for to in ["bob", "charlie", "diana"] {
+        let mut tx = Transaction::new("alice".to_string(), to.to_string(), 10)
+            .for_chain(&genesis.chain_id);
+        tx.sign(&genesis.dev_keypair("alice").unwrap());
+        ledger.add_transaction(tx).await.unwrap();
+    }
+    ledger.process_transactions(10).await.unwrap();
+    let block = ledger.get_latest_block().await;
+    assert_eq!(block.transactions.len(), 2);
+    assert!(block.header.hash.starts_with("000"));
+    assert_eq!(ledger.mempool_size(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inconsistent_params_are_refused_when_the_ledger_is_built() {
+    let defaults = ChainParams::default();
+    assert_eq!(DistributedLedger::with_params(defaults.clone()).unwrap().params(), defaults);
+    
+    let refused = [
+        ChainParams { max_block_transactions: 0, ..defaults.clone() },
+        ChainParams { difficulty: 65, ..defaults.clone() },
+        ChainParams { max_transaction_data: defaults.max_block_data + 1, ..defaults.clone() },
+        ChainParams { checkpoint_interval: Some(0), ..defaults.clone() },
+        ChainParams {
+            difficulty: 9,
+            difficulty_adjustment: Some(DifficultyAdjustment::default()),
+            ..defaults.clone()
+        },
+    ];
+    for params in refused {
+        assert!(matches!(DistributedLedger::with_params(params), Err(LedgerError::InvalidParameters(_))));
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use distributed_ledger::{Block, DistributedLedger, GenesisConfig, LedgerError, PoaConfig, ProofOfAuthority, Transaction};
+
+use crate::common::TestNode;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn authorities_take_turns_and_followers_accept_only_their_blocks() {
+    let node = TestNode::new("it-poa");
+    let [alice, bob, eve] = ["alice", "bob", "eve"].map(|name| node.genesis.dev_keypair(name).unwrap());
+    let config = PoaConfig { validators: vec![alice.public_key(), bob.public_key()], ..Default::default() };
+    let engine = ProofOfAuthority::new(config.clone(), vec![alice.clone(), bob.clone()]).unwrap();
+    let producer = DistributedLedger::with_consensus(node.genesis.clone(), Arc::new(engine)).unwrap();
+    let follower = DistributedLedger::with_consensus(
+        node.genesis.clone(),
+        Arc::new(ProofOfAuthority::new(config, Vec::new()).unwrap()),
+    ).unwrap();
+    
+    // Height 1 goes to the second authority in the list, height 2 back to the first
+    for (height, authority) in [(1, &bob), (2, &alice)] {
+        producer.add_transaction(node.transfer("charlie", "diana", 10)).await.unwrap();
+        producer.process_transactions(10).await.unwrap();
+        let block = producer.get_latest_block().await;
+        assert_eq!(block.header.producer, Some(authority.public_key()));
+        follower.import_block(block).await.unwrap();
+        assert_eq!(follower.chain_height(), height);
+    }
+    assert_eq!(follower.get_balance("diana").await, 1_000_020);
+    
+    // Signed by someone outside the set, or by the authority whose turn it isn't
+    let parent = producer.get_latest_block().await;
+    for key in [&eve, &alice] {
+        let mut block = Block::child_of(&parent, Vec::new());
+        block.sign(key);
+        assert!(matches!(follower.import_block(block).await, Err(LedgerError::BlockValidationFailed(_))));
+    }
+    assert_eq!(follower.chain_height(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_reloaded_validator_set_applies_from_the_next_block() {
+    let genesis = GenesisConfig::dev("it-poa-reload");
+    let alice = genesis.dev_keypair("alice").unwrap();
+    let bob = genesis.dev_keypair("bob").unwrap();
+    let engine = Arc::new(ProofOfAuthority::new(
+        PoaConfig { validators: vec![alice.public_key()], ..Default::default() },
+        vec![alice.clone()],
+    ).unwrap());
+    let ledger = DistributedLedger::with_consensus(genesis.clone(), engine.clone()).unwrap();
+    let transfer = || {
+        let mut tx = Transaction::new("charlie".to_string(), "diana".to_string(), 10);
+        tx.sign(&genesis.dev_keypair("charlie").unwrap());
+        tx
+    };
+    ledger.add_transaction(transfer()).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.chain_height(), 1);
+    
+    // A bad set is refused and the current one stays
+    let invalid = [
+        PoaConfig::default(),
+        PoaConfig { validators: vec![bob.public_key(), bob.public_key()], ..Default::default() },
+        PoaConfig { validators: vec![bob.public_key()], round_timeout_ms: Some(0) },
+    ];
+    for config in invalid {
+        assert!(matches!(engine.reload(config.clone()), Err(LedgerError::InvalidParameters(_))));
+        assert!(matches!(ProofOfAuthority::new(config, Vec::new()), Err(LedgerError::InvalidParameters(_))));
+    }
+    assert_eq!(engine.validators(), [alice.public_key()]);
+    
+    // Once bob alone is the authority, alice's node has no slot left
+    engine.reload(PoaConfig { validators: vec![bob.public_key()], ..Default::default() }).unwrap();
+    assert_eq!(engine.proposer_for(2), bob.public_key());
+    ledger.add_transaction(transfer()).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!((ledger.chain_height(), ledger.mempool_size()), (1, 1));
+    let mut block = Block::child_of(&ledger.get_latest_block().await, Vec::new());
+    block.sign(&alice);
+    assert!(matches!(ledger.import_block(block).await, Err(LedgerError::BlockValidationFailed(_))));
+}
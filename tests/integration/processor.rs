@@ -0,0 +1,1845 @@
+use std::sync::Arc;
+use std::time::Duration;
+use futures::StreamExt;
+
+use distributed_ledger::{AdminApi, Block, BlockImport, CancellationToken, ChainParams, ConflictSchedule, Consensus, Direction, DistributedLedger, EmissionSchedule, FeeDestination, GenesisConfig, HashLockStatus, IndexRetention, IssuancePolicy, HealthConfig, Invoice, InvoiceStatus, LedgerError, LedgerModel, LoadGenerator, LoadProfile, OutPoint, MempoolLimits, Page, Payment, PoaConfig, ProcessorConfig, ProductionPolicy, ProofOfAuthority, ProofOfWork, QueryBudget, QueryExecutor, Retention, ScheduleStep, SpendingLimit, Transaction, TransactionFilter, TransactionKind, TransactionStatus, BlockBound, VestingBalance, VestingSchedule};
+
+use crate::common::{wait_for, TestNode};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn background_processor_commits_submitted_load() {
+    let node = TestNode::start("it-processor").await;
+    
+    for _ in 0..100 {
+        node.ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    }
+    
+    wait_for(|| async { node.ledger.get_transaction_count().await == 100 }).await;
+    assert_eq!(node.ledger.get_balance("alice").await, 999_900);
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_100);
+    
+    assert!(node.ledger.health(&HealthConfig::default()).ready);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn paused_node_holds_transactions_until_resumed() {
+    let node = TestNode::start("it-pause").await;
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    
+    admin.pause_block_production("secret").unwrap();
+    node.ledger.add_transaction(node.transfer("bob", "carol", 10)).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(node.ledger.get_transaction_count().await, 0);
+    
+    admin.resume_block_production("secret").unwrap();
+    wait_for(|| async { node.ledger.get_transaction_count().await == 1 }).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_unsigned_spend_from_dev_account() {
+    let node = TestNode::new("it-unsigned");
+    let forged = distributed_ledger::Transaction::new("alice".to_string(), "eve".to_string(), 1);
+    
+    let result = node.ledger.add_transaction(forged).await;
+    assert!(matches!(result, Err(LedgerError::InvalidTransaction(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn production_policy_batches_until_a_trigger_fires() {
+    let node = TestNode::start("it-triggers").await;
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    let policy = ProductionPolicy { batch_threshold: Some(5), max_interval: None, produce_empty: false };
+    admin.set_production_policy("secret", policy).unwrap();
+    
+    for _ in 0..4 {
+        node.ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(node.ledger.get_latest_block().await.header.height, 0);
+    
+    node.ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    wait_for(|| async { node.ledger.get_transaction_count().await == 5 }).await;
+    assert_eq!(node.ledger.get_latest_block().await.header.height, 1);
+    
+    admin.set_production_policy("secret", ProductionPolicy::manual()).unwrap();
+    node.ledger.add_transaction(node.transfer("bob", "carol", 1)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(node.ledger.get_transaction_count().await, 5);
+    node.ledger.produce_block_now().await.unwrap();
+    assert_eq!(node.ledger.get_transaction_count().await, 6);
+    
+    let policy = ProductionPolicy {
+        batch_threshold: None,
+        max_interval: Some(Duration::from_millis(200)),
+        produce_empty: false,
+    };
+    admin.set_production_policy("secret", policy).unwrap();
+    node.ledger.add_transaction(node.transfer("bob", "carol", 1)).await.unwrap();
+    wait_for(|| async { node.ledger.get_transaction_count().await == 7 }).await;
+    
+    let invalid = ProductionPolicy { batch_threshold: Some(0), max_interval: None, produce_empty: false };
+    assert!(matches!(admin.set_production_policy("secret", invalid), Err(LedgerError::InvalidParameters(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pending_spends_reserve_funds_until_committed() {
+    let node = TestNode::new("it-reserve");
+    
+    node.ledger.add_transaction(node.transfer("eve", "bob", 600_000)).await.unwrap();
+    let second = node.ledger.add_transaction(node.transfer("eve", "charlie", 600_000)).await;
+    assert!(matches!(second, Err(LedgerError::InsufficientBalance)));
+    assert_eq!(node.ledger.reserved_balance("eve"), 600_000);
+    assert_eq!(node.ledger.available_balance("eve").await, 400_000);
+    
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.reserved_balance("eve"), 0);
+    assert_eq!(node.ledger.available_balance("eve").await, 400_000);
+    
+    let replay = node.ledger.search(&node.ledger.get_latest_block().await.transactions[0].id.to_string()).await;
+    let Some(distributed_ledger::SearchResult::Transaction { transaction, .. }) = replay else {
+        panic!("committed transaction not found");
+    };
+    assert!(matches!(node.ledger.add_transaction(transaction).await, Err(LedgerError::DuplicateTransaction)));
+    node.ledger.add_transaction(node.transfer("eve", "charlie", 400_000)).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transactions_and_blocks_from_another_chain_are_rejected() {
+    let staging = DistributedLedger::from_genesis(GenesisConfig::dev("it-chains").with_chain_id("staging")).unwrap();
+    let production = DistributedLedger::from_genesis(GenesisConfig::dev("it-chains").with_chain_id("production")).unwrap();
+    assert_ne!(staging.get_latest_block().await.header.hash, production.get_latest_block().await.header.hash);
+    
+    let keypair = staging.genesis().dev_keypair("alice").unwrap();
+    let mut tx = Transaction::new("alice".to_string(), "bob".to_string(), 10).for_chain("staging");
+    tx.sign(&keypair);
+    
+    staging.add_transaction(tx.clone()).await.unwrap();
+    assert!(matches!(production.add_transaction(tx.clone()).await, Err(LedgerError::InvalidTransaction(_))));
+    
+    // Re-labelling the transaction breaks the signature over its digest
+    let mut relabelled = tx.clone();
+    relabelled.chain_id = "production".to_string();
+    assert!(matches!(production.add_transaction(relabelled).await, Err(LedgerError::InvalidTransaction(_))));
+    
+    staging.process_transactions(10).await.unwrap();
+    let block = staging.get_latest_block().await;
+    assert!(matches!(production.import_block(block).await, Ok(BlockImport::Orphaned { .. })));
+    assert_eq!(production.get_latest_block().await.header.height, 0);
+    assert_eq!(production.get_balance("bob").await, 1_000_000);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocks_respect_the_configured_size_and_count_limits() {
+    let mut genesis = GenesisConfig::dev("it-limits");
+    let alice = genesis.dev_keypair("alice").unwrap();
+    let signed = |to: &str| {
+        let mut tx = Transaction::new("alice".to_string(), to.to_string(), 1);
+        tx.sign(&alice);
+        tx
+    };
+    let size = signed("bob").encoded_size();
+    genesis.params.max_block_transactions = 3;
+    genesis.params.max_block_bytes = size * 2 + size / 2;
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    
+    let batch: Vec<_> = (0..5).map(|_| signed("bob")).collect();
+    for tx in &batch {
+        ledger.add_transaction(tx.clone()).await.unwrap();
+    }
+    
+    // The byte limit binds before the count limit
+    ledger.process_transactions(100).await.unwrap();
+    let block = ledger.get_latest_block().await;
+    assert_eq!(block.transactions.len(), 2);
+    assert_eq!(block.header.weight, (size * 2) as u64);
+    ledger.process_transactions(100).await.unwrap();
+    ledger.process_transactions(100).await.unwrap();
+    assert_eq!(ledger.get_transaction_count().await, 5);
+    
+    let oversized = signed(&"b".repeat(size * 3));
+    assert!(matches!(ledger.add_transaction(oversized).await, Err(LedgerError::InvalidTransaction(_))));
+    
+    // A block packed by a node ignoring the limits is refused
+    let sibling = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let mut block = distributed_ledger::Block::child_of(&sibling.get_latest_block().await, batch[..3].to_vec());
+    block.mine(genesis.params.difficulty);
+    assert!(matches!(sibling.import_block(block).await, Err(LedgerError::BlockValidationFailed(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn fees_pay_the_producer_and_order_the_block() {
+    let mut genesis = GenesisConfig::dev("it-fees");
+    genesis.params.max_block_transactions = 3;
+    let charlie = genesis.dev_keypair("charlie").unwrap();
+    let config = PoaConfig { validators: vec![charlie.public_key()], ..PoaConfig::default() };
+    let engine = ProofOfAuthority::new(config, vec![charlie]).unwrap();
+    let ledger = DistributedLedger::with_consensus(genesis.clone(), Arc::new(engine)).unwrap();
+    let signed = |from: &str, fee: u64| {
+        let mut tx = Transaction::new(from.to_string(), "bob".to_string(), 10).with_fee(fee);
+        tx.sign(&genesis.dev_keypair(from).unwrap());
+        tx
+    };
+    assert_eq!(ledger.estimate_fee(200).await, 0);
+    
+    ledger.add_transaction(signed("alice", 0)).await.unwrap();
+    ledger.add_transaction(signed("eve", 1)).await.unwrap();
+    ledger.add_transaction(signed("diana", 300)).await.unwrap();
+    let overspend = signed("alice", 1_000_000);
+    assert!(matches!(ledger.add_transaction(overspend).await, Err(LedgerError::InsufficientBalance)));
+    
+    ledger.process_transactions(10).await.unwrap();
+    let block = ledger.get_latest_block().await;
+    let senders: Vec<_> = block.transactions.iter().map(|tx| tx.from.as_str()).collect();
+    assert_eq!(senders, ["diana", "eve", "alice"]);
+    assert_eq!(ledger.get_balance("diana").await, 1_000_000 - 310);
+    assert_eq!(ledger.get_balance("bob").await, 1_000_030);
+    assert_eq!(ledger.get_balance("charlie").await, 1_000_301);
+    
+    // Every recent block was full, so the estimate reaches the top fee rate seen
+    let top = block.transactions[0].fee_rate();
+    assert_eq!(ledger.estimate_fee(1_000).await, (top * 1_000).div_ceil(1_000_000) as u64);
+    
+    let mut burn = genesis.clone();
+    burn.params.fee_destination = FeeDestination::Burn;
+    let burning = DistributedLedger::from_genesis(burn).unwrap();
+    burning.add_transaction(signed("diana", 300)).await.unwrap();
+    burning.process_transactions(10).await.unwrap();
+    assert_eq!(burning.get_balance("diana").await, 1_000_000 - 310);
+    assert_eq!(burning.get_balance("bob").await, 1_000_010);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn full_mempool_evicts_the_lowest_priority_transactions() {
+    let node = TestNode::new("it-mempool");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    admin.set_mempool_limits("secret", MempoolLimits { capacity: 3, max_per_account: 2, ..Default::default() }).unwrap();
+    
+    node.ledger.add_transaction(node.transfer("alice", "bob", 10)).await.unwrap();
+    node.ledger.add_transaction(node.transfer("alice", "bob", 10)).await.unwrap();
+    let third = node.ledger.add_transaction(node.transfer("alice", "bob", 10)).await;
+    assert!(matches!(third, Err(LedgerError::PerformanceLimitExceeded(_))));
+    assert_eq!(node.ledger.pending_for("alice").len(), 2);
+    
+    node.ledger.add_transaction(node.transfer_with_fee("eve", "bob", 10, 50)).await.unwrap();
+    assert_eq!(node.ledger.mempool_size(), 3);
+    
+    // Nothing queued pays less than a fee-less newcomer, so it doesn't get in
+    let cheap = node.ledger.add_transaction(node.transfer("charlie", "bob", 10)).await;
+    assert!(matches!(cheap, Err(LedgerError::PerformanceLimitExceeded(_))));
+    assert_eq!(node.ledger.reserved_balance("charlie"), 0);
+    
+    // Outbidding evicts the newest of the cheapest and releases its reservation
+    node.ledger.add_transaction(node.transfer_with_fee("diana", "bob", 10, 100)).await.unwrap();
+    assert_eq!(node.ledger.mempool_size(), 3);
+    assert_eq!(node.ledger.pending_for("alice").len(), 1);
+    assert_eq!(node.ledger.reserved_balance("alice"), 10);
+    
+    node.ledger.process_transactions(10).await.unwrap();
+    let block = node.ledger.get_latest_block().await;
+    let senders: Vec<_> = block.transactions.iter().map(|tx| tx.from.as_str()).collect();
+    assert_eq!(senders, ["diana", "eve", "alice"]);
+    assert_eq!(node.ledger.mempool_size(), 0);
+    
+    let invalid = MempoolLimits { capacity: 0, max_per_account: 1, ..Default::default() };
+    assert!(matches!(admin.set_mempool_limits("secret", invalid), Err(LedgerError::InvalidParameters(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn time_locked_transactions_are_evicted_from_a_full_mempool_like_any_other() {
+    let node = TestNode::new("it-mempool-locked");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    admin.set_mempool_limits("secret", MempoolLimits { capacity: 2, ..Default::default() }).unwrap();
+    let locked = |from: &str, fee: u64| {
+        let mut tx = Transaction::new(from.to_string(), "bob".to_string(), 10)
+            .for_chain(&node.genesis.chain_id)
+            .with_fee(fee)
+            .with_not_before(BlockBound::Height(1_000));
+        tx.sign(&node.genesis.dev_keypair(from).unwrap());
+        tx
+    };
+    
+    // Nothing a block could take yet fills the pool
+    let alice = locked("alice", 5);
+    node.ledger.add_transaction(alice.clone()).await.unwrap();
+    node.ledger.add_transaction(locked("charlie", 20)).await.unwrap();
+    assert_eq!(node.ledger.mempool_size(), 2);
+    
+    // A fee-less newcomer doesn't outbid either
+    let cheap = node.ledger.add_transaction(node.transfer("eve", "bob", 10)).await;
+    assert!(matches!(cheap, Err(LedgerError::PerformanceLimitExceeded(_))));
+    
+    // The cheapest time-locked one makes room, and its reservation goes with it
+    node.ledger.add_transaction(node.transfer_with_fee("diana", "bob", 10, 50)).await.unwrap();
+    assert_eq!(node.ledger.mempool_size(), 2);
+    assert!(node.ledger.pending_for("alice").is_empty());
+    assert_eq!(node.ledger.reserved_balance("alice"), 0);
+    assert_eq!(node.ledger.pending_for("charlie").len(), 1);
+    
+    node.ledger.process_transactions(10).await.unwrap();
+    let block = node.ledger.get_latest_block().await;
+    assert_eq!(block.transactions.len(), 1);
+    assert_eq!(block.transactions[0].from, "diana");
+    assert_eq!(node.ledger.mempool_size(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn expired_transactions_leave_the_mempool_and_invalidate_blocks() {
+    let node = TestNode::new("it-expiry");
+    let expiring = |from: &str, valid_until: BlockBound| {
+        let mut tx = Transaction::new(from.to_string(), "bob".to_string(), 10)
+            .for_chain(&node.genesis.chain_id)
+            .with_valid_until(valid_until);
+        tx.sign(&node.genesis.dev_keypair(from).unwrap());
+        tx
+    };
+    
+    let stale = node.ledger.add_transaction(expiring("alice", BlockBound::Height(0))).await;
+    assert!(matches!(stale, Err(LedgerError::InvalidTransaction(_))));
+    let past = chrono::Utc::now() - chrono::Duration::seconds(1);
+    let stale = node.ledger.add_transaction(expiring("alice", BlockBound::Time(past))).await;
+    assert!(matches!(stale, Err(LedgerError::InvalidTransaction(_))));
+    
+    // The fee puts eve's transaction in block 1, leaving alice's to expire
+    node.ledger.add_transaction(expiring("alice", BlockBound::Height(1))).await.unwrap();
+    node.ledger.add_transaction(node.transfer_with_fee("eve", "bob", 10, 5)).await.unwrap();
+    node.ledger.process_transactions(1).await.unwrap();
+    assert_eq!(node.ledger.mempool_size(), 1);
+    
+    node.ledger.process_transactions(1).await.unwrap();
+    assert_eq!(node.ledger.mempool_size(), 0);
+    assert_eq!(node.ledger.reserved_balance("alice"), 0);
+    assert_eq!(node.ledger.get_latest_block().await.header.height, 1);
+    
+    let sibling = TestNode::new("it-expiry-sibling");
+    let late = {
+        let mut tx = Transaction::new("alice".to_string(), "bob".to_string(), 10)
+            .for_chain(&sibling.genesis.chain_id)
+            .with_valid_until(BlockBound::Height(0));
+        tx.sign(&sibling.genesis.dev_keypair("alice").unwrap());
+        tx
+    };
+    let mut block = distributed_ledger::Block::child_of(&sibling.ledger.get_latest_block().await, vec![late]);
+    block.mine(sibling.genesis.params.difficulty);
+    assert!(matches!(sibling.ledger.import_block(block).await, Err(LedgerError::BlockValidationFailed(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn payouts_pay_every_recipient_or_none() {
+    let node = TestNode::new("it-payout");
+    let payout = |payments: &[(&str, u64)]| {
+        let payments = payments.iter().map(|(to, amount)| Payment { to: to.to_string(), amount: *amount }).collect();
+        let mut tx = Transaction::payout("alice".to_string(), payments).for_chain(&node.genesis.chain_id);
+        tx.sign(&node.genesis.dev_keypair("alice").unwrap());
+        tx
+    };
+    
+    node.ledger.add_transaction(payout(&[("bob", 100), ("charlie", 200), ("diana", 300)])).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.get_balance("alice").await, 999_400);
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_100);
+    assert_eq!(node.ledger.get_balance("charlie").await, 1_000_200);
+    assert_eq!(node.ledger.get_balance("diana").await, 1_000_300);
+    
+    let overdrawn = payout(&[("bob", 999_000), ("charlie", 1_000)]);
+    assert!(matches!(node.ledger.add_transaction(overdrawn).await, Err(LedgerError::InsufficientBalance)));
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_100);
+    
+    let mut inflated = payout(&[("bob", 1)]);
+    inflated.amount = 2;
+    assert!(matches!(node.ledger.add_transaction(inflated).await, Err(LedgerError::InvalidTransaction(_))));
+    assert!(matches!(node.ledger.add_transaction(payout(&[])).await, Err(LedgerError::InvalidTransaction(_))));
+    assert!(matches!(node.ledger.add_transaction(payout(&[("alice", 5)])).await, Err(LedgerError::InvalidTransaction(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn swaps_settle_both_legs_once_both_parties_sign() {
+    let node = TestNode::new("it-swap");
+    let key = |name: &str| node.genesis.dev_keypair(name).unwrap();
+    let swap = |amount: u64, counter_amount: u64| {
+        let mut tx = Transaction::swap("alice".to_string(), "bob".to_string(), amount, counter_amount)
+            .for_chain(&node.genesis.chain_id);
+        tx.sign(&key("alice"));
+        tx
+    };
+    
+    let unsigned = swap(100, 40);
+    assert!(matches!(node.ledger.add_transaction(unsigned).await, Err(LedgerError::InvalidTransaction(_))));
+    let mut forged = swap(100, 40);
+    forged.countersign(&key("charlie"));
+    assert!(matches!(node.ledger.add_transaction(forged).await, Err(LedgerError::InvalidTransaction(_))));
+    
+    // Bob's leg is more than he has, so neither side is reserved
+    let mut overdrawn = swap(100, 2_000_000);
+    overdrawn.countersign(&key("bob"));
+    assert!(matches!(node.ledger.add_transaction(overdrawn).await, Err(LedgerError::InsufficientBalance)));
+    assert_eq!(node.ledger.reserved_balance("alice"), 0);
+    
+    let mut tx = swap(100, 40);
+    tx.countersign(&key("bob"));
+    node.ledger.add_transaction(tx).await.unwrap();
+    assert_eq!(node.ledger.reserved_balance("alice"), 100);
+    assert_eq!(node.ledger.reserved_balance("bob"), 40);
+    
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.get_balance("alice").await, 999_940);
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_060);
+    assert_eq!(node.ledger.reserved_balance("bob"), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn time_locked_transactions_wait_in_the_mempool_until_due() {
+    let node = TestNode::new("it-timelock");
+    let locked = |from: &str, not_before: BlockBound| {
+        let mut tx = Transaction::new(from.to_string(), "bob".to_string(), 10)
+            .for_chain(&node.genesis.chain_id)
+            .with_not_before(not_before);
+        tx.sign(&node.genesis.dev_keypair(from).unwrap());
+        tx
+    };
+    
+    node.ledger.add_transaction(locked("alice", BlockBound::Height(2))).await.unwrap();
+    assert_eq!(node.ledger.reserved_balance("alice"), 10);
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.get_latest_block().await.header.height, 0);
+    
+    node.ledger.add_transaction(node.transfer("eve", "bob", 10)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.get_latest_block().await.transactions[0].from, "eve");
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.get_latest_block().await.transactions[0].from, "alice");
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_020);
+    
+    let due = chrono::Utc::now() + chrono::Duration::milliseconds(300);
+    node.ledger.add_transaction(locked("charlie", BlockBound::Time(due))).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.pending_for("charlie").len(), 1);
+    wait_for(|| async {
+        node.ledger.process_transactions(10).await.unwrap();
+        node.ledger.mempool_size() == 0
+    }).await;
+    assert!(node.ledger.get_latest_block().await.header.timestamp >= due);
+    
+    // Another producer can't jump the lock
+    let sibling = TestNode::new("it-timelock-sibling");
+    let mut early = Transaction::new("alice".to_string(), "bob".to_string(), 10)
+        .for_chain(&sibling.genesis.chain_id)
+        .with_not_before(BlockBound::Height(5));
+    early.sign(&sibling.genesis.dev_keypair("alice").unwrap());
+    let mut block = distributed_ledger::Block::child_of(&sibling.ledger.get_latest_block().await, vec![early]);
+    block.mine(sibling.genesis.params.difficulty);
+    assert!(matches!(sibling.ledger.import_block(block).await, Err(LedgerError::BlockValidationFailed(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn data_payloads_are_priced_per_byte_and_bounded_per_block() {
+    let mut genesis = GenesisConfig::dev("it-data");
+    genesis.params.max_transaction_data = 64;
+    genesis.params.max_block_data = 100;
+    genesis.params.data_fee_per_byte = 2;
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let alice = genesis.dev_keypair("alice").unwrap();
+    let notarize = |data: Vec<u8>, fee: u64| {
+        let mut tx = Transaction::new("alice".into(), "bob".into(), 1)
+            .for_chain(&genesis.chain_id)
+            .with_fee(fee)
+            .with_data(data);
+        tx.sign(&alice);
+        tx
+    };
+    
+    assert!(matches!(ledger.add_transaction(notarize(vec![7; 32], 63)).await, Err(LedgerError::InvalidTransaction(_))));
+    assert!(matches!(ledger.add_transaction(notarize(vec![7; 65], 1_000)).await, Err(LedgerError::InvalidTransaction(_))));
+    
+    let mut tampered = notarize(vec![7; 32], 64);
+    tampered.data[0] = 8;
+    assert!(ledger.add_transaction(tampered).await.is_err());
+    
+    let first = notarize(vec![1; 60], 120);
+    ledger.add_transaction(first.clone()).await.unwrap();
+    ledger.add_transaction(notarize(vec![2; 60], 120)).await.unwrap();
+    
+    // Both payloads together exceed the block's allowance, so they land in separate blocks
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_latest_block().await.transactions.len(), 1);
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_transaction_count().await, 2);
+    assert_eq!(ledger.get_balance("alice").await, 1_000_000 - 2 - 240);
+    
+    let Some(distributed_ledger::SearchResult::Transaction { transaction, .. }) = ledger.search(&first.id.to_string()).await else {
+        panic!("notarized transaction not found");
+    };
+    assert_eq!(transaction.data, vec![1; 60]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pending_transactions_can_be_replaced_or_cancelled_for_a_higher_fee() {
+    let node = TestNode::new("it-replace");
+    let ledger = &node.ledger;
+    let mut replacements = ledger.subscribe_replacements();
+    let signed = |tx: Transaction, signer: &str| {
+        let mut tx = tx.for_chain(&node.genesis.chain_id);
+        tx.sign(&node.genesis.dev_keypair(signer).unwrap());
+        tx
+    };
+    
+    let original = node.transfer_with_fee("alice", "bob", 100, 5);
+    ledger.add_transaction(original.clone()).await.unwrap();
+    
+    let cheaper = signed(Transaction::new("alice".into(), "charlie".into(), 100).with_fee(5).replacing(original.id), "alice");
+    assert!(ledger.add_transaction(cheaper).await.is_err());
+    let foreign = signed(Transaction::new("bob".into(), "charlie".into(), 100).with_fee(50).replacing(original.id), "bob");
+    assert!(ledger.add_transaction(foreign).await.is_err());
+    
+    let redirected = signed(Transaction::new("alice".into(), "charlie".into(), 100).with_fee(10).replacing(original.id), "alice");
+    ledger.add_transaction(redirected.clone()).await.unwrap();
+    let event = replacements.recv().await.unwrap();
+    assert_eq!((event.replaced, event.replacement, event.cancelled), (original.id, redirected.id, false));
+    assert_eq!(ledger.pending_for("alice"), vec![redirected.clone()]);
+    assert_eq!(ledger.reserved_balance("alice"), 110);
+    
+    // Later rounds name the same original and take the place of whichever replacement is pending
+    let cancel = signed(Transaction::cancellation("alice".into(), original.id).with_fee(20), "alice");
+    ledger.add_transaction(cancel.clone()).await.unwrap();
+    let event = replacements.recv().await.unwrap();
+    assert_eq!((event.replaced, event.replacement, event.cancelled), (redirected.id, cancel.id, true));
+    
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_balance("alice").await, 1_000_000 - 20);
+    assert_eq!(ledger.get_balance("bob").await, 1_000_000);
+    assert_eq!(ledger.get_balance("charlie").await, 1_000_000);
+    
+    // Once the cancellation commits, neither earlier version can
+    assert!(ledger.add_transaction(original).await.is_err());
+    assert!(ledger.add_transaction(redirected).await.is_err());
+    assert_eq!(ledger.get_transaction_count().await, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn only_the_issuer_mints_and_burns_within_the_caps() {
+    let mut genesis = GenesisConfig::dev("it-issuance");
+    let eve = genesis.dev_keypair("eve").unwrap();
+    genesis.params.issuance = Some(IssuancePolicy {
+        issuer: "eve".into(),
+        public_key: eve.public_key(),
+        max_supply: Some(5_001_000),
+        max_mint_per_block: Some(600),
+    });
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let signed = |tx: Transaction, signer: &str| {
+        let mut tx = tx.for_chain(&genesis.chain_id);
+        tx.sign(&genesis.dev_keypair(signer).unwrap());
+        tx
+    };
+    assert_eq!(ledger.total_supply(), 5_000_000);
+    
+    assert!(ledger.add_transaction(signed(Transaction::mint("alice".into(), "bob".into(), 100), "alice")).await.is_err());
+    assert!(ledger.add_transaction(signed(Transaction::mint("eve".into(), "bob".into(), 700), "eve")).await.is_err());
+    
+    // Each mint fits the per-block cap alone, but not together
+    ledger.add_transaction(signed(Transaction::mint("eve".into(), "bob".into(), 400), "eve")).await.unwrap();
+    ledger.add_transaction(signed(Transaction::mint("eve".into(), "bob".into(), 400), "eve")).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_balance("bob").await, 1_000_400);
+    assert_eq!(ledger.total_supply(), 5_000_400);
+    
+    ledger.add_transaction(signed(Transaction::mint("eve".into(), "bob".into(), 600), "eve")).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.total_supply(), 5_001_000);
+    assert!(ledger.add_transaction(signed(Transaction::mint("eve".into(), "bob".into(), 1), "eve")).await.is_err());
+    
+    assert!(ledger.add_transaction(signed(Transaction::burn("bob".into(), 300), "bob")).await.is_err());
+    ledger.add_transaction(signed(Transaction::burn("eve".into(), 300), "eve")).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_balance("eve").await, 999_700);
+    assert_eq!(ledger.total_supply(), 5_000_700);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn spending_limits_cap_transfers_per_transaction_and_per_epoch() {
+    let mut genesis = GenesisConfig::dev("it-limits-spend");
+    genesis.params.epochs = Some(distributed_ledger::EpochSchedule {
+        length: distributed_ledger::EpochLength::Blocks(2),
+        epochs_per_era: 1,
+    });
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let admin = AdminApi::new(ledger.clone(), "secret");
+    let signed = |tx: Transaction, signer: &str| {
+        let mut tx = tx.for_chain(&genesis.chain_id);
+        tx.sign(&genesis.dev_keypair(signer).unwrap());
+        tx
+    };
+    let send = |from: &str, amount: u64| signed(Transaction::new(from.into(), "charlie".into(), amount), from);
+    
+    let limit = SpendingLimit { per_transaction: Some(500), per_epoch: Some(800) };
+    ledger.add_transaction(signed(Transaction::set_spending_limit("alice".into(), limit), "alice")).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.spending_limit("alice"), Some(limit));
+    
+    assert!(ledger.add_transaction(send("alice", 600)).await.is_err());
+    ledger.add_transaction(send("alice", 400)).await.unwrap();
+    ledger.add_transaction(send("alice", 400)).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.spent_this_epoch("alice"), 800);
+    assert!(ledger.add_transaction(send("alice", 100)).await.is_err());
+    
+    // Only this node enforces the operator's limit, and only at admission
+    admin.set_spending_limit("secret", "bob", Some(SpendingLimit { per_transaction: Some(50), per_epoch: None })).unwrap();
+    assert!(ledger.add_transaction(send("bob", 60)).await.is_err());
+    admin.set_spending_limit("secret", "bob", None).unwrap();
+    
+    // The counters start over with the next epoch
+    for _ in 0..2 {
+        ledger.add_transaction(send("bob", 60)).await.unwrap();
+        ledger.process_transactions(10).await.unwrap();
+    }
+    assert_eq!(ledger.spent_this_epoch("alice"), 0);
+    ledger.add_transaction(send("alice", 100)).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_balance("charlie").await, 1_000_000 + 800 + 120 + 100);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn memos_are_searchable_by_prefix_and_tag() {
+    let node = TestNode::new("it-memos");
+    let ledger = &node.ledger;
+    let memo = |to: &str, text: &str| {
+        let mut tx = Transaction::new("alice".into(), to.into(), 1)
+            .for_chain(&node.genesis.chain_id)
+            .with_fee(text.len() as u64)
+            .with_data(text.as_bytes().to_vec());
+        tx.sign(&node.genesis.dev_keypair("alice").unwrap());
+        tx
+    };
+    
+    let first = memo("bob", "INV-2024-001 #rent #March");
+    let second = memo("bob", "INV-2024-002 #rent");
+    let other = memo("charlie", "payroll #salary");
+    for tx in [&first, &second, &other] {
+        ledger.add_transaction(tx.clone()).await.unwrap();
+    }
+    ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    
+    let ids = |txs: Vec<Transaction>| txs.into_iter().map(|tx| tx.id).collect::<Vec<_>>();
+    assert_eq!(ids(ledger.find_transactions_by_memo_prefix("INV-2024-", 10).await), vec![first.id, second.id]);
+    assert_eq!(ids(ledger.find_transactions_by_memo_prefix("INV-2024-002", 10).await), vec![second.id]);
+    assert_eq!(ids(ledger.find_transactions_by_memo_prefix("INV-2024-", 1).await), vec![first.id]);
+    assert_eq!(ledger.find_transactions_by_tag("#rent", 10).await.len(), 2);
+    assert_eq!(ids(ledger.find_transactions_by_tag("march", 10).await), vec![first.id]);
+    assert!(ledger.find_transactions_by_tag("salary", 10).await.iter().all(|tx| tx.to == "charlie"));
+    assert!(ledger.find_transactions_by_memo_prefix("nothing", 10).await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn secondary_indexes_keep_only_their_window_and_rebuild_when_reenabled() {
+    let node = TestNode::new("it-retention");
+    let ledger = &node.ledger;
+    let admin = AdminApi::new(ledger.clone(), "secret");
+    let windowed = IndexRetention {
+        addresses: Retention::Recent(2),
+        memos: Retention::Disabled,
+        state: Retention::Full,
+    };
+    admin.set_index_retention("secret", windowed).await.unwrap();
+    let invalid = IndexRetention { memos: Retention::Recent(0), ..windowed };
+    assert!(matches!(admin.set_index_retention("secret", invalid).await, Err(LedgerError::InvalidParameters(_))));
+    
+    let mut sent = Vec::new();
+    for _ in 0..3 {
+        let mut tx = Transaction::new("alice".into(), "bob".into(), 1)
+            .for_chain(&node.genesis.chain_id)
+            .with_fee(5)
+            .with_data(b"#tick".to_vec());
+        tx.sign(&node.genesis.dev_keypair("alice").unwrap());
+        ledger.add_transaction(tx.clone()).await.unwrap();
+        ledger.process_transactions(10).await.unwrap();
+        sent.push(tx.id);
+    }
+    
+    let heights = |entries: Vec<distributed_ledger::AccountEntry>| entries.iter().map(|entry| entry.height).collect::<Vec<_>>();
+    assert_eq!(heights(ledger.get_account_history("bob", .., Page::default()).await), vec![2, 3]);
+    assert!(ledger.find_transactions_by_tag("tick", 10).await.is_empty());
+    let replay = ledger.get_transaction(sent[0]).await.0.unwrap();
+    assert!(matches!(ledger.add_transaction(replay).await, Err(LedgerError::DuplicateTransaction)));
+    
+    admin.set_index_retention("secret", IndexRetention::default()).await.unwrap();
+    assert_eq!(ledger.index_retention(), IndexRetention::default());
+    let tagged: Vec<_> = ledger.find_transactions_by_tag("tick", 10).await.into_iter().map(|tx| tx.id).collect();
+    assert_eq!(tagged, sent);
+    assert_eq!(heights(ledger.get_account_history("bob", .., Page::default()).await), vec![1, 2, 3]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn past_states_are_pruned_to_their_window_but_never_above_finality() {
+    let mut genesis = GenesisConfig::dev("it-state-retention");
+    genesis.params.finality_depth = 3;
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let admin = AdminApi::new(ledger.clone(), "secret");
+    let window = IndexRetention { state: Retention::Recent(1), ..IndexRetention::default() };
+    admin.set_index_retention("secret", window).await.unwrap();
+    
+    for amount in 1..=5 {
+        let mut tx = Transaction::new("alice".into(), "bob".into(), amount).for_chain(&genesis.chain_id);
+        tx.sign(&genesis.dev_keypair("alice").unwrap());
+        ledger.add_transaction(tx).await.unwrap();
+        ledger.process_transactions(10).await.unwrap();
+    }
+    
+    // A one-block window, but the three blocks above the finalized one can still be reorganised
+    assert_eq!(ledger.get_balance_at("bob", 1).await, None);
+    assert_eq!(ledger.get_balance_at("bob", 2).await, Some(1_000_003));
+    assert_eq!(ledger.get_balance_at("bob", 5).await, Some(1_000_015));
+    assert_eq!(ledger.state_diff(2, 5).await.unwrap().len(), 2);
+    assert!(ledger.state_diff(1, 5).await.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn past_balances_are_those_each_block_left_and_stay_put_as_the_chain_grows() {
+    let node = TestNode::new("it-balance-at");
+    node.ledger.add_transaction(node.transfer_with_fee("alice", "eve", 100, 5)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    node.ledger.add_transaction(node.transfer("eve", "bob", 40)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    
+    let at = |address: &'static str, height: u64| node.ledger.get_balance_at(address, height);
+    assert_eq!((at("alice", 0).await, at("alice", 1).await, at("alice", 2).await), (Some(1_000_000), Some(999_895), Some(999_895)));
+    assert_eq!((at("eve", 0).await, at("eve", 1).await, at("eve", 2).await), (Some(1_000_000), Some(1_000_100), Some(1_000_060)));
+    assert_eq!(at("bob", 2).await, Some(1_000_040));
+    
+    // Nothing is known above the tip, and later blocks don't rewrite what earlier ones left
+    assert_eq!(at("eve", 3).await, None);
+    node.ledger.add_transaction(node.transfer("eve", "bob", 1_000_060)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!((at("eve", 2).await, at("eve", 3).await), (Some(1_000_060), Some(0)));
+    assert_eq!(node.ledger.get_balance("eve").await, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn utxo_chains_spend_coins_and_burn_fees() {
+    let genesis = GenesisConfig::dev("it-utxo").with_model(LedgerModel::Utxo);
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let signed = |tx: Transaction, signer: &str| {
+        let mut tx = tx.for_chain(&genesis.chain_id);
+        tx.sign(&genesis.dev_keypair(signer).unwrap());
+        tx
+    };
+    let pay = |to: &str, amount: u64| Payment { to: to.into(), amount };
+    
+    let coins = ledger.utxos("alice");
+    assert_eq!(coins.len(), 1);
+    let (coin, _) = coins[0];
+    assert!(ledger.add_transaction(signed(Transaction::new("alice".into(), "bob".into(), 10), "alice")).await.is_err());
+    
+    // Inputs must cover the outputs and the fee exactly
+    let short = Transaction::spend("alice".into(), vec![coin], vec![pay("bob", 300), pay("alice", 699_700)]).with_fee(10);
+    assert!(ledger.add_transaction(signed(short, "alice")).await.is_err());
+    let theft = Transaction::spend("bob".into(), vec![coin], vec![pay("bob", 1_000_000)]);
+    assert!(ledger.add_transaction(signed(theft, "bob")).await.is_err());
+    
+    let spend = Transaction::spend("alice".into(), vec![coin], vec![pay("bob", 300), pay("alice", 999_690)]).with_fee(10);
+    let id = spend.id;
+    ledger.add_transaction(signed(spend, "alice")).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    
+    assert_eq!(ledger.utxo(&coin), None);
+    assert_eq!(ledger.utxo(&OutPoint { tx: id, index: 0 }).unwrap().amount, 300);
+    assert_eq!(ledger.utxos("bob").len(), 2);
+    assert_eq!(ledger.get_balance("alice").await, 999_690);
+    assert_eq!(ledger.get_balance("bob").await, 1_000_300);
+    assert_eq!(ledger.total_supply(), 4_999_990);
+    
+    let again = Transaction::spend("alice".into(), vec![coin], vec![pay("alice", 1_000_000)]);
+    assert!(ledger.add_transaction(signed(again, "alice")).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn vested_funds_unlock_after_the_cliff_and_then_linearly() {
+    let genesis = GenesisConfig::dev("it-vesting")
+        .with_vesting_allocation("frank", 800, VestingSchedule { cliff: 2, duration: 4 });
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let signed = |tx: Transaction, signer: &str| {
+        let mut tx = tx.for_chain(&genesis.chain_id);
+        tx.sign(&genesis.dev_keypair(signer).unwrap());
+        tx
+    };
+    let from_frank = |amount: u64| Transaction::new("frank".into(), "alice".into(), amount).for_chain(&genesis.chain_id);
+    assert_eq!(ledger.vesting_balance("frank"), VestingBalance { locked: 800, liquid: 0 });
+    assert!(matches!(ledger.add_transaction(from_frank(1)).await, Err(LedgerError::InsufficientBalance)));
+    
+    let schedule = VestingSchedule { cliff: 3, duration: 4 };
+    ledger.add_transaction(signed(Transaction::vest("alice".into(), "bob".into(), 1000, schedule), "alice")).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_balance("bob").await, 1_001_000);
+    assert_eq!(ledger.vesting_balance("bob"), VestingBalance { locked: 1000, liquid: 1_000_000 });
+    assert_eq!(ledger.vesting_grants("bob").len(), 1);
+    assert!(ledger.add_transaction(signed(Transaction::new("bob".into(), "alice".into(), 1_000_001), "bob")).await.is_err());
+    
+    ledger.add_transaction(signed(Transaction::new("charlie".into(), "diana".into(), 1), "charlie")).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    
+    // A block at height 3 is one of four blocks into frank's release, and the next block is what pending spends and
+    // the available balance are measured against
+    assert_eq!(ledger.chain_height(), 2);
+    assert_eq!(ledger.available_balance("frank").await, 200);
+    assert!(ledger.add_transaction(from_frank(201)).await.is_err());
+    ledger.add_transaction(from_frank(200)).await.unwrap();
+    assert_eq!(ledger.reserved_balance("frank"), 200);
+    assert_eq!(ledger.available_balance("frank").await, 0);
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.vesting_balance("frank"), VestingBalance { locked: 600, liquid: 0 });
+    
+    while ledger.get_latest_block().await.header.height < 6 {
+        ledger.add_transaction(signed(Transaction::new("charlie".into(), "diana".into(), 1), "charlie")).await.unwrap();
+        ledger.process_transactions(10).await.unwrap();
+    }
+    ledger.add_transaction(signed(Transaction::new("bob".into(), "alice".into(), 1_001_000), "bob")).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_balance("bob").await, 0);
+    assert_eq!(ledger.vesting_balance("frank"), VestingBalance { locked: 0, liquid: 600 });
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invoices_round_trip_through_uris_and_settle_on_payment() {
+    let node = TestNode::new("it-invoices");
+    let mut payments = node.ledger.subscribe_invoice_payments();
+    let expires_at = chrono::Utc::now() + chrono::Duration::minutes(5);
+    let invoice = Invoice::new("order #42", "bob", 250, expires_at);
+    
+    let scanned = Invoice::from_uri(&invoice.to_uri()).unwrap();
+    assert_eq!(scanned.reference, "order #42");
+    assert_eq!(scanned.expires_at.timestamp(), expires_at.timestamp());
+    node.ledger.create_invoice(scanned.clone()).unwrap();
+    assert!(node.ledger.create_invoice(scanned.clone()).is_err());
+    assert_eq!(node.ledger.invoice_status("order #42"), Some(InvoiceStatus::Pending));
+    
+    let pay = |amount: u64| {
+        let mut tx = Transaction::new("alice".into(), "bob".into(), amount)
+            .with_data(b"order #42".to_vec())
+            .for_chain(&node.genesis.chain_id)
+            .with_fee(100);
+        tx.sign(&node.genesis.dev_keypair("alice").unwrap());
+        tx
+    };
+    
+    // Short payments don't settle it
+    node.ledger.add_transaction(pay(249)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.invoice_status("order #42"), Some(InvoiceStatus::Pending));
+    
+    let mut payment = scanned.payment("alice").for_chain(&node.genesis.chain_id).with_fee(100);
+    payment.sign(&node.genesis.dev_keypair("alice").unwrap());
+    let id = payment.id;
+    node.ledger.add_transaction(payment).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    
+    let paid = payments.try_recv().unwrap();
+    assert_eq!((paid.reference.as_str(), paid.tx, paid.payer.as_str(), paid.height), ("order #42", id, "alice", 2));
+    assert_eq!(node.ledger.invoice_status("order #42"), Some(InvoiceStatus::Paid { tx: id, height: 2 }));
+    
+    node.ledger.create_invoice(Invoice::new("stale", "bob", 1, chrono::Utc::now() - chrono::Duration::seconds(1))).unwrap();
+    assert_eq!(node.ledger.invoice_status("stale"), Some(InvoiceStatus::Expired));
+    assert!(Invoice::from_uri("ledger:bob?amount=1&asset=gold&expires=0&ref=x").is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dust_policy_filters_tiny_transfers_and_balances() {
+    let node = TestNode::new("it-dust");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    node.ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    
+    let policy = MempoolLimits { min_transfer: 100, min_balance: 1_000, ..Default::default() };
+    admin.set_mempool_limits("secret", policy).unwrap();
+    assert!(node.ledger.add_transaction(node.transfer("alice", "bob", 99)).await.is_err());
+    assert!(node.ledger.add_transaction(node.transfer("alice", "frank", 500)).await.is_err());
+    node.ledger.add_transaction(node.transfer("alice", "frank", 1_000)).await.unwrap();
+    
+    // Emptying an account is fine, leaving dust in it isn't
+    node.ledger.process_transactions(10).await.unwrap();
+    assert!(node.ledger.add_transaction(node.transfer("charlie", "bob", 999_500)).await.is_err());
+    node.ledger.add_transaction(node.transfer("charlie", "bob", 1_000_000)).await.unwrap();
+    assert_eq!(node.ledger.mempool_limits(), policy);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bundles_commit_whole_in_one_block_or_not_at_all() {
+    let node = TestNode::new("it-bundles");
+    let unsigned = |from: &str, to: &str, amount: u64| {
+        Transaction::new(from.into(), to.into(), amount).for_chain(&node.genesis.chain_id)
+    };
+    let sign = |mut members: Vec<Transaction>| {
+        for tx in members.iter_mut().filter(|tx| tx.from != "frank") {
+            let keypair = node.genesis.dev_keypair(&tx.from).unwrap();
+            tx.sign(&keypair);
+        }
+        members
+    };
+    
+    // frank has nothing until alice's transfer lands, so his payment only works as part of the same bundle
+    let members = sign(distributed_ledger::bundle::bundle(vec![unsigned("alice", "frank", 500), unsigned("frank", "bob", 400)]));
+    assert!(node.ledger.add_transaction(members[1].clone()).await.is_err());
+    assert!(node.ledger.add_bundle(members[..1].to_vec()).await.is_err());
+    node.ledger.add_bundle(members.clone()).await.unwrap();
+    assert!(node.ledger.add_bundle(members.clone()).await.is_err());
+    
+    node.ledger.add_transaction(node.transfer("charlie", "diana", 7)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    let block = node.ledger.get_latest_block().await;
+    assert_eq!(block.transactions.len(), 3);
+    assert_eq!((block.transactions[0].id, block.transactions[1].id), (members[0].id, members[1].id));
+    assert_eq!(node.ledger.get_balance("frank").await, 100);
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_400);
+    assert!(node.ledger.pending_bundles().is_empty());
+    
+    // One member failing sinks the rest
+    let overdrawn = sign(distributed_ledger::bundle::bundle(vec![unsigned("alice", "frank", 10), unsigned("frank", "bob", 1_000)]));
+    assert!(matches!(node.ledger.add_bundle(overdrawn).await, Err(LedgerError::InvalidTransaction(_))));
+    assert_eq!(node.ledger.reserved_balance("alice"), 0);
+    
+    // A block splitting a bundle is refused
+    let sibling = node.sibling();
+    let bundle = sign(distributed_ledger::bundle::bundle(vec![unsigned("alice", "frank", 500), unsigned("frank", "bob", 400)]));
+    let mut block = distributed_ledger::Block::child_of(&sibling.get_latest_block().await, bundle[..1].to_vec());
+    block.mine(node.genesis.params.difficulty);
+    assert!(matches!(sibling.import_block(block).await, Err(LedgerError::BlockValidationFailed(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn spenders_move_funds_within_their_allowance_until_revoked() {
+    let node = TestNode::new("it-allowances");
+    let signed = |tx: Transaction, signer: &str| {
+        let mut tx = tx.for_chain(&node.genesis.chain_id);
+        tx.sign(&node.genesis.dev_keypair(signer).unwrap());
+        tx
+    };
+    let pull = |amount: u64| signed(Transaction::transfer_from("bob".into(), "alice".into(), "charlie".into(), amount), "bob");
+    
+    assert!(node.ledger.add_transaction(pull(1)).await.is_err());
+    node.ledger.add_transaction(signed(Transaction::approve("alice".into(), "bob".into(), 500), "alice")).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.allowance("alice", "bob"), 500);
+    assert_eq!(node.ledger.allowances_granted_by("alice"), vec![("bob".to_string(), 500)]);
+    
+    // Two pulls that each fit the allowance, but not together
+    assert!(node.ledger.add_transaction(pull(501)).await.is_err());
+    node.ledger.add_transaction(pull(300)).await.unwrap();
+    node.ledger.add_transaction(pull(300)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.get_balance("alice").await, 999_700);
+    assert_eq!(node.ledger.get_balance("charlie").await, 1_000_300);
+    assert_eq!(node.ledger.allowance("alice", "bob"), 200);
+    
+    // Only the owner's signature can stand in for an approval
+    assert!(node.ledger.add_transaction(signed(Transaction::approve("alice".into(), "bob".into(), 900), "bob")).await.is_err());
+    node.ledger.add_transaction(signed(Transaction::revoke_allowance("alice".into(), "bob".into()), "alice")).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.allowance("alice", "bob"), 0);
+    assert!(node.ledger.add_transaction(pull(100)).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn coinbases_pay_the_emission_schedule_plus_fees() {
+    let mut genesis = GenesisConfig::dev("it-coinbase");
+    genesis.params.emission = Some(EmissionSchedule { initial_reward: 50, halving_interval: 2 });
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let admin = AdminApi::new(ledger.clone(), "secret");
+    let transfer = |fee: u64| {
+        let mut tx = Transaction::new("alice".into(), "bob".into(), 10).for_chain(&genesis.chain_id).with_fee(fee);
+        tx.sign(&genesis.dev_keypair("alice").unwrap());
+        tx
+    };
+    let supply = ledger.total_supply();
+    
+    // Nobody to pay the coinbase to yet
+    ledger.add_transaction(transfer(5)).await.unwrap();
+    assert!(matches!(ledger.process_transactions(10).await, Err(LedgerError::InvalidParameters(_))));
+    admin.set_coinbase_address("secret", "frank").unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    
+    let block = ledger.get_latest_block().await;
+    assert_eq!(block.transactions.len(), 2);
+    assert_eq!(block.transactions[0].kind, TransactionKind::Coinbase { height: 1 });
+    assert_eq!(ledger.get_balance("frank").await, 55);
+    assert_eq!(ledger.total_supply(), supply + 50);
+    
+    let forged = Transaction::coinbase(2, "eve".into(), 25).for_chain(&genesis.chain_id);
+    assert!(matches!(ledger.add_transaction(forged).await, Err(LedgerError::InvalidTransaction(_))));
+    
+    // The reward halves from height 2
+    ledger.add_transaction(transfer(0)).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_balance("frank").await, 80);
+    
+    let sibling = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let parent = sibling.get_latest_block().await;
+    let forge = |transactions: Vec<Transaction>| {
+        let mut block = distributed_ledger::Block::child_of(&parent, transactions);
+        block.header.state_root = sibling.state_root();
+        block.mine(genesis.params.difficulty);
+        block
+    };
+    let coinbase = |amount: u64| Transaction::coinbase(1, "eve".into(), amount).for_chain(&genesis.chain_id);
+    assert!(matches!(sibling.import_block(forge(vec![transfer(5)])).await, Err(LedgerError::BlockValidationFailed(_))));
+    assert!(matches!(sibling.import_block(forge(vec![coinbase(56), transfer(5)])).await, Err(LedgerError::BlockValidationFailed(_))));
+    assert!(matches!(sibling.import_block(forge(vec![transfer(5), coinbase(55)])).await, Err(LedgerError::BlockValidationFailed(_))));
+    assert!(matches!(sibling.import_block(forge(vec![coinbase(55), transfer(5)])).await, Ok(BlockImport::Extended)));
+    assert_eq!(sibling.get_balance("eve").await, 1_000_055);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn hash_locks_release_to_the_preimage_or_refund_after_the_timeout() {
+    let node = TestNode::new("it-htlc");
+    let signed = |tx: Transaction, signer: &str| {
+        let mut tx = tx.for_chain(&node.genesis.chain_id);
+        tx.sign(&node.genesis.dev_keypair(signer).unwrap());
+        tx
+    };
+    let hash = distributed_ledger::htlc::hashlock(b"secret");
+    let lock = |amount: u64, hash: &str, timeout: u64| {
+        signed(Transaction::hash_lock("alice".into(), "bob".into(), amount, hash.to_string(), timeout), "alice")
+    };
+    let supply = node.ledger.total_supply();
+    
+    assert!(node.ledger.add_transaction(lock(500, "00", 10)).await.is_err());
+    let (swap, expiring) = (lock(500, &hash, 10), lock(200, &hash, 3));
+    node.ledger.add_transaction(swap.clone()).await.unwrap();
+    node.ledger.add_transaction(expiring.clone()).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.get_balance("alice").await, 999_300);
+    assert_eq!(node.ledger.open_hash_locks("bob").len(), 2);
+    assert_eq!(node.ledger.total_supply(), supply);
+    
+    // Anyone holding the preimage can release the funds, but only to the recipient
+    assert!(node.ledger.add_transaction(signed(Transaction::refund("alice".into(), expiring.id), "alice")).await.is_err());
+    assert!(node.ledger.add_transaction(signed(Transaction::claim("eve".into(), swap.id, b"guess".to_vec()), "eve")).await.is_err());
+    node.ledger.add_transaction(signed(Transaction::claim("eve".into(), swap.id, b"secret".to_vec()), "eve")).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_500);
+    assert_eq!(node.ledger.get_balance("eve").await, 1_000_000);
+    assert!(matches!(node.ledger.hash_lock(swap.id).unwrap().status, HashLockStatus::Claimed { ref preimage, .. } if preimage == b"secret"));
+    
+    // From the timeout on, only the sender can take the funds back
+    assert!(node.ledger.add_transaction(signed(Transaction::claim("bob".into(), expiring.id, b"secret".to_vec()), "bob")).await.is_err());
+    assert!(node.ledger.add_transaction(signed(Transaction::refund("bob".into(), expiring.id), "bob")).await.is_err());
+    node.ledger.add_transaction(signed(Transaction::refund("alice".into(), expiring.id), "alice")).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.get_balance("alice").await, 999_500);
+    assert!(node.ledger.open_hash_locks("alice").is_empty());
+    assert_eq!(node.ledger.total_supply(), supply);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn load_profiles_generate_reproducible_signed_traffic() {
+    let profile = LoadProfile { accounts: 10, conflict_bps: 10_000, ..LoadProfile::bursty(2, 6, 3) };
+    let genesis = profile.genesis().with_chain_id("it-loadgen");
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let shape = |tx: &Transaction| (tx.from.clone(), tx.to.clone(), tx.amount);
+    
+    let mut load = LoadGenerator::new(profile.clone(), &genesis).unwrap();
+    let ticks: Vec<_> = (0..3).map(|_| load.next_tick()).collect();
+    assert_eq!(ticks.iter().map(Vec::len).collect::<Vec<_>>(), [2, 2, 6]);
+    let mut again = LoadGenerator::new(profile.clone(), &genesis).unwrap();
+    assert!(ticks.concat().iter().map(shape).eq(again.by_ref().take(10).map(|tx| shape(&tx))));
+    
+    // Every transfer conflicts, so one sender sends them all
+    assert!(ticks.concat().iter().all(|tx| tx.from == ticks[0][0].from && tx.to != tx.from));
+    for tx in ticks.concat() {
+        ledger.add_transaction(tx).await.unwrap();
+    }
+    ledger.process_transactions(100).await.unwrap();
+    assert_eq!(ledger.get_latest_block().await.transactions.len(), 10);
+    
+    // A steep zipfian distribution leaves the first account sending most
+    let mut hot = LoadGenerator::new(LoadProfile { accounts: 10, ..LoadProfile::zipfian(3.0) }, &genesis).unwrap();
+    let first = hot.by_ref().take(1_000).filter(|tx| tx.from == LoadProfile::account(0)).count();
+    assert!(first > 700, "{}", first);
+    assert!(LoadGenerator::new(LoadProfile { accounts: 11, ..profile }, &genesis).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transactions_are_tracked_from_submission_to_inclusion_or_rejection() {
+    let node = TestNode::new("it-tx-status");
+    let ledger = &node.ledger;
+    
+    let sent = node.transfer("alice", "bob", 1);
+    assert_eq!(ledger.get_transaction(sent.id).await, (None, TransactionStatus::Unknown));
+    ledger.add_transaction(sent.clone()).await.unwrap();
+    assert_eq!(ledger.get_transaction(sent.id).await, (Some(sent.clone()), TransactionStatus::Pending));
+    
+    let original = node.transfer_with_fee("charlie", "bob", 1, 5);
+    ledger.add_transaction(original.clone()).await.unwrap();
+    let mut replacement = Transaction::new("charlie".into(), "diana".into(), 1)
+        .for_chain(&node.genesis.chain_id)
+        .with_fee(10)
+        .replacing(original.id);
+    replacement.sign(&node.genesis.dev_keypair("charlie").unwrap());
+    ledger.add_transaction(replacement.clone()).await.unwrap();
+    
+    ledger.process_transactions(10).await.unwrap();
+    let block = ledger.get_latest_block().await;
+    let included = TransactionStatus::Included { height: 1, block_hash: block.header.hash.clone() };
+    assert_eq!(ledger.get_transaction(sent.id).await, (Some(sent), included));
+    let replaced = TransactionStatus::Rejected { reason: format!("Replaced by {}", replacement.id) };
+    assert_eq!(ledger.get_transaction(original.id).await, (Some(original), replaced));
+    
+    let drained = node.transfer("eve", "bob", 1);
+    ledger.add_transaction(drained.clone()).await.unwrap();
+    AdminApi::new(ledger.clone(), "secret").drain_mempool("secret").unwrap();
+    let (_, status) = ledger.get_transaction(drained.id).await;
+    assert_eq!(status, TransactionStatus::Rejected { reason: "Drained from the mempool".to_string() });
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn account_histories_list_each_transaction_with_the_running_balance() {
+    let node = TestNode::new("it-history");
+    let ledger = &node.ledger;
+    
+    ledger.add_transaction(node.transfer("alice", "frank", 10)).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    ledger.add_transaction(Transaction::new("frank".into(), "bob".into(), 3).for_chain(&node.genesis.chain_id)).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    let payments = vec![Payment { to: "frank".into(), amount: 2 }, Payment { to: "eve".into(), amount: 1 }];
+    let mut payout = Transaction::payout("alice".into(), payments).for_chain(&node.genesis.chain_id);
+    payout.sign(&node.genesis.dev_keypair("alice").unwrap());
+    ledger.add_transaction(payout).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    
+    let statement = ledger.get_account_history("frank", .., Page::default()).await;
+    let lines: Vec<_> = statement.iter().map(|entry| (entry.height, entry.direction, entry.balance)).collect();
+    assert_eq!(lines, vec![(1, Direction::Incoming, 10), (2, Direction::Outgoing, 7), (3, Direction::Incoming, 9)]);
+    
+    let heights = |entries: Vec<distributed_ledger::AccountEntry>| entries.iter().map(|entry| entry.height).collect::<Vec<_>>();
+    assert_eq!(heights(ledger.get_account_history("frank", 2.., Page::default()).await), vec![2, 3]);
+    assert_eq!(heights(ledger.get_account_history("frank", ..=1, Page::default()).await), vec![1]);
+    assert_eq!(heights(ledger.get_account_history("frank", .., Page { offset: 1, limit: 1 }).await), vec![2]);
+    assert_eq!(heights(ledger.get_account_history("eve", .., Page::default()).await), vec![3]);
+    assert!(ledger.get_account_history("charlie", .., Page::default()).await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocks_index_the_accounts_they_touched() {
+    let node = TestNode::new("it-touched");
+    let ledger = &node.ledger;
+    
+    for (from, to, amount) in [("alice", "bob", 10), ("charlie", "diana", 5), ("bob", "eve", 3)] {
+        ledger.add_transaction(node.transfer(from, to, amount)).await.unwrap();
+        ledger.process_transactions(10).await.unwrap();
+    }
+    
+    let touched = |height| ledger.accounts_touched_at(height).unwrap().into_iter().collect::<Vec<_>>();
+    assert_eq!(touched(0), vec!["alice", "bob", "charlie", "diana", "eve"]);
+    assert_eq!(touched(2), vec!["charlie", "diana"]);
+    assert!(ledger.accounts_touched_at(4).is_none());
+    
+    let changes: Vec<_> = ledger.balance_changes("bob", 1..).into_iter()
+        .map(|(height, change)| (height, change.before, change.after))
+        .collect();
+    assert_eq!(changes, vec![(1, 1_000_000, 1_000_010), (3, 1_000_010, 1_000_007)]);
+    assert_eq!(ledger.balance_changes("bob", ..3).len(), 2);
+    assert_eq!(ledger.balance_changes("bob", 2..3), vec![]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rich_list_and_balance_distribution_follow_each_block() {
+    let node = TestNode::new("it-rich-list");
+    let queries = QueryExecutor::new(node.ledger.clone(), 1);
+    assert_eq!(queries.balance_summary().active_accounts, 5);
+    
+    node.ledger.add_transaction(node.transfer("alice", "frank", 10)).await.unwrap();
+    node.ledger.add_transaction(node.transfer("bob", "charlie", 500)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    
+    let top: Vec<_> = queries.rich_list(3).into_iter().map(|(address, _)| address).collect();
+    assert_eq!(top, vec!["charlie", "diana", "eve"]);
+    assert_eq!(queries.rich_list(1), vec![("charlie".to_string(), 1_000_500)]);
+    
+    let summary = queries.balance_summary();
+    assert_eq!((summary.active_accounts, summary.circulating), (6, 5_000_000));
+    let buckets: Vec<_> = summary.distribution.iter().map(|bucket| (bucket.min, bucket.accounts, bucket.balance)).collect();
+    assert_eq!(buckets, vec![(10, 1, 10), (100_000, 2, 1_999_490), (1_000_000, 3, 3_000_500)]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scans_stop_at_the_finalized_height_and_within_their_budgets() {
+    let node = TestNode::new("it-query-budgets");
+    let queries = QueryExecutor::new(node.ledger.clone(), 1);
+    for _ in 0..10 {
+        node.ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+        node.ledger.process_transactions(10).await.unwrap();
+    }
+    let heights = |budget: QueryBudget, to_height: Option<u64>| {
+        queries.scan(0, to_height, budget, CancellationToken::new(), Vec::new(), |mut heights: Vec<u64>, height, _| {
+            heights.push(height);
+            heights
+        })
+    };
+    
+    // Ten blocks, the last six of them not yet final
+    assert_eq!(heights(QueryBudget::default(), None).await.unwrap(), (0..=4).collect::<Vec<_>>());
+    assert_eq!(heights(QueryBudget::default(), Some(2)).await.unwrap(), vec![0, 1, 2]);
+    let unfinalized = QueryBudget { include_unfinalized: true, ..QueryBudget::default() };
+    assert_eq!(heights(unfinalized.clone(), None).await.unwrap(), (0..=10).collect::<Vec<_>>());
+    assert_eq!(heights(unfinalized, Some(7)).await.unwrap().len(), 8);
+    
+    let capped = QueryBudget { max_blocks: Some(3), ..QueryBudget::default() };
+    assert!(matches!(heights(capped, None).await, Err(LedgerError::QueryAborted(e)) if e.contains("Block budget")));
+    
+    let hurried = QueryBudget { max_duration: Duration::from_millis(50), ..QueryBudget::default() };
+    let slow = queries.scan(0, None, hurried, CancellationToken::new(), 0, |scanned, _, _| {
+        std::thread::sleep(Duration::from_millis(20));
+        scanned + 1
+    });
+    assert!(matches!(slow.await, Err(LedgerError::QueryAborted(e)) if e.contains("Time budget")));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_cancelled_scan_returns_promptly_while_blocks_keep_coming() {
+    let node = TestNode::start("it-query-cancel").await;
+    for _ in 0..20 {
+        node.ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+        wait_for(|| async { node.ledger.mempool_size() == 0 }).await;
+    }
+    let height = node.ledger.chain_height();
+    
+    // Long enough to outlast the test unless cancelled
+    let queries = QueryExecutor::new(node.ledger.clone(), 1);
+    let cancel = CancellationToken::new();
+    let budget = QueryBudget { max_duration: Duration::from_secs(60), include_unfinalized: true, ..QueryBudget::default() };
+    let scan = tokio::spawn({
+        let cancel = cancel.clone();
+        async move {
+            queries.scan(0, None, budget, cancel, 0, |scanned, _, _| {
+                std::thread::sleep(Duration::from_millis(100));
+                scanned + 1
+            }).await
+        }
+    });
+    
+    node.ledger.add_transaction(node.transfer("charlie", "diana", 1)).await.unwrap();
+    wait_for(|| async { node.ledger.chain_height() > height }).await;
+    assert!(!scan.is_finished());
+    
+    cancel.cancel();
+    let result = tokio::time::timeout(Duration::from_secs(1), scan).await.unwrap().unwrap();
+    assert!(matches!(result, Err(LedgerError::QueryAborted(e)) if e.contains("cancelled")));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocks_and_transactions_stream_across_chunks() {
+    let node = TestNode::new("it-streams");
+    for i in 0..70 {
+        let to = if i % 2 == 0 { "bob" } else { "charlie" };
+        node.ledger.add_transaction(node.transfer("alice", to, 1)).await.unwrap();
+        node.ledger.process_transactions(10).await.unwrap();
+    }
+    
+    let heights: Vec<u64> = node.ledger.iter_blocks(0).map(|block| block.header.height).collect().await;
+    assert_eq!(heights, (0..=70).collect::<Vec<_>>());
+    assert_eq!(node.ledger.iter_blocks(65).count().await, 6);
+    
+    let filter = TransactionFilter { from_height: 10, to_height: Some(20), address: Some("charlie".into()) };
+    let found: Vec<_> = node.ledger.iter_transactions(filter).collect().await;
+    assert_eq!(found.iter().map(|(height, _)| *height).collect::<Vec<_>>(), vec![10, 12, 14, 16, 18, 20]);
+    assert!(found.iter().all(|(_, tx)| tx.to == "charlie"));
+    assert_eq!(node.ledger.iter_transactions(TransactionFilter::default()).count().await, 70);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn state_diffs_list_the_accounts_that_changed_between_heights() {
+    let node = TestNode::new("it-state-diff");
+    for (from, to, amount) in [("alice", "frank", 10), ("bob", "charlie", 5), ("charlie", "bob", 5)] {
+        node.ledger.add_transaction(node.transfer(from, to, amount)).await.unwrap();
+        node.ledger.process_transactions(10).await.unwrap();
+    }
+    
+    let deltas = |changes: Vec<distributed_ledger::BalanceChange>| {
+        changes.iter().map(|change| (change.address.clone(), change.delta())).collect::<Vec<_>>()
+    };
+    // Bob and charlie end where they started
+    let diff = node.ledger.state_diff(0, 3).await.unwrap();
+    assert_eq!(deltas(diff.clone()), vec![("alice".to_string(), -10), ("frank".to_string(), 10)]);
+    assert_eq!((diff[1].before, diff[1].after), (0, 10));
+    assert_eq!(deltas(node.ledger.state_diff(1, 2).await.unwrap()), vec![("bob".to_string(), -5), ("charlie".to_string(), 5)]);
+    assert_eq!(deltas(node.ledger.state_diff(3, 0).await.unwrap()), vec![("alice".to_string(), 10), ("frank".to_string(), -10)]);
+    assert!(node.ledger.state_diff(2, 2).await.unwrap().is_empty());
+    assert!(node.ledger.state_diff(0, 4).await.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn read_views_hold_one_instant_while_blocks_apply() {
+    let node = TestNode::new("it-read-view");
+    let tx = node.transfer("alice", "bob", 10);
+    node.ledger.add_transaction(tx.clone()).await.unwrap();
+    
+    // Holding the view doesn't hold up the producer, and the view still sees the transaction pending and nothing applied
+    let view = node.ledger.read_view().await;
+    let producer = {
+        let ledger = node.ledger.clone();
+        tokio::spawn(async move { ledger.process_transactions(10).await })
+    };
+    tokio::time::timeout(Duration::from_secs(5), producer).await.unwrap().unwrap().unwrap();
+    assert_eq!(node.ledger.chain_height(), 1);
+    assert_eq!(view.height(), 0);
+    assert_eq!((view.balance("alice"), view.balance("bob")), (Some(1_000_000), Some(1_000_000)));
+    assert_eq!(view.pending_for("alice"), vec![&tx]);
+    assert_eq!(view.transaction(tx.id).1, TransactionStatus::Pending);
+    assert!(view.account_history("alice", .., Page::default()).is_empty());
+    drop(view);
+    
+    let view = node.ledger.read_view().await;
+    assert_eq!(view.height(), 1);
+    assert_eq!((view.balance("alice"), view.balance("bob")), (Some(999_990), Some(1_000_010)));
+    assert_eq!(view.balance("nobody"), Some(0));
+    assert!(view.pending().is_empty());
+    assert!(matches!(view.transaction(tx.id).1, TransactionStatus::Included { height: 1, .. }));
+    assert_eq!(view.account_history("alice", .., Page::default())[0].balance, 999_990);
+    
+    // With only recent state kept, the state at the view's tip is pruned once the chain is finalized past it, and the
+    // view can no longer tell
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    let window = IndexRetention { state: Retention::Recent(1), ..IndexRetention::default() };
+    admin.set_index_retention("secret", window).await.unwrap();
+    while node.ledger.chain_height() < 10 {
+        node.ledger.add_transaction(node.transfer("charlie", "diana", 1)).await.unwrap();
+        node.ledger.process_transactions(10).await.unwrap();
+    }
+    assert_eq!((view.balance("alice"), view.balance("nobody")), (None, None));
+    assert_eq!(node.ledger.read_view().await.balance("alice"), Some(999_990));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transfers_sharing_accounts_apply_in_block_order_between_parallel_waves() {
+    let profile = LoadProfile { accounts: 400, ..LoadProfile::default() };
+    let genesis = profile.genesis();
+    let mut load = LoadGenerator::new(profile.clone(), &genesis).unwrap();
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    // Every wave of more than one transfer goes to the thread pool
+    ledger.set_parallel_wave(2);
+    
+    for tx in load.by_ref().take(300) {
+        ledger.add_transaction(tx).await.unwrap();
+    }
+    ledger.process_transactions(300).await.unwrap();
+    let block = ledger.get_latest_block().await;
+    
+    // Transfers sharing no account go together, and enough overlap to need several waves
+    let schedule = ConflictSchedule::new(&block.transactions);
+    assert!(schedule.depth() > 1);
+    assert!(schedule.steps().iter().any(|step| matches!(
+        step,
+        ScheduleStep::Parallel { waves, .. } if waves.iter().any(|wave| wave.len() > 1)
+    )));
+    
+    let mut expected: std::collections::HashMap<String, u64> = (0..profile.accounts)
+        .map(|index| (LoadProfile::account(index), profile.balance))
+        .collect();
+    for tx in block.transactions.iter().filter(|tx| tx.kind == TransactionKind::Transfer) {
+        *expected.get_mut(&tx.from).unwrap() -= tx.amount;
+        *expected.entry(tx.to.clone()).or_insert(0) += tx.amount;
+    }
+    for (account, balance) in expected {
+        assert_eq!(ledger.get_balance(&account).await, balance, "{}", account);
+    }
+    
+    // A node applying the block one transfer at a time ends up with the same state
+    let serial = DistributedLedger::from_genesis(genesis).unwrap();
+    serial.set_parallel_wave(usize::MAX);
+    serial.import_block(block).await.unwrap();
+    assert_eq!(serial.state_root(), ledger.state_root());
+}
+
+// Proof of work that holds each block until the test lets it through
+struct GatedMiner {
+    gate: std::sync::Mutex<std::sync::mpsc::Receiver<()>>,
+}
+
+impl Consensus for GatedMiner {
+    fn name(&self) -> &'static str {
+        "gated-proof-of-work"
+    }
+    
+    fn propose(&self, parent: &Block, block: Block, params: &ChainParams) -> distributed_ledger::Result<Block> {
+        self.gate.lock().unwrap().recv().unwrap();
+        ProofOfWork.propose(parent, block, params)
+    }
+    
+    fn validate(&self, block: &Block, parent: &Block, params: &ChainParams) -> distributed_ledger::Result<()> {
+        ProofOfWork.validate(block, parent, params)
+    }
+}
+
+// Lets a gated miner's blocks through one at a time up to `height`, each once the processor has taken the batch
+// after it, so every batch is sized by the block two before it
+async fn release_blocks_to(ledger: &DistributedLedger, release: &std::sync::mpsc::Sender<()>, height: u64) {
+    for height in ledger.chain_height() + 1..=height {
+        wait_for(|| async {
+            let depth = ledger.get_performance_stats().mining_queue_depth;
+            depth == 2 || (depth == 1 && ledger.mempool_size() == 0)
+        }).await;
+        release.send(()).unwrap();
+        wait_for(|| async { ledger.chain_height() == height }).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transactions_are_taken_while_a_block_is_mined() {
+    let node = TestNode::new("it-mining-pool");
+    let (release, gate) = std::sync::mpsc::channel();
+    let miner = Arc::new(GatedMiner { gate: std::sync::Mutex::new(gate) });
+    let ledger = DistributedLedger::with_consensus(node.genesis.clone(), miner).unwrap();
+    
+    ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    let producer = tokio::spawn({
+        let ledger = ledger.clone();
+        async move { ledger.process_transactions(10).await }
+    });
+    wait_for(|| async { ledger.get_performance_stats().mining_queue_depth == 1 }).await;
+    
+    let second = node.transfer("charlie", "diana", 2);
+    ledger.add_transaction(second.clone()).await.unwrap();
+    assert_eq!(ledger.get_transaction(second.id).await.1, TransactionStatus::Pending);
+    assert_eq!(ledger.get_latest_block().await.header.height, 0);
+    
+    release.send(()).unwrap();
+    producer.await.unwrap().unwrap();
+    assert_eq!(ledger.get_latest_block().await.header.height, 1);
+    assert_eq!(ledger.get_performance_stats().mining_queue_depth, 0);
+}
+
+// Proof of work that refuses to seal anything while `failing` is set
+struct FlakyMiner {
+    failing: std::sync::atomic::AtomicBool,
+}
+
+impl Consensus for FlakyMiner {
+    fn name(&self) -> &'static str {
+        "flaky-proof-of-work"
+    }
+    
+    fn propose(&self, parent: &Block, block: Block, params: &ChainParams) -> distributed_ledger::Result<Block> {
+        if self.failing.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err(LedgerError::Consensus("No proposal this round".to_string()));
+        }
+        ProofOfWork.propose(parent, block, params)
+    }
+    
+    fn validate(&self, block: &Block, parent: &Block, params: &ChainParams) -> distributed_ledger::Result<()> {
+        ProofOfWork.validate(block, parent, params)
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_batch_that_fails_to_seal_goes_back_to_the_mempool() {
+    let node = TestNode::new("it-failed-seal");
+    let miner = Arc::new(FlakyMiner { failing: std::sync::atomic::AtomicBool::new(true) });
+    let ledger = DistributedLedger::with_consensus(node.genesis.clone(), miner.clone()).unwrap();
+    
+    let transfers = [node.transfer("alice", "bob", 5), node.transfer("charlie", "diana", 7)];
+    for tx in &transfers {
+        ledger.add_transaction(tx.clone()).await.unwrap();
+    }
+    assert!(matches!(ledger.process_transactions(10).await, Err(LedgerError::Consensus(_))));
+    
+    // Still pending, queued and reserved, as if the attempt never happened
+    assert_eq!(ledger.get_latest_block().await.header.height, 0);
+    assert_eq!(ledger.mempool_occupancy().pending, 2);
+    for tx in &transfers {
+        assert_eq!(ledger.get_transaction(tx.id).await.1, TransactionStatus::Pending);
+    }
+    assert_eq!(ledger.available_balance("alice").await, 999_995);
+    assert_eq!(ledger.get_performance_stats().mining_queue_depth, 0);
+    
+    miner.failing.store(false, std::sync::atomic::Ordering::SeqCst);
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_latest_block().await.transactions.len(), 2);
+    assert_eq!(ledger.mempool_occupancy().pending, 0);
+    assert_eq!(ledger.get_balance("diana").await, 1_000_007);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_cancelled_producer_stops_counting_its_block_once_mining_ends() {
+    let node = TestNode::new("it-cancelled-mining");
+    let (release, gate) = std::sync::mpsc::channel();
+    let miner = Arc::new(GatedMiner { gate: std::sync::Mutex::new(gate) });
+    let ledger = DistributedLedger::with_consensus(node.genesis.clone(), miner).unwrap();
+    
+    ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    let producer = tokio::spawn({
+        let ledger = ledger.clone();
+        async move { ledger.process_transactions(10).await }
+    });
+    wait_for(|| async { ledger.get_performance_stats().mining_queue_depth == 1 }).await;
+    
+    producer.abort();
+    assert!(producer.await.unwrap_err().is_cancelled());
+    release.send(()).unwrap();
+    wait_for(|| async { ledger.get_performance_stats().mining_queue_depth == 0 }).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_cancelled_producer_puts_its_batch_back_in_the_mempool() {
+    let node = TestNode::new("it-cancelled-batch");
+    let (release, gate) = std::sync::mpsc::channel();
+    let miner = Arc::new(GatedMiner { gate: std::sync::Mutex::new(gate) });
+    let ledger = DistributedLedger::with_consensus(node.genesis.clone(), miner).unwrap();
+    
+    let transfer = node.transfer("alice", "bob", 5);
+    ledger.add_transaction(transfer.clone()).await.unwrap();
+    let producer = tokio::spawn({
+        let ledger = ledger.clone();
+        async move { ledger.process_transactions(10).await }
+    });
+    wait_for(|| async { ledger.get_performance_stats().mining_queue_depth == 1 }).await;
+    assert_eq!(ledger.mempool_occupancy().pending, 0);
+    
+    producer.abort();
+    assert!(producer.await.unwrap_err().is_cancelled());
+    assert_eq!(ledger.mempool_occupancy().pending, 1);
+    assert_eq!(ledger.get_transaction(transfer.id).await.1, TransactionStatus::Pending);
+    
+    // One release for the abandoned search, one for the block that includes the transfer after all
+    release.send(()).unwrap();
+    release.send(()).unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_latest_block().await.header.height, 1);
+    assert!(matches!(ledger.get_transaction(transfer.id).await.1, TransactionStatus::Included { height: 1, .. }));
+    assert_eq!(ledger.get_balance("bob").await, 1_000_005);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn the_processor_takes_the_next_batch_and_keeps_its_heartbeat_while_a_block_is_mined() {
+    let node = TestNode::new("it-mining-pipeline");
+    let (release, gate) = std::sync::mpsc::channel();
+    let miner = Arc::new(GatedMiner { gate: std::sync::Mutex::new(gate) });
+    let ledger = DistributedLedger::with_consensus(node.genesis.clone(), miner).unwrap();
+    ledger.start_background_processor().await;
+    
+    ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    wait_for(|| async { ledger.get_performance_stats().mining_queue_depth == 1 }).await;
+    let second = node.transfer("charlie", "diana", 2);
+    ledger.add_transaction(second.clone()).await.unwrap();
+    wait_for(|| async { ledger.get_performance_stats().mining_queue_depth == 2 }).await;
+    assert_eq!(ledger.mempool_occupancy().pending, 0);
+    assert_eq!(ledger.get_transaction(second.id).await.1, TransactionStatus::Pending);
+    
+    // Held far past the heartbeat limit, the block doesn't make the processor look stuck
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let health = ledger.health(&HealthConfig { max_heartbeat_age: Duration::from_millis(100), ..HealthConfig::default() });
+    assert!(health.processor_running);
+    assert_eq!(ledger.chain_height(), 0);
+    
+    release.send(()).unwrap();
+    release.send(()).unwrap();
+    wait_for(|| async { ledger.chain_height() == 2 }).await;
+    assert!(matches!(ledger.get_transaction(second.id).await.1, TransactionStatus::Included { height: 2, .. }));
+    wait_for(|| async { ledger.get_performance_stats().mining_queue_depth == 0 }).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn batches_grow_with_the_backlog_and_shrink_past_the_latency_target() {
+    let node = TestNode::new("it-adaptive-batches");
+    let (release, gate) = std::sync::mpsc::channel();
+    let miner = Arc::new(GatedMiner { gate: std::sync::Mutex::new(gate) });
+    let ledger = DistributedLedger::with_consensus(node.genesis.clone(), miner).unwrap();
+    ledger.start_background_processor().await;
+    let admin = AdminApi::new(ledger.clone(), "secret");
+    admin.set_batch_size("secret", 2).unwrap();
+    admin.set_processor_config("secret", ProcessorConfig {
+        target_latency: Duration::from_secs(10),
+        max_batch: 8,
+    }).unwrap();
+    
+    admin.pause_block_production("secret").unwrap();
+    for _ in 0..30 {
+        ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    }
+    admin.resume_block_production("secret").unwrap();
+    
+    release_blocks_to(&ledger, &release, 7).await;
+    
+    let view = ledger.read_view().await;
+    let sizes: Vec<usize> = (1..=view.height()).map(|height| view.block(height).unwrap().transactions.len()).collect();
+    assert_eq!(sizes, vec![2, 2, 4, 4, 8, 8, 2]);
+    drop(view);
+    
+    // Every block now misses the target, so the next one halves the batch
+    admin.set_processor_config("secret", ProcessorConfig {
+        target_latency: Duration::from_nanos(1),
+        max_batch: 8,
+    }).unwrap();
+    ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    release.send(()).unwrap();
+    wait_for(|| async { ledger.batch_size() == 4 }).await;
+    
+    let invalid = ProcessorConfig { max_batch: 0, ..ProcessorConfig::default() };
+    assert!(matches!(admin.set_processor_config("secret", invalid), Err(LedgerError::InvalidParameters(_))));
+}
+
+// Proof of work on a node whose turn never comes
+struct Bystander;
+
+impl Consensus for Bystander {
+    fn name(&self) -> &'static str {
+        "bystander-proof-of-work"
+    }
+    
+    fn can_propose(&self, _parent: &Block) -> bool {
+        false
+    }
+    
+    fn propose(&self, parent: &Block, block: Block, params: &ChainParams) -> distributed_ledger::Result<Block> {
+        ProofOfWork.propose(parent, block, params)
+    }
+    
+    fn validate(&self, block: &Block, parent: &Block, params: &ChainParams) -> distributed_ledger::Result<()> {
+        ProofOfWork.validate(block, parent, params)
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn batches_only_adapt_to_blocks_this_node_sealed() {
+    let node = TestNode::new("it-bystander-batches");
+    let ledger = DistributedLedger::with_consensus(node.genesis.clone(), Arc::new(Bystander)).unwrap();
+    ledger.start_background_processor().await;
+    let admin = AdminApi::new(ledger.clone(), "secret");
+    admin.set_batch_size("secret", 2).unwrap();
+    admin.set_processor_config("secret", ProcessorConfig {
+        target_latency: Duration::from_secs(10),
+        max_batch: 64,
+    }).unwrap();
+    
+    for _ in 0..10 {
+        ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    
+    // Every poll finds a backlog, but no block to size the next batch by
+    assert_eq!(ledger.chain_height(), 0);
+    assert_eq!(ledger.mempool_size(), 10);
+    assert_eq!(ledger.batch_size(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn an_explicit_batch_size_holds_until_a_processor_config_resumes_adapting() {
+    let node = TestNode::new("it-fixed-batches");
+    let (release, gate) = std::sync::mpsc::channel();
+    let miner = Arc::new(GatedMiner { gate: std::sync::Mutex::new(gate) });
+    let ledger = DistributedLedger::with_consensus(node.genesis.clone(), miner).unwrap();
+    ledger.start_background_processor().await;
+    let admin = AdminApi::new(ledger.clone(), "secret");
+    admin.set_batch_size("secret", 3).unwrap();
+    
+    admin.pause_block_production("secret").unwrap();
+    for _ in 0..12 {
+        ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    }
+    admin.resume_block_production("secret").unwrap();
+    release_blocks_to(&ledger, &release, 4).await;
+    
+    // The backlog would have doubled an adapting batch after the first block
+    let view = ledger.read_view().await;
+    let sizes: Vec<usize> = (1..=view.height()).map(|height| view.block(height).unwrap().transactions.len()).collect();
+    assert_eq!(sizes, vec![3, 3, 3, 3]);
+    drop(view);
+    assert_eq!(ledger.batch_size(), 3);
+    
+    admin.pause_block_production("secret").unwrap();
+    for _ in 0..12 {
+        ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    }
+    admin.set_processor_config("secret", ProcessorConfig {
+        target_latency: Duration::from_secs(10),
+        max_batch: 8,
+    }).unwrap();
+    admin.resume_block_production("secret").unwrap();
+    release_blocks_to(&ledger, &release, 7).await;
+    
+    let view = ledger.read_view().await;
+    let sizes: Vec<usize> = (5..=view.height()).map(|height| view.block(height).unwrap().transactions.len()).collect();
+    assert_eq!(sizes, vec![3, 3, 6]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn backpressured_submissions_wait_for_room_in_a_full_mempool() {
+    let node = TestNode::new("it-backpressure");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    admin.set_mempool_limits("secret", MempoolLimits { capacity: 2, ..Default::default() }).unwrap();
+    
+    node.ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    node.ledger.add_transaction(node.transfer("charlie", "bob", 1)).await.unwrap();
+    let waiter = node.transfer("diana", "bob", 1);
+    let waiting = tokio::spawn({
+        let ledger = node.ledger.clone();
+        let tx = waiter.clone();
+        async move { ledger.add_transaction_with_backpressure(tx).await }
+    });
+    wait_for(|| async { node.ledger.mempool_occupancy().waiting_for_room == 1 }).await;
+    
+    let occupancy = node.ledger.mempool_occupancy();
+    assert_eq!((occupancy.pending, occupancy.capacity, occupancy.utilization), (2, 2, 1.0));
+    let refused = node.ledger.try_add_transaction(node.transfer("eve", "bob", 1)).await;
+    assert!(matches!(refused, Err(LedgerError::PerformanceLimitExceeded(_))));
+    assert!(!waiting.is_finished());
+    
+    // Room opens as the block takes its batch, so the waiter may get into that same block
+    node.ledger.process_transactions(10).await.unwrap();
+    waiting.await.unwrap().unwrap();
+    let status = node.ledger.get_transaction(waiter.id).await.1;
+    assert!(matches!(status, TransactionStatus::Pending | TransactionStatus::Included { .. }));
+    assert_eq!(node.ledger.mempool_occupancy().waiting_for_room, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn fail_fast_submissions_never_evict_from_a_full_mempool() {
+    let node = TestNode::new("it-fail-fast");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    admin.set_mempool_limits("secret", MempoolLimits { capacity: 2, ..Default::default() }).unwrap();
+    
+    let queued = [node.transfer_with_fee("alice", "bob", 1, 1), node.transfer_with_fee("charlie", "bob", 1, 1)];
+    for tx in &queued {
+        node.ledger.try_add_transaction(tx.clone()).await.unwrap();
+    }
+    
+    // A better fee would evict a queued transaction, but only through the regular path
+    let outbidding = node.transfer_with_fee("diana", "bob", 1, 50);
+    let refused = node.ledger.try_add_transaction(outbidding.clone()).await;
+    assert!(matches!(refused, Err(LedgerError::PerformanceLimitExceeded(_))));
+    assert!(queued.iter().all(|tx| node.ledger.pending_for(&tx.from).len() == 1));
+    
+    node.ledger.add_transaction(outbidding).await.unwrap();
+    assert_eq!(node.ledger.pending_for("diana").len(), 1);
+    assert_eq!(node.ledger.mempool_occupancy().pending, 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn racing_fail_fast_submissions_fill_the_mempool_without_evicting() {
+    let node = TestNode::new("it-fail-fast-race");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    admin.set_mempool_limits("secret", MempoolLimits { capacity: 4, ..Default::default() }).unwrap();
+    
+    // Each pays more than the last, so one that got in by evicting would push out a transaction queued before it
+    let senders = ["alice", "bob", "charlie", "diana", "eve"];
+    let submitters: Vec<_> = (0..40).map(|i| {
+        let ledger = node.ledger.clone();
+        let tx = node.transfer_with_fee(senders[i % senders.len()], "zoe", 1, 1 + i as u64);
+        tokio::spawn(async move { (tx.id, ledger.try_add_transaction(tx).await) })
+    }).collect();
+    
+    let mut admitted = Vec::new();
+    for submitter in submitters {
+        match submitter.await.unwrap() {
+            (id, Ok(())) => admitted.push(id),
+            (_, refused) => assert!(matches!(refused, Err(LedgerError::PerformanceLimitExceeded(_)))),
+        }
+    }
+    assert_eq!(admitted.len(), 4);
+    for id in admitted {
+        assert_eq!(node.ledger.get_transaction(id).await.1, TransactionStatus::Pending);
+    }
+    assert_eq!(node.ledger.mempool_occupancy().pending, 4);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn backpressured_submitters_racing_for_room_never_evict() {
+    let node = TestNode::new("it-backpressure-race");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    admin.set_mempool_limits("secret", MempoolLimits { capacity: 2, ..Default::default() }).unwrap();
+    
+    let mut sent = vec![node.transfer_with_fee("alice", "zoe", 1, 1), node.transfer_with_fee("bob", "zoe", 1, 1)];
+    for tx in &sent {
+        node.ledger.add_transaction(tx.clone()).await.unwrap();
+    }
+    
+    // All woken together each time a block makes room, the later ones outbidding whoever got in first
+    let senders = ["charlie", "diana", "eve"];
+    let waiting: Vec<_> = (0..9).map(|i| {
+        let ledger = node.ledger.clone();
+        let tx = node.transfer_with_fee(senders[i % senders.len()], "zoe", 1, 10 + i as u64);
+        sent.push(tx.clone());
+        tokio::spawn(async move { ledger.add_transaction_with_backpressure(tx).await })
+    }).collect();
+    wait_for(|| async { node.ledger.mempool_occupancy().waiting_for_room == 9 }).await;
+    
+    wait_for(|| async {
+        node.ledger.process_transactions(2).await.unwrap();
+        node.ledger.get_transaction_count().await == sent.len()
+    }).await;
+    for submitter in waiting {
+        submitter.await.unwrap().unwrap();
+    }
+    for tx in &sent {
+        assert!(matches!(node.ledger.get_transaction(tx.id).await.1, TransactionStatus::Included { .. }));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_senders_fill_separate_shards_that_drain_in_fee_order() {
+    let node = TestNode::new("it-mempool-shards");
+    let senders = ["alice", "bob", "charlie", "diana", "eve"];
+    let submitters: Vec<_> = senders.iter().map(|&sender| {
+        let ledger = node.ledger.clone();
+        let transactions: Vec<Transaction> = (0..20)
+            .map(|i| node.transfer_with_fee(sender, "zoe", 1 + i, (i * 7) % 5))
+            .collect();
+        tokio::spawn(async move {
+            for tx in transactions {
+                ledger.add_transaction(tx).await.unwrap();
+            }
+        })
+    }).collect();
+    for submitter in submitters {
+        submitter.await.unwrap();
+    }
+    
+    let occupancy = node.ledger.mempool_occupancy();
+    assert_eq!(occupancy.pending, 100);
+    assert_eq!(occupancy.shards.iter().sum::<usize>(), 100);
+    assert!(occupancy.shards.iter().filter(|&&pending| pending > 0).count() > 1);
+    let head = node.ledger.mempool_head(100);
+    assert!(head.windows(2).all(|pair| pair[0].fee_rate() >= pair[1].fee_rate()));
+    
+    // A full pool makes room by evicting the lowest-priority transaction, whichever shard holds it
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    admin.set_mempool_limits("secret", MempoolLimits { capacity: 100, ..Default::default() }).unwrap();
+    let lowest = head.last().unwrap().clone();
+    let outbidding = node.transfer_with_fee("alice", "charlie", 1, 10);
+    node.ledger.add_transaction(outbidding.clone()).await.unwrap();
+    assert_eq!(node.ledger.mempool_size(), 100);
+    assert_eq!(node.ledger.mempool_head(1)[0].id, outbidding.id);
+    assert!(!node.ledger.mempool_head(100).iter().any(|tx| tx.id == lowest.id));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inclusion_latency_percentiles_cover_every_batch_and_the_latest_alone() {
+    let node = TestNode::new("it-latency-percentiles");
+    for amount in 1..=10 {
+        node.ledger.add_transaction(node.transfer("alice", "bob", amount)).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    node.ledger.process_transactions(100).await.unwrap();
+    
+    let slow = node.ledger.get_performance_stats();
+    assert!(slow.inclusion_latency.p50 >= Duration::from_millis(200));
+    assert_eq!(slow.batch_latency, slow.inclusion_latency);
+    
+    node.ledger.add_transaction(node.transfer("charlie", "bob", 1)).await.unwrap();
+    node.ledger.process_transactions(100).await.unwrap();
+    
+    let stats = node.ledger.get_performance_stats();
+    assert!(stats.batch_latency.max < Duration::from_millis(200));
+    let overall = stats.inclusion_latency;
+    assert!(overall.p50 <= overall.p95 && overall.p95 <= overall.p99 && overall.p99 <= overall.max);
+    assert!(overall.max >= Duration::from_millis(200));
+}
+
+// Stats are read without handing the thread to the runtime, which a single-threaded one has no room for
+#[tokio::test]
+async fn performance_stats_are_read_on_a_current_thread_runtime() {
+    let node = TestNode::new("it-stats-current-thread");
+    node.ledger.add_transaction(node.transfer("alice", "bob", 5)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    
+    let stats = node.ledger.get_performance_stats();
+    assert_eq!(stats.total_transactions, 1);
+    assert!(stats.peak_tps > 0.0 && stats.average_batch_time > Duration::ZERO);
+    assert_eq!(stats.batch_latency, stats.inclusion_latency);
+}
\ No newline at end of file
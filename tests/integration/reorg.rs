@@ -0,0 +1,456 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use distributed_ledger::ipc::serve_ipc;
+use distributed_ledger::{
+    AdminApi, Block, BlockImport, ChainHead, Confirmation, ConfirmationTracker, DistributedLedger, IpcClient, LedgerError,
+    LedgerEvent, SearchResult, Violation,
+};
+
+use crate::common::TestNode;
+
+async fn block_at(ledger: &DistributedLedger, height: u64) -> distributed_ledger::Block {
+    match ledger.search(&height.to_string()).await {
+        Some(SearchResult::Block { block, .. }) => block,
+        other => panic!("no block at height {}: {:?}", height, other),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn heavier_fork_reorganizes_and_requeues_dropped_transactions() {
+    let node = TestNode::new("it-reorg");
+    let rival = node.sibling();
+    assert_eq!(block_at(&node.ledger, 0).await.header.hash, block_at(&rival, 0).await.header.hash);
+    let mut reorgs = node.ledger.subscribe_reorgs();
+    
+    node.ledger.add_transaction(node.transfer("alice", "bob", 10)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    let local = block_at(&node.ledger, 1).await;
+    
+    rival.add_transaction(node.transfer("alice", "charlie", 20)).await.unwrap();
+    rival.process_transactions(10).await.unwrap();
+    rival.add_transaction(node.transfer("bob", "charlie", 5)).await.unwrap();
+    rival.process_transactions(10).await.unwrap();
+    
+    let first = block_at(&rival, 1).await;
+    assert_eq!(node.ledger.import_block(first.clone()).await.unwrap(), BlockImport::SideChain);
+    assert!(matches!(node.ledger.import_block(first.clone()).await, Err(LedgerError::DuplicateBlock)));
+    assert_eq!(node.ledger.stale_blocks()[0].hash, first.header.hash);
+    
+    let second = block_at(&rival, 2).await;
+    let BlockImport::Reorganized(event) = node.ledger.import_block(second.clone()).await.unwrap() else {
+        panic!("expected a reorg");
+    };
+    assert_eq!(event.fork_height, 0);
+    assert_eq!(event.reverted, vec![local.header.hash.clone()]);
+    assert_eq!(event.applied, vec![first.header.hash, second.header.hash.clone()]);
+    assert_eq!(reorgs.recv().await.unwrap(), event);
+    
+    // The winning side block is back on the main chain; the block it displaced went stale
+    let stale = node.ledger.stale_blocks();
+    assert_eq!((stale.len(), stale[0].hash.as_str(), stale[0].height), (1, local.header.hash.as_str(), 1));
+    let stats = node.ledger.stale_stats().await;
+    assert_eq!(stats.last_hour, 1);
+    assert!((stats.stale_rate - 1.0 / 3.0).abs() < f64::EPSILON);
+    
+    assert_eq!(node.ledger.get_latest_block().await.header.hash, second.header.hash);
+    assert_eq!(node.ledger.get_balance("alice").await, 999_980);
+    assert_eq!(node.ledger.get_balance("bob").await, 999_995);
+    assert_eq!(node.ledger.get_balance("charlie").await, 1_000_025);
+    
+    // The transfer only the losing block carried is mined again on the new chain
+    node.ledger.process_transactions(10).await.unwrap();
+    assert_eq!(node.ledger.get_latest_block().await.header.height, 3);
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_005);
+    
+    // History follows the new chain
+    assert_eq!(node.ledger.get_balance_at("bob", 0).await, Some(1_000_000));
+    assert_eq!(node.ledger.get_balance_at("bob", 1).await, Some(1_000_000));
+    assert_eq!(node.ledger.get_balance_at("charlie", 1).await, Some(1_000_020));
+    assert_eq!(node.ledger.get_balance_at("bob", 2).await, Some(999_995));
+    assert_eq!(node.ledger.get_balance_at("bob", 3).await, Some(1_000_005));
+    assert_eq!(node.ledger.get_balance_at("frank", 3).await, Some(0));
+    assert_eq!(node.ledger.get_balance_at("bob", 4).await, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tip_reads_agree_with_the_chain_after_a_reorg() {
+    let node = TestNode::new("it-reorg-tip");
+    let rival = node.sibling();
+    let head = node.ledger.watch_head();
+    
+    node.ledger.add_transaction(node.transfer("alice", "bob", 10)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    for amount in 1..=2 {
+        rival.add_transaction(node.transfer("charlie", "diana", amount)).await.unwrap();
+        rival.process_transactions(10).await.unwrap();
+    }
+    
+    let before = node.ledger.latest_block();
+    node.ledger.import_block(block_at(&rival, 1).await).await.unwrap();
+    assert!(Arc::ptr_eq(&node.ledger.latest_block(), &before));
+    
+    let winner = block_at(&rival, 2).await;
+    let outcome = node.ledger.import_block(winner.clone()).await.unwrap();
+    assert!(matches!(outcome, BlockImport::Reorganized(_)));
+    
+    // Every tip read sees the new chain's tip, and the same shared block each time
+    let tip = node.ledger.latest_block();
+    assert_eq!(tip.header.hash, winner.header.hash);
+    assert!(Arc::ptr_eq(&tip, &node.ledger.latest_block()));
+    assert_eq!(node.ledger.chain_height(), 2);
+    assert_eq!(node.ledger.get_latest_block().await.header.hash, winner.header.hash);
+    assert_eq!(*head.borrow(), ChainHead::of(&winner));
+    let headers = node.ledger.get_headers(0, 10).await;
+    assert_eq!(headers.last().unwrap().hash, tip.header.hash);
+    
+    // The displaced tip is untouched for readers still holding it
+    assert_eq!(before.header.height, 1);
+    assert_ne!(before.header.hash, tip.header.hash);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn head_watchers_never_see_a_reorg_that_fails_partway() {
+    let node = TestNode::new("it-reorg-failed");
+    let rival = node.sibling();
+    
+    node.ledger.add_transaction(node.transfer("alice", "bob", 10)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    rival.add_transaction(node.transfer("charlie", "diana", 5)).await.unwrap();
+    rival.process_transactions(10).await.unwrap();
+    let first = block_at(&rival, 1).await;
+    
+    // Commits to this node's state rather than the branch's, so the reorg gets past `first` and fails on it
+    let mut second = Block::child_of(&first, Vec::new());
+    second.header.state_root = node.ledger.state_root();
+    second.mine(node.genesis.params.difficulty);
+    
+    let before = node.ledger.latest_block();
+    let mut head = node.ledger.watch_head();
+    head.mark_unchanged();
+    assert_eq!(node.ledger.import_block(first).await.unwrap(), BlockImport::SideChain);
+    assert!(matches!(node.ledger.import_block(second).await, Err(LedgerError::BlockValidationFailed(_))));
+    
+    assert!(!head.has_changed().unwrap());
+    assert_eq!(*head.borrow(), ChainHead::of(&before));
+    assert!(Arc::ptr_eq(&node.ledger.latest_block(), &before));
+    assert_eq!(node.ledger.get_headers(0, 10).await.last().unwrap().hash, before.header.hash);
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_010);
+    assert_eq!(node.ledger.get_balance("diana").await, 1_000_000);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_reorg_across_a_segment_of_the_chain_leaves_reads_on_the_new_branch() {
+    let node = TestNode::new("it-reorg-segments");
+    let rival = node.sibling();
+    
+    // The chain is stored 256 blocks to a segment; the fork sits below the boundary, within the finality depth, and
+    // both branches cross it
+    for _ in 0..253 {
+        node.ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+        node.ledger.process_transactions(10).await.unwrap();
+    }
+    for height in 1..=253 {
+        rival.import_block(block_at(&node.ledger, height).await).await.unwrap();
+    }
+    for _ in 0..5 {
+        node.ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+        node.ledger.process_transactions(10).await.unwrap();
+    }
+    for _ in 0..7 {
+        rival.add_transaction(node.transfer("charlie", "diana", 1)).await.unwrap();
+        rival.process_transactions(10).await.unwrap();
+    }
+    let reader = node.ledger.get_headers(0, 300).await;
+    
+    for height in 254..=260 {
+        node.ledger.import_block(block_at(&rival, height).await).await.unwrap();
+    }
+    assert_eq!(node.ledger.chain_height(), 260);
+    assert_eq!(node.ledger.get_headers(0, 300).await, rival.get_headers(0, 300).await);
+    let headers = node.ledger.get_headers(255, 3).await;
+    assert_eq!(headers.iter().map(|header| header.height).collect::<Vec<_>>(), vec![255, 256, 257]);
+    assert_eq!(block_at(&node.ledger, 256).await, block_at(&rival, 256).await);
+    assert_eq!(node.ledger.get_balance("diana").await, 1_000_007);
+    
+    // Headers read before the reorg still show the branch it displaced
+    assert_eq!((reader.len(), &reader[..254]), (259, &node.ledger.get_headers(0, 254).await[..]));
+    assert_ne!(reader[256].hash, headers[1].hash);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn holds_blocks_with_unknown_parents_until_the_parent_arrives() {
+    let node = TestNode::new("it-orphan");
+    let rival = node.sibling();
+    
+    for _ in 0..2 {
+        rival.add_transaction(node.transfer("eve", "bob", 1)).await.unwrap();
+        rival.process_transactions(10).await.unwrap();
+    }
+    
+    let result = node.ledger.import_block(block_at(&rival, 2).await).await.unwrap();
+    assert!(matches!(result, BlockImport::Orphaned { .. }));
+    assert_eq!(node.ledger.import_block(block_at(&rival, 1).await).await.unwrap(), BlockImport::Extended);
+    assert_eq!(node.ledger.get_latest_block().await.header.height, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocks_arriving_before_their_parents_connect_once_the_gap_is_filled() {
+    let node = TestNode::new("it-orphans");
+    let source = node.sibling();
+    let mut requests = node.ledger.subscribe_missing_blocks();
+    
+    for amount in 1..=3 {
+        source.add_transaction(node.transfer("alice", "bob", amount)).await.unwrap();
+        source.process_transactions(10).await.unwrap();
+    }
+    let chain = [block_at(&source, 1).await, block_at(&source, 2).await, block_at(&source, 3).await];
+    
+    let outcome = node.ledger.import_block(chain[2].clone()).await.unwrap();
+    assert_eq!(outcome, BlockImport::Orphaned { missing_ancestor: chain[1].header.hash.clone() });
+    let outcome = node.ledger.import_block(chain[1].clone()).await.unwrap();
+    assert_eq!(outcome, BlockImport::Orphaned { missing_ancestor: chain[0].header.hash.clone() });
+    assert!(matches!(node.ledger.import_block(chain[1].clone()).await, Err(LedgerError::DuplicateBlock)));
+    
+    assert_eq!(requests.recv().await.unwrap(), chain[1].header.hash);
+    assert_eq!(requests.recv().await.unwrap(), chain[0].header.hash);
+    assert_eq!(node.ledger.missing_ancestors(), vec![chain[0].header.hash.clone()]);
+    assert_eq!(node.ledger.get_latest_block().await.header.height, 0);
+    
+    assert_eq!(node.ledger.import_block(chain[0].clone()).await.unwrap(), BlockImport::Extended);
+    assert_eq!(node.ledger.get_latest_block().await.header.hash, chain[2].header.hash);
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_006);
+    assert!(node.ledger.missing_ancestors().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn peers_sending_invalid_blocks_are_banned_until_an_admin_lifts_it() {
+    let node = TestNode::new("it-peers");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    let rival = node.sibling();
+    rival.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    rival.process_transactions(10).await.unwrap();
+    let valid = block_at(&rival, 1).await;
+    
+    for nonce in 0..2 {
+        let mut tampered = valid.clone();
+        tampered.header.nonce = nonce + 1_000_000;
+        let result = node.ledger.import_block_from("mallory", tampered).await;
+        assert!(matches!(result, Err(LedgerError::BlockValidationFailed(_))));
+    }
+    
+    let record = node.ledger.peers().peer("mallory").unwrap();
+    assert!(record.is_banned());
+    assert_eq!(record.violations.len(), 2);
+    assert_eq!(record.violations[0].violation, Violation::InvalidBlock);
+    
+    let refused = node.ledger.import_block_from("mallory", valid.clone()).await;
+    assert!(matches!(refused, Err(LedgerError::Unauthorized(_))));
+    
+    assert!(admin.unban_peer("secret", "mallory").unwrap());
+    assert_eq!(node.ledger.import_block_from("mallory", valid).await.unwrap(), BlockImport::Extended);
+    
+    admin.ban_peer("secret", "trent", std::time::Duration::from_secs(60)).unwrap();
+    let peers = admin.list_peers("secret").unwrap();
+    assert_eq!(peers.iter().map(|p| p.peer.as_str()).collect::<Vec<_>>(), vec!["mallory", "trent"]);
+    assert!(peers[1].is_banned());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocks_larger_than_any_block_may_be_count_against_the_peer_unvalidated() {
+    let node = TestNode::new("it-peers-oversized");
+    let tip = node.ledger.get_latest_block().await;
+    let stuffed = node.transfer("alice", "bob", 1).with_data(vec![0; node.ledger.params().max_block_bytes]);
+    let oversized = Block::child_of(&tip, vec![stuffed]);
+    
+    let result = node.ledger.import_block_from("oscar", oversized).await;
+    assert!(matches!(result, Err(LedgerError::BlockValidationFailed(_))));
+    let record = node.ledger.peers().peer("oscar").unwrap();
+    assert_eq!(record.violations[0].violation, Violation::OversizedMessage);
+    assert_eq!(record.score, 25);
+    assert!(!record.is_banned());
+    assert_eq!(node.ledger.chain_height(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn subscribers_see_admissions_inclusions_blocks_and_reorgs_in_order() {
+    let node = TestNode::new("it-events");
+    let rival = node.sibling();
+    let mut events = node.ledger.subscribe();
+    
+    let local = node.transfer("alice", "bob", 10);
+    node.ledger.add_transaction(local.clone()).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    let produced = block_at(&node.ledger, 1).await;
+    
+    let remote = node.transfer("alice", "charlie", 20);
+    rival.add_transaction(remote.clone()).await.unwrap();
+    rival.process_transactions(10).await.unwrap();
+    let later = node.transfer("bob", "charlie", 5);
+    rival.add_transaction(later.clone()).await.unwrap();
+    rival.process_transactions(10).await.unwrap();
+    node.ledger.import_block(block_at(&rival, 1).await).await.unwrap();
+    let BlockImport::Reorganized(reorg) = node.ledger.import_block(block_at(&rival, 2).await).await.unwrap() else {
+        panic!("expected a reorg");
+    };
+    
+    let mut received = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        received.push(event);
+    }
+    assert_eq!(received, vec![
+        LedgerEvent::TransactionAdmitted { transaction: local.clone() },
+        LedgerEvent::TransactionIncluded { transaction: local, height: 1, block_hash: produced.header.hash.clone() },
+        LedgerEvent::BlockProduced { height: 1, hash: produced.header.hash, transactions: 1 },
+        LedgerEvent::TransactionIncluded { transaction: remote, height: 1, block_hash: reorg.applied[0].clone() },
+        LedgerEvent::TransactionIncluded { transaction: later, height: 2, block_hash: reorg.applied[1].clone() },
+        LedgerEvent::ReorgOccurred(reorg),
+    ]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invariants_hold_across_fees_and_reorgs() {
+    let node = TestNode::new("it-invariants");
+    let rival = node.sibling();
+    node.ledger.set_invariant_interval(1);
+    rival.set_invariant_interval(1);
+    
+    node.ledger.add_transaction(node.transfer_with_fee("alice", "bob", 10, 3)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    for amount in [20, 30] {
+        rival.add_transaction(node.transfer_with_fee("charlie", "diana", amount, 2)).await.unwrap();
+        rival.process_transactions(10).await.unwrap();
+    }
+    
+    node.ledger.import_block(block_at(&rival, 1).await).await.unwrap();
+    let import = node.ledger.import_block(block_at(&rival, 2).await).await.unwrap();
+    assert!(matches!(import, BlockImport::Reorganized(_)));
+    
+    assert_eq!(node.ledger.check_invariants().await, vec![]);
+    assert_eq!(node.ledger.total_supply(), rival.total_supply());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn forks_can_be_listed_inspected_and_compared() {
+    let node = TestNode::new("it-branches");
+    let rival = node.sibling();
+    let shared = node.transfer("diana", "eve", 1);
+    let local = node.transfer("alice", "bob", 10);
+    let remote = node.transfer("alice", "charlie", 20);
+    let later = node.transfer("bob", "charlie", 5);
+    
+    for tx in [&shared, &local] {
+        node.ledger.add_transaction(tx.clone()).await.unwrap();
+    }
+    node.ledger.process_transactions(10).await.unwrap();
+    for tx in [&shared, &remote] {
+        rival.add_transaction(tx.clone()).await.unwrap();
+    }
+    rival.process_transactions(10).await.unwrap();
+    rival.add_transaction(later.clone()).await.unwrap();
+    rival.process_transactions(10).await.unwrap();
+    let (ours, first, second) = (block_at(&node.ledger, 1).await, block_at(&rival, 1).await, block_at(&rival, 2).await);
+    
+    assert_eq!(node.ledger.import_block(first.clone()).await.unwrap(), BlockImport::SideChain);
+    let tips = node.ledger.chain_tips().await;
+    assert_eq!(tips.iter().map(|tip| (tip.hash.clone(), tip.fork_height, tip.main)).collect::<Vec<_>>(), vec![
+        (ours.header.hash.clone(), 1, true),
+        (first.header.hash.clone(), 0, false),
+    ]);
+    let branch = node.ledger.get_branch(&first.header.hash).await.unwrap();
+    assert_eq!((branch.fork_height, branch.blocks), (0, vec![first.clone()]));
+    
+    node.ledger.import_block(second.clone()).await.unwrap();
+    let tips = node.ledger.chain_tips().await;
+    assert_eq!(tips.iter().map(|tip| (tip.hash.clone(), tip.main)).collect::<Vec<_>>(), vec![
+        (second.header.hash.clone(), true),
+        (ours.header.hash.clone(), false),
+    ]);
+    assert!(node.ledger.get_branch(&second.header.hash).await.unwrap().blocks.is_empty());
+    assert!(node.ledger.get_branch("unknown").await.is_none());
+    
+    let comparison = node.ledger.compare_branches(&ours.header.hash, &second.header.hash).await.unwrap();
+    assert_eq!(comparison.common_height, 0);
+    assert_eq!(comparison.only_in_first, vec![local]);
+    assert_eq!(comparison.only_in_second, vec![remote, later]);
+    assert_eq!(comparison.in_both, vec![shared.id]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn confirmation_tracker_rearms_when_a_reorg_drops_the_transaction() {
+    let node = TestNode::new("it-confirmations");
+    let rival = node.sibling();
+    let tracker = Arc::new(ConfirmationTracker::new(node.ledger.clone()));
+    let settle = || tokio::time::sleep(Duration::from_millis(100));
+    
+    let tx = node.transfer("alice", "bob", 10);
+    node.ledger.add_transaction(tx.clone()).await.unwrap();
+    let waiting = tokio::spawn({
+        let tracker = Arc::clone(&tracker);
+        async move { tracker.wait_for(tx.id, 2).await }
+    });
+    node.ledger.process_transactions(10).await.unwrap();
+    settle().await;
+    assert!(!waiting.is_finished());
+    
+    // A heavier chain without the transfer sends it back to the pool
+    for _ in 0..2 {
+        rival.add_transaction(node.transfer("charlie", "diana", 1)).await.unwrap();
+        rival.process_transactions(10).await.unwrap();
+    }
+    node.ledger.import_block(block_at(&rival, 1).await).await.unwrap();
+    node.ledger.import_block(block_at(&rival, 2).await).await.unwrap();
+    assert_eq!(*node.ledger.watch_head().borrow(), ChainHead::of(&block_at(&rival, 2).await));
+    settle().await;
+    assert!(!waiting.is_finished());
+    
+    // Mined again at height 3, it only counts as confirmed once a block lands on top
+    node.ledger.process_transactions(10).await.unwrap();
+    settle().await;
+    assert!(!waiting.is_finished());
+    node.ledger.add_transaction(node.transfer("eve", "bob", 1)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    
+    let confirmation = tokio::time::timeout(Duration::from_secs(5), waiting).await.unwrap().unwrap().unwrap();
+    let block = block_at(&node.ledger, 3).await;
+    assert_eq!(confirmation, Confirmation { height: 3, block_hash: block.header.hash, confirmations: 2 });
+    assert_eq!(tracker.wait_for(tx.id, 1).await.unwrap().confirmations, 2);
+    
+    // One dropped from the pool fails instead of waiting forever
+    let drained = node.transfer("bob", "alice", 1);
+    node.ledger.add_transaction(drained.clone()).await.unwrap();
+    AdminApi::new(node.ledger.clone(), "secret").drain_mempool("secret").unwrap();
+    assert!(matches!(tracker.wait_for(drained.id, 1).await, Err(LedgerError::InvalidTransaction(_))));
+}
+#[tokio::test(flavor = "multi_thread")]
+async fn blocks_losing_a_tie_are_listed_once_as_stale_and_leave_balances_alone() {
+    let node = TestNode::new("it-stale");
+    let rival = node.sibling();
+    let stats = node.ledger.stale_stats().await;
+    assert_eq!((stats.recorded, stats.last_hour, stats.stale_rate), (0, 0, 0.0));
+    
+    node.ledger.add_transaction(node.transfer("alice", "bob", 10)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    rival.add_transaction(node.transfer("charlie", "bob", 20)).await.unwrap();
+    rival.process_transactions(10).await.unwrap();
+    
+    // Equal work keeps the chain the node already has
+    let losing = block_at(&rival, 1).await;
+    assert_eq!(node.ledger.import_block(losing.clone()).await.unwrap(), BlockImport::SideChain);
+    assert!(matches!(node.ledger.import_block(losing.clone()).await, Err(LedgerError::DuplicateBlock)));
+    let stale = node.ledger.stale_blocks();
+    assert_eq!(stale.len(), 1);
+    assert_eq!((stale[0].hash.as_str(), stale[0].height), (losing.header.hash.as_str(), 1));
+    assert_eq!(stale[0].producer, None);
+    assert_eq!(node.ledger.get_balance("bob").await, 1_000_010);
+    assert_eq!(node.ledger.get_balance("charlie").await, 1_000_000);
+    
+    // One of the last hour's two blocks at height 1 went stale
+    let stats = node.ledger.stale_stats().await;
+    assert_eq!((stats.recorded, stats.last_hour, stats.stale_rate), (1, 1, 0.5));
+    
+    let socket = node.path("ledger.sock");
+    serve_ipc(node.ledger.clone(), &socket).await.unwrap();
+    let mut client = IpcClient::connect(&socket).await.unwrap();
+    assert_eq!(client.get_stale_blocks().await.unwrap(), stale);
+}
@@ -0,0 +1,54 @@
+use distributed_ledger::{AdminApi, LedgerError, MempoolDump, ValidationState};
+
+use crate::common::{wait_for, TestNode};
+
+// Without durable storage a restart loses the node; the mempool dump is what carries pending load across
+#[tokio::test(flavor = "multi_thread")]
+async fn pending_load_survives_restart_via_mempool_dump() {
+    let node = TestNode::start("it-restart").await;
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    
+    admin.pause_block_production("secret").unwrap();
+    for _ in 0..20 {
+        node.ledger.add_transaction(node.transfer("alice", "diana", 2)).await.unwrap();
+    }
+    
+    let dump = node.path("mempool.json");
+    assert_eq!(node.ledger.dump_mempool(&dump).await.unwrap(), 20);
+    
+    let restarted = node.sibling();
+    restarted.start_background_processor().await;
+    assert_eq!(restarted.import_mempool(&dump).await.unwrap(), 20);
+    
+    wait_for(|| async { restarted.get_transaction_count().await == 20 }).await;
+    assert_eq!(restarted.get_balance("diana").await, 1_000_040);
+}
+#[tokio::test(flavor = "multi_thread")]
+async fn mempool_dumps_keep_processing_order_and_imports_skip_what_the_node_refuses() {
+    let node = TestNode::new("it-restart-dump");
+    let cheap = node.transfer_with_fee("alice", "bob", 10, 1);
+    let urgent = node.transfer_with_fee("charlie", "bob", 20, 50);
+    node.ledger.add_transaction(cheap.clone()).await.unwrap();
+    node.ledger.add_transaction(urgent.clone()).await.unwrap();
+    
+    let path = node.path("mempool.json");
+    assert_eq!(node.ledger.dump_mempool(&path).await.unwrap(), 2);
+    let dump: MempoolDump = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    let entries: Vec<_> = dump.entries.iter()
+        .map(|entry| (entry.transaction.id, entry.priority, entry.validation.clone()))
+        .collect();
+    assert_eq!(entries, [(urgent.id, 0, ValidationState::Valid), (cheap.id, 1, ValidationState::Valid)]);
+    
+    // The restarted node already committed one of them, so only the other is queued again
+    let restarted = node.sibling();
+    restarted.add_transaction(urgent.clone()).await.unwrap();
+    restarted.process_transactions(10).await.unwrap();
+    assert_eq!(restarted.import_mempool(&path).await.unwrap(), 1);
+    assert_eq!(restarted.pending_for("alice"), [cheap]);
+    assert!(restarted.pending_for("charlie").is_empty());
+    
+    assert!(matches!(restarted.import_mempool(node.path("missing.json")).await, Err(LedgerError::Internal(_))));
+    std::fs::write(node.path("garbage.json"), b"not a dump").unwrap();
+    assert!(matches!(restarted.import_mempool(node.path("garbage.json")).await, Err(LedgerError::Internal(_))));
+    assert_eq!(restarted.mempool_size(), 1);
+}
@@ -0,0 +1,76 @@
+use distributed_ledger::{DistributedLedger, EpochLength, EpochSchedule, GenesisConfig, Transaction};
+
+struct Network {
+    genesis: GenesisConfig,
+    ledger: DistributedLedger,
+}
+
+impl Network {
+    fn new(epochs: Option<EpochSchedule>) -> Self {
+        let mut genesis = GenesisConfig::dev("it-schedules");
+        genesis.params.epochs = epochs;
+        let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+        Self { genesis, ledger }
+    }
+    
+    async fn submit(&self, signer: &str, mut tx: Transaction) -> distributed_ledger::Result<uuid::Uuid> {
+        tx.sign(&self.genesis.dev_keypair(signer).unwrap());
+        let id = tx.id;
+        self.ledger.add_transaction(tx).await?;
+        self.ledger.process_transactions(10).await?;
+        Ok(id)
+    }
+    
+    async fn next_epoch(&self) {
+        let epoch = self.ledger.current_epoch().unwrap().epoch.number;
+        while self.ledger.current_epoch().unwrap().epoch.number == epoch {
+            self.submit("eve", Transaction::new("eve".into(), "frank".into(), 1)).await.unwrap();
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn standing_orders_pay_each_interval_until_the_payer_cancels() {
+    let net = Network::new(Some(EpochSchedule { length: EpochLength::Blocks(2), epochs_per_era: 1 }));
+    
+    let order = net.submit("alice", Transaction::schedule("alice".into(), "bob".into(), 250, 2)).await.unwrap();
+    let registered = net.ledger.standing_order(order).unwrap();
+    assert_eq!(registered.next_due, net.ledger.current_epoch().unwrap().epoch.number + 2);
+    assert_eq!(net.ledger.standing_orders("bob"), vec![registered]);
+    
+    net.next_epoch().await;
+    assert_eq!(net.ledger.get_balance("bob").await, 1_000_000);
+    
+    net.next_epoch().await;
+    let epoch = net.ledger.current_epoch().unwrap();
+    assert_eq!(epoch.installments.len(), 1);
+    assert!(epoch.installments[0].paid);
+    assert_eq!(net.ledger.get_balance("bob").await, 1_000_250);
+    assert_eq!(net.ledger.get_balance("alice").await, 999_750);
+    
+    assert!(net.submit("bob", Transaction::cancel_schedule("bob".into(), order)).await.is_err());
+    net.submit("alice", Transaction::cancel_schedule("alice".into(), order)).await.unwrap();
+    assert!(net.submit("alice", Transaction::cancel_schedule("alice".into(), order)).await.is_err());
+    assert!(net.ledger.standing_orders("alice").is_empty());
+    
+    net.next_epoch().await;
+    net.next_epoch().await;
+    let cancelled = net.ledger.standing_order(order).unwrap();
+    assert_eq!((cancelled.payments, cancelled.missed), (1, 0));
+    assert_eq!(net.ledger.get_balance("bob").await, 1_000_250);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn standing_orders_need_epochs_and_skip_payments_the_payer_cannot_cover() {
+    let net = Network::new(None);
+    assert!(net.submit("alice", Transaction::schedule("alice".into(), "bob".into(), 1, 1)).await.is_err());
+    
+    let net = Network::new(Some(EpochSchedule { length: EpochLength::Blocks(2), epochs_per_era: 1 }));
+    let order = net.submit("diana", Transaction::schedule("diana".into(), "bob".into(), 2_000_000, 1)).await.unwrap();
+    
+    net.next_epoch().await;
+    let order = net.ledger.standing_order(order).unwrap();
+    assert_eq!((order.payments, order.missed), (0, 1));
+    assert_eq!(net.ledger.get_balance("diana").await, 1_000_000);
+    assert!(order.is_active());
+}
\ No newline at end of file
@@ -0,0 +1,49 @@
+use distributed_ledger::SearchResult;
+
+use crate::common::TestNode;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_resolves_heights_hashes_transactions_and_addresses() {
+    let node = TestNode::new("it-search");
+    let committed = node.transfer("alice", "bob", 10);
+    node.ledger.add_transaction(committed.clone()).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    let pending = node.transfer("charlie", "bob", 20);
+    node.ledger.add_transaction(pending.clone()).await.unwrap();
+    let tip = node.ledger.get_latest_block().await;
+    
+    let Some(SearchResult::Block { height, block }) = node.ledger.search("1").await else {
+        panic!("height 1 not found");
+    };
+    assert_eq!((height, block.header.hash.as_str()), (1, tip.header.hash.as_str()));
+    
+    // Hashes match whatever their case, and surrounding whitespace is ignored
+    let query = format!("  {}\n", tip.header.hash.to_ascii_uppercase());
+    assert!(matches!(node.ledger.search(&query).await, Some(SearchResult::Block { height: 1, .. })));
+    
+    let Some(SearchResult::Transaction { transaction, block_height, .. }) =
+        node.ledger.search(&committed.id.to_string()).await
+    else {
+        panic!("committed transaction not found");
+    };
+    assert_eq!((transaction.id, block_height), (committed.id, Some(1)));
+    assert!(matches!(
+        node.ledger.search(&pending.id.to_string()).await,
+        Some(SearchResult::Transaction { block_height: None, .. })
+    ));
+    
+    let Some(SearchResult::Address { address, balance, .. }) = node.ledger.search("bob").await else {
+        panic!("bob not found");
+    };
+    assert_eq!((address.as_str(), balance), ("bob", 1_000_010));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_finds_nothing_for_unknown_input() {
+    let node = TestNode::new("it-search-miss");
+    node.ledger.process_transactions(10).await.unwrap();
+    
+    for query in ["7", &"f".repeat(64), &uuid::Uuid::new_v4().to_string(), "nobody", ""] {
+        assert!(node.ledger.search(query).await.is_none(), "{:?} resolved", query);
+    }
+}
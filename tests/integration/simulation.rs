@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use distributed_ledger::simulation::{SimConfig, Simulation};
+use distributed_ledger::{Consensus, GenesisConfig, ProofOfWork, Transaction};
+
+async fn partitioned_run(seed: u64) -> (Simulation, String) {
+    let genesis = GenesisConfig::dev("it-simulation");
+    let engines: Vec<Arc<dyn Consensus>> = (0..3).map(|_| Arc::new(ProofOfWork) as Arc<dyn Consensus>).collect();
+    let mut sim = Simulation::new(SimConfig { seed, ..Default::default() }, genesis.clone(), engines).unwrap();
+    
+    let transfer = |sim: &Simulation, from: &str, amount: u64| {
+        sim.within(|| {
+            let mut tx = Transaction::new(from.to_string(), "bob".to_string(), amount);
+            tx.sign(&genesis.dev_keypair(from).unwrap());
+            tx
+        })
+    };
+    
+    sim.submit(0, transfer(&sim, "alice", 1)).await.unwrap();
+    sim.produce(0).await.unwrap().unwrap();
+    sim.run_until_idle().await.unwrap();
+    assert!(sim.converged().await);
+    
+    // Node 0 alone builds one block while nodes 1 and 2 build two
+    sim.partition(&[&[0], &[1, 2]]);
+    sim.submit(0, transfer(&sim, "alice", 2)).await.unwrap();
+    sim.produce(0).await.unwrap();
+    for amount in [3, 4] {
+        sim.advance(50);
+        sim.submit(1, transfer(&sim, "charlie", amount)).await.unwrap();
+        sim.produce(1).await.unwrap();
+        sim.run_for(200).await.unwrap();
+    }
+    assert!(!sim.converged().await);
+    
+    // After healing, the next block pulls node 0 onto the heavier chain through the orphan path
+    sim.heal();
+    sim.advance(50);
+    sim.submit(2, transfer(&sim, "diana", 5)).await.unwrap();
+    sim.produce(2).await.unwrap();
+    sim.run_until_idle().await.unwrap();
+    
+    let tip = sim.node(0).get_latest_block().await.header.hash;
+    (sim, tip)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn partitioned_network_converges_identically_for_a_seed() {
+    let (sim, tip) = partitioned_run(7).await;
+    assert!(sim.converged().await);
+    assert_eq!(sim.node(0).get_latest_block().await.header.height, 4);
+    assert_eq!(sim.node(0).get_balance("charlie").await, 999_993);
+    
+    let (_, replayed) = partitioned_run(7).await;
+    assert_eq!(tip, replayed);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn nodes_ban_a_peer_gossiping_invalid_blocks_and_drop_what_it_sends_next() {
+    let genesis = GenesisConfig::dev("it-simulation-peers");
+    let engines: Vec<Arc<dyn Consensus>> = (0..3).map(|_| Arc::new(ProofOfWork) as Arc<dyn Consensus>).collect();
+    let mut sim = Simulation::new(SimConfig::default(), genesis.clone(), engines).unwrap();
+    
+    // Node 2 builds a block on its own, then sends tampered copies of it before the real one
+    sim.partition(&[&[0, 1], &[2]]);
+    let tx = sim.within(|| {
+        let mut tx = Transaction::new("alice".to_string(), "bob".to_string(), 1);
+        tx.sign(&genesis.dev_keypair("alice").unwrap());
+        tx
+    });
+    sim.submit(2, tx).await.unwrap();
+    let block = sim.produce(2).await.unwrap().unwrap();
+    sim.heal();
+    
+    for nonce in 0..2 {
+        let mut tampered = block.clone();
+        tampered.header.nonce = nonce + 1_000_000;
+        sim.gossip(2, tampered);
+        sim.run_until_idle().await.unwrap();
+    }
+    sim.gossip(2, block);
+    sim.run_until_idle().await.unwrap();
+    
+    for node in 0..2 {
+        let record = sim.node(node).peers().peer("node-2").unwrap();
+        assert!(record.is_banned());
+        assert_eq!(record.violations.len(), 2);
+        assert_eq!(sim.node(node).chain_height(), 0);
+    }
+    assert!(!sim.converged().await);
+}
+
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use distributed_ledger::{
+    Block, DistributedLedger, Evidence, GenesisConfig, LedgerError, PosConfig, ProofOfStake, SlashReason, Transaction,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn double_signing_burns_stake_and_jails_the_validator() {
+    let dev = GenesisConfig::dev("it-slash");
+    let alice = dev.dev_keypair("alice").unwrap();
+    let bob = dev.dev_keypair("bob").unwrap();
+    let genesis = dev.clone()
+        .with_validator("alice", &alice.public_key(), 1_000)
+        .with_validator("bob", &bob.public_key(), 1_000);
+    
+    let engine = ProofOfStake::new(PosConfig::default(), vec![alice.clone(), bob.clone()]);
+    let ledger = DistributedLedger::with_consensus(genesis.clone(), Arc::new(engine)).unwrap();
+    
+    let parent = ledger.get_latest_block().await;
+    let mut first = Block::child_of(&parent, Vec::new());
+    first.sign(&alice);
+    let mut second = Block::child_of(&parent, Vec::new());
+    second.sign(&alice);
+    let evidence = Evidence::DoubleSign { first: Box::new(first.header), second: Box::new(second.header) };
+    
+    let mut report = Transaction::evidence("eve".into(), "alice".into(), evidence.clone());
+    report.sign(&genesis.dev_keypair("eve").unwrap());
+    ledger.add_transaction(report).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    
+    let history = ledger.slash_history("alice");
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].reason, SlashReason::DoubleSign);
+    assert_eq!(history[0].total_penalty(), 50);
+    assert_eq!(ledger.stakes().validator("alice").unwrap().total_stake(), 950);
+    
+    let mut repeat = Transaction::evidence("eve".into(), "alice".into(), evidence);
+    repeat.sign(&genesis.dev_keypair("eve").unwrap());
+    assert!(matches!(ledger.add_transaction(repeat).await, Err(LedgerError::InvalidTransaction(_))));
+    
+    // While jailed, only bob is elected
+    for amount in 1..=5 {
+        let mut tx = Transaction::new("charlie".into(), "diana".into(), amount);
+        tx.sign(&genesis.dev_keypair("charlie").unwrap());
+        ledger.add_transaction(tx).await.unwrap();
+        ledger.process_transactions(10).await.unwrap();
+        assert_eq!(ledger.get_latest_block().await.header.producer, Some(bob.public_key()));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn downtime_over_the_window_is_slashed() {
+    let dev = GenesisConfig::dev("it-downtime");
+    let bob = dev.dev_keypair("bob").unwrap();
+    let mut genesis = dev.clone().with_validator("bob", &bob.public_key(), 10_000);
+    genesis.params.slashing.downtime_window = 3;
+    
+    // Proof-of-work blocks carry no producer, so the bonded validator never signs one
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let eve = genesis.dev_keypair("eve").unwrap();
+    for amount in 1..=3 {
+        let mut tx = Transaction::new("eve".into(), "alice".into(), amount);
+        tx.sign(&eve);
+        ledger.add_transaction(tx).await.unwrap();
+        ledger.process_transactions(10).await.unwrap();
+    }
+    
+    let mut short = Transaction::evidence("eve".into(), "bob".into(), Evidence::Downtime { from_height: 1, to_height: 2 });
+    short.sign(&eve);
+    assert!(ledger.add_transaction(short).await.is_err());
+    
+    let mut report = Transaction::evidence("eve".into(), "bob".into(), Evidence::Downtime { from_height: 1, to_height: 3 });
+    report.sign(&eve);
+    ledger.add_transaction(report).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    
+    let validator = ledger.stakes().validator("bob").unwrap();
+    assert_eq!(validator.total_stake(), 9_900);
+    assert!(validator.is_jailed(5));
+    assert_eq!(ledger.slash_history("bob")[0].reason, SlashReason::Downtime);
+}
\ No newline at end of file
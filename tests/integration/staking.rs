@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use distributed_ledger::{DistributedLedger, Election, GenesisConfig, LedgerError, PosConfig, ProofOfStake, Transaction};
+
+fn signed(genesis: &GenesisConfig, signer: &str, mut tx: Transaction) -> Transaction {
+    tx.sign(&genesis.dev_keypair(signer).unwrap());
+    tx
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stake_delegate_unstake_and_rewards_flow_through_blocks() {
+    let dev = GenesisConfig::dev("it-pos");
+    let alice = dev.dev_keypair("alice").unwrap();
+    let genesis = dev.clone().with_validator("alice", &alice.public_key(), 1_000);
+    
+    let engine = ProofOfStake::new(PosConfig { block_reward: 100, ..Default::default() }, vec![alice]);
+    let ledger = DistributedLedger::with_consensus(genesis.clone(), Arc::new(engine)).unwrap();
+    
+    // Rewards for a block use the bonds in place before it, so the delegation earns from block 2
+    ledger.add_transaction(signed(&genesis, "charlie", Transaction::delegate("charlie".into(), "alice".into(), 1_000))).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_balance("alice").await, 1_000_100);
+    assert_eq!(ledger.get_balance("charlie").await, 999_000);
+    
+    ledger.add_transaction(signed(&genesis, "eve", Transaction::new("eve".into(), "bob".into(), 1))).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_balance("alice").await, 1_000_150);
+    assert_eq!(ledger.get_balance("charlie").await, 999_050);
+    
+    let too_much = signed(&genesis, "charlie", Transaction::unstake("charlie".into(), "alice".into(), 5_000));
+    assert!(matches!(ledger.add_transaction(too_much).await, Err(LedgerError::InvalidTransaction(_))));
+    
+    ledger.add_transaction(signed(&genesis, "charlie", Transaction::unstake("charlie".into(), "alice".into(), 400))).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    assert_eq!(ledger.get_balance("charlie").await, 999_500);
+    assert_eq!(ledger.stakes().validator("alice").unwrap().total_stake(), 1_600);
+    
+    let block = ledger.get_latest_block().await;
+    assert_eq!(block.header.producer, Some(genesis.dev_keypair("alice").unwrap().public_key()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn staking_registers_new_validators_for_delegation() {
+    let dev = GenesisConfig::dev("it-pos-register");
+    let alice = dev.dev_keypair("alice").unwrap();
+    let genesis = dev.clone().with_validator("alice", &alice.public_key(), 1_000);
+    // Holding diana's key too means the node can produce whichever of them is elected
+    let keys = vec![alice, genesis.dev_keypair("diana").unwrap()];
+    let ledger = DistributedLedger::with_consensus(
+        genesis.clone(),
+        Arc::new(ProofOfStake::new(PosConfig::default(), keys)),
+    ).unwrap();
+    
+    let early = signed(&genesis, "eve", Transaction::delegate("eve".into(), "diana".into(), 10));
+    assert!(matches!(ledger.add_transaction(early).await, Err(LedgerError::InvalidTransaction(_))));
+    
+    let unsigned = Transaction::stake("diana".into(), 500);
+    assert!(ledger.add_transaction(unsigned).await.is_err());
+    
+    ledger.add_transaction(signed(&genesis, "diana", Transaction::stake("diana".into(), 500))).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    ledger.add_transaction(signed(&genesis, "eve", Transaction::delegate("eve".into(), "diana".into(), 10))).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    
+    let diana = ledger.stakes().validator("diana").unwrap();
+    assert_eq!(diana.public_key, genesis.dev_keypair("diana").unwrap().public_key());
+    assert_eq!(diana.self_bond(), 500);
+    assert_eq!(diana.total_stake(), 510);
+    assert_eq!(ledger.stakes().total_stake(), 1_510);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn vrf_elected_blocks_carry_proofs_that_other_nodes_verify() {
+    let dev = GenesisConfig::dev("it-pos-vrf");
+    let keys = vec![dev.dev_keypair("alice").unwrap(), dev.dev_keypair("bob").unwrap()];
+    let genesis = dev.clone()
+        .with_validator_keypair("alice", &keys[0], 1_000)
+        .with_validator_keypair("bob", &keys[1], 1_000);
+    let config = PosConfig { election: Election::Vrf, ..Default::default() };
+    
+    let producer = DistributedLedger::with_consensus(
+        genesis.clone(),
+        Arc::new(ProofOfStake::new(config.clone(), keys.clone())),
+    ).unwrap();
+    let follower = DistributedLedger::with_consensus(
+        genesis.clone(),
+        Arc::new(ProofOfStake::new(config, Vec::new())),
+    ).unwrap();
+    
+    for amount in 1..=3 {
+        producer.add_transaction(signed(&genesis, "eve", Transaction::new("eve".into(), "carol".into(), amount))).await.unwrap();
+        producer.process_transactions(10).await.unwrap();
+        let block = producer.get_latest_block().await;
+        assert!(block.header.vrf_output.is_some() && block.header.vrf_proof.is_some());
+        
+        // A producer re-signing a block with an output it picked itself is caught by the proof
+        let mut forged = block.clone();
+        forged.header.vrf_output = Some("00".repeat(32));
+        let signer = keys.iter().find(|k| Some(k.public_key()) == block.header.producer).unwrap();
+        forged.sign(signer);
+        assert!(follower.import_block(forged).await.is_err());
+        
+        follower.import_block(block).await.unwrap();
+    }
+    
+    assert_eq!(follower.get_balance("carol").await, 6);
+    assert_eq!(follower.get_latest_block().await.header.hash, producer.get_latest_block().await.header.hash);
+}
\ No newline at end of file
@@ -0,0 +1,90 @@
+use distributed_ledger::{AdminApi, AuditFilter, LedgerError, LedgerSnapshot, SearchResult};
+
+use crate::common::TestNode;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn snapshot_write_failure_is_reported_and_node_keeps_running() {
+    let node = TestNode::new("it-storage");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    
+    let unwritable = node.path("missing-dir").join("snapshot.json");
+    let result = admin.trigger_snapshot("secret", &unwritable).await;
+    assert!(matches!(result, Err(LedgerError::Internal(_))));
+    
+    node.ledger.add_transaction(node.transfer("eve", "bob", 3)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    
+    let path = node.path("snapshot.json");
+    admin.trigger_snapshot("secret", &path).await.unwrap();
+    let snapshot: LedgerSnapshot = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(snapshot.blocks.len(), 2);
+    assert_eq!(snapshot.balances["bob"], 1_000_003);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn address_labels_annotate_search_and_snapshots_and_survive_export() {
+    let node = TestNode::new("it-labels");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    assert!(matches!(node.ledger.label_address("alice", " "), Err(LedgerError::InvalidParameters(_))));
+    
+    node.ledger.label_address("alice", "alice-hot-wallet").unwrap();
+    assert!(node.ledger.labels().tag("alice", "hot"));
+    assert!(node.ledger.labels().tag("bob", "cold"));
+    let tx = node.transfer("alice", "bob", 5);
+    node.ledger.add_transaction(tx.clone()).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    
+    let Some(SearchResult::Address { label, .. }) = node.ledger.search("alice").await else {
+        panic!("expected an address");
+    };
+    assert_eq!(label.unwrap().label, "alice-hot-wallet");
+    let Some(SearchResult::Transaction { labels, .. }) = node.ledger.search(&tx.id.to_string()).await else {
+        panic!("expected a transaction");
+    };
+    assert_eq!(labels.keys().collect::<Vec<_>>(), vec!["alice", "bob"]);
+    assert_eq!(labels["bob"].label, "");
+    
+    let path = node.path("snapshot.json");
+    admin.trigger_snapshot("secret", &path).await.unwrap();
+    let snapshot: LedgerSnapshot = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(snapshot.labels, node.ledger.labels().export());
+    
+    let exported = node.path("labels.json");
+    assert_eq!(node.ledger.export_labels(&exported).await.unwrap(), 2);
+    let other = TestNode::new("it-labels-import");
+    other.ledger.label_address("alice", "stale-name").unwrap();
+    assert_eq!(other.ledger.import_labels(&exported).await.unwrap(), 2);
+    assert_eq!(other.ledger.labels().export(), node.ledger.labels().export());
+    assert_eq!(other.ledger.labels().tagged("cold"), vec!["bob"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejected_transactions_are_audited_with_their_submitter_across_restarts() {
+    let node = TestNode::new("it-rejection-audit");
+    let admin = AdminApi::new(node.ledger.clone(), "secret");
+    let journal = node.path("rejections.jsonl");
+    assert_eq!(node.ledger.open_audit_log(&journal).unwrap(), 0);
+    
+    let overspend = node.transfer("alice", "bob", 2_000_000);
+    assert!(node.ledger.add_transaction_from("wallet-7", overspend.clone()).await.is_err());
+    let drained = node.transfer("alice", "charlie", 5);
+    node.ledger.add_transaction_from("wallet-7", drained.clone()).await.unwrap();
+    node.ledger.add_transaction(node.transfer("bob", "charlie", 1)).await.unwrap();
+    admin.drain_mempool("secret").unwrap();
+    
+    let filter = AuditFilter { address: Some("alice".into()), ..AuditFilter::default() };
+    let audited = admin.rejected_transactions("secret", &filter).unwrap();
+    assert_eq!(audited.iter().map(|entry| entry.transaction.id).collect::<Vec<_>>(), vec![drained.id, overspend.id]);
+    assert_eq!((audited[0].reason.as_str(), audited[0].pooled), ("Drained from the mempool", true));
+    assert!(!audited[1].pooled);
+    assert!(audited.iter().all(|entry| entry.submitter.as_deref() == Some("wallet-7")));
+    assert_eq!(admin.rejected_transactions("secret", &AuditFilter::default()).unwrap().len(), 3);
+    assert!(matches!(admin.rejected_transactions("wrong", &filter), Err(LedgerError::Unauthorized(_))));
+    
+    // A restarted node picks the log up where the last one left it
+    let restarted = node.sibling();
+    assert_eq!(restarted.open_audit_log(&journal).unwrap(), 3);
+    let admin = AdminApi::new(restarted, "secret");
+    let filter = AuditFilter { transaction: Some(drained.id), ..AuditFilter::default() };
+    assert_eq!(admin.rejected_transactions("secret", &filter).unwrap(), vec![audited[0].clone()]);
+}
\ No newline at end of file
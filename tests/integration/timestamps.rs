@@ -0,0 +1,190 @@
+use chrono::Duration;
+use distributed_ledger::{
+    Block, ChainStats, DistributedLedger, GenesisConfig, LedgerError, TimeBucket, TimestampOrdering, Transaction, VolumeBucket,
+};
+
+use crate::common::TestNode;
+
+fn mined_at(ledger: &DistributedLedger, parent: &Block, offset: Duration, difficulty: usize) -> Block {
+    mined_with(ledger, parent, offset, difficulty, Vec::new())
+}
+
+fn mined_with(
+    ledger: &DistributedLedger,
+    parent: &Block,
+    offset: Duration,
+    difficulty: usize,
+    transactions: Vec<Transaction>,
+) -> Block {
+    let mut block = Block::child_of(parent, transactions);
+    block.header.timestamp = parent.header.timestamp + offset;
+    block.header.state_root = ledger.state_root();
+    block.mine(difficulty);
+    block
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocks_must_follow_their_parent_and_stay_near_the_local_clock() {
+    let node = TestNode::new("it-timestamps");
+    let difficulty = node.genesis.params.difficulty;
+    let rival = node.sibling();
+    
+    node.ledger.add_transaction(node.transfer("alice", "bob", 1)).await.unwrap();
+    node.ledger.process_transactions(10).await.unwrap();
+    let parent = node.ledger.get_latest_block().await;
+    rival.import_block(parent.clone()).await.unwrap();
+    
+    let same_time = mined_at(&rival, &parent, Duration::zero(), difficulty);
+    assert!(matches!(rival.import_block(same_time).await, Err(LedgerError::BlockValidationFailed(_))));
+    
+    let future = mined_at(&rival, &parent, Duration::minutes(1), difficulty);
+    assert!(matches!(rival.import_block(future).await, Err(LedgerError::BlockValidationFailed(_))));
+    
+    let within_drift = mined_at(&rival, &parent, Duration::seconds(5), difficulty);
+    rival.import_block(within_drift).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn median_time_past_allows_timestamps_behind_the_parent() {
+    let mut genesis = GenesisConfig::dev("it-median-time");
+    genesis.params.block_time.ordering = TimestampOrdering::MedianTimePast { window: 3 };
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let difficulty = genesis.params.difficulty;
+    
+    let mut parent = Block::child_of(&ledger.get_latest_block().await, Vec::new());
+    parent.header.timestamp = chrono::Utc::now() - Duration::seconds(1);
+    parent.header.state_root = ledger.state_root();
+    parent.mine(difficulty);
+    ledger.import_block(parent.clone()).await.unwrap();
+    
+    // Three more blocks 100ms apart
+    for _ in 0..3 {
+        parent = mined_at(&ledger, &parent, Duration::milliseconds(100), difficulty);
+        ledger.import_block(parent.clone()).await.unwrap();
+    }
+    
+    // The last three are at +100, +200 and +300ms, so +250ms is accepted although it is behind the parent
+    let behind_parent = mined_at(&ledger, &parent, Duration::milliseconds(-50), difficulty);
+    ledger.import_block(behind_parent.clone()).await.unwrap();
+    
+    // Now the median is +250ms
+    let before_median = mined_at(&ledger, &behind_parent, Duration::milliseconds(-50), difficulty);
+    assert!(matches!(ledger.import_block(before_median).await, Err(LedgerError::BlockValidationFailed(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chain_stats_summarize_intervals_fill_and_pool_depth_over_a_window() {
+    let node = TestNode::new("it-chain-stats");
+    let difficulty = node.genesis.params.difficulty;
+    let first = node.transfer("alice", "bob", 1);
+    let second = node.transfer("charlie", "diana", 2);
+    node.ledger.add_transaction(first.clone()).await.unwrap();
+    node.ledger.add_transaction(second.clone()).await.unwrap();
+    
+    let genesis = node.ledger.get_latest_block().await;
+    let start = chrono::Utc::now() - Duration::seconds(1) - genesis.header.timestamp;
+    let one = mined_with(&node.ledger, &genesis, start, difficulty, vec![first]);
+    node.ledger.import_block(one.clone()).await.unwrap();
+    let two = mined_at(&node.ledger, &one, Duration::milliseconds(200), difficulty);
+    node.ledger.import_block(two.clone()).await.unwrap();
+    let three = mined_with(&node.ledger, &two, Duration::milliseconds(400), difficulty, vec![second]);
+    node.ledger.import_block(three).await.unwrap();
+    
+    assert_eq!(node.ledger.chain_stats(2).await, ChainStats {
+        from_height: 2,
+        to_height: 3,
+        average_interval_ms: 300.0,
+        average_transactions: 0.5,
+        empty_ratio: 0.5,
+        mempool_depth: vec![(2, 1), (3, 0)],
+        difficulty: vec![(2, difficulty), (3, difficulty)],
+    });
+    
+    let all = node.ledger.chain_stats(100).await;
+    assert_eq!((all.from_height, all.to_height), (1, 3));
+    assert_eq!(all.mempool_depth, vec![(1, 1), (2, 1), (3, 0)]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn volume_is_bucketed_by_block_time_and_follows_reorgs() {
+    let node = TestNode::new("it-volume");
+    let rival = node.sibling();
+    let difficulty = node.genesis.params.difficulty;
+    let at = |time: &str| time.parse::<chrono::DateTime<chrono::Utc>>().unwrap();
+    let mine = |ledger: &DistributedLedger, parent: &Block, time: &str, transactions: Vec<Transaction>| {
+        mined_with(ledger, parent, at(time) - parent.header.timestamp, difficulty, transactions)
+    };
+    
+    let genesis = node.ledger.get_latest_block().await;
+    let one = mine(&node.ledger, &genesis, "2026-01-01T10:15:00Z", vec![node.transfer("alice", "bob", 10)]);
+    node.ledger.import_block(one.clone()).await.unwrap();
+    let two = mine(&node.ledger, &one, "2026-01-01T10:45:00Z", vec![
+        node.transfer("charlie", "diana", 5),
+        node.transfer("alice", "charlie", 2),
+    ]);
+    node.ledger.import_block(two.clone()).await.unwrap();
+    let three = mine(&node.ledger, &two, "2026-01-02T09:00:00Z", vec![node.transfer("bob", "alice", 4)]);
+    node.ledger.import_block(three).await.unwrap();
+    
+    let bucket = |start: &str, volume: u128, transactions: u64| VolumeBucket { start: at(start), volume, transactions };
+    assert_eq!(node.ledger.aggregate_volume(None, TimeBucket::Hour), vec![
+        bucket("2026-01-01T10:00:00Z", 17, 3),
+        bucket("2026-01-02T09:00:00Z", 4, 1),
+    ]);
+    assert_eq!(node.ledger.aggregate_volume(Some("alice"), TimeBucket::Day), vec![
+        bucket("2026-01-01T00:00:00Z", 12, 2),
+        bucket("2026-01-02T00:00:00Z", 4, 1),
+    ]);
+    assert!(node.ledger.aggregate_volume(Some("nobody"), TimeBucket::Day).is_empty());
+    
+    // A longer rival branch from block two takes the last transfer back out
+    rival.import_block(one).await.unwrap();
+    rival.import_block(two.clone()).await.unwrap();
+    let four = mine(&rival, &two, "2026-01-01T11:30:00Z", vec![node.transfer("diana", "eve", 1)]);
+    rival.import_block(four.clone()).await.unwrap();
+    let five = mine(&rival, &four, "2026-01-01T11:40:00Z", Vec::new());
+    node.ledger.import_block(four).await.unwrap();
+    node.ledger.import_block(five).await.unwrap();
+    
+    assert_eq!(node.ledger.aggregate_volume(None, TimeBucket::Day), vec![bucket("2026-01-01T00:00:00Z", 18, 4)]);
+    assert_eq!(node.ledger.aggregate_volume(Some("bob"), TimeBucket::Hour), vec![
+        bucket("2026-01-01T10:00:00Z", 10, 1),
+    ]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn headers_record_the_slots_skipped_since_their_parent() {
+    let mut genesis = GenesisConfig::dev("it-skipped-slots");
+    genesis.params.block_time.slot_ms = Some(100);
+    let ledger = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let difficulty = genesis.params.difficulty;
+    let rules = genesis.params.block_time.clone();
+    
+    let mut transfer = Transaction::new("alice".to_string(), "bob".to_string(), 1).for_chain(&genesis.chain_id);
+    transfer.sign(&genesis.dev_keypair("alice").unwrap());
+    ledger.add_transaction(transfer).await.unwrap();
+    ledger.process_transactions(10).await.unwrap();
+    let parent = ledger.get_latest_block().await;
+    let since = genesis.block().header.timestamp;
+    assert_eq!(parent.header.skipped_slots, rules.skipped_slots(since, parent.header.timestamp));
+    
+    // Nothing was produced for a second, so the next block is ten slots on and nine were skipped
+    let mut unrecorded = Block::child_of(&parent, Vec::new());
+    unrecorded.header.timestamp = parent.header.timestamp + Duration::seconds(1);
+    unrecorded.header.state_root = ledger.state_root();
+    unrecorded.mine(difficulty);
+    assert!(matches!(ledger.import_block(unrecorded.clone()).await, Err(LedgerError::BlockValidationFailed(_))));
+    
+    let mut recorded = unrecorded;
+    recorded.header.skipped_slots = 9;
+    recorded.mine(difficulty);
+    ledger.import_block(recorded.clone()).await.unwrap();
+    
+    // The next slot along skips none
+    let next = mined_at(&ledger, &recorded, Duration::milliseconds(150), difficulty);
+    assert_eq!(next.header.skipped_slots, 0);
+    ledger.import_block(next).await.unwrap();
+    
+    genesis.params.block_time.slot_ms = Some(0);
+    assert!(matches!(DistributedLedger::from_genesis(genesis), Err(LedgerError::InvalidParameters(_))));
+}
\ No newline at end of file
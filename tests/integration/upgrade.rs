@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use distributed_ledger::{Block, ConsensusSchedule, DistributedLedger, EngineConfig, GenesisConfig, LedgerError, PoaConfig, Transaction};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scheduled_switch_from_work_to_authority_applies_at_the_boundary() {
+    let dev = GenesisConfig::dev("it-hybrid");
+    let alice = dev.dev_keypair("alice").unwrap();
+    let authorities = PoaConfig { validators: vec![alice.public_key()], ..Default::default() };
+    let schedule = ConsensusSchedule::new(EngineConfig::ProofOfWork)
+        .switch_at(3, EngineConfig::ProofOfAuthority(authorities));
+    let genesis = dev.with_consensus_schedule(schedule.clone());
+    
+    let producer = DistributedLedger::with_consensus(genesis.clone(), Arc::new(schedule.build(vec![alice.clone()]).unwrap())).unwrap();
+    let follower = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    let late = DistributedLedger::from_genesis(genesis.clone()).unwrap();
+    
+    let mut blocks = Vec::new();
+    for _ in 0..4 {
+        let mut tx = Transaction::new("alice".to_string(), "bob".to_string(), 1);
+        tx.sign(&alice);
+        producer.add_transaction(tx).await.unwrap();
+        producer.process_transactions(10).await.unwrap();
+        blocks.push(producer.get_latest_block().await);
+        follower.import_block(blocks.last().unwrap().clone()).await.unwrap();
+    }
+    
+    assert!(blocks[..2].iter().all(|block| block.header.producer.is_none() && block.header.hash.starts_with("00")));
+    assert!(blocks[2..].iter().all(|block| block.header.producer == Some(alice.public_key())));
+    assert_eq!(follower.get_latest_block().await.header.hash, blocks[3].header.hash);
+    
+    // Past the boundary a mined block no longer counts, however much work it carries
+    late.import_block(blocks[0].clone()).await.unwrap();
+    late.import_block(blocks[1].clone()).await.unwrap();
+    let mut mined = Block::child_of(&blocks[1], Vec::new());
+    mined.mine(genesis.params.difficulty);
+    assert!(matches!(late.import_block(mined).await, Err(LedgerError::BlockValidationFailed(_))));
+    late.import_block(blocks[2].clone()).await.unwrap();
+    
+    // A node without the authority's key follows but cannot produce once authorities take over
+    let mut tx = Transaction::new("bob".to_string(), "eve".to_string(), 1);
+    tx.sign(&genesis.dev_keypair("bob").unwrap());
+    follower.add_transaction(tx).await.unwrap();
+    follower.process_transactions(10).await.unwrap();
+    assert_eq!(follower.get_latest_block().await.header.height, 4);
+    
+    let backwards = ConsensusSchedule::new(EngineConfig::ProofOfWork)
+        .switch_at(5, EngineConfig::ProofOfWork)
+        .switch_at(5, EngineConfig::ProofOfWork);
+    let invalid = GenesisConfig::dev("it-hybrid").with_consensus_schedule(backwards);
+    assert!(matches!(DistributedLedger::from_genesis(invalid), Err(LedgerError::InvalidParameters(_))));
+}
\ No newline at end of file
@@ -0,0 +1,46 @@
+use distributed_ledger::zk::verify_groth16;
+use distributed_ledger::LedgerError;
+
+// A Groth16 proof over BN254 of knowing x and y with x * y = 33, for public input 33; generated with arkworks from a
+// fixed seed, in compressed canonical encoding
+const VERIFYING_KEY: &str = concat!(
+    "2696e1ed1d689f3a48334935390b2b8798a58cded4e373399875b9fdd0f85aab5a4889c61b1364f46131141d840654c8",
+    "85db091027ebe4d98f357db57220cf2fe1e8f8bfd5c20d9bd0e01f802fb7114085c7e66fcef482a1f3beadd92b3fb2ab",
+    "b1833ab8a6af5afcd5c13b4886c11da6dab8d2d77394ac343e24ade434d012152caaeaf38441afb736c0af023d5708eb",
+    "69fba50c28232d2e84988c3821577f97466e1467be3e652fccab6435c961623a20c73706d3568fe31d31e2fbf8bb2113",
+    "af0a9aaf3a951881e0af347949786dbe84e45b1c83a04cce669da157ceb25c100200000000000000ab966552e71f61e4",
+    "a7228abcb360426826d818a6c42c870c55a9e8072e6c4e2662ffacb84b0ed7f55ce73b2a23c1223fbafe0525c74ed9c8",
+    "193d59ebe0e99e2b",
+);
+
+const PROOF: &str = concat!(
+    "009557d94af674b9ea78bf297950f29325dfd7dd34a0d4dc1fb35339a6bbc28adad4c70d475000c9cd662860e2d2a9c2",
+    "f35da5542a757018b3ad907c7a4d122fe8ec852a255cb4b80d81b5bc5cab16facb3adcf8c0956259d44a8eeb66340a23",
+    "340668c35334b38abd55c89bbaddf5968e6db3028cb5a947612c86fde87170aa",
+);
+
+const PRODUCT: &str = "2100000000000000000000000000000000000000000000000000000000000000";
+
+fn decode(encoded: &str) -> Vec<u8> {
+    hex::decode(encoded).unwrap()
+}
+
+#[test]
+fn groth16_accepts_a_known_good_proof_and_rejects_tampered_ones() {
+    let (vk, proof) = (decode(VERIFYING_KEY), decode(PROOF));
+    assert!(verify_groth16(&vk, &proof, &[decode(PRODUCT)]).unwrap());
+    
+    // The same proof claimed for another product
+    let mut other = decode(PRODUCT);
+    other[0] += 1;
+    assert!(!verify_groth16(&vk, &proof, &[other]).unwrap());
+    
+    // A proof altered in transit either no longer decodes or no longer verifies
+    for position in [0, proof.len() / 2, proof.len() - 1] {
+        let mut tampered = proof.clone();
+        tampered[position] ^= 0x01;
+        assert!(!matches!(verify_groth16(&vk, &tampered, &[decode(PRODUCT)]), Ok(true)), "byte {}", position);
+    }
+    
+    assert!(matches!(verify_groth16(&vk, &proof, &[]), Err(LedgerError::InvalidProof(_))));
+}
\ No newline at end of file